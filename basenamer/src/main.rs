@@ -0,0 +1,215 @@
+use anyhow::{Result, bail};
+use clap::Parser;
+use cli_utils::{exit_with_error, parse_args};
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    /// NAME to strip, then an optional SUFFIX to remove as well; with -a or
+    /// -s, every operand is a NAME
+    #[arg(value_name = "NAME", required = true)]
+    names: Vec<String>,
+
+    /// Strip every operand rather than just the first
+    #[arg(short('a'), long)]
+    multiple: bool,
+
+    /// Remove a trailing SUFFIX too; implies -a
+    #[arg(short('s'), long, value_name = "SUFFIX")]
+    suffix: Option<String>,
+
+    /// End each name with NUL rather than newline
+    #[arg(short('z'), long)]
+    zero: bool,
+}
+
+/// Returns the last component of `name` as POSIX `basename` does: trailing
+/// slashes go, then everything up to the last slash left, and then `suffix`
+/// unless it makes up the whole of what remains. A name of only slashes is
+/// `/`.
+///
+/// This works on the string rather than with `Path::file_name`, which gives
+/// nothing for `..` or `/` and drops a trailing `.` component, so `a/.`
+/// would come out as `a`.
+fn basename<'a>(name: &'a str, suffix: Option<&str>) -> &'a str {
+    if name.is_empty() {
+        return "";
+    }
+    let trimmed = name.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return "/";
+    }
+    let base = trimmed.rsplit('/').next().unwrap_or(trimmed);
+    match suffix {
+        Some(suffix) if base != suffix => base.strip_suffix(suffix).unwrap_or(base),
+        _ => base,
+    }
+}
+
+/// Picks out the names to strip and the suffix to remove from them. Without
+/// -a or -s there is one NAME, and a second operand is its SUFFIX.
+fn operands(args: &Args) -> Result<(&[String], Option<&str>)> {
+    if args.multiple || args.suffix.is_some() {
+        return Ok((&args.names, args.suffix.as_deref()));
+    }
+    match args.names.as_slice() {
+        [name, suffix] => Ok((std::slice::from_ref(name), Some(suffix))),
+        [_, _, extra, ..] => bail!("extra operand '{extra}'"),
+        _ => Ok((&args.names, None)),
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let (names, suffix) = operands(&args)?;
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    for name in names {
+        stdout.write_all(basename(name, suffix).as_bytes())?;
+        stdout.write_all(if args.zero { b"\0" } else { b"\n" })?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Args, basename, operands};
+    use clap::Parser;
+
+    fn check(cases: &[(&str, &str)]) {
+        for &(name, expected) in cases {
+            assert_eq!(basename(name, None), expected, "{name:?}");
+        }
+    }
+
+    fn check_suffix(cases: &[(&str, &str, &str)]) {
+        for &(name, suffix, expected) in cases {
+            assert_eq!(
+                basename(name, Some(suffix)),
+                expected,
+                "{name:?} {suffix:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_plain() {
+        check(&[
+            ("file", "file"),
+            ("file.txt", "file.txt"),
+            ("/path/to/file.txt", "file.txt"),
+            ("path/to/file", "file"),
+            ("a b/c d", "c d"),
+            ("/usr/lib", "lib"),
+        ]);
+    }
+
+    #[test]
+    fn test_empty() {
+        check(&[("", "")]);
+        check_suffix(&[("", "x", "")]);
+    }
+
+    #[test]
+    fn test_root() {
+        // POSIX leaves `//` to the implementation; it is `/` here, as in GNU
+        check(&[("/", "/"), ("//", "/"), ("///", "/"), ("////", "/")]);
+        check_suffix(&[("/", "/", "/")]);
+    }
+
+    #[test]
+    fn test_trailing_slashes() {
+        check(&[
+            ("a/", "a"),
+            ("a//", "a"),
+            ("/a/", "a"),
+            ("/usr/lib/", "lib"),
+            ("a/b///", "b"),
+            ("//a//b//", "b"),
+        ]);
+    }
+
+    #[test]
+    fn test_repeated_slashes() {
+        check(&[("a//b", "b"), ("//a", "a"), ("///a", "a"), ("a///b", "b")]);
+    }
+
+    #[test]
+    fn test_dots() {
+        check(&[
+            (".", "."),
+            ("..", ".."),
+            ("./", "."),
+            ("../", ".."),
+            ("./a", "a"),
+            ("../a", "a"),
+            ("a/.", "."),
+            ("a/..", ".."),
+            ("/.", "."),
+            ("/..", ".."),
+            ("...", "..."),
+            (".hidden", ".hidden"),
+        ]);
+    }
+
+    #[test]
+    fn test_suffix() {
+        check_suffix(&[
+            ("/path/to/file.txt", ".txt", "file"),
+            ("file.txt", "txt", "file."),
+            ("file.txt", ".c", "file.txt"),
+            ("file.tar.gz", ".gz", "file.tar"),
+            ("file.tar.gz", ".tar", "file.tar.gz"),
+            ("dir.d/", ".d", "dir"),
+            ("a.txt/", ".txt", "a"),
+            ("/a/b.c//", "b.c", "b.c"),
+            // only one copy of the suffix goes
+            ("a.c.c", ".c", "a.c"),
+        ]);
+    }
+
+    #[test]
+    fn test_suffix_whole_name() {
+        // a suffix that is all there is stays
+        check_suffix(&[
+            (".txt", ".txt", ".txt"),
+            ("a/.txt", ".txt", ".txt"),
+            ("file", "file", "file"),
+            ("..", ".", "."),
+            ("...", "..", "."),
+        ]);
+    }
+
+    #[test]
+    fn test_suffix_not_in_directory() {
+        // the suffix is matched against the last component only
+        check_suffix(&[("a.txt/b", ".txt", "b"), ("x/", "x/", "x")]);
+    }
+
+    #[test]
+    fn test_operands() {
+        let parse = |args: &[&str]| Args::parse_from(["basenamer"].iter().chain(args));
+
+        let args = parse(&["/a/b.c"]);
+        assert_eq!(operands(&args).unwrap(), (&args.names[..], None));
+
+        let args = parse(&["/a/b.c", ".c"]);
+        assert_eq!(operands(&args).unwrap(), (&args.names[..1], Some(".c")));
+
+        let args = parse(&["-a", "/a/b.c", ".c"]);
+        assert_eq!(operands(&args).unwrap(), (&args.names[..], None));
+
+        let args = parse(&["-s", ".c", "/a/b.c", "d.c"]);
+        assert_eq!(operands(&args).unwrap(), (&args.names[..], Some(".c")));
+
+        let args = parse(&["a", "b", "c", "d"]);
+        assert_eq!(
+            operands(&args).unwrap_err().to_string(),
+            "extra operand 'c'"
+        );
+    }
+}
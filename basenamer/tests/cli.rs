@@ -0,0 +1,101 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const PRG: &str = "basenamer";
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_extra_operand() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["a", "b", "c"])
+        .assert()
+        .failure()
+        .stderr("extra operand 'c'\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn name() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg("/path/to/file.txt")
+        .assert()
+        .success()
+        .stdout("file.txt\n");
+    Command::cargo_bin(PRG)?
+        .arg("/usr/lib/")
+        .assert()
+        .success()
+        .stdout("lib\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn suffix_operand() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["/path/to/file.txt", ".txt"])
+        .assert()
+        .success()
+        .stdout("file\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple() -> Result<()> {
+    for flag in ["-a", "--multiple"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "/a/b.c", "d/", "/", ""])
+            .assert()
+            .success()
+            .stdout("b.c\nd\n/\n\n");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn suffix() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-s", ".c", "/a/b.c", "d.h", ".c"])
+        .assert()
+        .success()
+        .stdout("b\nd.h\n.c\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn zero() -> Result<()> {
+    for flag in ["-z", "--zero"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "-a", "a/b", "c\nd/e"])
+            .assert()
+            .success()
+            .stdout("b\0e\0");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{exit_with_error, open, parse_args};
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    #[arg(short('n'), long("number"))]
+    number_lines: bool,
+
+    #[arg(short('b'), long("number-nonblank"))]
+    number_nonblank_lines: bool,
+
+    #[arg(short('s'), long)]
+    squeeze_blank: bool,
+
+    #[arg(short('E'), long)]
+    show_ends: bool,
+
+    #[arg(short('T'), long)]
+    show_tabs: bool,
+}
+
+/// Applies the line options in the order POSIX `cat` does: squeezing
+/// decides whether a line is printed at all, then it is numbered, and
+/// finally tabs and line ends are made visible. The state carries over
+/// from one file to the next, so numbering and squeezing are continuous.
+#[derive(Debug)]
+struct LineTransformer {
+    number_lines: bool,
+    number_nonblank_lines: bool,
+    squeeze_blank: bool,
+    show_ends: bool,
+    show_tabs: bool,
+    line_num: usize,
+    last_blank: bool,
+    // the last line had no newline, so the next file continues it
+    mid_line: bool,
+}
+
+impl LineTransformer {
+    fn new(args: &Args) -> Self {
+        Self {
+            number_lines: args.number_lines,
+            number_nonblank_lines: args.number_nonblank_lines,
+            squeeze_blank: args.squeeze_blank,
+            show_ends: args.show_ends,
+            show_tabs: args.show_tabs,
+            line_num: 0,
+            last_blank: false,
+            mid_line: false,
+        }
+    }
+
+    /// Returns the bytes to print for `line`, or `None` if it is squeezed.
+    fn transform(&mut self, line: &[u8]) -> Option<Vec<u8>> {
+        let blank = line == b"\n" && !self.mid_line;
+        if blank && self.squeeze_blank && self.last_blank {
+            return None;
+        }
+        self.last_blank = blank;
+
+        let mut output = Vec::with_capacity(line.len() + 8);
+        let numbered = if self.number_nonblank_lines {
+            !blank
+        } else {
+            self.number_lines
+        };
+        if numbered && !self.mid_line {
+            self.line_num += 1;
+            output.extend_from_slice(format!("{:>6}\t", self.line_num).as_bytes());
+        }
+
+        let (content, ending): (&[u8], &[u8]) = match line.strip_suffix(b"\n") {
+            Some(rest) if self.show_ends => match rest.strip_suffix(b"\r") {
+                Some(rest) => (rest, b"^M$\n"),
+                None => (rest, b"$\n"),
+            },
+            Some(rest) => (rest, b"\n"),
+            None => (line, b""),
+        };
+        for &byte in content {
+            if byte == b'\t' && self.show_tabs {
+                output.extend_from_slice(b"^I");
+            } else {
+                output.push(byte);
+            }
+        }
+        output.extend_from_slice(ending);
+
+        self.mid_line = ending.is_empty();
+        Some(output)
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut transformer = LineTransformer::new(&args);
+    let mut stdout = io::stdout().lock();
+    let mut line = vec![];
+
+    for filename in &args.files {
+        match open(filename) {
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(mut file) => loop {
+                line.clear();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+                if let Some(output) = transformer.transform(&line) {
+                    stdout.write_all(&output)?;
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Args, LineTransformer};
+    use clap::Parser;
+
+    fn transformer(flags: &[&str]) -> LineTransformer {
+        LineTransformer::new(&Args::parse_from(["catr"].iter().chain(flags)))
+    }
+
+    fn transform_all(transformer: &mut LineTransformer, lines: &[&str]) -> String {
+        lines
+            .iter()
+            .filter_map(|line| transformer.transform(line.as_bytes()))
+            .map(|output| String::from_utf8(output).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_no_flags() {
+        let mut t = transformer(&[]);
+        assert_eq!(transform_all(&mut t, &["a\tb\n", "\n", "c"]), "a\tb\n\nc");
+    }
+
+    #[test]
+    fn test_number() {
+        let mut t = transformer(&["-n"]);
+        assert_eq!(
+            transform_all(&mut t, &["a\n", "\n", "b\n"]),
+            "     1\ta\n     2\t\n     3\tb\n"
+        );
+
+        // -b overrides -n and leaves blank lines unnumbered
+        let mut t = transformer(&["-n", "-b"]);
+        assert_eq!(
+            transform_all(&mut t, &["a\n", "\n", "b\n"]),
+            "     1\ta\n\n     2\tb\n"
+        );
+    }
+
+    #[test]
+    fn test_squeeze_then_number() {
+        let mut t = transformer(&["-sn"]);
+        assert_eq!(
+            transform_all(&mut t, &["\n", "\n", "a\n", "\n", "\n", "\n"]),
+            "     1\t\n     2\ta\n     3\t\n"
+        );
+    }
+
+    #[test]
+    fn test_show_tabs_and_ends() {
+        let mut t = transformer(&["-T", "-E"]);
+        assert_eq!(
+            transform_all(&mut t, &["a\tb\n", "crlf\r\n", "\n", "no newline"]),
+            "a^Ib$\ncrlf^M$\n$\nno newline"
+        );
+    }
+
+    #[test]
+    fn test_continued_line() {
+        // a file without a trailing newline runs into the next one
+        let mut t = transformer(&["-n", "-s"]);
+        assert_eq!(
+            transform_all(&mut t, &["a", "\n", "\n", "\n", "b\n"]),
+            "     1\ta\n     2\t\n     3\tb\n"
+        );
+    }
+}
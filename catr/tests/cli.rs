@@ -0,0 +1,348 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "catr";
+// Reuse the grepr corpus so both tools are exercised on the same text
+const EMPTY: &str = "../grepr/tests/inputs/empty.txt";
+const FOX: &str = "../grepr/tests/inputs/fox.txt";
+const BUSTLE: &str = "../grepr/tests/inputs/bustle.txt";
+const NOBODY: &str = "../grepr/tests/inputs/nobody.txt";
+const SPACES: &str = "tests/inputs/spaces.txt";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&expected)
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(input_file: &str, args: &[&str], expected_file: &str) -> Result<()> {
+    let input = fs::read(input_file)?;
+    let expected = fs::read(expected_file)?;
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        String::from_utf8_lossy(&expected)
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args([FOX, &bad, FOX])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string("tests/expected/fox.txt.out")?.repeat(2))
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_flag() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-x", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unexpected argument '-x'"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> Result<()> {
+    run(&[EMPTY], "tests/expected/empty.txt.out")
+}
+
+#[test]
+fn empty_number() -> Result<()> {
+    run(&["-n", EMPTY], "tests/expected/empty.txt.n.out")
+}
+
+#[test]
+fn empty_stdin() -> Result<()> {
+    run_stdin(EMPTY, &[], "tests/expected/empty.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn fox() -> Result<()> {
+    run(&[FOX], "tests/expected/fox.txt.out")
+}
+
+#[test]
+fn fox_number() -> Result<()> {
+    run(&["-n", FOX], "tests/expected/fox.txt.n.out")
+}
+
+#[test]
+fn fox_number_nonblank() -> Result<()> {
+    run(&["-b", FOX], "tests/expected/fox.txt.b.out")
+}
+
+#[test]
+fn fox_squeeze() -> Result<()> {
+    run(&["-s", FOX], "tests/expected/fox.txt.s.out")
+}
+
+#[test]
+fn fox_show_ends() -> Result<()> {
+    run(&["-E", FOX], "tests/expected/fox.txt.E.out")
+}
+
+#[test]
+fn fox_show_tabs() -> Result<()> {
+    run(&["-T", FOX], "tests/expected/fox.txt.T.out")
+}
+
+#[test]
+fn fox_squeeze_number() -> Result<()> {
+    run(&["-sn", FOX], "tests/expected/fox.txt.sn.out")
+}
+
+#[test]
+fn fox_squeeze_number_nonblank() -> Result<()> {
+    run(&["-sb", FOX], "tests/expected/fox.txt.sb.out")
+}
+
+#[test]
+fn fox_number_show_all() -> Result<()> {
+    run(&["-nET", FOX], "tests/expected/fox.txt.nET.out")
+}
+
+#[test]
+fn fox_stdin() -> Result<()> {
+    run_stdin(FOX, &[], "tests/expected/fox.txt.out")
+}
+
+#[test]
+fn fox_stdin_number() -> Result<()> {
+    run_stdin(FOX, &["-n", "-"], "tests/expected/fox.txt.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle() -> Result<()> {
+    run(&[BUSTLE], "tests/expected/bustle.txt.out")
+}
+
+#[test]
+fn bustle_number() -> Result<()> {
+    run(&["-n", BUSTLE], "tests/expected/bustle.txt.n.out")
+}
+
+#[test]
+fn bustle_number_nonblank() -> Result<()> {
+    run(&["-b", BUSTLE], "tests/expected/bustle.txt.b.out")
+}
+
+#[test]
+fn bustle_squeeze() -> Result<()> {
+    run(&["-s", BUSTLE], "tests/expected/bustle.txt.s.out")
+}
+
+#[test]
+fn bustle_show_ends() -> Result<()> {
+    run(&["-E", BUSTLE], "tests/expected/bustle.txt.E.out")
+}
+
+#[test]
+fn bustle_show_tabs() -> Result<()> {
+    run(&["-T", BUSTLE], "tests/expected/bustle.txt.T.out")
+}
+
+#[test]
+fn bustle_squeeze_number() -> Result<()> {
+    run(&["-sn", BUSTLE], "tests/expected/bustle.txt.sn.out")
+}
+
+#[test]
+fn bustle_squeeze_number_nonblank() -> Result<()> {
+    run(&["-sb", BUSTLE], "tests/expected/bustle.txt.sb.out")
+}
+
+#[test]
+fn bustle_number_show_all() -> Result<()> {
+    run(&["-nET", BUSTLE], "tests/expected/bustle.txt.nET.out")
+}
+
+#[test]
+fn bustle_stdin() -> Result<()> {
+    run_stdin(BUSTLE, &[], "tests/expected/bustle.txt.out")
+}
+
+#[test]
+fn bustle_stdin_number() -> Result<()> {
+    run_stdin(BUSTLE, &["-n", "-"], "tests/expected/bustle.txt.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn nobody() -> Result<()> {
+    run(&[NOBODY], "tests/expected/nobody.txt.out")
+}
+
+#[test]
+fn nobody_number() -> Result<()> {
+    run(&["-n", NOBODY], "tests/expected/nobody.txt.n.out")
+}
+
+#[test]
+fn nobody_number_nonblank() -> Result<()> {
+    run(&["-b", NOBODY], "tests/expected/nobody.txt.b.out")
+}
+
+#[test]
+fn nobody_squeeze() -> Result<()> {
+    run(&["-s", NOBODY], "tests/expected/nobody.txt.s.out")
+}
+
+#[test]
+fn nobody_show_ends() -> Result<()> {
+    run(&["-E", NOBODY], "tests/expected/nobody.txt.E.out")
+}
+
+#[test]
+fn nobody_show_tabs() -> Result<()> {
+    run(&["-T", NOBODY], "tests/expected/nobody.txt.T.out")
+}
+
+#[test]
+fn nobody_squeeze_number() -> Result<()> {
+    run(&["-sn", NOBODY], "tests/expected/nobody.txt.sn.out")
+}
+
+#[test]
+fn nobody_squeeze_number_nonblank() -> Result<()> {
+    run(&["-sb", NOBODY], "tests/expected/nobody.txt.sb.out")
+}
+
+#[test]
+fn nobody_number_show_all() -> Result<()> {
+    run(&["-nET", NOBODY], "tests/expected/nobody.txt.nET.out")
+}
+
+#[test]
+fn nobody_stdin() -> Result<()> {
+    run_stdin(NOBODY, &[], "tests/expected/nobody.txt.out")
+}
+
+#[test]
+fn nobody_stdin_number() -> Result<()> {
+    run_stdin(NOBODY, &["-n", "-"], "tests/expected/nobody.txt.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn spaces() -> Result<()> {
+    run(&[SPACES], "tests/expected/spaces.txt.out")
+}
+
+#[test]
+fn spaces_number() -> Result<()> {
+    run(&["-n", SPACES], "tests/expected/spaces.txt.n.out")
+}
+
+#[test]
+fn spaces_number_nonblank() -> Result<()> {
+    run(&["-b", SPACES], "tests/expected/spaces.txt.b.out")
+}
+
+#[test]
+fn spaces_squeeze() -> Result<()> {
+    run(&["-s", SPACES], "tests/expected/spaces.txt.s.out")
+}
+
+#[test]
+fn spaces_show_ends() -> Result<()> {
+    run(&["-E", SPACES], "tests/expected/spaces.txt.E.out")
+}
+
+#[test]
+fn spaces_show_tabs() -> Result<()> {
+    run(&["-T", SPACES], "tests/expected/spaces.txt.T.out")
+}
+
+#[test]
+fn spaces_squeeze_number() -> Result<()> {
+    run(&["-sn", SPACES], "tests/expected/spaces.txt.sn.out")
+}
+
+#[test]
+fn spaces_squeeze_number_nonblank() -> Result<()> {
+    run(&["-sb", SPACES], "tests/expected/spaces.txt.sb.out")
+}
+
+#[test]
+fn spaces_number_show_all() -> Result<()> {
+    run(&["-nET", SPACES], "tests/expected/spaces.txt.nET.out")
+}
+
+#[test]
+fn spaces_stdin() -> Result<()> {
+    run_stdin(SPACES, &[], "tests/expected/spaces.txt.out")
+}
+
+#[test]
+fn spaces_stdin_number() -> Result<()> {
+    run_stdin(SPACES, &["-n", "-"], "tests/expected/spaces.txt.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn all_number() -> Result<()> {
+    run(&["-n", BUSTLE, FOX, NOBODY], "tests/expected/all.n.out")
+}
+
+#[test]
+fn all_number_nonblank_squeeze_show_ends() -> Result<()> {
+    run(
+        &["-bsE", NOBODY, SPACES, BUSTLE],
+        "tests/expected/all.bsE.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{exit_with_error, open, parse_args};
+use serde::Serialize;
+use std::io::{self, BufWriter, Read, Write};
+use std::ops::RangeInclusive;
+use unicode_general_category::{GeneralCategory, get_general_category};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    /// The text to describe, a character at a time, or standard input when
+    /// there is none
+    #[arg(value_name = "TEXT", conflicts_with("range"))]
+    text: Vec<String>,
+
+    /// Print each character as a JSON object on a line of its own
+    #[arg(long)]
+    json: bool,
+
+    /// Print only the code points, on one line
+    #[arg(long, conflicts_with("json"))]
+    only_codepoints: bool,
+
+    /// Describe every character from one code point to another, such as
+    /// U+0000..U+007F, rather than any text. Surrogates are skipped, as
+    /// they are not characters.
+    #[arg(long, value_name = "FROM..TO", value_parser = parse_range)]
+    range: Option<RangeInclusive<u32>>,
+}
+
+/// What is printed about a character.
+#[derive(Debug, PartialEq, Serialize)]
+struct CharInfo {
+    #[serde(rename = "char")]
+    character: char,
+    /// As `U+XXXX`, with at least four digits
+    codepoint: String,
+    /// The bytes of its UTF-8 encoding, in hex and separated by spaces
+    utf8: String,
+    /// The two-letter abbreviation of its general category, such as `Lu`
+    category: &'static str,
+    name: String,
+    control: bool,
+}
+
+fn codepoint(c: char) -> String {
+    format!("U+{:04X}", c as u32)
+}
+
+fn utf8_hex(c: char) -> String {
+    let mut buf = [0; 4];
+    let bytes: Vec<_> = c
+        .encode_utf8(&mut buf)
+        .bytes()
+        .map(|b| format!("{b:02X}"))
+        .collect();
+    bytes.join(" ")
+}
+
+/// U+FDD0 to U+FDEF, and the last two code points of each plane, which are
+/// never assigned.
+fn is_noncharacter(c: char) -> bool {
+    let n = c as u32;
+    (0xFDD0..=0xFDEF).contains(&n) || n & 0xFFFE == 0xFFFE
+}
+
+/// The Unicode name of `c` or, for a character without one, a label such
+/// as `<control-0009>` made as the Unicode standard makes them.
+fn name(c: char, category: GeneralCategory) -> String {
+    if let Some(name) = unicode_names2::name(c) {
+        return name.to_string();
+    }
+    let kind = match category {
+        GeneralCategory::Control => "control",
+        GeneralCategory::PrivateUse => "private-use",
+        _ if is_noncharacter(c) => "noncharacter",
+        _ => "reserved",
+    };
+    format!("<{kind}-{:04X}>", c as u32)
+}
+
+fn describe(c: char) -> CharInfo {
+    let category = get_general_category(c);
+    CharInfo {
+        character: c,
+        codepoint: codepoint(c),
+        utf8: utf8_hex(c),
+        category: category.abbreviation(),
+        name: name(c, category),
+        control: c.is_control(),
+    }
+}
+
+/// A line of the default output. The character itself is left out, as a
+/// control character would garble it.
+fn format_row(info: &CharInfo) -> String {
+    format!(
+        "{:<8} {:<11} {} {:<7} {}",
+        info.codepoint,
+        info.utf8,
+        info.category,
+        if info.control { "control" } else { "-" },
+        info.name
+    )
+}
+
+/// Reads a code point as hex, with or without `U+` in front.
+fn parse_codepoint(value: &str) -> Option<u32> {
+    let hex = value
+        .strip_prefix("U+")
+        .or_else(|| value.strip_prefix("u+"))
+        .unwrap_or(value);
+    if hex.is_empty() || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    u32::from_str_radix(hex, 16)
+        .ok()
+        .filter(|&n| n <= char::MAX as u32)
+}
+
+fn parse_range(value: &str) -> Result<RangeInclusive<u32>, String> {
+    let invalid = || format!("invalid range '{value}', expected one such as U+0000..U+007F");
+    let (from, to) = value.split_once("..").ok_or_else(invalid)?;
+    let from = parse_codepoint(from).ok_or_else(invalid)?;
+    let to = parse_codepoint(to).ok_or_else(invalid)?;
+    if from > to {
+        return Err(format!(
+            "invalid range '{value}', which ends before it starts"
+        ));
+    }
+    Ok(from..=to)
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut input = String::new();
+    let chars: Box<dyn Iterator<Item = char>> = match &args.range {
+        Some(range) => Box::new(range.clone().filter_map(char::from_u32)),
+        None if args.text.is_empty() => {
+            open("-")?.read_to_string(&mut input)?;
+            Box::new(input.chars())
+        }
+        None => Box::new(args.text.iter().flat_map(|text| text.chars())),
+    };
+
+    let mut out = BufWriter::new(io::stdout().lock());
+    if args.only_codepoints {
+        let codepoints: Vec<_> = chars.map(codepoint).collect();
+        if !codepoints.is_empty() {
+            writeln!(out, "{}", codepoints.join(" "))?;
+        }
+    } else {
+        for c in chars {
+            let info = describe(c);
+            if args.json {
+                writeln!(out, "{}", serde_json::to_string(&info)?)?;
+            } else {
+                writeln!(out, "{}", format_row(&info))?;
+            }
+        }
+    }
+    out.flush()?;
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        CharInfo, codepoint, describe, format_row, parse_codepoint, parse_range, utf8_hex,
+    };
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_describe() {
+        assert_eq!(
+            describe('A'),
+            CharInfo {
+                character: 'A',
+                codepoint: "U+0041".to_string(),
+                utf8: "41".to_string(),
+                category: "Lu",
+                name: "LATIN CAPITAL LETTER A".to_string(),
+                control: false,
+            }
+        );
+        let row = |c| format_row(&describe(c));
+        assert_eq!(
+            row('z'),
+            "U+007A   7A          Ll -       LATIN SMALL LETTER Z"
+        );
+        assert_eq!(
+            row('中'),
+            "U+4E2D   E4 B8 AD    Lo -       CJK UNIFIED IDEOGRAPH-4E2D"
+        );
+        assert_eq!(row('🦀'), "U+1F980  F0 9F A6 80 So -       CRAB");
+        assert_eq!(
+            row('\u{301}'),
+            "U+0301   CC 81       Mn -       COMBINING ACUTE ACCENT"
+        );
+        assert_eq!(row('\t'), "U+0009   09          Cc control <control-0009>");
+        assert_eq!(
+            row('\u{85}'),
+            "U+0085   C2 85       Cc control <control-0085>"
+        );
+    }
+
+    #[test]
+    fn test_unnamed() {
+        // characters without a name get a label for what they are
+        assert_eq!(describe('\u{e000}').name, "<private-use-E000>");
+        assert_eq!(describe('\u{e000}').category, "Co");
+        assert_eq!(describe('\u{fdd0}').name, "<noncharacter-FDD0>");
+        assert_eq!(describe('\u{10ffff}').name, "<noncharacter-10FFFF>");
+        assert_eq!(describe('\u{378}').name, "<reserved-0378>");
+        assert_eq!(describe('\u{378}').category, "Cn");
+    }
+
+    #[test]
+    fn test_codepoint() {
+        assert_eq!(codepoint('\0'), "U+0000");
+        assert_eq!(codepoint('é'), "U+00E9");
+        assert_eq!(codepoint('\u{10ffff}'), "U+10FFFF");
+        assert_eq!(utf8_hex('é'), "C3 A9");
+        assert_eq!(utf8_hex('€'), "E2 82 AC");
+    }
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("U+0000..U+007F"), Ok(0..=0x7f));
+        assert_eq!(parse_range("u+41..5a"), Ok(0x41..=0x5a));
+        assert_eq!(parse_range("1F980..1F980"), Ok(0x1f980..=0x1f980));
+        assert_eq!(parse_codepoint("10FFFF"), Some(0x10ffff));
+        assert_eq!(parse_codepoint("110000"), None);
+        assert_eq!(parse_codepoint("+41"), None);
+        assert_eq!(parse_codepoint("U+"), None);
+        for bad in [
+            "",
+            "U+0041",
+            "U+0041..",
+            "..U+0041",
+            "U+0041-U+0042",
+            "x..y",
+        ] {
+            assert_eq!(
+                parse_range(bad),
+                Err(format!(
+                    "invalid range '{bad}', expected one such as U+0000..U+007F"
+                ))
+            );
+        }
+        assert_eq!(
+            parse_range("U+0042..U+0041"),
+            Err("invalid range 'U+0042..U+0041', which ends before it starts".to_string())
+        );
+    }
+}
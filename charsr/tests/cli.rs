@@ -0,0 +1,251 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+
+const PRG: &str = "charsr";
+
+// --------------------------------------------------
+/// Runs charsr with `args` and `stdin`, and returns what it printed.
+fn run(args: &[&str], stdin: &str) -> Result<String> {
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(stdin)
+        .output()?;
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+// --------------------------------------------------
+#[test]
+fn ascii() -> Result<()> {
+    assert_eq!(
+        run(&["a1 "], "")?,
+        concat!(
+            "U+0061   61          Ll -       LATIN SMALL LETTER A\n",
+            "U+0031   31          Nd -       DIGIT ONE\n",
+            "U+0020   20          Zs -       SPACE\n",
+        )
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn cjk() -> Result<()> {
+    assert_eq!(
+        run(&["中文"], "")?,
+        concat!(
+            "U+4E2D   E4 B8 AD    Lo -       CJK UNIFIED IDEOGRAPH-4E2D\n",
+            "U+6587   E6 96 87    Lo -       CJK UNIFIED IDEOGRAPH-6587\n",
+        )
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn emoji() -> Result<()> {
+    // a flag is two regional indicators, each described on its own
+    assert_eq!(
+        run(&["🦀🇫🇷"], "")?,
+        concat!(
+            "U+1F980  F0 9F A6 80 So -       CRAB\n",
+            "U+1F1EB  F0 9F 87 AB So -       REGIONAL INDICATOR SYMBOL LETTER F\n",
+            "U+1F1F7  F0 9F 87 B7 So -       REGIONAL INDICATOR SYMBOL LETTER R\n",
+        )
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn combining_mark() -> Result<()> {
+    assert_eq!(
+        run(&["e\u{301}"], "")?,
+        concat!(
+            "U+0065   65          Ll -       LATIN SMALL LETTER E\n",
+            "U+0301   CC 81       Mn -       COMBINING ACUTE ACCENT\n",
+        )
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn control() -> Result<()> {
+    assert_eq!(
+        run(&["\t\u{1b}\u{7f}"], "")?,
+        concat!(
+            "U+0009   09          Cc control <control-0009>\n",
+            "U+001B   1B          Cc control <control-001B>\n",
+            "U+007F   7F          Cc control <control-007F>\n",
+        )
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn several_args() -> Result<()> {
+    // the arguments are described one after another, with nothing between
+    assert_eq!(
+        run(&["a", "b"], "")?,
+        concat!(
+            "U+0061   61          Ll -       LATIN SMALL LETTER A\n",
+            "U+0062   62          Ll -       LATIN SMALL LETTER B\n",
+        )
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin() -> Result<()> {
+    assert_eq!(
+        run(&[], "é\n")?,
+        concat!(
+            "U+00E9   C3 A9       Ll -       LATIN SMALL LETTER E WITH ACUTE\n",
+            "U+000A   0A          Cc control <control-000A>\n",
+        )
+    );
+    assert_eq!(run(&[], "")?, "");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_invalid_utf8() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .write_stdin(&b"\xff"[..])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("valid UTF-8"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn json() -> Result<()> {
+    assert_eq!(
+        run(&["--json"], "A中🦀\u{301}\0")?,
+        concat!(
+            r#"{"char":"A","codepoint":"U+0041","utf8":"41","category":"Lu","name":"LATIN CAPITAL LETTER A","control":false}"#,
+            "\n",
+            r#"{"char":"中","codepoint":"U+4E2D","utf8":"E4 B8 AD","category":"Lo","name":"CJK UNIFIED IDEOGRAPH-4E2D","control":false}"#,
+            "\n",
+            r#"{"char":"🦀","codepoint":"U+1F980","utf8":"F0 9F A6 80","category":"So","name":"CRAB","control":false}"#,
+            "\n",
+            r#"{"char":"́","codepoint":"U+0301","utf8":"CC 81","category":"Mn","name":"COMBINING ACUTE ACCENT","control":false}"#,
+            "\n",
+            r#"{"char":"\u0000","codepoint":"U+0000","utf8":"00","category":"Cc","name":"<control-0000>","control":true}"#,
+            "\n",
+        )
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn only_codepoints() -> Result<()> {
+    assert_eq!(
+        run(&["--only-codepoints", "a中🦀\u{301}\t"], "")?,
+        "U+0061 U+4E2D U+1F980 U+0301 U+0009\n"
+    );
+    assert_eq!(run(&["--only-codepoints"], "")?, "");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn range() -> Result<()> {
+    assert_eq!(
+        run(&["--range", "U+0041..U+0043"], "")?,
+        concat!(
+            "U+0041   41          Lu -       LATIN CAPITAL LETTER A\n",
+            "U+0042   42          Lu -       LATIN CAPITAL LETTER B\n",
+            "U+0043   43          Lu -       LATIN CAPITAL LETTER C\n",
+        )
+    );
+    // standard input is not read
+    assert_eq!(
+        run(&["--range", "41..41", "--only-codepoints"], "xyz")?,
+        "U+0041\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn range_ascii() -> Result<()> {
+    let out = run(&["--range", "U+0000..U+007F"], "")?;
+    let lines: Vec<_> = out.lines().collect();
+    assert_eq!(lines.len(), 128);
+    assert_eq!(lines[0], "U+0000   00          Cc control <control-0000>");
+    assert_eq!(lines[0x7e], "U+007E   7E          Sm -       TILDE");
+    assert_eq!(lines.iter().filter(|l| l.contains(" Cc ")).count(), 33);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn range_skips_surrogates() -> Result<()> {
+    assert_eq!(
+        run(&["--range", "U+D7FF..U+E000", "--only-codepoints"], "")?,
+        "U+D7FF U+E000\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_range() -> Result<()> {
+    for (range, expected) in [
+        ("U+0041", "expected one such as U+0000..U+007F"),
+        ("U+0041..U+110000", "expected one such as U+0000..U+007F"),
+        ("U+0042..U+0041", "which ends before it starts"),
+    ] {
+        Command::cargo_bin(PRG)?
+            .args(["--range", range])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "invalid value '{range}' for '--range <FROM..TO>': invalid range '{range}', {expected}"
+            )));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_range_with_text() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--range", "U+0041..U+0042", "abc"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_json_with_only_codepoints() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--json", "--only-codepoints", "a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use cli_utils::{open, parse_args};
+use std::io::{self, BufRead, BufReader};
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    /// Files to sum; standard input, printed without a name, if none
+    #[arg(value_name = "FILE")]
+    files: Vec<String>,
+
+    /// Read sums from the FILEs and check them
+    #[arg(short('c'), long)]
+    check: bool,
+}
+
+/// The table for the CRC of POSIX `cksum`, whose polynomial is 0x04C11DB7
+/// taken most significant bit first. The `crc32fast` crate computes the
+/// CRC-32 of zlib instead, with the bits reflected, which gives different
+/// sums, so this one is done here.
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = (i as u32) << 24;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 0x8000_0000 != 0 {
+                (crc << 1) ^ 0x04C1_1DB7
+            } else {
+                crc << 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+fn crc_update(crc: u32, bytes: &[u8]) -> u32 {
+    bytes.iter().fold(crc, |crc, &byte| {
+        (crc << 8) ^ CRC_TABLE[((crc >> 24) as u8 ^ byte) as usize]
+    })
+}
+
+/// Returns the CRC and the length of everything `file` holds. As POSIX
+/// has it, the length goes into the CRC after the data, least significant
+/// byte first and in as few bytes as it takes, and the result is
+/// complemented.
+fn cksum(mut file: impl BufRead) -> Result<(u32, u64)> {
+    let mut crc = 0;
+    let mut len: u64 = 0;
+    loop {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        crc = crc_update(crc, buf);
+        len += buf.len() as u64;
+        let n = buf.len();
+        file.consume(n);
+    }
+    let mut rest = len;
+    while rest > 0 {
+        crc = crc_update(crc, &[rest as u8]);
+        rest >>= 8;
+    }
+    Ok((!crc, len))
+}
+
+/// Splits a line as `cksum` prints it into the CRC, the length and the
+/// filename.
+fn parse_line(line: &str) -> Option<(u32, u64, &str)> {
+    let (crc, rest) = line.split_once(' ')?;
+    let (len, filename) = rest.split_once(' ')?;
+    let all_digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    if !all_digits(crc) || !all_digits(len) || filename.is_empty() {
+        return None;
+    }
+    Some((crc.parse().ok()?, len.parse().ok()?, filename))
+}
+
+/// Checks the files listed in `list` against their sums, and returns
+/// whether every one of them matched.
+fn check(list: &str) -> Result<bool> {
+    let file = open(list).map_err(|e| anyhow!("{list}: {e}"))?;
+    let mut all_ok = true;
+    let mut checked = 0;
+    for (line_num, line) in file.lines().enumerate() {
+        let line = line?;
+        let Some((crc, len, filename)) = parse_line(&line) else {
+            eprintln!(
+                "{list}: {}: improperly formatted checksum line",
+                line_num + 1
+            );
+            continue;
+        };
+        checked += 1;
+        match open(filename).and_then(cksum) {
+            Err(e) => {
+                eprintln!("{filename}: {e}");
+                eprintln!("{filename}: FAILED open or read");
+                all_ok = false;
+            }
+            Ok(sum) if sum == (crc, len) => println!("{filename}: OK"),
+            Ok(_) => {
+                eprintln!("{filename}: FAILED");
+                all_ok = false;
+            }
+        }
+    }
+    if checked == 0 {
+        bail!("{list}: no properly formatted checksum lines found");
+    }
+    Ok(all_ok)
+}
+
+/// Prints the sum of each file, or checks the sums listed in each, and
+/// returns whether all went well.
+fn run(args: Args) -> Result<bool> {
+    if args.files.is_empty() {
+        if args.check {
+            return check("-");
+        }
+        let (crc, len) = cksum(BufReader::new(io::stdin()))?;
+        println!("{crc} {len}");
+        return Ok(true);
+    }
+    let mut all_ok = true;
+    for filename in &args.files {
+        if args.check {
+            match check(filename) {
+                Err(e) => {
+                    eprintln!("{e}");
+                    all_ok = false;
+                }
+                Ok(ok) => all_ok &= ok,
+            }
+            continue;
+        }
+        match open(filename).and_then(cksum) {
+            Err(e) => {
+                eprintln!("{filename}: {e}");
+                all_ok = false;
+            }
+            Ok((crc, len)) => println!("{crc} {len} {filename}"),
+        }
+    }
+    Ok(all_ok)
+}
+
+fn main() -> ExitCode {
+    match run(parse_args()) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cksum, parse_line};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_cksum() {
+        // as GNU cksum prints them
+        for (text, expected) in [
+            ("", (4294967295, 0)),
+            ("a", (1220704766, 1)),
+            ("abc", (1219131554, 3)),
+            ("123456789", (930766865, 9)),
+        ] {
+            assert_eq!(cksum(Cursor::new(text)).unwrap(), expected, "{text}");
+        }
+        // a length of several bytes, read in small pieces
+        let text = "0123456789".repeat(10_000);
+        let small = std::io::BufReader::with_capacity(7, Cursor::new(&text));
+        assert_eq!(cksum(small).unwrap(), (4002145000, 100_000));
+    }
+
+    #[test]
+    fn test_parse_line() {
+        assert_eq!(
+            parse_line("3040684256 45 a.txt"),
+            Some((3040684256, 45, "a.txt"))
+        );
+        assert_eq!(parse_line("1 2 a b"), Some((1, 2, "a b")));
+        assert_eq!(parse_line("3040684256 45"), None);
+        assert_eq!(parse_line("3040684256 45 "), None);
+        assert_eq!(parse_line("4294967296 45 a.txt"), None);
+        assert_eq!(parse_line("-1 45 a.txt"), None);
+        assert_eq!(parse_line("+1 45 a.txt"), None);
+        assert_eq!(parse_line("x 45 a.txt"), None);
+        assert_eq!(parse_line(""), None);
+    }
+}
@@ -0,0 +1,500 @@
+//! Line, word, byte and character counts as `wcr` reports them, for any
+//! tool that wants to count its input.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    ops::Add,
+    path::Path,
+};
+
+use anyhow::Result;
+use memchr::memchr_iter;
+#[cfg(feature = "sentences")]
+use regex::Regex;
+#[cfg(feature = "sentences")]
+use std::sync::LazyLock;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The counts for one input, or for several added together.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct FileInfo {
+    pub num_lines: usize,
+    pub num_words: usize,
+    pub num_bytes: usize,
+    pub num_chars: usize,
+    /// Extended grapheme clusters, what a reader would call characters.
+    pub num_graphemes: usize,
+    /// The most characters on a line, not counting its line ending.
+    pub max_line_length: usize,
+    /// Runs of non-blank lines between blank ones.
+    pub num_paragraphs: usize,
+    /// The characters in all the words, for their average length.
+    pub total_word_chars: usize,
+    /// Sentence endings, as `count_sentences` finds them, with the
+    /// `sentences` feature; otherwise always 0.
+    pub num_sentences: usize,
+}
+
+impl FileInfo {
+    /// The mean number of characters in a word, or 0 when there are no
+    /// words. For a total, this is weighted by the words in each input.
+    ///
+    /// ```
+    /// use cli_utils::count::{Counts, FileInfo, count_reader};
+    ///
+    /// let info = count_reader("one three\n".as_bytes(), Counts::ALL).unwrap();
+    /// assert_eq!(info.avg_word_length(), 4.0);
+    ///
+    /// let total = info + count_reader("a\n".as_bytes(), Counts::ALL).unwrap();
+    /// assert_eq!(total.avg_word_length(), 3.0);
+    ///
+    /// assert_eq!(FileInfo::default().avg_word_length(), 0.0);
+    /// ```
+    pub fn avg_word_length(&self) -> f64 {
+        if self.num_words == 0 {
+            0.0
+        } else {
+            self.total_word_chars as f64 / self.num_words as f64
+        }
+    }
+}
+
+/// Combines the counts of two inputs as a running total: everything is
+/// summed, except that the longest line is the longer of the two.
+///
+/// ```
+/// use cli_utils::count::{Counts, FileInfo, count_reader};
+///
+/// let inputs = ["one line\n", "and two\nmore lines\n"];
+/// let total = inputs
+///     .iter()
+///     .map(|text| count_reader(text.as_bytes(), Counts::ALL).unwrap())
+///     .fold(FileInfo::default(), |total, info| total + info);
+/// assert_eq!(total.num_lines, 3);
+/// assert_eq!(total.num_words, 6);
+/// assert_eq!(total.num_bytes, 28);
+/// assert_eq!(total.max_line_length, 10);
+/// assert_eq!(total.num_paragraphs, 2);
+/// ```
+impl Add for FileInfo {
+    type Output = FileInfo;
+
+    fn add(self, other: FileInfo) -> FileInfo {
+        FileInfo {
+            num_lines: self.num_lines + other.num_lines,
+            num_words: self.num_words + other.num_words,
+            num_bytes: self.num_bytes + other.num_bytes,
+            num_chars: self.num_chars + other.num_chars,
+            num_graphemes: self.num_graphemes + other.num_graphemes,
+            max_line_length: self.max_line_length.max(other.max_line_length),
+            num_paragraphs: self.num_paragraphs + other.num_paragraphs,
+            total_word_chars: self.total_word_chars + other.total_word_chars,
+            num_sentences: self.num_sentences + other.num_sentences,
+        }
+    }
+}
+
+/// The counts `count_reader` makes beyond lines, words, bytes and
+/// characters. Each is another look at every line, and sentences and
+/// graphemes cost far more than the rest, so only those asked for are
+/// made; the others are left at 0.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Counts {
+    pub graphemes: bool,
+    pub max_line_length: bool,
+    pub paragraphs: bool,
+    /// The characters in all the words, for their average length.
+    pub word_chars: bool,
+    /// Only made with the `sentences` feature.
+    pub sentences: bool,
+}
+
+impl Counts {
+    /// Every count there is.
+    pub const ALL: Counts = Counts {
+        graphemes: true,
+        max_line_length: true,
+        paragraphs: true,
+        word_chars: true,
+        sentences: true,
+    };
+}
+
+/// Counts what `reader` holds, which must be valid UTF-8, making the
+/// `counts` asked for along with lines, words, bytes and characters.
+///
+/// ```
+/// use cli_utils::count::{Counts, count_reader};
+///
+/// let text = "The quick brown fox\n\njumps\n";
+/// let info = count_reader(text.as_bytes(), Counts::ALL).unwrap();
+/// assert_eq!(info.num_lines, 3);
+/// assert_eq!(info.num_words, 5);
+/// assert_eq!(info.num_bytes, 27);
+/// assert_eq!(info.num_paragraphs, 2);
+///
+/// let info = count_reader(text.as_bytes(), Counts::default()).unwrap();
+/// assert_eq!(info.num_words, 5);
+/// assert_eq!(info.num_paragraphs, 0);
+///
+/// assert!(count_reader(&[0xff, b'\n'][..], Counts::ALL).is_err());
+/// ```
+pub fn count_reader(mut reader: impl BufRead, counts: Counts) -> Result<FileInfo> {
+    let mut info = FileInfo::default();
+    let mut previous_blank = true;
+    let mut line = String::new();
+
+    loop {
+        let line_bytes = reader.read_line(&mut line)?;
+        if line_bytes == 0 {
+            break;
+        }
+        info.num_bytes += line_bytes;
+        info.num_lines += 1;
+        if counts.word_chars {
+            for word in line.split_whitespace() {
+                info.num_words += 1;
+                info.total_word_chars += word.chars().count();
+            }
+        } else {
+            info.num_words += line.split_whitespace().count();
+        }
+        info.num_chars += line.chars().count();
+        #[cfg(feature = "sentences")]
+        if counts.sentences {
+            info.num_sentences += count_sentences(&line);
+        }
+        if counts.graphemes {
+            info.num_graphemes += line.graphemes(true).count();
+        }
+        if counts.max_line_length {
+            info.max_line_length = info
+                .max_line_length
+                .max(line.trim_end_matches(['\r', '\n']).chars().count());
+        }
+        if counts.paragraphs {
+            // a paragraph starts at each non-blank line after a blank one
+            let blank = line.trim().is_empty();
+            if previous_blank && !blank {
+                info.num_paragraphs += 1;
+            }
+            previous_blank = blank;
+        }
+        line.clear();
+    }
+
+    Ok(info)
+}
+
+/// Counts the NUL-terminated records `reader` holds, which needn't be
+/// UTF-8, as `num_lines`. Words are separated by whitespace or NULs. Only
+/// records, words and bytes are counted; as with lines, a last record
+/// without a NUL still counts.
+///
+/// ```
+/// use cli_utils::count::count_records;
+///
+/// let info = count_records(&b"one two\0three\nfour\0\xff five"[..]).unwrap();
+/// assert_eq!(info.num_lines, 3);
+/// assert_eq!(info.num_words, 6);
+/// assert_eq!(info.num_bytes, 25);
+/// ```
+pub fn count_records(mut reader: impl BufRead) -> Result<FileInfo> {
+    let mut info = FileInfo::default();
+    let mut record = vec![];
+    loop {
+        let record_bytes = reader.read_until(b'\0', &mut record)?;
+        if record_bytes == 0 {
+            break;
+        }
+        info.num_bytes += record_bytes;
+        info.num_lines += 1;
+        info.num_words += record
+            .split(|b| b.is_ascii_whitespace() || *b == b'\0')
+            .filter(|word| !word.is_empty())
+            .count();
+        record.clear();
+    }
+    Ok(info)
+}
+
+/// Counts only the lines and bytes of `data`, such as a file mapped into
+/// memory. Nothing is decoded, so `data` needn't be UTF-8, and the other
+/// counts are left at 0. As with `count_reader`, a last line without a
+/// newline still counts.
+///
+/// ```
+/// use cli_utils::count::count_mmap;
+///
+/// let info = count_mmap(b"one\n\xfftwo\nthree");
+/// assert_eq!(info.num_lines, 3);
+/// assert_eq!(info.num_bytes, 14);
+/// assert_eq!(info.num_words, 0);
+/// ```
+pub fn count_mmap(data: &[u8]) -> FileInfo {
+    FileInfo {
+        num_lines: memchr_iter(b'\n', data).count()
+            + usize::from(data.last().is_some_and(|&b| b != b'\n')),
+        num_bytes: data.len(),
+        ..FileInfo::default()
+    }
+}
+
+/// Counts the file at `path`, making the `counts` asked for.
+///
+/// ```
+/// use cli_utils::count::{Counts, count_path};
+///
+/// let info = count_path("Cargo.toml".as_ref(), Counts::ALL).unwrap();
+/// assert!(info.num_lines > 0);
+///
+/// assert!(count_path("no/such/file".as_ref(), Counts::ALL).is_err());
+/// ```
+pub fn count_path(path: &Path, counts: Counts) -> Result<FileInfo> {
+    count_reader(BufReader::new(File::open(path)?), counts)
+}
+
+/// Words that end in a period without ending a sentence, in lowercase and
+/// without that last period.
+#[cfg(feature = "sentences")]
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "vs", "etc", "e.g", "i.e", "cf",
+];
+
+/// A run of `.`, `?` or `!`, perhaps followed by closing quotes or
+/// brackets, then by whitespace or the end of the text, along with the word
+/// just before it.
+#[cfg(feature = "sentences")]
+static SENTENCE_END: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"([\w.'’]*)([.?!]+)["'”’)\]]*(?:\s|$)"#).unwrap());
+
+/// Counts the sentences that end in `text`. A period after one of a few
+/// common abbreviations, such as `Mr.` or `etc.`, doesn't end a sentence,
+/// even where it really does.
+///
+/// ```
+/// use cli_utils::count::count_sentences;
+///
+/// assert_eq!(count_sentences("Hi. How are you? Fine!"), 3);
+/// assert_eq!(count_sentences("Dr. Who met Mr. Smith at 3.30 today."), 1);
+/// assert_eq!(count_sentences("no ending"), 0);
+/// ```
+#[cfg(feature = "sentences")]
+pub fn count_sentences(text: &str) -> usize {
+    SENTENCE_END
+        .captures_iter(text)
+        .filter(|caps| &caps[2] != "." || !ABBREVIATIONS.contains(&caps[1].to_lowercase().as_str()))
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "sentences")]
+    use super::count_sentences;
+    use super::{Counts, FileInfo, count_mmap, count_reader, count_records};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_count() {
+        let text = "I don't want the word.\nI just want your half.\r\n";
+        let info = count_reader(Cursor::new(text), Counts::ALL);
+        assert!(info.is_ok());
+        let expected = FileInfo {
+            num_lines: 2,
+            num_words: 10,
+            num_chars: 47,
+            num_bytes: 47,
+            num_graphemes: 46,
+            max_line_length: 22,
+            num_paragraphs: 1,
+            total_word_chars: 36,
+            num_sentences: if cfg!(feature = "sentences") { 2 } else { 0 },
+        };
+        assert_eq!(info.unwrap(), expected);
+    }
+
+    #[test]
+    fn test_count_only_asked_for() {
+        let text = "Hi there. Bye!\n\ncafe\u{301}\n";
+        let all = count_reader(Cursor::new(text), Counts::ALL).unwrap();
+        let info = count_reader(Cursor::new(text), Counts::default()).unwrap();
+        assert_eq!(
+            info,
+            FileInfo {
+                num_lines: all.num_lines,
+                num_words: all.num_words,
+                num_bytes: all.num_bytes,
+                num_chars: all.num_chars,
+                ..FileInfo::default()
+            }
+        );
+
+        let graphemes = Counts {
+            graphemes: true,
+            ..Counts::default()
+        };
+        let info = count_reader(Cursor::new(text), graphemes).unwrap();
+        assert_eq!(info.num_graphemes, all.num_graphemes);
+        assert_eq!(info.num_paragraphs, 0);
+    }
+
+    #[test]
+    fn test_count_mmap() {
+        // lines and bytes agree with count_reader, with or without a final
+        // newline
+        for text in [
+            "",
+            "\n",
+            "a",
+            "one\ntwo",
+            "I don't want the word.\nI just want your half.\r\n",
+        ] {
+            let expected = count_reader(Cursor::new(text), Counts::ALL).unwrap();
+            let info = count_mmap(text.as_bytes());
+            assert_eq!(info.num_lines, expected.num_lines, "{text:?}");
+            assert_eq!(info.num_bytes, expected.num_bytes, "{text:?}");
+        }
+        assert_eq!(count_mmap(b"\xff\n\x00\n").num_lines, 2);
+    }
+
+    #[test]
+    fn test_count_records() {
+        let blob = b"one two\0three\nfour\0\xff\xfe five\0";
+        let info = count_records(Cursor::new(blob)).unwrap();
+        let expected = FileInfo {
+            num_lines: 3,
+            num_words: 6,
+            num_bytes: 27,
+            ..FileInfo::default()
+        };
+        assert_eq!(info, expected);
+
+        // empty records count, but hold no words
+        let info = count_records(Cursor::new(b"\0\0 \0")).unwrap();
+        assert_eq!((info.num_lines, info.num_words, info.num_bytes), (3, 0, 4));
+        assert_eq!(
+            count_records(Cursor::new(b"")).unwrap(),
+            FileInfo::default()
+        );
+    }
+
+    #[test]
+    fn test_count_graphemes() {
+        // a ZWJ family emoji is one grapheme built from five scalar values
+        let info = count_reader(Cursor::new("👨\u{200d}👩\u{200d}👧"), Counts::ALL).unwrap();
+        assert_eq!(info.num_graphemes, 1);
+        assert_eq!(info.num_chars, 5);
+        assert_eq!(info.num_bytes, 18);
+
+        // "e" followed by a combining acute accent, and CRLF, are one each
+        let info = count_reader(Cursor::new("cafe\u{301}\r\n"), Counts::ALL).unwrap();
+        assert_eq!(info.num_graphemes, 5);
+        assert_eq!(info.num_chars, 7);
+        assert_eq!(info.num_bytes, 8);
+    }
+
+    #[test]
+    fn test_count_max_line_length() {
+        // line endings are not part of the line length
+        let text = "ab\r\n\nabcdé\nabc";
+        let info = count_reader(Cursor::new(text), Counts::ALL).unwrap();
+        assert_eq!(info.max_line_length, 5);
+
+        let info = count_reader(Cursor::new(""), Counts::ALL).unwrap();
+        assert_eq!(info.max_line_length, 0);
+    }
+
+    #[test]
+    fn test_count_paragraphs() {
+        let count = |text| {
+            count_reader(Cursor::new(text), Counts::ALL)
+                .unwrap()
+                .num_paragraphs
+        };
+        assert_eq!(count("a\nb\n\nc\n\n\n\nd\n"), 3);
+
+        // blank lines at either end don't start or end a paragraph, and a
+        // line of only whitespace counts as blank
+        assert_eq!(count("\n \t\na\n  \nb\n\n"), 2);
+        assert_eq!(count("no blank lines\nat all"), 1);
+        assert_eq!(count(""), 0);
+        assert_eq!(count("\n\n"), 0);
+    }
+
+    #[test]
+    fn test_count_word_chars() {
+        // words are counted in characters, not bytes
+        let info = count_reader(Cursor::new("ab  çé\tf\n\nghij"), Counts::ALL).unwrap();
+        assert_eq!(info.num_words, 4);
+        assert_eq!(info.total_word_chars, 9);
+        assert_eq!(info.avg_word_length(), 2.25);
+
+        let info = count_reader(Cursor::new(" \n\t"), Counts::ALL).unwrap();
+        assert_eq!(info.total_word_chars, 0);
+        assert_eq!(info.avg_word_length(), 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "sentences")]
+    fn test_count_sentences() {
+        let text = "Mr. and Mrs. Dursley, of number four, Privet Drive, were proud \
+            to say that they were perfectly normal, thank you very much. They \
+            brought apples, pears, etc. from the market, i.e. from Dr. Brown! \
+            \"Was it Prof. Plum?\" Yes. Maybe... (It was.) ";
+        assert_eq!(count_sentences(text), 6);
+
+        // a period inside a word or number ends nothing
+        assert_eq!(count_sentences("see example.com or v1.2.3"), 0);
+        assert_eq!(count_sentences("Really?! Yes."), 2);
+        assert_eq!(count_sentences("MR. Jones.\n"), 1);
+        assert_eq!(count_sentences(""), 0);
+    }
+
+    #[test]
+    #[cfg(feature = "sentences")]
+    fn test_count_sentences_lines() {
+        // a sentence is counted on the line where it ends
+        let info = count_reader(
+            Cursor::new("One sentence\nover two lines. Two\nmore."),
+            Counts::ALL,
+        )
+        .unwrap();
+        assert_eq!(info.num_sentences, 2);
+    }
+
+    #[test]
+    fn test_add() {
+        let a = FileInfo {
+            num_lines: 1,
+            num_words: 2,
+            num_bytes: 3,
+            num_chars: 4,
+            num_graphemes: 5,
+            max_line_length: 6,
+            num_paragraphs: 7,
+            total_word_chars: 8,
+            num_sentences: 9,
+        };
+        let b = FileInfo {
+            max_line_length: 2,
+            ..a
+        };
+        assert_eq!(
+            a + b,
+            FileInfo {
+                num_lines: 2,
+                num_words: 4,
+                num_bytes: 6,
+                num_chars: 8,
+                num_graphemes: 10,
+                max_line_length: 6,
+                num_paragraphs: 14,
+                total_word_chars: 16,
+                num_sentences: 18,
+            }
+        );
+        assert_eq!(a + FileInfo::default(), a);
+        assert_eq!(FileInfo::default() + a, a);
+    }
+}
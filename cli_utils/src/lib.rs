@@ -0,0 +1,211 @@
+//! Helpers shared by the command-line tools in this repository.
+
+use std::{
+    env,
+    ffi::OsString,
+    fmt::Display,
+    fs::File,
+    io::{self, BufRead, BufReader},
+    process,
+};
+
+use anyhow::{Result, anyhow, bail};
+use clap::{Arg, Parser, value_parser};
+use clap_complete::{Shell, generate};
+
+pub mod count;
+pub mod schema;
+
+/// Opens `filename` for buffered reading, with `-` standing for stdin.
+///
+/// ```
+/// use std::io::BufRead;
+///
+/// let mut file = cli_utils::open("Cargo.toml").unwrap();
+/// let mut line = String::new();
+/// file.read_line(&mut line).unwrap();
+/// assert_eq!(line, "[package]\n");
+///
+/// assert!(cli_utils::open("no/such/file").is_err());
+/// ```
+pub fn open(filename: &str) -> Result<Box<dyn BufRead>> {
+    match filename {
+        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
+        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    }
+}
+
+/// Right-aligns `value` in an 8-column field, or returns an empty string
+/// when the field is not shown.
+///
+/// ```
+/// use cli_utils::format_count;
+///
+/// assert_eq!(format_count(42, true), "      42");
+/// assert_eq!(format_count("1.5Ki", true), "   1.5Ki");
+/// assert_eq!(format_count(42, false), "");
+/// ```
+pub fn format_count(value: impl Display, show: bool) -> String {
+    if show {
+        format!("{value:>8}")
+    } else {
+        "".to_string()
+    }
+}
+
+/// Tab stops as given to `-t`: a single size for a stop every N columns,
+/// or a list of the columns to stop at, after the last of which there are
+/// no more stops. Columns count from 0.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TabStops {
+    Every(usize),
+    List(Vec<usize>),
+}
+
+impl TabStops {
+    /// Parses a tab size or an ascending list of columns, separated by
+    /// commas or blanks, the way GNU `expand` reads `-t`.
+    ///
+    /// ```
+    /// use cli_utils::TabStops;
+    ///
+    /// assert_eq!(TabStops::parse("4").unwrap(), TabStops::Every(4));
+    /// assert_eq!(TabStops::parse("2,5 9").unwrap(), TabStops::List(vec![2, 5, 9]));
+    /// assert_eq!(
+    ///     TabStops::parse("0").unwrap_err().to_string(),
+    ///     "tab size cannot be 0"
+    /// );
+    /// assert_eq!(
+    ///     TabStops::parse("4,2").unwrap_err().to_string(),
+    ///     "tab sizes must be ascending"
+    /// );
+    /// assert_eq!(
+    ///     TabStops::parse("4,x").unwrap_err().to_string(),
+    ///     "invalid tab size: 'x'"
+    /// );
+    /// ```
+    pub fn parse(list: &str) -> Result<TabStops> {
+        let stops = list
+            .split([',', ' ', '\t'])
+            .filter(|stop| !stop.is_empty())
+            .map(|stop| {
+                stop.parse::<usize>()
+                    .map_err(|_| anyhow!("invalid tab size: '{stop}'"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        match stops[..] {
+            [] => bail!("invalid tab size: '{list}'"),
+            [0, ..] => bail!("tab size cannot be 0"),
+            [size] => Ok(TabStops::Every(size)),
+            _ if stops.windows(2).any(|pair| pair[0] >= pair[1]) => {
+                bail!("tab sizes must be ascending")
+            }
+            _ => Ok(TabStops::List(stops)),
+        }
+    }
+
+    /// The first stop after `column`, or `None` once a list has run out.
+    ///
+    /// ```
+    /// use cli_utils::TabStops;
+    ///
+    /// assert_eq!(TabStops::Every(8).next_stop(0), Some(8));
+    /// assert_eq!(TabStops::Every(8).next_stop(13), Some(16));
+    ///
+    /// let stops = TabStops::List(vec![2, 5]);
+    /// assert_eq!(stops.next_stop(0), Some(2));
+    /// assert_eq!(stops.next_stop(2), Some(5));
+    /// assert_eq!(stops.next_stop(5), None);
+    /// ```
+    pub fn next_stop(&self, column: usize) -> Option<usize> {
+        match self {
+            TabStops::Every(size) => Some(column + size - column % size),
+            TabStops::List(stops) => stops
+                .get(stops.partition_point(|&stop| stop <= column))
+                .copied(),
+        }
+    }
+}
+
+/// Parses the command line into `A`, after first answering a hidden
+/// `--completions SHELL` flag by printing that shell's completion script
+/// and exiting.
+///
+/// The flag is checked before the other arguments are validated, so tools
+/// with required arguments can still generate their scripts.
+///
+/// ```no_run
+/// use clap::Parser;
+///
+/// #[derive(Parser)]
+/// struct Args {
+///     file: String,
+/// }
+///
+/// // `prog --completions bash` prints a script and exits here
+/// let args: Args = cli_utils::parse_args();
+/// ```
+pub fn parse_args<A: Parser>() -> A {
+    parse_args_from(env::args_os())
+}
+
+/// Like `parse_args`, but parses `args`, the first of which is the program
+/// name, rather than the command line. This is for tools that take some of
+/// their arguments out before clap sees the rest.
+///
+/// ```
+/// use clap::Parser;
+///
+/// #[derive(Parser)]
+/// struct Args {
+///     #[arg(short)]
+///     verbose: bool,
+/// }
+///
+/// let args: Args = cli_utils::parse_args_from(["prog", "-v"]);
+/// assert!(args.verbose);
+/// ```
+pub fn parse_args_from<A: Parser>(args: impl IntoIterator<Item = impl Into<OsString>>) -> A {
+    let args: Vec<OsString> = args.into_iter().map(Into::into).collect();
+    let mut cmd = A::command().arg(
+        Arg::new("completions")
+            .long("completions")
+            .value_name("SHELL")
+            .value_parser(value_parser!(Shell))
+            .hide(true),
+    );
+    if let Ok(matches) = cmd.clone().ignore_errors(true).try_get_matches_from(&args)
+        && let Some(&shell) = matches.get_one::<Shell>("completions")
+    {
+        let name = cmd.get_name().to_string();
+        generate(shell, &mut cmd, name, &mut io::stdout());
+        process::exit(0);
+    }
+    let mut matches = cmd
+        .try_get_matches_from_mut(args)
+        .unwrap_or_else(|e| e.exit());
+    A::from_arg_matches_mut(&mut matches).unwrap_or_else(|e| e.format(&mut cmd).exit())
+}
+
+/// Prints the error from a `Result` to stderr and exits with status 1.
+///
+/// Tools call this from `main` with the result of `run`:
+///
+/// ```
+/// use cli_utils::exit_with_error;
+///
+/// fn run() -> anyhow::Result<()> {
+///     Ok(())
+/// }
+///
+/// exit_with_error!(run());
+/// ```
+#[macro_export]
+macro_rules! exit_with_error {
+    ($result:expr) => {
+        if let Err(e) = $result {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+}
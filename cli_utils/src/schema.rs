@@ -0,0 +1,294 @@
+//! The `--machine-readable` output that `grepr`, `wcr`, `uniqr` and `echor`
+//! share, so a script can read any of them the same way.
+//!
+//! As JSON, the output is a single object naming the schema version and
+//! the tool, with one record per result:
+//!
+//! ```json
+//! {"schema_version":1,"tool":"wcr","records":[{"file":"fox.txt","lines":1,"words":9,"bytes":48,"chars":48}]}
+//! ```
+//!
+//! As CSV, it is a header row with the names of the tool's record fields,
+//! then one row per record, with an empty field for a missing value. The
+//! header is printed even when there are no records.
+//!
+//! Every record field is always present, as `null` in JSON when it has no
+//! value. A field is only renamed, removed or given another meaning along
+//! with a new `schema_version`.
+
+use std::io::Write;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+
+/// The version of the schema described here, given as `schema_version` in
+/// JSON output.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The formats `--machine-readable` can print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum MachineFormat {
+    Json,
+    Csv,
+}
+
+/// The results of one run of a tool, named in JSON by `tool`, with the
+/// list of records as `records`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "tool", content = "records", rename_all = "lowercase")]
+pub enum MachineOutput {
+    Grepr(Vec<GreprRecord>),
+    Wcr(Vec<WcrRecord>),
+    Uniqr(Vec<UniqrRecord>),
+    Echor(Vec<EchorRecord>),
+}
+
+/// A matching line, a count of matches, or a file that matched or didn't,
+/// as `grepr`'s other options call for.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GreprRecord {
+    /// The file searched, or none for the total of `--total-count`.
+    pub file: Option<String>,
+    /// The number of the matching line, from 1.
+    pub line_number: Option<usize>,
+    /// The matching line, without its line ending.
+    pub line: Option<String>,
+    /// The number of matches in `file`, with `--count`, or in all of the
+    /// files, with `--total-count`.
+    pub count: Option<usize>,
+}
+
+/// The counts for one file, or for all of them when `file` is `total`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WcrRecord {
+    /// The file counted, or `-` for standard input.
+    pub file: String,
+    /// The number of lines, including a last one without a newline.
+    pub lines: usize,
+    /// The number of whitespace-separated words.
+    pub words: usize,
+    /// The number of bytes.
+    pub bytes: usize,
+    /// The number of Unicode scalar values.
+    pub chars: usize,
+}
+
+/// A run of lines with the same key, which `uniqr` prints as one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UniqrRecord {
+    /// The number of lines in the run.
+    pub count: u64,
+    /// The first line of the run, without its line ending.
+    pub line: String,
+}
+
+/// What `echor` prints.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EchorRecord {
+    /// The text, with escapes expanded when asked for, but without the
+    /// final newline.
+    pub text: String,
+    /// Whether a newline follows `text`, which `-n` and `\c` prevent.
+    pub newline: bool,
+}
+
+/// A record that is a row in CSV output.
+trait Row: Serialize {
+    /// The header of the CSV output, with the name of each field.
+    const FIELDS: &'static [&'static str];
+}
+
+impl Row for GreprRecord {
+    const FIELDS: &'static [&'static str] = &["file", "line_number", "line", "count"];
+}
+
+impl Row for WcrRecord {
+    const FIELDS: &'static [&'static str] = &["file", "lines", "words", "bytes", "chars"];
+}
+
+impl Row for UniqrRecord {
+    const FIELDS: &'static [&'static str] = &["count", "line"];
+}
+
+impl Row for EchorRecord {
+    const FIELDS: &'static [&'static str] = &["text", "newline"];
+}
+
+/// The JSON document, with the version ahead of the tool's output.
+#[derive(Serialize)]
+struct Document<'a> {
+    schema_version: u32,
+    #[serde(flatten)]
+    output: &'a MachineOutput,
+}
+
+fn write_csv<T: Row>(records: &[T], out: impl Write) -> Result<()> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(out);
+    writer.write_record(T::FIELDS)?;
+    for record in records {
+        writer.serialize(record)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+impl MachineOutput {
+    /// Writes the output to `out` in `format`, ending with a newline.
+    ///
+    /// ```
+    /// use cli_utils::schema::{MachineFormat, MachineOutput, UniqrRecord};
+    ///
+    /// let output = MachineOutput::Uniqr(vec![UniqrRecord {
+    ///     count: 2,
+    ///     line: "a, b".to_string(),
+    /// }]);
+    /// let mut json = vec![];
+    /// output.write(MachineFormat::Json, &mut json).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(json).unwrap(),
+    ///     "{\"schema_version\":1,\"tool\":\"uniqr\",\"records\":[{\"count\":2,\"line\":\"a, b\"}]}\n"
+    /// );
+    ///
+    /// let mut csv = vec![];
+    /// output.write(MachineFormat::Csv, &mut csv).unwrap();
+    /// assert_eq!(String::from_utf8(csv).unwrap(), "count,line\n2,\"a, b\"\n");
+    /// ```
+    pub fn write(&self, format: MachineFormat, mut out: impl Write) -> Result<()> {
+        match format {
+            MachineFormat::Json => {
+                let document = Document {
+                    schema_version: SCHEMA_VERSION,
+                    output: self,
+                };
+                serde_json::to_writer(&mut out, &document)?;
+                writeln!(out)?;
+            }
+            MachineFormat::Csv => match self {
+                MachineOutput::Grepr(records) => write_csv(records, out)?,
+                MachineOutput::Wcr(records) => write_csv(records, out)?,
+                MachineOutput::Uniqr(records) => write_csv(records, out)?,
+                MachineOutput::Echor(records) => write_csv(records, out)?,
+            },
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        EchorRecord, GreprRecord, MachineFormat, MachineOutput, Row, UniqrRecord, WcrRecord,
+    };
+
+    fn write(output: &MachineOutput, format: MachineFormat) -> String {
+        let mut out = vec![];
+        output.write(format, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    /// The header csv would take from the fields of `record` itself.
+    fn fields_of<T: Row>(record: &T) -> String {
+        let mut writer = csv::Writer::from_writer(vec![]);
+        writer.serialize(record).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        csv.lines().next().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_fields() {
+        let grepr = GreprRecord {
+            file: None,
+            line_number: None,
+            line: None,
+            count: None,
+        };
+        assert_eq!(fields_of(&grepr), GreprRecord::FIELDS.join(","));
+        let wcr = WcrRecord {
+            file: String::new(),
+            lines: 0,
+            words: 0,
+            bytes: 0,
+            chars: 0,
+        };
+        assert_eq!(fields_of(&wcr), WcrRecord::FIELDS.join(","));
+        let uniqr = UniqrRecord {
+            count: 0,
+            line: String::new(),
+        };
+        assert_eq!(fields_of(&uniqr), UniqrRecord::FIELDS.join(","));
+        let echor = EchorRecord {
+            text: String::new(),
+            newline: false,
+        };
+        assert_eq!(fields_of(&echor), EchorRecord::FIELDS.join(","));
+    }
+
+    #[test]
+    fn test_write_json() {
+        let output = MachineOutput::Grepr(vec![
+            GreprRecord {
+                file: Some("fox.txt".to_string()),
+                line_number: Some(1),
+                line: Some("The quick brown fox".to_string()),
+                count: None,
+            },
+            GreprRecord {
+                file: None,
+                line_number: None,
+                line: None,
+                count: Some(1),
+            },
+        ]);
+        assert_eq!(
+            write(&output, MachineFormat::Json),
+            concat!(
+                r#"{"schema_version":1,"tool":"grepr","records":["#,
+                r#"{"file":"fox.txt","line_number":1,"line":"The quick brown fox","count":null},"#,
+                r#"{"file":null,"line_number":null,"line":null,"count":1}]}"#,
+                "\n"
+            )
+        );
+        assert_eq!(
+            write(&MachineOutput::Wcr(vec![]), MachineFormat::Json),
+            "{\"schema_version\":1,\"tool\":\"wcr\",\"records\":[]}\n"
+        );
+    }
+
+    #[test]
+    fn test_write_csv() {
+        let output = MachineOutput::Grepr(vec![
+            GreprRecord {
+                file: Some("a,b.txt".to_string()),
+                line_number: Some(2),
+                line: Some("say \"hi\"".to_string()),
+                count: None,
+            },
+            GreprRecord {
+                file: None,
+                line_number: None,
+                line: None,
+                count: Some(3),
+            },
+        ]);
+        assert_eq!(
+            write(&output, MachineFormat::Csv),
+            "file,line_number,line,count\n\"a,b.txt\",2,\"say \"\"hi\"\"\",\n,,,3\n"
+        );
+        let output = MachineOutput::Echor(vec![EchorRecord {
+            text: "two\nlines".to_string(),
+            newline: true,
+        }]);
+        assert_eq!(
+            write(&output, MachineFormat::Csv),
+            "text,newline\n\"two\nlines\",true\n"
+        );
+        // the header is there even without records
+        assert_eq!(
+            write(&MachineOutput::Uniqr(vec![]), MachineFormat::Csv),
+            "count,line\n"
+        );
+    }
+}
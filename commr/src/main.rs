@@ -1,10 +1,10 @@
 use crate::Column::*;
 use anyhow::{Result, anyhow, bail};
 use clap::{ArgAction, Parser};
+use cli_utils::{exit_with_error, open, parse_args};
 use std::{
     cmp::Ordering::*,
-    fs::File,
-    io::{self, BufRead, BufReader},
+    io::{self, BufRead},
 };
 
 enum Column<'a> {
@@ -41,22 +41,18 @@ pub struct Args {
     /// Output delimiter
     #[arg(short, long("output-delimiter"), default_value = "\t")]
     delimiter: String,
-}
 
-fn main() {
-    if let Err(e) = run(Args::parse()) {
-        eprintln!("{e}");
-        std::process::exit(1);
-    }
+    /// Fail as soon as an input is found not to be sorted
+    #[arg(long, conflicts_with("nocheck_order"))]
+    check_order: bool,
+
+    /// Do not check that the inputs are sorted, which is the default
+    #[arg(long)]
+    nocheck_order: bool,
 }
 
-fn open(filename: &str) -> Result<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(
-            File::open(filename).map_err(|e| anyhow!("{filename}:{e}"))?,
-        ))),
-    }
+fn main() {
+    exit_with_error!(run(parse_args()));
 }
 
 fn run(args: Args) -> Result<()> {
@@ -95,7 +91,7 @@ fn run(args: Args) -> Result<()> {
     let file1 = &args.file1;
     let file2 = &args.file2;
     if file1 == "-" && file2 == "-" {
-        bail!(r#"Both input files cannot be STDIN("-)"#);
+        bail!(r#"Both input files cannot be STDIN ("-")"#);
     }
 
     let case = |line: String| {
@@ -106,34 +102,52 @@ fn run(args: Args) -> Result<()> {
         }
     };
 
-    let mut lines1 = open(file1)?.lines().map_while(Result::ok).map(case);
-    let mut lines2 = open(file2)?.lines().map_while(Result::ok).map(case);
-    let mut line1 = lines1.next();
-    let mut line2 = lines2.next();
+    let open = |filename: &str| open(filename).map_err(|e| anyhow!("{filename}: {e}"));
+    let mut lines1 = open(file1)?.lines().map(|line| line.map(case));
+    let mut lines2 = open(file2)?.lines().map(|line| line.map(case));
+
+    // With --check-order, a line that sorts before the one above it fails
+    // the run; otherwise unsorted input is compared as it comes
+    let next_line = |lines: &mut dyn Iterator<Item = io::Result<String>>,
+                     previous: &Option<String>,
+                     file_num: usize|
+     -> Result<Option<String>> {
+        let line = lines.next().transpose()?;
+        if let (Some(previous), Some(line)) = (previous, &line)
+            && args.check_order
+            && line < previous
+        {
+            bail!("file {file_num} is not in sorted order");
+        }
+        Ok(line)
+    };
+
+    let mut line1 = next_line(&mut lines1, &None, 1)?;
+    let mut line2 = next_line(&mut lines2, &None, 2)?;
     while line1.is_some() || line2.is_some() {
         match (&line1, &line2) {
             (Some(val1), Some(val2)) => match val1.cmp(val2) {
                 Equal => {
                     print(Col3(val1));
-                    line1 = lines1.next();
-                    line2 = lines2.next();
+                    line1 = next_line(&mut lines1, &line1, 1)?;
+                    line2 = next_line(&mut lines2, &line2, 2)?;
                 }
                 Less => {
                     print(Col1(val1));
-                    line1 = lines1.next();
+                    line1 = next_line(&mut lines1, &line1, 1)?;
                 }
                 Greater => {
                     print(Col2(val2));
-                    line2 = lines2.next();
+                    line2 = next_line(&mut lines2, &line2, 2)?;
                 }
             },
             (Some(val1), None) => {
                 print(Col1(val1));
-                line1 = lines1.next();
+                line1 = next_line(&mut lines1, &line1, 1)?;
             }
             (None, Some(val2)) => {
                 print(Col2(val2));
-                line2 = lines2.next();
+                line2 = next_line(&mut lines2, &line2, 2)?;
             }
             _ => (),
         };
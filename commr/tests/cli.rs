@@ -342,3 +342,83 @@ fn file1_file2_123_delim() -> Result<()> {
 fn blank_file1() -> Result<()> {
     run(&[BLANK, FILE1], "tests/expected/blank_file1.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn disjoint1_disjoint2() -> Result<()> {
+    run(
+        &["tests/inputs/disjoint1.txt", "tests/inputs/disjoint2.txt"],
+        "tests/expected/disjoint1_disjoint2.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn disjoint2_disjoint1() -> Result<()> {
+    run(
+        &["tests/inputs/disjoint2.txt", "tests/inputs/disjoint1.txt"],
+        "tests/expected/disjoint2_disjoint1.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn unsorted_file1() -> Result<()> {
+    // without --check-order, unsorted input is compared as it comes
+    run(&["tests/inputs/foo.txt", FILE1], "tests/expected/foo_file1.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn unsorted_file1_check_order() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--check-order", "tests/inputs/foo.txt", FILE1])
+        .assert()
+        .failure()
+        .stdout("\ta\n\tb\n\tc\n\td\nfoo\n")
+        .stderr("file 1 is not in sorted order\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn unsorted_file2_check_order() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--check-order", FILE1, "tests/inputs/foo.txt"])
+        .assert()
+        .failure()
+        .stdout("a\nb\nc\nd\n\tfoo\n")
+        .stderr("file 2 is not in sorted order\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn unsorted_nocheck_order() -> Result<()> {
+    run(
+        &["--nocheck-order", "tests/inputs/foo.txt", FILE1],
+        "tests/expected/foo_file1.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_check_order_conflict() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--check-order", "--nocheck-order", FILE1, FILE2])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
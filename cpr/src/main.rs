@@ -0,0 +1,261 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use cli_utils::parse_args;
+use indicatif::{ProgressBar, ProgressStyle};
+use std::fs::{self, File, FileTimes};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use walkdir::WalkDir;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "SOURCE", required = true)]
+    sources: Vec<String>,
+
+    /// The file to copy to, or the directory to copy into
+    #[arg(value_name = "DEST", required = true)]
+    dest: String,
+
+    /// Copy directories and everything in them
+    #[arg(short('r'), long)]
+    recursive: bool,
+
+    /// Keep the permissions and the access and modification times
+    #[arg(short('p'), long)]
+    preserve: bool,
+
+    /// Leave files that already exist as they are
+    #[arg(short('n'), long)]
+    no_clobber: bool,
+
+    /// Print each file as it is copied
+    #[arg(short('v'), long)]
+    verbose: bool,
+
+    /// Show a progress bar for each file
+    #[arg(long)]
+    progress: bool,
+}
+
+/// Gives `dst` the permissions and the access and modification times of
+/// `src`.
+fn copy_metadata(src: &Path, dst: &Path) -> Result<()> {
+    let metadata = fs::metadata(src)?;
+    let times = FileTimes::new()
+        .set_accessed(metadata.accessed()?)
+        .set_modified(metadata.modified()?);
+    File::open(dst)?.set_times(times)?;
+    // after the times, since the permissions may not let dst be opened
+    fs::set_permissions(dst, metadata.permissions())?;
+    Ok(())
+}
+
+/// Copies the contents of `src` to `dst` a buffer at a time, showing how
+/// far it has got on a bar. The permissions are copied as `fs::copy` does.
+fn copy_with_progress(src: &Path, dst: &Path) -> Result<()> {
+    let file = File::open(src)?;
+    let metadata = file.metadata()?;
+    let bar = ProgressBar::new(metadata.len())
+        .with_style(
+            ProgressStyle::with_template(
+                "{msg} [{bar:40}] {bytes}/{total_bytes} ({bytes_per_sec})",
+            )?
+            .progress_chars("=> "),
+        )
+        .with_message(src.display().to_string());
+    io::copy(&mut bar.wrap_read(file), &mut File::create(dst)?)?;
+    fs::set_permissions(dst, metadata.permissions())?;
+    bar.finish();
+    Ok(())
+}
+
+/// Copies the file `src` to `dst`, unless `dst` is there already and is
+/// not to be clobbered.
+fn copy_file(src: &Path, dst: &Path, args: &Args) -> Result<()> {
+    if args.no_clobber && dst.symlink_metadata().is_ok() {
+        return Ok(());
+    }
+    if args.progress {
+        copy_with_progress(src, dst)?;
+    } else {
+        fs::copy(src, dst)?;
+    }
+    if args.preserve {
+        copy_metadata(src, dst)?;
+    }
+    if args.verbose {
+        println!("'{}' -> '{}'", src.display(), dst.display());
+    }
+    Ok(())
+}
+
+/// Makes `dst` a symbolic link to wherever `src` leads, or on systems
+/// without them, a copy of what it leads to.
+fn copy_symlink(src: &Path, dst: &Path, args: &Args) -> Result<()> {
+    #[cfg(unix)]
+    {
+        if dst.symlink_metadata().is_ok() {
+            if args.no_clobber {
+                return Ok(());
+            }
+            fs::remove_file(dst)?;
+        }
+        std::os::unix::fs::symlink(fs::read_link(src)?, dst)?;
+        if args.verbose {
+            println!("'{}' -> '{}'", src.display(), dst.display());
+        }
+        Ok(())
+    }
+    #[cfg(not(unix))]
+    copy_file(src, dst, args)
+}
+
+/// Copies the directory `src` and everything under it to `dst`, printing
+/// what can't be copied and going on with the rest, and returns whether
+/// everything was. Symbolic links are copied as links. Directories get
+/// their metadata last, as copying into them changes their times.
+fn copy_tree(src: &Path, dst: &Path, args: &Args) -> Result<bool> {
+    // a copy inside the source would go on to copy itself
+    let parent = match dst.parent() {
+        Some(parent) if parent != Path::new("") => parent,
+        _ => Path::new("."),
+    };
+    if let (Ok(src_dir), Ok(dst_dir), Some(name)) =
+        (src.canonicalize(), parent.canonicalize(), dst.file_name())
+        && dst_dir.join(name).starts_with(&src_dir)
+    {
+        bail!(
+            "cannot copy a directory, '{}', into itself, '{}'",
+            src.display(),
+            dst.display()
+        );
+    }
+
+    let mut all_ok = true;
+    let mut dirs: Vec<(PathBuf, PathBuf)> = vec![];
+    for entry in WalkDir::new(src).sort_by_file_name() {
+        let result = entry.map_err(|e| anyhow!(e)).and_then(|entry| {
+            // joining the empty path of src itself would add a slash
+            let target = match entry.path().strip_prefix(src)? {
+                path if path.as_os_str().is_empty() => dst.to_path_buf(),
+                path => dst.join(path),
+            };
+            let file_type = entry.file_type();
+            if file_type.is_dir() {
+                if !target.is_dir() {
+                    fs::create_dir(&target).map_err(|e| anyhow!("{}: {e}", target.display()))?;
+                    if args.verbose {
+                        println!("'{}' -> '{}'", entry.path().display(), target.display());
+                    }
+                }
+                dirs.push((entry.into_path(), target));
+                Ok(())
+            } else if file_type.is_symlink() {
+                copy_symlink(entry.path(), &target, args)
+                    .map_err(|e| anyhow!("{}: {e}", entry.path().display()))
+            } else {
+                copy_file(entry.path(), &target, args)
+                    .map_err(|e| anyhow!("{}: {e}", entry.path().display()))
+            }
+        });
+        if let Err(e) = result {
+            eprintln!("{e}");
+            all_ok = false;
+        }
+    }
+    if args.preserve {
+        for (src, dst) in dirs.iter().rev() {
+            if let Err(e) = copy_metadata(src, dst) {
+                eprintln!("{}: {e}", src.display());
+                all_ok = false;
+            }
+        }
+    }
+    Ok(all_ok)
+}
+
+/// Copies `src`, whatever it is, to `dst`, and returns whether all of it
+/// was copied.
+fn copy_path(src: &Path, dst: &Path, args: &Args) -> Result<bool> {
+    // copying a file onto itself would truncate it first
+    if matches!((src.canonicalize(), dst.canonicalize()), (Ok(src), Ok(dst)) if src == dst) {
+        bail!(
+            "'{}' and '{}' are the same file",
+            src.display(),
+            dst.display()
+        );
+    }
+    // only a recursive copy leaves symbolic links as they are
+    let metadata = if args.recursive {
+        src.symlink_metadata()
+    } else {
+        src.metadata()
+    }
+    .map_err(|e| anyhow!("{}: {e}", src.display()))?;
+    if metadata.is_dir() {
+        if !args.recursive {
+            bail!("-r not specified; omitting directory '{}'", src.display());
+        }
+        return copy_tree(src, dst, args);
+    }
+    if metadata.is_symlink() {
+        copy_symlink(src, dst, args)
+    } else {
+        copy_file(src, dst, args)
+    }
+    .map_err(|e| anyhow!("{}: {e}", src.display()))?;
+    Ok(true)
+}
+
+/// Copies each SOURCE to DEST, or into it when it is a directory, and
+/// returns whether they all were copied.
+fn run(args: Args) -> Result<bool> {
+    let dest = Path::new(&args.dest);
+    let into_dir = dest.is_dir();
+    if args.sources.len() > 1 && !into_dir {
+        bail!("target '{}' is not a directory", args.dest);
+    }
+
+    let mut all_ok = true;
+    for source in &args.sources {
+        let src = Path::new(source);
+        let dst = if into_dir {
+            // `.` and `..` have no name of their own to copy to
+            let name = src.file_name().map(|name| name.to_owned()).or_else(|| {
+                let path = src.canonicalize().ok()?;
+                path.file_name().map(|name| name.to_owned())
+            });
+            match name {
+                Some(name) => dest.join(name),
+                None => {
+                    eprintln!("cannot copy '{source}' into '{}'", args.dest);
+                    all_ok = false;
+                    continue;
+                }
+            }
+        } else {
+            dest.to_path_buf()
+        };
+        match copy_path(src, &dst, &args) {
+            Err(e) => {
+                eprintln!("{e}");
+                all_ok = false;
+            }
+            Ok(ok) => all_ok &= ok,
+        }
+    }
+    Ok(all_ok)
+}
+
+fn main() -> ExitCode {
+    match run(parse_args()) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+    }
+}
@@ -0,0 +1,374 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs::{self, File, FileTimes};
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+const PRG: &str = "cpr";
+
+// --------------------------------------------------
+/// Makes a small tree in `dir`: `src` with a file, an executable, and a
+/// directory holding a read-only file, all last changed some days ago.
+fn make_tree(dir: &Path) -> Result<()> {
+    let src = dir.join("src");
+    fs::create_dir_all(src.join("sub"))?;
+    fs::write(src.join("a.txt"), "The quick brown fox\n")?;
+    fs::write(src.join("run.sh"), "#!/bin/sh\necho hi\n")?;
+    fs::write(src.join("sub/b.txt"), "jumps over the lazy dog\n")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+
+        let chmod = |path: &str, mode| {
+            fs::set_permissions(src.join(path), fs::Permissions::from_mode(mode))
+        };
+        chmod("run.sh", 0o755)?;
+        chmod("sub/b.txt", 0o444)?;
+        chmod("sub", 0o750)?;
+    }
+    for (days, path) in (1..).zip(["a.txt", "run.sh", "sub/b.txt", "sub", ""]) {
+        let time = SystemTime::now() - Duration::from_secs(days * 86_400);
+        let times = FileTimes::new().set_accessed(time).set_modified(time);
+        File::open(src.join(path))?.set_times(times)?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+/// Returns the paths under `dir`, relative to it, in order.
+fn list(dir: &Path) -> Result<Vec<String>> {
+    WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .map(|entry| {
+            let entry = entry?;
+            Ok(entry.path().strip_prefix(dir)?.display().to_string())
+        })
+        .collect()
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Command::cargo_bin(PRG)?
+        .arg("file")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn copies_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["src/a.txt", "copy.txt"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("copy.txt"))?,
+        "The quick brown fox\n"
+    );
+
+    // a file that is there is overwritten
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["src/run.sh", "copy.txt"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("copy.txt"))?,
+        "#!/bin/sh\necho hi\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn copies_into_dir() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    fs::create_dir(dir.path().join("dest"))?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-v", "src/a.txt", "src/sub/b.txt", "dest"])
+        .assert()
+        .success()
+        .stdout("'src/a.txt' -> 'dest/a.txt'\n'src/sub/b.txt' -> 'dest/b.txt'\n");
+    assert_eq!(list(&dir.path().join("dest"))?, ["", "a.txt", "b.txt"]);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_many_sources_not_into_dir() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["src/a.txt", "src/run.sh", "dest"])
+        .assert()
+        .failure()
+        .stderr("target 'dest' is not a directory\n");
+    assert!(!dir.path().join("dest").exists());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_dir_without_recursive() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["src", "src/a.txt", "dest.txt"])
+        .assert()
+        .failure();
+    fs::create_dir(dir.path().join("dest"))?;
+    // the files among the sources are still copied
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["src", "src/a.txt", "dest"])
+        .assert()
+        .code(1)
+        .stderr("-r not specified; omitting directory 'src'\n");
+    assert_eq!(list(&dir.path().join("dest"))?, ["", "a.txt"]);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_missing_source() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["nothing", "dest"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::starts_with(
+            "nothing: No such file or directory",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_same_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["src/a.txt", "src/../src/a.txt"])
+        .assert()
+        .code(1)
+        .stderr("'src/a.txt' and 'src/../src/a.txt' are the same file\n");
+    assert_eq!(
+        fs::read_to_string(dir.path().join("src/a.txt"))?,
+        "The quick brown fox\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_into_itself() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-r", "src", "src/sub/copy"])
+        .assert()
+        .code(1)
+        .stderr("cannot copy a directory, 'src', into itself, 'src/sub/copy'\n");
+    assert!(!dir.path().join("src/sub/copy").exists());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-r", "src", "dest"])
+        .assert()
+        .success()
+        .stdout("");
+    let (src, dest) = (dir.path().join("src"), dir.path().join("dest"));
+    assert_eq!(list(&dest)?, list(&src)?);
+    for path in ["a.txt", "run.sh", "sub/b.txt"] {
+        assert_eq!(fs::read(dest.join(path))?, fs::read(src.join(path))?);
+    }
+
+    // a directory that is there is copied into
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["--recursive", "src", "dest"])
+        .assert()
+        .success();
+    assert!(dest.join("src/sub/b.txt").is_file());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_verbose() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-rv", "src", "dest"])
+        .assert()
+        .success()
+        .stdout(
+            "'src' -> 'dest'\n\
+             'src/a.txt' -> 'dest/a.txt'\n\
+             'src/run.sh' -> 'dest/run.sh'\n\
+             'src/sub' -> 'dest/sub'\n\
+             'src/sub/b.txt' -> 'dest/sub/b.txt'\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn preserve() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-rp", "src", "dest"])
+        .assert()
+        .success();
+    let (src, dest) = (dir.path().join("src"), dir.path().join("dest"));
+    assert_eq!(list(&dest)?, list(&src)?);
+    for path in list(&src)? {
+        let (from, to) = (
+            fs::metadata(src.join(&path))?,
+            fs::metadata(dest.join(&path))?,
+        );
+        if from.is_file() {
+            assert_eq!(fs::read(dest.join(&path))?, fs::read(src.join(&path))?);
+        }
+        assert_eq!(to.modified()?, from.modified()?, "{path}");
+        assert_eq!(to.permissions(), from.permissions(), "{path}");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn without_preserve() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["src/a.txt", "a.txt"])
+        .assert()
+        .success();
+    let modified = |path: &str| fs::metadata(dir.path().join(path))?.modified();
+    assert!(modified("a.txt")? > modified("src/a.txt")?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn recursive_symlink() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    std::os::unix::fs::symlink("a.txt", dir.path().join("src/link"))?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-r", "src", "dest"])
+        .assert()
+        .success();
+    // the link is copied as a link, with the same target
+    assert_eq!(
+        fs::read_link(dir.path().join("dest/link"))?,
+        Path::new("a.txt")
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_clobber() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    fs::create_dir(dir.path().join("dest"))?;
+    fs::write(dir.path().join("dest/a.txt"), "mine\n")?;
+    // only the files that aren't there are copied or printed
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-nv", "src/a.txt", "src/run.sh", "dest"])
+        .assert()
+        .success()
+        .stdout("'src/run.sh' -> 'dest/run.sh'\n");
+    assert_eq!(fs::read_to_string(dir.path().join("dest/a.txt"))?, "mine\n");
+
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-rn", "src", "dest"])
+        .assert()
+        .success();
+    fs::write(dir.path().join("dest/src/sub/b.txt"), "")?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["--recursive", "--no-clobber", "src", "dest"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(dir.path().join("dest/src/sub/b.txt"))?,
+        ""
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn progress() -> Result<()> {
+    // the bars go to stderr, and only when it is a terminal
+    let dir = tempfile::tempdir()?;
+    make_tree(dir.path())?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-r", "--progress", "src", "dest"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    let (src, dest) = (dir.path().join("src"), dir.path().join("dest"));
+    for path in ["a.txt", "run.sh", "sub/b.txt"] {
+        assert_eq!(fs::read(dest.join(path))?, fs::read(src.join(path))?);
+        assert_eq!(
+            fs::metadata(dest.join(path))?.permissions(),
+            fs::metadata(src.join(path))?.permissions()
+        );
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
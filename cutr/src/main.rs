@@ -1,13 +1,8 @@
 use anyhow::{Result, anyhow, bail};
 use clap::Parser;
-use csv::{ReaderBuilder, StringRecord, WriterBuilder};
+use cli_utils::{exit_with_error, open, parse_args};
 use regex::Regex;
-use std::{
-    fs::File,
-    io::{self, BufRead, BufReader},
-    num::NonZeroUsize,
-    ops::Range,
-};
+use std::{io::BufRead, num::NonZeroUsize, ops::Range};
 
 #[derive(Debug, Parser)]
 #[command(author, version, about)]
@@ -18,6 +13,9 @@ struct Args {
     #[arg(short, long, value_name = "DELIMITER", default_value = "\t")]
     delimiter: String,
 
+    #[arg(short('s'), long, conflicts_with_all(["bytes", "chars"]))]
+    only_delimited: bool,
+
     #[command(flatten)]
     extract: ArgsExtract,
 }
@@ -25,28 +23,28 @@ struct Args {
 #[derive(Debug, clap::Args)]
 #[group(required = true, multiple = false)]
 struct ArgsExtract {
-    #[arg(short, long, value_name = "FIELDS")]
+    #[arg(short, long, value_name = "FIELDS", allow_hyphen_values = true)]
     fields: Option<String>,
 
-    #[arg(short, long, value_name = "BYTES")]
+    #[arg(short, long, value_name = "BYTES", allow_hyphen_values = true)]
     bytes: Option<String>,
 
-    #[arg(short, long, value_name = "CHARS")]
+    #[arg(short, long, value_name = "CHARS", allow_hyphen_values = true)]
     chars: Option<String>,
 }
 
 fn run(args: Args) -> Result<()> {
     let delim_bytes = args.delimiter.as_bytes();
     if delim_bytes.len() != 1 {
-        bail!(r#"--delim "{}" must be single byte"#, args.delimiter);
+        bail!(r#"--delim "{}" must be a single byte"#, args.delimiter);
     }
-    let delimiter: u8 = *delim_bytes.first().unwrap();
+    let delimiter = *delim_bytes.first().unwrap() as char;
 
-    let extract = if let Some(fields) = args.extract.fields.map(parse_pos).transpose()? {
+    let extract = if let Some(fields) = args.extract.fields.as_deref().map(parse_pos).transpose()? {
         Extract::Fields(fields)
-    } else if let Some(bytes) = args.extract.bytes.map(parse_pos).transpose()? {
+    } else if let Some(bytes) = args.extract.bytes.as_deref().map(parse_pos).transpose()? {
         Extract::Bytes(bytes)
-    } else if let Some(chars) = args.extract.chars.map(parse_pos).transpose()? {
+    } else if let Some(chars) = args.extract.chars.as_deref().map(parse_pos).transpose()? {
         Extract::Chars(chars)
     } else {
         unreachable!("Must have --fields, --bytes, or --chars");
@@ -54,33 +52,31 @@ fn run(args: Args) -> Result<()> {
 
     for filename in &args.files {
         match open(filename) {
-            Err(err) => eprintln!("{filename}:{err}"),
-            Ok(file) => match &extract {
-                Extract::Fields(field_pos) => {
-                    let mut reader = ReaderBuilder::new()
-                        .delimiter(delimiter)
-                        .has_headers(false)
-                        .from_reader(file);
-
-                    let mut wtr = WriterBuilder::new()
-                        .delimiter(delimiter)
-                        .from_writer(io::stdout());
-
-                    for record in reader.records() {
-                        wtr.write_record(extract_fields(&record?, field_pos))?;
-                    }
-                }
-                Extract::Bytes(bytes_pos) => {
-                    for line in file.lines() {
-                        println!("{}", extract_bytes(&line?, bytes_pos));
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(file) => {
+                for line in file.lines() {
+                    let line = line?;
+                    match &extract {
+                        // Like cut, lines without a delimiter are printed whole
+                        Extract::Fields(_) if !line.contains(delimiter) => {
+                            if !args.only_delimited {
+                                println!("{line}");
+                            }
+                        }
+                        Extract::Fields(field_pos) => {
+                            let record: Vec<_> = line.split(delimiter).collect();
+                            let fields = extract_fields(&record, field_pos);
+                            println!("{}", fields.join(&delimiter.to_string()));
+                        }
+                        Extract::Bytes(bytes_pos) => {
+                            println!("{}", extract_bytes(&line, bytes_pos));
+                        }
+                        Extract::Chars(char_pos) => {
+                            println!("{}", extract_chars(&line, char_pos));
+                        }
                     }
                 }
-                Extract::Chars(char_pos) => {
-                    for line in file.lines() {
-                        println!("{}", extract_chars(&line?, char_pos))
-                    }
-                }
-            },
+            }
         }
     }
     Ok(())
@@ -95,37 +91,43 @@ pub enum Extract {
     Chars(PositionList),
 }
 
-fn open(filename: &str) -> Result<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
-    }
-}
-
 fn parse_index(input: &str) -> Result<usize> {
     let value_error = || anyhow!(r#"illegal list value: "{input}""#);
-    input
-        .starts_with("+")
-        .then(|| Err(value_error()))
-        .unwrap_or_else(|| {
-            input
-                .parse::<NonZeroUsize>()
-                .map(|n| usize::from(n) - 1)
-                .map_err(|_| value_error())
-        })
+    if input.starts_with('+') {
+        Err(value_error())
+    } else {
+        input
+            .parse::<NonZeroUsize>()
+            .map(|n| usize::from(n) - 1)
+            .map_err(|_| value_error())
+    }
 }
 
-fn parse_pos(range: String) -> Result<PositionList> {
-    let range_re = Regex::new(r"^(\d+)-(\d+)$").unwrap();
-    range
+/// Parses a list such as `1,3-5,7-` into zero-based ranges. An open start
+/// (`-M`) begins at the first position and an open end (`N-`) runs to the
+/// end of the line. The ranges are sorted and overlapping ones merged, so
+/// each position is selected once and in input order, as `cut` does.
+fn parse_pos(range: &str) -> Result<PositionList> {
+    let range_re = Regex::new(r"^(\d*)-(\d*)$").unwrap();
+    let mut ranges = range
         .split(',')
-        .into_iter()
         .map(|val| {
             parse_index(val).map(|n| n..n + 1).or_else(|e| {
                 range_re.captures(val).ok_or(e).and_then(|captures| {
-                    let n1 = parse_index(&captures[1])?;
-                    let n2 = parse_index(&captures[2])?;
-                    if n1 >= n2 {
+                    let (start, end) = (&captures[1], &captures[2]);
+                    if start.is_empty() && end.is_empty() {
+                        bail!(r#"illegal list value: "{val}""#);
+                    }
+                    let n1 = if start.is_empty() {
+                        0
+                    } else {
+                        parse_index(start)?
+                    };
+                    if end.is_empty() {
+                        return Ok(n1..usize::MAX);
+                    }
+                    let n2 = parse_index(end)?;
+                    if !start.is_empty() && n1 >= n2 {
                         bail!(
                             "First number in range ({}) \
                             must be lower than second number ({})",
@@ -137,16 +139,29 @@ fn parse_pos(range: String) -> Result<PositionList> {
                 })
             })
         })
-        .collect::<Result<_, _>>()
-        .map_err(From::from)
+        .collect::<Result<PositionList>>()?;
+
+    ranges.sort_by_key(|range| range.start);
+    let mut merged: PositionList = Vec::with_capacity(ranges.len());
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+            _ => merged.push(range),
+        }
+    }
+    Ok(merged)
+}
+
+/// Clamps an open-ended range to the length of what is being cut.
+fn clamp(range: &Range<usize>, len: usize) -> Range<usize> {
+    range.start..range.end.min(len)
 }
 
 fn extract_chars(line: &str, char_pos: &[Range<usize>]) -> String {
     let chars: Vec<_> = line.chars().collect();
     char_pos
         .iter()
-        .cloned()
-        .flat_map(|range| range.filter_map(|i| chars.get(i)))
+        .flat_map(|range| clamp(range, chars.len()).filter_map(|i| chars.get(i)))
         .collect()
 }
 
@@ -154,31 +169,29 @@ fn extract_bytes(line: &str, byte_pos: &[Range<usize>]) -> String {
     let bytes = line.as_bytes();
     let selected: Vec<_> = byte_pos
         .iter()
-        .cloned()
-        .flat_map(|range| range.filter_map(|i| bytes.get(i)).copied())
+        .flat_map(|range| {
+            clamp(range, bytes.len())
+                .filter_map(|i| bytes.get(i))
+                .copied()
+        })
         .collect();
     String::from_utf8_lossy(&selected).into_owned()
 }
 
-fn extract_fields<'a>(record: &'a StringRecord, field_pos: &[Range<usize>]) -> Vec<&'a str> {
+fn extract_fields<'a>(record: &[&'a str], field_pos: &[Range<usize>]) -> Vec<&'a str> {
     field_pos
         .iter()
-        .cloned()
-        .flat_map(|range| range.filter_map(|i| record.get(i)))
+        .flat_map(|range| clamp(range, record.len()).filter_map(|i| record.get(i).copied()))
         .collect()
 }
 
 fn main() {
-    if let Err(e) = run(Args::parse()) {
-        eprintln!("{e}");
-        std::process::exit(1);
-    }
+    exit_with_error!(run(parse_args()));
 }
 
 #[cfg(test)]
+#[allow(clippy::single_range_in_vec_init)]
 mod unit_tests {
-    use csv::StringRecord;
-
     use super::extract_bytes;
     use super::extract_chars;
     use super::extract_fields;
@@ -186,7 +199,7 @@ mod unit_tests {
 
     #[test]
     fn test_extract_fields() {
-        let rec = StringRecord::from(vec!["Captain", "Sham", "12345"]);
+        let rec = ["Captain", "Sham", "12345"];
         assert_eq!(extract_fields(&rec, &[1..2]), &["Sham"]);
         assert_eq!(extract_fields(&rec, &[0..1, 2..3]), &["Captain", "12345"]);
         assert_eq!(extract_fields(&rec, &[0..1, 3..4]), &["Captain"]);
@@ -216,30 +229,30 @@ mod unit_tests {
     #[test]
     fn test_parse_pos() {
         // the empty string is an error
-        assert!(parse_pos("".to_string()).is_err());
+        assert!(parse_pos("").is_err());
 
         // zero is an error
-        let res = parse_pos("0".to_string());
+        let res = parse_pos("0");
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "0""#);
 
-        let res = parse_pos("0-1".to_string());
+        let res = parse_pos("0-1");
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "0""#);
 
         // a leading "+" is an error
-        let res = parse_pos("+1".to_string());
+        let res = parse_pos("+1");
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "+1""#,);
 
-        let res = parse_pos("+1-2".to_string());
+        let res = parse_pos("+1-2");
         assert!(res.is_err());
         assert_eq!(
             res.unwrap_err().to_string(),
             r#"illegal list value: "+1-2""#,
         );
 
-        let res = parse_pos("1-+2".to_string());
+        let res = parse_pos("1-+2");
         assert!(res.is_err());
         assert_eq!(
             res.unwrap_err().to_string(),
@@ -247,49 +260,46 @@ mod unit_tests {
         );
 
         // any number is an error
-        let res = parse_pos("a".to_string());
+        let res = parse_pos("a");
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "a""#);
 
-        let res = parse_pos("1,a".to_string());
+        let res = parse_pos("1,a");
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "a""#);
 
-        let res = parse_pos("1-a".to_string());
+        let res = parse_pos("1-a");
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "1-a""#,);
 
-        let res = parse_pos("a-1".to_string());
+        let res = parse_pos("a-1");
         assert!(res.is_err());
         assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "a-1""#,);
 
         // wonky ranges
-        let res = parse_pos("-".to_string());
+        let res = parse_pos("-");
         assert!(res.is_err());
 
-        let res = parse_pos(",".to_string());
+        let res = parse_pos(",");
         assert!(res.is_err());
 
-        let res = parse_pos("1,".to_string());
+        let res = parse_pos("1,");
         assert!(res.is_err());
 
-        let res = parse_pos("1-".to_string());
+        let res = parse_pos("1-1-1");
         assert!(res.is_err());
 
-        let res = parse_pos("1-1-1".to_string());
+        let res = parse_pos("1-1-a");
         assert!(res.is_err());
 
-        let res = parse_pos("1-1-a".to_string());
-        assert!(res.is_err());
-
-        let res = parse_pos("1-1".to_string());
+        let res = parse_pos("1-1");
         assert!(res.is_err());
         assert_eq!(
             res.unwrap_err().to_string(),
             "First number in range (1) must be lower than second number (1)"
         );
 
-        let res = parse_pos("2-1".to_string());
+        let res = parse_pos("2-1");
         assert!(res.is_err());
         assert_eq!(
             res.unwrap_err().to_string(),
@@ -297,36 +307,79 @@ mod unit_tests {
         );
 
         // all the following are acceptable
-        let res = parse_pos("1".to_string());
+        let res = parse_pos("1");
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![0..1]);
 
-        let res = parse_pos("01".to_string());
+        let res = parse_pos("01");
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![0..1]);
 
-        let res = parse_pos("1,3".to_string());
+        let res = parse_pos("1,3");
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![0..1, 2..3]);
 
-        let res = parse_pos("001,0003".to_string());
+        let res = parse_pos("001,0003");
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![0..1, 2..3]);
 
-        let res = parse_pos("1-3".to_string());
+        let res = parse_pos("1-3");
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![0..3]);
 
-        let res = parse_pos("0001-03".to_string());
+        let res = parse_pos("0001-03");
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![0..3]);
 
-        let res = parse_pos("1,7,3-5".to_string());
+        // ranges come back sorted
+        let res = parse_pos("1,7,3-5");
         assert!(res.is_ok());
-        assert_eq!(res.unwrap(), vec![0..1, 6..7, 2..5]);
+        assert_eq!(res.unwrap(), vec![0..1, 2..5, 6..7]);
 
-        let res = parse_pos("15,19-20".to_string());
+        let res = parse_pos("15,19-20");
         assert!(res.is_ok());
         assert_eq!(res.unwrap(), vec![14..15, 18..20]);
+
+        // open-ended ranges
+        let res = parse_pos("-3");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![0..3]);
+
+        let res = parse_pos("3-");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![2..usize::MAX]);
+
+        let res = parse_pos("-0");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "0""#);
+
+        let res = parse_pos("0-");
+        assert!(res.is_err());
+        assert_eq!(res.unwrap_err().to_string(), r#"illegal list value: "0""#);
+
+        // duplicate, overlapping and adjacent ranges are merged
+        let res = parse_pos("1,1");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![0..1]);
+
+        let res = parse_pos("3,1,2");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![0..3]);
+
+        let res = parse_pos("1-3,2-5,8");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![0..5, 7..8]);
+
+        let res = parse_pos("5-,-2,7");
+        assert!(res.is_ok());
+        assert_eq!(res.unwrap(), vec![0..2, 4..usize::MAX]);
+    }
+
+    #[test]
+    fn test_extract_open_ranges() {
+        assert_eq!(extract_chars("ábcdé", &[2..usize::MAX]), "cdé".to_string());
+        assert_eq!(extract_bytes("ábc", &[2..usize::MAX]), "bc".to_string());
+        let rec = ["Captain", "Sham", "12345"];
+        assert_eq!(extract_fields(&rec, &[1..usize::MAX]), &["Sham", "12345"]);
     }
 }
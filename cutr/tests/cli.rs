@@ -9,6 +9,9 @@ const PRG: &str = "cutr";
 const CSV: &str = "tests/inputs/movies1.csv";
 const TSV: &str = "tests/inputs/movies1.tsv";
 const BOOKS: &str = "tests/inputs/books.tsv";
+const MOVIES2_CSV: &str = "tests/inputs/movies2.csv";
+const MOVIES2_TSV: &str = "tests/inputs/movies2.tsv";
+const RAGGED: &str = "tests/inputs/ragged.tsv";
 
 // --------------------------------------------------
 fn random_string() -> String {
@@ -338,5 +341,122 @@ fn tsv_c1_8() -> Result<()> {
 // --------------------------------------------------
 #[test]
 fn repeated_value() -> Result<()> {
+    // a position listed twice is only selected once
     run(&[BOOKS, "-c", "1,1"], "tests/expected/books.c1,1.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn tsv_f3_1_reordered() -> Result<()> {
+    run(&[TSV, "-f", "3,1"], "tests/expected/movies1.tsv.f3,1.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_f2_open_end() -> Result<()> {
+    run(&[TSV, "-f", "2-"], "tests/expected/movies1.tsv.f2-.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_f2_open_start() -> Result<()> {
+    run(&[TSV, "-f", "-2"], "tests/expected/movies1.tsv.f-2.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_f_overlapping() -> Result<()> {
+    run(
+        &[TSV, "-f", "1-2,2-3"],
+        "tests/expected/movies1.tsv.f1-2,2-3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_b3_open_start() -> Result<()> {
+    run(&[TSV, "-b", "-3"], "tests/expected/movies1.tsv.b-3.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_b5_open_end() -> Result<()> {
+    run(&[TSV, "-b", "5-"], "tests/expected/movies1.tsv.b5-.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_no_delimiter() -> Result<()> {
+    run(
+        &[TSV, "-d", ",", "-f", "2"],
+        "tests/expected/movies1.tsv.f2.dcomma.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_quoted_field() -> Result<()> {
+    run(
+        &[MOVIES2_CSV, "-d", ",", "-f", "1,3"],
+        "tests/expected/movies2.csv.f1,3.dcomma.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tsv_ragged() -> Result<()> {
+    run(
+        &[MOVIES2_TSV, "-f", "3"],
+        "tests/expected/movies2.tsv.f3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn ragged_f2() -> Result<()> {
+    run(&[RAGGED, "-f", "2"], "tests/expected/ragged.tsv.f2.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn ragged_f2_only_delimited() -> Result<()> {
+    run(
+        &[RAGGED, "-f", "2", "-s"],
+        "tests/expected/ragged.tsv.f2.s.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn ragged_f3_open_end_only_delimited() -> Result<()> {
+    run(
+        &[RAGGED, "-f", "3-", "--only-delimited"],
+        "tests/expected/ragged.tsv.f3-.s.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn ragged_f1_4() -> Result<()> {
+    run(&[RAGGED, "-f", "1,4"], "tests/expected/ragged.tsv.f1,4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_only_delimited_without_fields() -> Result<()> {
+    dies(
+        &[TSV, "-c", "1", "-s"],
+        "the argument '--chars <CHARS>' cannot be used with '--only-delimited'",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{exit_with_error, parse_args};
+use std::io::{self, BufWriter, Write};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "NAME", required = true)]
+    names: Vec<String>,
+
+    /// End each name with NUL rather than newline
+    #[arg(short('z'), long)]
+    zero: bool,
+}
+
+/// Returns `name` without its last component as POSIX `dirname` does:
+/// trailing slashes go, then the last component, then the slashes before
+/// it. A name with no slash is in `.`, and one left empty is `/`.
+///
+/// This works on the string rather than with `Path::parent`, which gives
+/// nothing for `/` and an empty path for `a`, and skips `.` components, so
+/// `a/.` would come out as an empty path rather than `a`.
+fn dirname(name: &str) -> &str {
+    let trimmed = name.trim_end_matches('/');
+    if trimmed.is_empty() {
+        return if name.is_empty() { "." } else { "/" };
+    }
+    match trimmed.rfind('/') {
+        None => ".",
+        Some(slash) => match trimmed[..slash].trim_end_matches('/') {
+            "" => "/",
+            dir => dir,
+        },
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    for name in &args.names {
+        stdout.write_all(dirname(name).as_bytes())?;
+        stdout.write_all(if args.zero { b"\0" } else { b"\n" })?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::dirname;
+
+    fn check(cases: &[(&str, &str)]) {
+        for &(name, expected) in cases {
+            assert_eq!(dirname(name), expected, "{name:?}");
+        }
+    }
+
+    #[test]
+    fn test_plain() {
+        check(&[
+            ("/path/to/file.txt", "/path/to"),
+            ("path/to/file", "path/to"),
+            ("/usr/lib", "/usr"),
+            ("a b/c d", "a b"),
+            ("a/b/c/d", "a/b/c"),
+        ]);
+    }
+
+    #[test]
+    fn test_no_slash() {
+        check(&[("file", "."), ("file.txt", "."), ("usr", ".")]);
+    }
+
+    #[test]
+    fn test_empty() {
+        check(&[("", ".")]);
+    }
+
+    #[test]
+    fn test_root() {
+        // POSIX leaves `//` to the implementation; it is `/` here, as in GNU
+        check(&[
+            ("/", "/"),
+            ("//", "/"),
+            ("///", "/"),
+            ("/a", "/"),
+            ("//a", "/"),
+            ("///a", "/"),
+            ("/a/", "/"),
+        ]);
+    }
+
+    #[test]
+    fn test_trailing_slashes() {
+        check(&[
+            ("a/", "."),
+            ("a//", "."),
+            ("/usr/lib/", "/usr"),
+            ("a/b/", "a"),
+            ("a/b//", "a"),
+        ]);
+    }
+
+    #[test]
+    fn test_repeated_slashes() {
+        // only the slashes between the last two components go
+        check(&[
+            ("a//b", "a"),
+            ("a///b", "a"),
+            ("//a//b//", "//a"),
+            ("a//b/c", "a//b"),
+        ]);
+    }
+
+    #[test]
+    fn test_dots() {
+        check(&[
+            (".", "."),
+            ("..", "."),
+            ("./", "."),
+            ("../", "."),
+            ("./a", "."),
+            ("../a", ".."),
+            ("a/.", "a"),
+            ("a/..", "a"),
+            ("/.", "/"),
+            ("/..", "/"),
+            ("../..", ".."),
+            (".hidden/x", ".hidden"),
+        ]);
+    }
+}
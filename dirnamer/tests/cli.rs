@@ -0,0 +1,61 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const PRG: &str = "dirnamer";
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn name() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg("/path/to/file.txt")
+        .assert()
+        .success()
+        .stdout("/path/to\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn names() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["a/b/", "file", "/", "//a", "", "../a", "--", "-x/y"])
+        .assert()
+        .success()
+        .stdout("a\n.\n/\n/\n.\n..\n-x\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn zero() -> Result<()> {
+    for flag in ["-z", "--zero"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "a/b", "c\nd/e"])
+            .assert()
+            .success()
+            .stdout("a\0c\nd\0");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
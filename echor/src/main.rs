@@ -1,27 +1,273 @@
-use clap::{Arg, ArgAction, Command};
+use anyhow::Result;
+use clap::{ArgAction, Parser};
+use cli_utils::schema::{EchorRecord, MachineFormat, MachineOutput};
+use cli_utils::{exit_with_error, parse_args};
+use std::io::{self, Write};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "echor",
+    version = "0.0.1",
+    author = "Vitalie Brinza",
+    about = "Rust version of echo"
+)]
+struct Args {
+    /// input text
+    #[arg(value_name = "TEXT", required = true, num_args = 1..)]
+    text: Vec<String>,
+
+    /// Do not print newline
+    #[arg(short = 'n', action = ArgAction::SetTrue)]
+    omit_newline: bool,
+
+    /// Enable interpretation of backslash escapes
+    #[arg(short = 'e', long = "enable-escapes", action = ArgAction::SetTrue)]
+    enable_escapes: bool,
+
+    /// Disable interpretation of backslash escapes (default)
+    #[arg(
+        short = 'E',
+        long = "disable-escapes",
+        action = ArgAction::SetTrue,
+        conflicts_with = "enable_escapes"
+    )]
+    disable_escapes: bool,
+
+    /// String printed between each TEXT
+    #[arg(short = 's', long, value_name = "SEP", default_value = " ")]
+    separator: String,
+
+    /// Print the text as a record in the common JSON or CSV schema
+    #[arg(long, value_name = "FORMAT", value_enum)]
+    machine_readable: Option<MachineFormat>,
+}
+
+/// Expands the escape sequences understood by `echo -e`. The flag is set
+/// when a `\c` was found, in which case everything after it is dropped.
+/// Octal and hex escapes produce raw bytes, so a sequence like `\xc3\xa9`
+/// yields "é" and `\xff` the single byte 0xff, as `echo -e` writes it.
+fn parse_escapes(s: &str) -> (Vec<u8>, bool) {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut chars = s.chars().peekable();
+    let mut stop = false;
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        match chars.peek().copied() {
+            Some('\\') => bytes.push(b'\\'),
+            Some('a') => bytes.push(0x07),
+            Some('b') => bytes.push(0x08),
+            Some('e') => bytes.push(0x1b),
+            Some('f') => bytes.push(0x0c),
+            Some('n') => bytes.push(b'\n'),
+            Some('r') => bytes.push(b'\r'),
+            Some('t') => bytes.push(b'\t'),
+            Some('v') => bytes.push(0x0b),
+            Some('c') => {
+                stop = true;
+                break;
+            }
+            Some('0') => {
+                chars.next();
+                bytes.push(take_digits(&mut chars, 8, 3));
+                continue;
+            }
+            Some('x') if chars.clone().nth(1).is_some_and(|d| d.is_ascii_hexdigit()) => {
+                chars.next();
+                bytes.push(take_digits(&mut chars, 16, 2));
+                continue;
+            }
+            // Unknown escapes and a trailing backslash are printed as is
+            _ => {
+                bytes.push(b'\\');
+                continue;
+            }
+        }
+        chars.next();
+    }
+
+    (bytes, stop)
+}
+
+/// Consumes up to `max` digits in the given radix and returns their value,
+/// truncated to a byte the way `echo` does for `\0777`.
+fn take_digits(chars: &mut std::iter::Peekable<std::str::Chars>, radix: u32, max: usize) -> u8 {
+    let mut value: u32 = 0;
+    for _ in 0..max {
+        match chars.peek().and_then(|c| c.to_digit(radix)) {
+            Some(digit) => {
+                value = value * radix + digit;
+                chars.next();
+            }
+            None => break,
+        }
+    }
+    value as u8
+}
+
+/// The text to print, and whether a newline follows it. Escapes can make
+/// bytes that are not UTF-8, so the text is bytes.
+fn echo(args: &Args) -> (Vec<u8>, bool) {
+    let mut parts = vec![];
+    let mut newline = !args.omit_newline;
+    for text in &args.text {
+        if !args.enable_escapes {
+            parts.push(text.as_bytes().to_vec());
+            continue;
+        }
+        let (parsed, stop) = parse_escapes(text);
+        parts.push(parsed);
+        if stop {
+            newline = false;
+            break;
+        }
+    }
+    (parts.join(args.separator.as_bytes()), newline)
+}
+
+fn output(args: &Args) -> Vec<u8> {
+    let (mut text, newline) = echo(args);
+    if newline {
+        text.push(b'\n');
+    }
+    text
+}
+
+fn run(args: Args) -> Result<()> {
+    match args.machine_readable {
+        Some(format) => {
+            // JSON and CSV hold text, so bytes that aren't UTF-8 become
+            // U+FFFD there
+            let (text, newline) = echo(&args);
+            let text = String::from_utf8_lossy(&text).into_owned();
+            let output = MachineOutput::Echor(vec![EchorRecord { text, newline }]);
+            output.write(format, io::stdout().lock())?;
+        }
+        None => io::stdout().lock().write_all(&output(&args))?,
+    }
+    Ok(())
+}
 
 fn main() {
-    let matches = Command::new("echor")
-        .version("0.0.1")
-        .author("Vitalie Brinza")
-        .about("Rust version of echo")
-        .arg(
-            Arg::new("text")
-                .value_name("TEXT")
-                .help("input text")
-                .required(true)
-                .num_args(1..),
-        )
-        .arg(
-            Arg::new("omit_newline")
-                .short('n')
-                .action(ArgAction::SetTrue)
-                .help("Do not print newline"),
-        )
-        .get_matches();
-    // println!("{:#?}", matches);
-    let text: Vec<String> = matches.get_many("text").unwrap().cloned().collect();
-    let omit_newline = matches.get_flag("omit_newline");
-
-    print!("{}{}", text.join(" "), if omit_newline { "" } else { "\n" });
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod unit_tests {
+    use super::{Args, echo, output, parse_escapes, run};
+    use clap::Parser;
+
+    fn parse(args: &[&str]) -> Args {
+        Args::try_parse_from(["echor"].iter().chain(args)).unwrap()
+    }
+
+    #[test]
+    fn test_run() {
+        assert!(run(parse(&["Hello", "there"])).is_ok());
+        assert!(run(parse(&["-n", "Hello"])).is_ok());
+    }
+
+    #[test]
+    fn test_output() {
+        assert_eq!(output(&parse(&["Hello there"])), b"Hello there\n");
+        assert_eq!(output(&parse(&["Hello", "there"])), b"Hello there\n");
+        assert_eq!(output(&parse(&["Hello there", "-n"])), b"Hello there");
+        assert_eq!(output(&parse(&["-n", "Hello", "there"])), b"Hello there");
+    }
+
+    #[test]
+    fn test_echo() {
+        assert_eq!(echo(&parse(&["a", "b"])), (b"a b".to_vec(), true));
+        assert_eq!(echo(&parse(&["-n", "a"])), (b"a".to_vec(), false));
+        assert_eq!(echo(&parse(&["-e", r"a\cb"])), (b"a".to_vec(), false));
+    }
+
+    #[test]
+    fn test_requires_text() {
+        assert!(Args::try_parse_from(["echor"]).is_err());
+        assert!(Args::try_parse_from(["echor", "-n"]).is_err());
+    }
+
+    #[test]
+    fn test_parse_escapes() {
+        let cases = [
+            (r"a\\b", "a\\b"),
+            (r"\a", "\x07"),
+            (r"\b", "\x08"),
+            (r"\e", "\x1b"),
+            (r"\f", "\x0c"),
+            (r"\n", "\n"),
+            (r"\r", "\r"),
+            (r"\t", "\t"),
+            (r"\v", "\x0b"),
+            (r"\0101", "A"),
+            (r"\0", "\0"),
+            (r"\08", "\08"),
+            (r"\01018", "A8"),
+            (r"\x41", "A"),
+            (r"\x4g", "\x04g"),
+            (r"\x414", "A4"),
+            (r"\x", r"\x"),
+            (r"\q", r"\q"),
+            (r"end\", r"end\"),
+            // multibyte UTF-8 built up one byte at a time
+            (r"\xc3\xa9", "é"),
+            (r"\0303\0251", "é"),
+            (r"\xe2\x82\xac", "€"),
+            (r"\xf0\x9f\xa6\x80", "🦀"),
+            ("plain ünïcödé", "plain ünïcödé"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                parse_escapes(input),
+                (expected.as_bytes().to_vec(), false),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_escapes_raw_bytes() {
+        // bytes that are not UTF-8 are kept as they are, not replaced
+        assert_eq!(parse_escapes(r"\xff"), (vec![0xff], false));
+        assert_eq!(parse_escapes(r"\0377"), (vec![0xff], false));
+        assert_eq!(parse_escapes(r"a\xc3b"), (b"a\xc3b".to_vec(), false));
+    }
+
+    #[test]
+    fn test_parse_escapes_stop() {
+        assert_eq!(parse_escapes(r"abc\cdef"), (b"abc".to_vec(), true));
+        assert_eq!(parse_escapes(r"\c"), (b"".to_vec(), true));
+        assert_eq!(parse_escapes(r"\\c"), (b"\\c".to_vec(), false));
+    }
+
+    #[test]
+    fn test_output_escapes() {
+        assert_eq!(output(&parse(&["-e", r"a\tb"])), b"a\tb\n");
+        assert_eq!(output(&parse(&["-E", r"a\tb"])), b"a\\tb\n");
+        assert_eq!(output(&parse(&[r"a\tb"])), b"a\\tb\n");
+        // \c drops the rest of its argument, later arguments and the newline
+        assert_eq!(output(&parse(&["-e", r"a\cb", "c"])), b"a");
+        assert_eq!(output(&parse(&["-e", "a", r"b\c", "c"])), b"a b");
+        assert!(Args::try_parse_from(["echor", "-e", "-E", "a"]).is_err());
+    }
+
+    #[test]
+    fn test_output_separator() {
+        assert_eq!(output(&parse(&["-s", ",", "a", "b", "c"])), b"a,b,c\n");
+        assert_eq!(
+            output(&parse(&["--separator", " :: ", "a", "b"])),
+            b"a :: b\n"
+        );
+        assert_eq!(output(&parse(&["-s", "", "a", "b", "c"])), b"abc\n");
+        assert_eq!(output(&parse(&["-s", "\n", "a", "b"])), b"a\nb\n");
+        assert_eq!(output(&parse(&["-n", "-s", ":", "a", "b"])), b"a:b");
+        assert_eq!(output(&parse(&["-s", "-", "a", "b"])), b"a-b\n");
+        // the separator is not part of the argument list, so \c stops before it
+        assert_eq!(output(&parse(&["-e", "-s", ",", r"a\c", "b"])), b"a");
+    }
 }
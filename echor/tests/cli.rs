@@ -43,3 +43,115 @@ fn hello1_no_newline() -> Result<()> {
 fn hello2_no_newline() -> Result<()> {
     run(&["-n", "Hello", "there"], "tests/expected/hello2.n.txt")
 }
+
+#[test]
+fn escapes_enabled() -> Result<()> {
+    run(
+        &["-e", r"Hello\tthere\nworld\\"],
+        "tests/expected/escapes.e.txt",
+    )?;
+    run(
+        &["--enable-escapes", r"Hello\tthere\nworld\\"],
+        "tests/expected/escapes.e.txt",
+    )
+}
+
+#[test]
+fn escapes_disabled() -> Result<()> {
+    run(&[r"Hello\tthere\nworld\\"], "tests/expected/escapes.E.txt")?;
+    run(
+        &["-E", r"Hello\tthere\nworld\\"],
+        "tests/expected/escapes.E.txt",
+    )
+}
+
+#[test]
+fn escapes_stop_output() -> Result<()> {
+    run(&["-e", r"Hello\c", "there"], "tests/expected/escapes.c.txt")
+}
+
+#[test]
+fn escapes_raw_bytes() -> Result<()> {
+    // a byte that is not UTF-8 is written as it is, as echo -e does
+    for escape in [r"\xff", r"\0377"] {
+        let output = Command::cargo_bin("echor")?.args(["-e", escape]).output()?;
+        assert!(output.status.success());
+        assert_eq!(output.stdout, [0xff, b'\n']);
+    }
+    Ok(())
+}
+
+#[test]
+fn dies_enable_and_disable_escapes() -> Result<()> {
+    Command::cargo_bin("echor")?
+        .args(["-e", "-E", "Hello"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+#[test]
+fn hello2_separator() -> Result<()> {
+    run(
+        &["-s", ",", "Hello", "there"],
+        "tests/expected/hello2.s.txt",
+    )?;
+    run(
+        &["Hello", "--separator=,", "there"],
+        "tests/expected/hello2.s.txt",
+    )
+}
+
+#[test]
+fn hello2_newline_separator_no_newline() -> Result<()> {
+    run(
+        &["-n", "-s", "\n", "Hello", "there"],
+        "tests/expected/hello2.sn.txt",
+    )
+}
+
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin("echor")?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
+
+#[test]
+fn machine_readable_json() -> Result<()> {
+    let expected = concat!(
+        r#"{"schema_version":1,"tool":"echor","records":"#,
+        r#"[{"text":"Hello there","newline":true}]}"#,
+        "\n"
+    );
+    Command::cargo_bin("echor")?
+        .args(["--machine-readable", "json", "Hello", "there"])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+fn machine_readable_csv() -> Result<()> {
+    Command::cargo_bin("echor")?
+        .args(["--machine-readable=csv", "-n", "-e", r"a,\tb"])
+        .assert()
+        .success()
+        .stdout("text,newline\n\"a,\tb\",false\n");
+    Ok(())
+}
+
+#[test]
+fn dies_bad_machine_readable() -> Result<()> {
+    Command::cargo_bin("echor")?
+        .args(["--machine-readable=xml", "a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'xml'"));
+    Ok(())
+}
@@ -0,0 +1,245 @@
+use anyhow::{Result, bail};
+use clap::{ArgAction, Parser};
+use cli_utils::parse_args;
+use std::ffi::OsString;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::iter;
+use std::mem;
+use std::process::{Command, ExitCode};
+
+// Failures of enver itself have exit statuses of their own, as in GNU `env`,
+// so that they aren't mistaken for those of the command
+const FAILURE: u8 = 125;
+const CANNOT_RUN: u8 = 126;
+const NOT_FOUND: u8 = 127;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    /// NAME=VALUE assignments, then the COMMAND to run and its arguments
+    #[arg(
+        value_name = "OPERAND",
+        trailing_var_arg = true,
+        allow_hyphen_values = true
+    )]
+    operands: Vec<String>,
+
+    #[arg(short('i'), long)]
+    ignore_environment: bool,
+
+    #[arg(short('u'), long, value_name = "NAME", action(ArgAction::Append))]
+    unset: Vec<String>,
+
+    #[arg(short('0'), long)]
+    null: bool,
+
+    #[arg(short('S'), long, value_name = "STRING")]
+    split_string: Option<String>,
+}
+
+/// The environment to run the command in, along with the command itself
+#[derive(Debug, PartialEq)]
+struct Invocation {
+    vars: Vec<(OsString, OsString)>,
+    command: Vec<String>,
+}
+
+/// Splits the string of `-S` on whitespace and parses the words as if they
+/// had been given in its place, options and all. This lets a `#!` line,
+/// which passes everything after the interpreter as one argument, give
+/// enver several.
+fn split_string(mut args: Args) -> Result<Args> {
+    while let Some(string) = args.split_string.take() {
+        let words = string.split_whitespace().map(str::to_string);
+        let argv = iter::once("enver".to_string())
+            .chain(words)
+            .chain(mem::take(&mut args.operands));
+        let split = Args::try_parse_from(argv)?;
+        args.ignore_environment |= split.ignore_environment;
+        args.unset.extend(split.unset);
+        args.null |= split.null;
+        args.split_string = split.split_string;
+        args.operands = split.operands;
+    }
+    Ok(args)
+}
+
+/// Builds the environment from `env`: with `-i` it starts out empty, then
+/// loses the variables named by `-u`, and then takes the `NAME=VALUE`
+/// assignments up to the first operand that isn't one, which starts the
+/// command.
+fn invocation(args: Args, env: impl Iterator<Item = (OsString, OsString)>) -> Result<Invocation> {
+    for name in &args.unset {
+        if name.is_empty() || name.contains('=') {
+            bail!("cannot unset '{name}': Invalid argument");
+        }
+    }
+    let mut vars: Vec<_> = env
+        .filter(|_| !args.ignore_environment)
+        .filter(|(name, _)| !args.unset.iter().any(|unset| name == unset.as_str()))
+        .collect();
+
+    let mut operands = args.operands.into_iter().peekable();
+    while let Some((name, value)) = operands.peek().and_then(|op| op.split_once('=')) {
+        if name.is_empty() {
+            bail!("cannot set '{name}={value}': Invalid argument");
+        }
+        let (name, value) = (OsString::from(name), OsString::from(value));
+        vars.retain(|(existing, _)| *existing != name);
+        vars.push((name, value));
+        operands.next();
+    }
+    let command: Vec<_> = operands.collect();
+    if args.null && !command.is_empty() {
+        bail!("cannot specify --null (-0) with command");
+    }
+    Ok(Invocation { vars, command })
+}
+
+fn print_env(vars: &[(OsString, OsString)], null: bool) -> Result<()> {
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    for (name, value) in vars {
+        stdout.write_all(name.as_encoded_bytes())?;
+        stdout.write_all(b"=")?;
+        stdout.write_all(value.as_encoded_bytes())?;
+        stdout.write_all(if null { b"\0" } else { b"\n" })?;
+    }
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Runs the command in exactly the environment given, and passes on its
+/// exit status. A command killed by a signal exits with 128 plus the
+/// signal number, as a shell reports it.
+fn run_command(command: &[String], vars: Vec<(OsString, OsString)>) -> ExitCode {
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .env_clear()
+        .envs(vars)
+        .status();
+    match status {
+        Err(e) => {
+            eprintln!("{}: {e}", command[0]);
+            ExitCode::from(match e.kind() {
+                ErrorKind::NotFound => NOT_FOUND,
+                _ => CANNOT_RUN,
+            })
+        }
+        Ok(status) => {
+            #[cfg(unix)]
+            if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&status) {
+                return ExitCode::from(128 + signal as u8);
+            }
+            ExitCode::from(status.code().unwrap_or(1) as u8)
+        }
+    }
+}
+
+fn run(args: Args) -> Result<ExitCode> {
+    let args = split_string(args)?;
+    let null = args.null;
+    let invocation = invocation(args, std::env::vars_os())?;
+    if invocation.command.is_empty() {
+        print_env(&invocation.vars, null)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    Ok(run_command(&invocation.command, invocation.vars))
+}
+
+fn main() -> ExitCode {
+    match run(parse_args()) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::from(FAILURE)
+        }
+        Ok(code) => code,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Args, Invocation, invocation, split_string};
+    use clap::Parser;
+    use std::ffi::OsString;
+
+    fn env() -> impl Iterator<Item = (OsString, OsString)> {
+        [("HOME", "/root"), ("LANG", "C"), ("PATH", "/bin")]
+            .into_iter()
+            .map(|(name, value)| (name.into(), value.into()))
+    }
+
+    fn build(args: &[&str]) -> Invocation {
+        let args = Args::parse_from(["enver"].iter().chain(args));
+        invocation(args, env()).unwrap()
+    }
+
+    fn names(invocation: &Invocation) -> Vec<String> {
+        invocation
+            .vars
+            .iter()
+            .map(|(name, value)| format!("{}={}", name.display(), value.display()))
+            .collect()
+    }
+
+    #[test]
+    fn test_invocation() {
+        let built = build(&[]);
+        assert_eq!(names(&built), ["HOME=/root", "LANG=C", "PATH=/bin"]);
+        assert!(built.command.is_empty());
+
+        // a new value replaces the old one, and is passed on last
+        let built = build(&["LANG=en", "X=a=b", "ls", "-l", "Y=1"]);
+        assert_eq!(
+            names(&built),
+            ["HOME=/root", "PATH=/bin", "LANG=en", "X=a=b"]
+        );
+        assert_eq!(built.command, ["ls", "-l", "Y=1"]);
+
+        let built = build(&["-i", "X=1", "-u", "X"]);
+        assert_eq!(names(&built), ["X=1"]);
+
+        let built = build(&["-u", "HOME", "--unset=PATH", "ls"]);
+        assert_eq!(names(&built), ["LANG=C"]);
+        assert_eq!(built.command, ["ls"]);
+
+        // an empty value is still set
+        let built = build(&["-i", "EMPTY="]);
+        assert_eq!(names(&built), ["EMPTY="]);
+    }
+
+    #[test]
+    fn test_split_string() {
+        let split = |args: &[&str]| {
+            let args = Args::parse_from(["enver"].iter().chain(args));
+            split_string(args).unwrap()
+        };
+        let args = split(&["-S", " X=1  sh\t-c ", "echo $X"]);
+        assert_eq!(args.operands, ["X=1", "sh", "-c", "echo $X"]);
+        assert_eq!(args.split_string, None);
+
+        // options in the string are options of enver
+        let args = split(&["-u", "A", "-S", "-i -u B -0 -S Y=2", "X=1"]);
+        assert!(args.ignore_environment);
+        assert!(args.null);
+        assert_eq!(args.unset, ["A", "B"]);
+        assert_eq!(args.operands, ["Y=2", "X=1"]);
+    }
+
+    #[test]
+    fn test_invocation_errors() {
+        let error = |args: &[&str]| {
+            let args = Args::parse_from(["enver"].iter().chain(args));
+            invocation(args, env()).unwrap_err().to_string()
+        };
+        assert_eq!(
+            error(&["-u", "A=B"]),
+            "cannot unset 'A=B': Invalid argument"
+        );
+        assert_eq!(error(&["-u", ""]), "cannot unset '': Invalid argument");
+        assert_eq!(error(&["=x"]), "cannot set '=x': Invalid argument");
+        assert_eq!(
+            error(&["-0", "ls"]),
+            "cannot specify --null (-0) with command"
+        );
+    }
+}
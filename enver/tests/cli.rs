@@ -0,0 +1,167 @@
+use anyhow::Result;
+use assert_cmd::{Command, cargo::cargo_bin};
+use predicates::prelude::*;
+use std::path::Path;
+
+const PRG: &str = "enver";
+
+// --------------------------------------------------
+/// enver itself, run as the command so it prints the environment it got
+fn child() -> &'static Path {
+    cargo_bin!()
+}
+
+// --------------------------------------------------
+#[test]
+fn prints_environment() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .env("ENVER_TEST", "some value")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("(?m)^ENVER_TEST=some value$")?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn ignore_environment() -> Result<()> {
+    for flag in ["-i", "--ignore-environment"] {
+        Command::cargo_bin(PRG)?
+            .env("ENVER_TEST", "1")
+            .args([flag, "A=1", "B=two words"])
+            .assert()
+            .success()
+            .stdout("A=1\nB=two words\n");
+    }
+    Command::cargo_bin(PRG)?
+        .arg("-i")
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn null() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-0", "-i", "A=1", "B=x\ny"])
+        .assert()
+        .success()
+        .stdout("A=1\0B=x\ny\0");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn child_gets_environment() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .env("ENVER_TEST", "1")
+        .args(["-i", "A=1", "A=2", "B=3"])
+        .arg(child())
+        .assert()
+        .success()
+        .stdout("A=2\nB=3\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn child_loses_unset() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .env("ENVER_TEST", "1")
+        .env("ENVER_KEEP", "2")
+        .args(["-u", "ENVER_TEST", "--unset", "PATH"])
+        .arg(child())
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("(?m)^ENVER_KEEP=2$")?)
+        .stdout(predicate::str::is_match("(?m)^ENVER_TEST=")?.not())
+        .stdout(predicate::str::is_match("(?m)^PATH=")?.not());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn child_args() -> Result<()> {
+    // options after the command are the command's own
+    Command::cargo_bin(PRG)?
+        .args(["-i", "A=1"])
+        .arg(child())
+        .args(["-u", "A", "-0", "B=2"])
+        .assert()
+        .success()
+        .stdout("B=2\0");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn split_string() -> Result<()> {
+    let script = format!("-i A=1 {} -0", child().display());
+    Command::cargo_bin(PRG)?
+        .args(["-S", &script, "B=2"])
+        .assert()
+        .success()
+        .stdout("A=1\0B=2\0");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn exit_status() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["sh", "-c", "exit 3"])
+        .assert()
+        .code(3);
+    Command::cargo_bin(PRG)?
+        .args(["sh", "-c", "kill -TERM $$"])
+        .assert()
+        .code(128 + 15);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_such_command() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg("enver-no-such-command")
+        .assert()
+        .code(127)
+        .stderr(predicate::str::starts_with("enver-no-such-command: "));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_null_with_command() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-0", "ls"])
+        .assert()
+        .code(125)
+        .stderr("cannot specify --null (-0) with command\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_unset() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-u", "A=B"])
+        .assert()
+        .code(125)
+        .stderr("cannot unset 'A=B': Invalid argument\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
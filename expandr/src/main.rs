@@ -0,0 +1,132 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{TabStops, exit_with_error, open, parse_args};
+use std::io::{self, BufRead, BufWriter, Write};
+use std::iter;
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    #[arg(
+        short('t'),
+        long,
+        value_name = "LIST",
+        default_value = "8",
+        value_parser = TabStops::parse
+    )]
+    tabs: TabStops,
+}
+
+/// Splits `line` into the pieces that each take a column: grapheme
+/// clusters, where invalid UTF-8 counts byte by byte.
+fn units(line: &[u8]) -> Vec<&[u8]> {
+    let mut units = vec![];
+    let mut rest = line;
+    for chunk in line.utf8_chunks() {
+        for grapheme in chunk.valid().graphemes(true) {
+            let (unit, tail) = rest.split_at(grapheme.len());
+            units.push(unit);
+            rest = tail;
+        }
+        for _ in chunk.invalid() {
+            let (unit, tail) = rest.split_at(1);
+            units.push(unit);
+            rest = tail;
+        }
+    }
+    units
+}
+
+/// Appends `line` to `output` with each tab replaced by enough spaces to
+/// reach the next tab stop.
+fn expand(line: &[u8], tabs: &TabStops, output: &mut Vec<u8>) {
+    let mut column = 0;
+    for unit in units(line) {
+        match unit {
+            b"\t" => {
+                // past the last stop of a list, a tab is a single space
+                let next = tabs.next_stop(column).unwrap_or(column + 1);
+                output.extend(iter::repeat_n(b' ', next - column));
+                column = next;
+            }
+            b"\x08" => {
+                output.extend(unit);
+                column = column.saturating_sub(1);
+            }
+            _ => {
+                output.extend(unit);
+                column += 1;
+            }
+        }
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut line = vec![];
+    let mut output = vec![];
+
+    for filename in &args.files {
+        match open(filename) {
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(mut file) => loop {
+                line.clear();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+                output.clear();
+                expand(&line, &args.tabs, &mut output);
+                stdout.write_all(&output)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expand, units};
+    use cli_utils::TabStops;
+
+    fn expand_str(line: &str, tabs: TabStops) -> String {
+        let mut output = vec![];
+        expand(line.as_bytes(), &tabs, &mut output);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_units() {
+        assert_eq!(units(b"ab"), [b"a", b"b"]);
+        // a letter and its combining accent make one column
+        assert_eq!(units("e\u{301}x".as_bytes()), ["e\u{301}".as_bytes(), b"x"]);
+        assert_eq!(units(b"a\xffb"), [b"a", b"\xff", b"b"]);
+    }
+
+    #[test]
+    fn test_expand() {
+        assert_eq!(expand_str("\tx\n", TabStops::Every(8)), "        x\n");
+        assert_eq!(expand_str("abc\tx", TabStops::Every(4)), "abc x");
+        assert_eq!(expand_str("abcd\tx", TabStops::Every(4)), "abcd    x");
+        assert_eq!(expand_str("a b\n", TabStops::Every(8)), "a b\n");
+        assert_eq!(expand_str("日本\tx", TabStops::Every(4)), "日本  x");
+        assert_eq!(
+            expand_str("e\u{301}\tx", TabStops::Every(4)),
+            "e\u{301}   x"
+        );
+        assert_eq!(expand_str("ab\x08\tx", TabStops::Every(4)), "ab\x08   x");
+    }
+
+    #[test]
+    fn test_expand_list() {
+        let tabs = TabStops::List(vec![3, 5]);
+        assert_eq!(expand_str("a\tb\tc\td\n", tabs), "a  b c d\n");
+    }
+}
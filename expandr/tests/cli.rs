@@ -0,0 +1,140 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "expandr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const MIXED: &str = "tests/inputs/mixed.txt";
+const TABBED: &str = "tests/inputs/tabbed.txt";
+const UTF8: &str = "tests/inputs/utf8.txt";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_tabs() -> Result<()> {
+    for (tabs, message) in [
+        ("0", "tab size cannot be 0"),
+        ("8,4", "tab sizes must be ascending"),
+        ("4,x", "invalid tab size: 'x'"),
+    ] {
+        Command::cargo_bin(PRG)?
+            .args(["-t", tabs, MIXED])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(message));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args([MIXED, &bad])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string("tests/expected/mixed.txt.out")?)
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn default_tabs() -> Result<()> {
+    run(&[MIXED], "tests/expected/mixed.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tab_size() -> Result<()> {
+    run(&["-t", "4", MIXED], "tests/expected/mixed.txt.t4.out")?;
+    run(&["--tabs=4", MIXED], "tests/expected/mixed.txt.t4.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn tab_list() -> Result<()> {
+    // past the last stop each tab is a single space
+    run(
+        &["-t", "4,10,12", MIXED],
+        "tests/expected/mixed.txt.t_list.out",
+    )?;
+    run(
+        &["--tabs", "4 10 12", MIXED],
+        "tests/expected/mixed.txt.t_list.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn round_trip() -> Result<()> {
+    // tabbed.txt is mixed.txt expanded and then unexpanded with -a
+    run(&[TABBED], "tests/expected/mixed.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn utf8() -> Result<()> {
+    // each grapheme cluster takes a single column
+    run(&[UTF8], "tests/expected/utf8.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> Result<()> {
+    run(&[EMPTY], EMPTY)
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin() -> Result<()> {
+    let input = fs::read_to_string(MIXED)?;
+    let expected = fs::read_to_string("tests/expected/mixed.txt.out")?;
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
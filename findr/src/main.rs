@@ -1,10 +1,54 @@
-use anyhow::Result;
+use anyhow::{Result, anyhow, bail};
 use clap::{ArgAction, Parser, ValueEnum, builder::PossibleValue};
+use cli_utils::{exit_with_error, parse_args_from};
+use glob::{MatchOptions, Pattern};
 use regex::Regex;
+use std::{
+    cmp::Ordering,
+    env,
+    ffi::OsString,
+    fs,
+    io::{self, BufWriter, Write},
+    iter::Peekable,
+    time::SystemTime,
+};
 use walkdir::{DirEntry, WalkDir};
 
+/// The words that start a find-style expression, which is taken off the
+/// command line before the rest is parsed
+const EXPRESSION_START: &[&str] = &[
+    "-name",
+    "-iname",
+    "-type",
+    "-maxdepth",
+    "-mindepth",
+    "-newer",
+    "-size",
+    "-empty",
+    "-not",
+    "!",
+    "-and",
+    "-a",
+    "-or",
+    "-o",
+    "-print",
+    "-print0",
+    "(",
+];
+
 #[derive(Debug, Parser)]
-#[command(author, version, author)]
+#[command(
+    author,
+    version,
+    about,
+    override_usage = "findr [OPTIONS] [PATH]... [EXPRESSION]",
+    after_help = "\
+An EXPRESSION after the paths selects entries as find does, from these:
+  -name GLOB, -iname GLOB, -type f|d|l, -newer FILE, -size [+-]N[bckMG], -empty,
+  -maxdepth N, -mindepth N, -print, -print0,
+  ! EXPR, -not EXPR, EXPR [-a|-and] EXPR, EXPR -o|-or EXPR, ( EXPR )
+Entries are printed with -print unless the expression has -print or -print0."
+)]
 struct Args {
     #[arg(value_name = "PATH", default_value = ".")]
     paths: Vec<String>,
@@ -50,14 +94,283 @@ impl ValueEnum for EntryType {
     }
 }
 
-fn run(args: Args) -> Result<()> {
+impl EntryType {
+    fn matches(&self, entry: &DirEntry) -> bool {
+        match self {
+            EntryType::Link => entry.file_type().is_symlink(),
+            EntryType::Dir => entry.file_type().is_dir(),
+            EntryType::File => entry.file_type().is_file(),
+        }
+    }
+}
+
+/// A node of a find-style expression: a test of an entry, an action, or
+/// tests combined. Actions are true, like tests that always pass.
+#[derive(Debug)]
+enum Predicate {
+    True,
+    Name(Pattern),
+    IName(Pattern),
+    Type(EntryType),
+    /// Modified after this time
+    Newer(SystemTime),
+    /// Of a size that, in `unit`-byte units rounded up, compares as `cmp`
+    /// with `units`
+    Size {
+        cmp: Ordering,
+        units: u64,
+        unit: u64,
+    },
+    /// An empty regular file or directory
+    Empty,
+    /// Prints the path, ended by this byte
+    Print(u8),
+    Not(Box<Predicate>),
+    And(Box<Predicate>, Box<Predicate>),
+    Or(Box<Predicate>, Box<Predicate>),
+}
+
+impl Predicate {
+    /// Whether `entry` passes, printing it to `out` if an action says to.
+    /// The right side of `-a` and `-o` is only evaluated when it matters.
+    fn eval(&self, entry: &DirEntry, out: &mut impl Write) -> Result<bool> {
+        let name = || entry.file_name().to_string_lossy();
+        Ok(match self {
+            Predicate::True => true,
+            Predicate::Name(glob) => glob.matches(&name()),
+            Predicate::IName(glob) => glob.matches_with(
+                &name(),
+                MatchOptions {
+                    case_sensitive: false,
+                    ..MatchOptions::new()
+                },
+            ),
+            Predicate::Type(entry_type) => entry_type.matches(entry),
+            Predicate::Newer(time) => entry.metadata()?.modified()? > *time,
+            Predicate::Size { cmp, units, unit } => {
+                entry.metadata()?.len().div_ceil(*unit).cmp(units) == *cmp
+            }
+            Predicate::Empty => {
+                let file_type = entry.file_type();
+                (file_type.is_file() && entry.metadata()?.len() == 0)
+                    || (file_type.is_dir() && fs::read_dir(entry.path())?.next().is_none())
+            }
+            Predicate::Print(end) => {
+                write!(out, "{}", entry.path().display())?;
+                out.write_all(&[*end])?;
+                true
+            }
+            Predicate::Not(predicate) => !predicate.eval(entry, out)?,
+            Predicate::And(left, right) => left.eval(entry, out)? && right.eval(entry, out)?,
+            Predicate::Or(left, right) => left.eval(entry, out)? || right.eval(entry, out)?,
+        })
+    }
+}
+
+/// A parsed expression, with the depth options pulled out of it.
+#[derive(Debug)]
+struct Expression {
+    predicate: Predicate,
+    max_depth: Option<usize>,
+    min_depth: usize,
+}
+
+/// Reads `-size`'s `[+-]N[bckMG]`. Without a suffix, N counts 512-byte
+/// blocks, as in find.
+fn parse_size(value: &str) -> Result<Predicate> {
+    let (cmp, rest) = match value.as_bytes().first() {
+        Some(b'+') => (Ordering::Greater, &value[1..]),
+        Some(b'-') => (Ordering::Less, &value[1..]),
+        _ => (Ordering::Equal, value),
+    };
+    let (digits, unit) = match rest.char_indices().last() {
+        Some((i, suffix)) if suffix.is_ascii_alphabetic() => {
+            let unit = match suffix {
+                'b' => 512,
+                'c' => 1,
+                'k' => 1 << 10,
+                'M' => 1 << 20,
+                'G' => 1 << 30,
+                _ => bail!("invalid argument '{value}' to '-size'"),
+            };
+            (&rest[..i], unit)
+        }
+        _ => (rest, 512),
+    };
+    match digits.parse() {
+        Ok(units) if !digits.starts_with('+') => Ok(Predicate::Size { cmp, units, unit }),
+        _ => bail!("invalid argument '{value}' to '-size'"),
+    }
+}
+
+/// A recursive descent parser for expressions, where `-o` binds less
+/// tightly than `-a`, which may be left out, and `!` binds most tightly.
+struct ExpressionParser<I: Iterator<Item = String>> {
+    tokens: Peekable<I>,
+    max_depth: Option<usize>,
+    min_depth: usize,
+    has_action: bool,
+}
+
+impl<I: Iterator<Item = String>> ExpressionParser<I> {
+    fn or(&mut self) -> Result<Predicate> {
+        let mut left = self.and()?;
+        while let Some(op) = self.tokens.next_if(|t| t == "-o" || t == "-or") {
+            if matches!(self.tokens.peek().map(String::as_str), None | Some(")")) {
+                bail!("expected an expression after '{op}'");
+            }
+            let right = self.and()?;
+            left = Predicate::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and(&mut self) -> Result<Predicate> {
+        let mut left = self.not()?;
+        loop {
+            let op = self.tokens.next_if(|t| t == "-a" || t == "-and");
+            match self.tokens.peek().map(String::as_str) {
+                None | Some("-o" | "-or" | ")") => match op {
+                    Some(op) => bail!("expected an expression after '{op}'"),
+                    None => return Ok(left),
+                },
+                _ => {
+                    let right = self.not()?;
+                    left = Predicate::And(Box::new(left), Box::new(right));
+                }
+            }
+        }
+    }
+
+    fn not(&mut self) -> Result<Predicate> {
+        match self.tokens.next_if(|t| t == "!" || t == "-not") {
+            Some(op) => match self.tokens.peek() {
+                None => bail!("expected an expression after '{op}'"),
+                Some(_) => Ok(Predicate::Not(Box::new(self.not()?))),
+            },
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Predicate> {
+        let Some(token) = self.tokens.next() else {
+            bail!("expected an expression");
+        };
+        let mut arg = || {
+            self.tokens
+                .next()
+                .ok_or_else(|| anyhow!("missing argument to '{token}'"))
+        };
+        Ok(match token.as_str() {
+            "(" => {
+                let inner = self.or()?;
+                if self.tokens.next().as_deref() != Some(")") {
+                    bail!("expected ')' after '('");
+                }
+                inner
+            }
+            "-name" | "-iname" => {
+                let glob = arg()?;
+                let pattern =
+                    Pattern::new(&glob).map_err(|e| anyhow!("invalid pattern '{glob}': {e}"))?;
+                if token == "-name" {
+                    Predicate::Name(pattern)
+                } else {
+                    Predicate::IName(pattern)
+                }
+            }
+            "-type" => {
+                let value = arg()?;
+                Predicate::Type(
+                    EntryType::from_str(&value, false)
+                        .map_err(|_| anyhow!("invalid argument '{value}' to '-type'"))?,
+                )
+            }
+            "-maxdepth" | "-mindepth" => {
+                let value = arg()?;
+                let depth = value
+                    .parse()
+                    .map_err(|_| anyhow!("invalid argument '{value}' to '{token}'"))?;
+                if token == "-maxdepth" {
+                    self.max_depth = Some(depth);
+                } else {
+                    self.min_depth = depth;
+                }
+                Predicate::True
+            }
+            "-newer" => {
+                let file = arg()?;
+                let time = fs::symlink_metadata(&file)
+                    .and_then(|metadata| metadata.modified())
+                    .map_err(|e| anyhow!("{file}: {e}"))?;
+                Predicate::Newer(time)
+            }
+            "-size" => parse_size(&arg()?)?,
+            "-empty" => Predicate::Empty,
+            "-print" | "-print0" => {
+                self.has_action = true;
+                Predicate::Print(if token == "-print" { b'\n' } else { b'\0' })
+            }
+            _ => bail!("unknown predicate '{token}'"),
+        })
+    }
+}
+
+/// Parses the expression in `tokens`. With no action, the entries that
+/// pass are printed, as if the expression were `( EXPR ) -print`.
+fn parse_expression(tokens: impl IntoIterator<Item = String>) -> Result<Expression> {
+    let mut parser = ExpressionParser {
+        tokens: tokens.into_iter().peekable(),
+        max_depth: None,
+        min_depth: 0,
+        has_action: false,
+    };
+    let predicate = if parser.tokens.peek().is_none() {
+        Predicate::True
+    } else {
+        parser.or()?
+    };
+    if let Some(token) = parser.tokens.next() {
+        bail!("unexpected '{token}'");
+    }
+    let predicate = if parser.has_action {
+        predicate
+    } else {
+        Predicate::And(Box::new(predicate), Box::new(Predicate::Print(b'\n')))
+    };
+    Ok(Expression {
+        predicate,
+        max_depth: parser.max_depth,
+        min_depth: parser.min_depth,
+    })
+}
+
+/// Splits the command line at the first word of an expression.
+fn split_expression(args: Vec<OsString>) -> (Vec<OsString>, Vec<String>) {
+    let start = args
+        .iter()
+        .skip(1)
+        .position(|arg| {
+            arg.to_str()
+                .is_some_and(|arg| EXPRESSION_START.contains(&arg))
+        })
+        .map_or(args.len(), |i| i + 1);
+    let mut args = args;
+    let expression = args
+        .split_off(start)
+        .into_iter()
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    (args, expression)
+}
+
+fn run(args: Args, expression: Expression) -> Result<()> {
     let type_filter = |entry: &DirEntry| {
         args.entry_types.is_empty()
-            || args.entry_types.iter().any(|entry_type| match entry_type {
-                EntryType::Link => entry.file_type().is_symlink(),
-                EntryType::Dir => entry.file_type().is_dir(),
-                EntryType::File => entry.file_type().is_file(),
-            })
+            || args
+                .entry_types
+                .iter()
+                .any(|entry_type| entry_type.matches(entry))
     };
 
     let name_filter = |entry: &DirEntry| {
@@ -68,8 +381,12 @@ fn run(args: Args) -> Result<()> {
                 .any(|re| re.is_match(&entry.file_name().to_string_lossy()))
     };
 
+    let mut out = BufWriter::new(io::stdout().lock());
     for path in &args.paths {
-        let entries = WalkDir::new(path)
+        let walk = WalkDir::new(path)
+            .min_depth(expression.min_depth)
+            .max_depth(expression.max_depth.unwrap_or(usize::MAX));
+        let entries = walk
             .into_iter()
             .filter_map(|e| match e {
                 Err(e) => {
@@ -79,17 +396,155 @@ fn run(args: Args) -> Result<()> {
                 Ok(entry) => Some(entry),
             })
             .filter(type_filter)
-            .filter(name_filter)
-            .map(|entry| entry.path().display().to_string())
-            .collect::<Vec<_>>();
-        println!("{}", entries.join("\n"));
+            .filter(name_filter);
+        for entry in entries {
+            if let Err(e) = expression.predicate.eval(&entry, &mut out) {
+                eprintln!("{}: {e}", entry.path().display());
+            }
+        }
     }
+    out.flush()?;
     Ok(())
 }
 
 fn main() {
-    if let Err(e) = run(Args::parse()) {
-        eprintln!("{e}");
-        std::process::exit(1)
+    let (args, expression) = split_expression(env::args_os().collect());
+    let args = parse_args_from(args);
+    exit_with_error!(parse_expression(expression).and_then(|expression| run(args, expression)));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Predicate, parse_expression, parse_size, split_expression};
+    use std::{cmp::Ordering, ffi::OsString};
+
+    fn tokens(expression: &str) -> Vec<String> {
+        expression.split(' ').map(str::to_string).collect()
+    }
+
+    /// The parsed `expression`, in prefix form, without the implied -print.
+    fn parse(expression: &str) -> String {
+        fn show(predicate: &Predicate) -> String {
+            match predicate {
+                Predicate::True => "true".to_string(),
+                Predicate::Name(glob) => format!("name {glob}"),
+                Predicate::IName(glob) => format!("iname {glob}"),
+                Predicate::Type(entry_type) => format!("type {entry_type:?}"),
+                Predicate::Newer(_) => "newer".to_string(),
+                Predicate::Size { cmp, units, unit } => format!("size {cmp:?} {units}x{unit}"),
+                Predicate::Empty => "empty".to_string(),
+                Predicate::Print(end) => format!("print {end}"),
+                Predicate::Not(p) => format!("(not {})", show(p)),
+                Predicate::And(l, r) => format!("(and {} {})", show(l), show(r)),
+                Predicate::Or(l, r) => format!("(or {} {})", show(l), show(r)),
+            }
+        }
+        let expression = parse_expression(tokens(expression)).unwrap();
+        match expression.predicate {
+            Predicate::And(predicate, print) if matches!(*print, Predicate::Print(b'\n')) => {
+                show(&predicate)
+            }
+            predicate => show(&predicate),
+        }
+    }
+
+    fn parse_err(expression: &str) -> String {
+        parse_expression(tokens(expression))
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn test_parse_expression() {
+        assert_eq!(parse("-name *.rs"), "name *.rs");
+        assert_eq!(parse("-type f -name a"), "(and type File name a)");
+        assert_eq!(parse("-type f -a -name a"), "(and type File name a)");
+        // -a binds more tightly than -o, and ! more tightly still
+        assert_eq!(
+            parse("-empty -o -type d -and -iname A*"),
+            "(or empty (and type Dir iname A*))"
+        );
+        assert_eq!(parse("! -empty -o -empty"), "(or (not empty) empty)");
+        assert_eq!(parse("-not -not -empty"), "(not (not empty))");
+        assert_eq!(
+            parse("( -empty -or -type l ) -type f"),
+            "(and (or empty type Link) type File)"
+        );
+    }
+
+    #[test]
+    fn test_parse_actions() {
+        // an explicit action replaces the implied -print
+        assert_eq!(
+            parse("-name a -o -name b -print0"),
+            "(or name a (and name b print 0))"
+        );
+        assert_eq!(parse("-print"), "print 10");
+        assert_eq!(parse("-empty"), "empty");
+
+        let expression = parse_expression(tokens("-maxdepth 2 -mindepth 1 -empty")).unwrap();
+        assert_eq!(expression.max_depth, Some(2));
+        assert_eq!(expression.min_depth, 1);
+        let expression = parse_expression(vec![]).unwrap();
+        assert_eq!(expression.max_depth, None);
+        assert!(matches!(expression.predicate, Predicate::And(..)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse_err("-name"), "missing argument to '-name'");
+        assert_eq!(parse_err("-type x"), "invalid argument 'x' to '-type'");
+        assert_eq!(
+            parse_err("-maxdepth -1"),
+            "invalid argument '-1' to '-maxdepth'"
+        );
+        assert_eq!(parse_err("-empty -o"), "expected an expression after '-o'");
+        assert_eq!(parse_err("-empty -a"), "expected an expression after '-a'");
+        assert_eq!(parse_err("-empty !"), "expected an expression after '!'");
+        assert_eq!(parse_err("-o -empty"), "unknown predicate '-o'");
+        assert_eq!(parse_err("( -empty"), "expected ')' after '('");
+        assert_eq!(parse_err("-empty )"), "unexpected ')'");
+        assert_eq!(parse_err("-bogus"), "unknown predicate '-bogus'");
+        assert!(parse_err("-newer no/such/file").starts_with("no/such/file: "));
+        assert!(parse_err("-name [").starts_with("invalid pattern '[': "));
+    }
+
+    #[test]
+    fn test_parse_size() {
+        let size = |value| match parse_size(value).unwrap() {
+            Predicate::Size { cmp, units, unit } => (cmp, units, unit),
+            _ => unreachable!(),
+        };
+        assert_eq!(size("10"), (Ordering::Equal, 10, 512));
+        assert_eq!(size("+10c"), (Ordering::Greater, 10, 1));
+        assert_eq!(size("-1k"), (Ordering::Less, 1, 1024));
+        assert_eq!(size("2M"), (Ordering::Equal, 2, 1 << 20));
+        assert_eq!(size("+0G"), (Ordering::Greater, 0, 1 << 30));
+        assert_eq!(size("3b"), (Ordering::Equal, 3, 512));
+        for bad in ["", "+", "k", "1x", "1.5k", "++1", "+-1", "1kk"] {
+            assert_eq!(
+                parse_size(bad).unwrap_err().to_string(),
+                format!("invalid argument '{bad}' to '-size'")
+            );
+        }
+    }
+
+    #[test]
+    fn test_split_expression() {
+        let split = |args: &[&str]| {
+            let (args, expression) = split_expression(args.iter().map(OsString::from).collect());
+            (args, expression)
+        };
+        let (args, expression) = split(&["findr", "a", "-t", "f", "-name", "*.rs", "-print"]);
+        assert_eq!(args, ["findr", "a", "-t", "f"]);
+        assert_eq!(expression, ["-name", "*.rs", "-print"]);
+
+        let (args, expression) = split(&["findr", "!", "-empty"]);
+        assert_eq!(args, ["findr"]);
+        assert_eq!(expression, ["!", "-empty"]);
+
+        let (args, expression) = split(&["findr", "-n", "a", "."]);
+        assert_eq!(args, ["findr", "-n", "a", "."]);
+        assert!(expression.is_empty());
     }
 }
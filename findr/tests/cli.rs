@@ -3,7 +3,13 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use rand::{distributions::Alphanumeric, Rng};
-use std::{borrow::Cow, fs, path::Path};
+use std::{
+    borrow::Cow,
+    fs::{self, File},
+    path::Path,
+    time::{Duration, SystemTime},
+};
+use tempfile::TempDir;
 
 const PRG: &str = "findr";
 
@@ -60,14 +66,14 @@ fn dies_bad_type() -> Result<()> {
 
 // --------------------------------------------------
 #[cfg(windows)]
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     // Equivalent to: Cow::Owned(format!("{}.windows", expected_file))
     format!("{}.windows", expected_file).into()
 }
 
 // --------------------------------------------------
 #[cfg(not(windows))]
-fn format_file_name(expected_file: &str) -> Cow<str> {
+fn format_file_name(expected_file: &str) -> Cow<'_, str> {
     // Equivalent to: Cow::Borrowed(expected_file)
     expected_file.into()
 }
@@ -312,3 +318,231 @@ fn unreadable_dir() -> Result<()> {
     assert!(stderr.contains("cant-touch-this: Permission denied"));
     Ok(())
 }
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
+
+// --------------------------------------------------
+/// Makes a tree of files of various sizes and ages, with `ref` beside it
+/// as the reference for `-newer`:
+///
+/// ```text
+/// tree/a.txt     0 bytes    tree/big.bin   1M
+/// tree/B.TXT     1 byte     tree/big1.bin  1M + 1 byte
+/// tree/c.log   512 bytes    tree/sub/e.txt 1024 bytes
+/// tree/d.log   513 bytes    tree/sub/deep/
+/// tree/link -> a.txt
+/// ```
+///
+/// `c.log` is newer than `ref`, `d.log` as old, and the others older.
+fn make_tree() -> Result<TempDir> {
+    let dir = tempfile::tempdir()?;
+    let tree = dir.path().join("tree");
+    fs::create_dir_all(tree.join("sub/deep"))?;
+    let now = SystemTime::now() - Duration::from_secs(1000);
+    let files: &[(&str, usize, SystemTime)] = &[
+        ("../ref", 0, now),
+        ("a.txt", 0, now - Duration::from_secs(100)),
+        ("B.TXT", 1, now - Duration::from_secs(100)),
+        ("c.log", 512, now + Duration::from_secs(10)),
+        ("d.log", 513, now),
+        ("big.bin", 1 << 20, now - Duration::from_secs(100)),
+        ("big1.bin", (1 << 20) + 1, now - Duration::from_secs(100)),
+        ("sub/e.txt", 1024, now - Duration::from_secs(100)),
+    ];
+    for (name, size, modified) in files {
+        let file = File::create(tree.join(name))?;
+        file.set_len(*size as u64)?;
+        file.set_modified(*modified)?;
+    }
+    #[cfg(unix)]
+    std::os::unix::fs::symlink("a.txt", tree.join("link"))?;
+    Ok(dir)
+}
+
+// --------------------------------------------------
+/// Runs findr on the tree with an expression, and returns what it printed,
+/// sorted, as the walk order depends on the file system.
+fn find_in_tree(expression: &[&str]) -> Result<Vec<String>> {
+    let dir = make_tree()?;
+    let output = Command::cargo_bin(PRG)?
+        .current_dir(dir.path().join("tree"))
+        .arg(".")
+        .args(expression)
+        .output()?;
+    assert!(output.status.success(), "findr {expression:?} failed");
+    let stdout = String::from_utf8(output.stdout)?.replace('\\', "/");
+    let end = if expression.contains(&"-print0") { '\0' } else { '\n' };
+    let mut lines: Vec<String> =
+        stdout.split_terminator(end).map(str::to_string).collect();
+    lines.sort();
+    Ok(lines)
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_name() -> Result<()> {
+    assert_eq!(find_in_tree(&["-name", "*.txt"])?, ["./a.txt", "./sub/e.txt"]);
+    assert_eq!(
+        find_in_tree(&["-iname", "*.txt"])?,
+        ["./B.TXT", "./a.txt", "./sub/e.txt"]
+    );
+    assert_eq!(find_in_tree(&["-name", "?.log"])?, ["./c.log", "./d.log"]);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_type() -> Result<()> {
+    assert_eq!(find_in_tree(&["-type", "d"])?, [".", "./sub", "./sub/deep"]);
+    #[cfg(unix)]
+    assert_eq!(find_in_tree(&["-type", "l"])?, ["./link"]);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_size() -> Result<()> {
+    let find = |size| find_in_tree(&["-type", "f", "-size", size]);
+    // sizes are rounded up to whole units
+    assert_eq!(find("0")?, ["./a.txt"]);
+    assert_eq!(find("1")?, ["./B.TXT", "./c.log"]);
+    assert_eq!(find("512c")?, ["./c.log"]);
+    assert_eq!(find("1k")?, ["./B.TXT", "./c.log", "./d.log", "./sub/e.txt"]);
+    assert_eq!(find("2")?, ["./d.log", "./sub/e.txt"]);
+    assert_eq!(
+        find("+512c")?,
+        ["./big.bin", "./big1.bin", "./d.log", "./sub/e.txt"]
+    );
+    assert_eq!(find("+1M")?, ["./big1.bin"]);
+    assert_eq!(find("2M")?, ["./big1.bin"]);
+    // so less than 1M is only what is empty
+    assert_eq!(find("-1M")?, ["./a.txt"]);
+    assert_eq!(find("-1c")?, ["./a.txt"]);
+    assert!(find("+1G")?.is_empty());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_newer() -> Result<()> {
+    // a file as old as the reference is not newer
+    assert_eq!(find_in_tree(&["-type", "f", "-newer", "../ref"])?, ["./c.log"]);
+    assert_eq!(
+        find_in_tree(&["-type", "f", "!", "-newer", "../ref"])?,
+        [
+            "./B.TXT",
+            "./a.txt",
+            "./big.bin",
+            "./big1.bin",
+            "./d.log",
+            "./sub/e.txt"
+        ]
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_empty() -> Result<()> {
+    assert_eq!(find_in_tree(&["-empty"])?, ["./a.txt", "./sub/deep"]);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_operators() -> Result<()> {
+    assert_eq!(
+        find_in_tree(&["-name", "*.log", "-o", "-name", "*.bin"])?,
+        ["./big.bin", "./big1.bin", "./c.log", "./d.log"]
+    );
+    assert_eq!(
+        find_in_tree(&["-type", "f", "-and", "-not", "-name", "*.*"])?,
+        Vec::<String>::new()
+    );
+    // -a binds more tightly than -o, unless in parentheses
+    assert_eq!(
+        find_in_tree(&["-type", "d", "-a", "-empty", "-or", "-name", "c.*"])?,
+        ["./c.log", "./sub/deep"]
+    );
+    assert_eq!(
+        find_in_tree(&["-type", "d", "(", "-empty", "-o", "-name", "s*", ")"])?,
+        ["./sub", "./sub/deep"]
+    );
+    #[cfg(unix)]
+    assert_eq!(
+        find_in_tree(&["!", "-type", "f", "-not", "-type", "d"])?,
+        ["./link"]
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_print() -> Result<()> {
+    assert_eq!(
+        find_in_tree(&["-name", "*.log", "-print0"])?,
+        ["./c.log", "./d.log"]
+    );
+    // an explicit action replaces the implied -print
+    assert_eq!(
+        find_in_tree(&["-name", "c.log", "-print", "-o", "-name", "d.log"])?,
+        ["./c.log"]
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_depth() -> Result<()> {
+    assert_eq!(
+        find_in_tree(&["-maxdepth", "1", "-type", "d"])?,
+        [".", "./sub"]
+    );
+    assert_eq!(
+        find_in_tree(&["-mindepth", "2"])?,
+        ["./sub/deep", "./sub/e.txt"]
+    );
+    assert_eq!(find_in_tree(&["-maxdepth", "0"])?, ["."]);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn expr_after_options() -> Result<()> {
+    assert_eq!(
+        find_in_tree(&["-t", "f", "-n", "txt", "-size", "+0"])?,
+        ["./sub/e.txt"]
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_expression() -> Result<()> {
+    for (expression, expected) in [
+        (&["-name"][..], "missing argument to '-name'"),
+        (&["(", "-empty"], "expected ')' after '('"),
+        (&["-size", "1x"], "invalid argument '1x' to '-size'"),
+        (&["-type", "x"], "invalid argument 'x' to '-type'"),
+        (&["-empty", "-bogus"], "unknown predicate '-bogus'"),
+        (&["-newer", "no/such/file"], "no/such/file: "),
+    ] {
+        Command::cargo_bin(PRG)?
+            .arg("tests/inputs")
+            .args(expression)
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(predicate::str::contains(expected));
+    }
+    Ok(())
+}
@@ -0,0 +1,203 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{exit_with_error, open, parse_args};
+use std::io::{self, BufRead, BufWriter, Write};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    #[arg(
+        short('w'),
+        long,
+        value_name = "WIDTH",
+        default_value_t = 80,
+        value_parser(clap::value_parser!(u64).range(1..))
+    )]
+    width: u64,
+
+    #[arg(short('s'), long)]
+    spaces: bool,
+
+    #[arg(short('c'), long)]
+    characters: bool,
+
+    #[arg(
+        short('t'),
+        long,
+        value_name = "TABSIZE",
+        default_value_t = 8,
+        value_parser(clap::value_parser!(u64).range(1..))
+    )]
+    tab_size: u64,
+}
+
+/// Wraps lines the way GNU `fold` does. Each byte, or each character with
+/// `-c`, takes one column, except that a tab moves to the next tab stop, a
+/// backspace moves back one column and a carriage return goes back to the
+/// start of the line.
+#[derive(Debug)]
+struct Folder {
+    width: usize,
+    tab_size: usize,
+    spaces: bool,
+    characters: bool,
+}
+
+impl Folder {
+    fn new(args: &Args) -> Self {
+        Self {
+            width: args.width as usize,
+            tab_size: args.tab_size as usize,
+            spaces: args.spaces,
+            characters: args.characters,
+        }
+    }
+
+    fn next_column(&self, column: usize, unit: &[u8]) -> usize {
+        match unit {
+            b"\t" => column + self.tab_size - column % self.tab_size,
+            b"\x08" => column.saturating_sub(1),
+            b"\r" => 0,
+            _ => column + 1,
+        }
+    }
+
+    /// Splits `line` into the pieces that each take a column: single bytes,
+    /// or whole characters with `-c`, where invalid UTF-8 counts byte by
+    /// byte.
+    fn units<'a>(&self, line: &'a [u8]) -> Vec<&'a [u8]> {
+        if !self.characters {
+            return line.chunks(1).collect();
+        }
+        let mut units = vec![];
+        let mut rest = line;
+        for chunk in line.utf8_chunks() {
+            for c in chunk.valid().chars() {
+                let (unit, tail) = rest.split_at(c.len_utf8());
+                units.push(unit);
+                rest = tail;
+            }
+            for _ in chunk.invalid() {
+                let (unit, tail) = rest.split_at(1);
+                units.push(unit);
+                rest = tail;
+            }
+        }
+        units
+    }
+
+    /// Appends `line` to `output`, with a newline wherever the next column
+    /// would go past the width.
+    fn fold(&self, line: &[u8], output: &mut Vec<u8>) {
+        let (text, ending) = match line.strip_suffix(b"\n") {
+            Some(text) => (text, &b"\n"[..]),
+            None => (line, &b""[..]),
+        };
+        let mut pending: Vec<&[u8]> = vec![];
+        let mut column = 0;
+        for unit in self.units(text) {
+            let mut next = self.next_column(column, unit);
+            // a unit wider than the whole line still has to go somewhere
+            while next > self.width && !pending.is_empty() {
+                let blank = self
+                    .spaces
+                    .then(|| pending.iter().rposition(|u| matches!(u, [b' ' | b'\t'])))
+                    .flatten();
+                // with -s, break after the last blank, and carry the rest
+                // of the pending text over to the next line
+                let split = blank.map_or(pending.len(), |i| i + 1);
+                output.extend(pending.drain(..split).flatten());
+                output.push(b'\n');
+                column = pending
+                    .iter()
+                    .fold(0, |col, unit| self.next_column(col, unit));
+                next = self.next_column(column, unit);
+            }
+            pending.push(unit);
+            column = next;
+        }
+        output.extend(pending.into_iter().flatten());
+        output.extend(ending);
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let folder = Folder::new(&args);
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut line = vec![];
+    let mut output = vec![];
+
+    for filename in &args.files {
+        match open(filename) {
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(mut file) => loop {
+                line.clear();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+                output.clear();
+                folder.fold(&line, &mut output);
+                stdout.write_all(&output)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Folder;
+
+    fn fold(line: &str, width: usize, spaces: bool, characters: bool) -> String {
+        let folder = Folder {
+            width,
+            tab_size: 8,
+            spaces,
+            characters,
+        };
+        let mut output = vec![];
+        folder.fold(line.as_bytes(), &mut output);
+        String::from_utf8_lossy(&output).into_owned()
+    }
+
+    #[test]
+    fn test_fold() {
+        assert_eq!(fold("abcdef\n", 3, false, false), "abc\ndef\n");
+        assert_eq!(fold("abc\n", 3, false, false), "abc\n");
+        assert_eq!(fold("ab\n", 3, false, false), "ab\n");
+        assert_eq!(fold("abcdefg", 3, false, false), "abc\ndef\ng");
+        assert_eq!(fold("\n", 3, false, false), "\n");
+    }
+
+    #[test]
+    fn test_fold_spaces() {
+        assert_eq!(fold("ab cd ef\n", 5, true, false), "ab \ncd ef\n");
+        assert_eq!(fold("ab cd ef\n", 5, false, false), "ab cd\n ef\n");
+        // without a blank to break at, -s breaks at the width
+        assert_eq!(fold("abcdefgh\n", 3, true, false), "abc\ndef\ngh\n");
+        assert_eq!(fold("a\tbcdefghij\n", 10, true, false), "a\t\nbcdefghij\n");
+    }
+
+    #[test]
+    fn test_fold_tabs() {
+        // a tab counts up to the next stop, and never splits
+        assert_eq!(fold("ab\tcd\n", 10, false, false), "ab\tcd\n");
+        assert_eq!(fold("ab\tcd\n", 9, false, false), "ab\tc\nd\n");
+        assert_eq!(fold("\t\t\n", 4, false, false), "\t\n\t\n");
+        assert_eq!(fold("abc\rdef\n", 3, false, false), "abc\rdef\n");
+    }
+
+    #[test]
+    fn test_fold_characters() {
+        assert_eq!(fold("ααββ\n", 2, false, true), "αα\nββ\n");
+        assert_eq!(fold("ααββ\n", 4, false, false), "αα\nββ\n");
+        assert_eq!(fold("日本 語\n", 2, true, true), "日本\n 語\n");
+    }
+}
@@ -0,0 +1,201 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "foldr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const EXACT: &str = "tests/inputs/exact.txt";
+const FOX: &str = "tests/inputs/fox.txt";
+const LOREM: &str = "tests/inputs/lorem.txt";
+const NOSPACE: &str = "tests/inputs/nospace.txt";
+const TABS: &str = "tests/inputs/tabs.txt";
+const UTF8: &str = "tests/inputs/utf8.txt";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_width() -> Result<()> {
+    for width in ["0", "wide"] {
+        Command::cargo_bin(PRG)?
+            .args(["-w", width, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(format!(
+                "invalid value '{width}' for '--width <WIDTH>'"
+            )));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args(["-w", "10", FOX, &bad, FOX])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string("tests/expected/fox.txt.w10.out")?.repeat(2))
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn default_width() -> Result<()> {
+    run(&[LOREM], "tests/expected/lorem.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn exact_width() -> Result<()> {
+    // lines of exactly the width are left alone
+    run(&["-w", "20", EXACT], "tests/expected/exact.txt.w20.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn shorter_than_width() -> Result<()> {
+    run(
+        &["--width", "40", EXACT],
+        "tests/expected/exact.txt.w40.out",
+    )?;
+    run(&["-w", "300", LOREM], LOREM)
+}
+
+// --------------------------------------------------
+#[test]
+fn breaks_mid_word() -> Result<()> {
+    run(&["-w", "30", LOREM], "tests/expected/lorem.txt.w30.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn spaces() -> Result<()> {
+    run(
+        &["-s", "-w", "30", LOREM],
+        "tests/expected/lorem.txt.s_w30.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn spaces_without_whitespace() -> Result<()> {
+    // with nowhere better to break, -s still breaks at the width
+    run(
+        &["--spaces", "-w", "20", NOSPACE],
+        "tests/expected/nospace.txt.s_w20.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tabs() -> Result<()> {
+    run(&["-w", "15", TABS], "tests/expected/tabs.txt.w15.out")?;
+    run(
+        &["-s", "-w", "20", TABS],
+        "tests/expected/tabs.txt.s_w20.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tab_size() -> Result<()> {
+    run(
+        &["-t", "4", "-w", "15", TABS],
+        "tests/expected/tabs.txt.t4_w15.out",
+    )?;
+    run(
+        &["--tab-size=4", "-w", "15", TABS],
+        "tests/expected/tabs.txt.t4_w15.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn utf8_bytes() -> Result<()> {
+    // counting bytes splits multi-byte characters, as GNU fold does
+    let expected = fs::read("tests/expected/utf8.txt.w10.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["-w", "10", UTF8])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn utf8_characters() -> Result<()> {
+    run(
+        &["-c", "-w", "10", UTF8],
+        "tests/expected/utf8.txt.c_w10.out",
+    )?;
+    run(
+        &["--characters", "-s", "-w", "12", UTF8],
+        "tests/expected/utf8.txt.cs_w12.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files() -> Result<()> {
+    run(
+        &["-w", "10", FOX, EMPTY, EXACT],
+        "tests/expected/all.w10.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin() -> Result<()> {
+    let input = fs::read_to_string(FOX)?;
+    let expected = fs::read_to_string("tests/expected/fox.txt.w10.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["-w", "10"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,49 @@
+//! Compares counting the lines with "eternity" in a 100 MB file of the
+//! words in tests/inputs as a regex and with `-F`, with and without `-i`.
+//! The counts go to /dev/null, so only the searching is timed.
+//!
+//! Run with `cargo bench --bench fixed`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use std::fs::{self, File};
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+const SIZE: usize = 100 * 1024 * 1024;
+
+fn count(file: &Path, flags: &[&str]) {
+    let status = Command::new(env!("CARGO_BIN_EXE_grepr"))
+        .arg("-c")
+        .args(flags)
+        .args(["eternity".as_ref(), file.as_os_str()])
+        .stdout(File::create("/dev/null").unwrap())
+        .stderr(Stdio::inherit())
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn fixed(c: &mut Criterion) {
+    let mut words = String::new();
+    for name in ["bustle.txt", "fox.txt", "nobody.txt"] {
+        words += &fs::read_to_string(Path::new("tests/inputs").join(name)).unwrap();
+    }
+    let dir = tempfile::tempdir().unwrap();
+    let file = dir.path().join("words.txt");
+    fs::write(&file, words.repeat(SIZE / words.len() + 1)).unwrap();
+
+    let mut group = c.benchmark_group("100 MB");
+    group.sample_size(10);
+    for (name, flags) in [
+        ("regex", &[][..]),
+        ("fixed", &["-F"]),
+        ("regex -i", &["-i"]),
+        ("fixed -i", &["-Fi"]),
+    ] {
+        group.bench_function(name, |b| b.iter(|| count(&file, flags)));
+    }
+    group.finish();
+}
+
+criterion_group!(benches, fixed);
+criterion_main!(benches);
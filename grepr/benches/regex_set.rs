@@ -0,0 +1,81 @@
+//! Compares matching each line of 10 MB of random words against the 50
+//! patterns in tests/patterns/words.txt one regex at a time, as grepr did
+//! before `combine_regexes`, with matching them all at once as a `RegexSet`.
+//! Almost no line matches, so each one is scanned for every pattern.
+//!
+//! Run with `cargo bench --bench regex_set`.
+//!
+//! The set took 46ms against 147ms one regex at a time, and 94ms against
+//! 337ms with `-i`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+use std::fs;
+
+const SIZE: usize = 10 * 1024 * 1024;
+
+/// Lines of a few random lowercase words, always the same ones.
+fn corpus() -> String {
+    let mut rng = StdRng::seed_from_u64(0);
+    let mut text = String::with_capacity(SIZE);
+    while text.len() < SIZE {
+        for i in 0..8 {
+            if i > 0 {
+                text.push(' ');
+            }
+            let len = rng.gen_range(3..9);
+            text.extend((0..len).map(|_| rng.gen_range('a'..='z')));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+fn each_regex(text: &str, regexes: &[Regex]) -> usize {
+    text.lines()
+        .filter(|line| regexes.iter().any(|re| re.is_match(line)))
+        .count()
+}
+
+fn set(text: &str, set: &RegexSet) -> usize {
+    text.lines().filter(|line| set.is_match(line)).count()
+}
+
+fn regex_set(c: &mut Criterion) {
+    let words = fs::read_to_string("tests/patterns/words.txt").unwrap();
+    let patterns: Vec<_> = words.lines().filter(|line| !line.is_empty()).collect();
+    assert_eq!(patterns.len(), 50);
+    let text = corpus();
+
+    let mut group = c.benchmark_group("50 patterns");
+    group.sample_size(10);
+    for insensitive in [false, true] {
+        let regexes: Vec<_> = patterns
+            .iter()
+            .map(|pattern| {
+                RegexBuilder::new(pattern)
+                    .case_insensitive(insensitive)
+                    .build()
+                    .unwrap()
+            })
+            .collect();
+        let regex_set = RegexSetBuilder::new(&patterns)
+            .case_insensitive(insensitive)
+            .build()
+            .unwrap();
+        assert_eq!(each_regex(&text, &regexes), set(&text, &regex_set));
+
+        let suffix = if insensitive { " -i" } else { "" };
+        group.bench_function(format!("each regex{suffix}"), |b| {
+            b.iter(|| each_regex(&text, &regexes))
+        });
+        group.bench_function(format!("set{suffix}"), |b| {
+            b.iter(|| set(&text, &regex_set))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, regex_set);
+criterion_main!(benches);
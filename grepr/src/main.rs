@@ -1,158 +1,2985 @@
 use std::{
+    borrow::Cow,
+    cmp::Reverse,
+    collections::VecDeque,
+    env,
     fs::{self, File},
-    io::{self, BufRead, BufReader},
-    mem,
+    io::{self, BufRead, BufReader, BufWriter, Cursor, IsTerminal, LineWriter, Read, Seek, Write},
+    iter, mem,
+    ops::Range,
+    path::Path,
+    process::ExitCode,
+    str,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    thread,
+    time::{Duration, Instant},
 };
 
 use anyhow::{Result, anyhow};
-use clap::Parser;
-use regex::{Regex, RegexBuilder};
+use clap::{ArgAction, Parser, ValueEnum};
+use cli_utils::schema::{GreprRecord, MachineFormat, MachineOutput};
+use cli_utils::{open, parse_args};
+use flate2::read::GzDecoder;
+#[cfg(feature = "async")]
+use futures_lite::{Stream, StreamExt, stream};
+use glob::Pattern;
+use memchr::{Memchr, memchr_iter};
+use memmap2::Mmap;
+use regex::{Regex, RegexBuilder, RegexSet, RegexSetBuilder};
+use serde::{Deserialize, Serialize};
+use tar::{Archive, Entry};
+use unicode_segmentation::UnicodeSegmentation;
 use walkdir::WalkDir;
+use zip::{ZipArchive, read::ZipFile};
 
 #[derive(Debug, Parser)]
-#[command(author, version, about)]
+#[command(author, version, about, disable_help_flag = true)]
 struct Args {
-    #[arg()]
-    pattern: String,
-    #[arg(default_value = "-", value_name = "FILE")]
+    #[arg(required_unless_present_any(["regexp", "file"]))]
+    pattern: Option<String>,
+    #[arg(value_name = "FILE")]
     files: Vec<String>,
+    #[arg(short('e'), long, value_name = "PATTERN", action(ArgAction::Append))]
+    regexp: Vec<String>,
+    #[arg(short('f'), long, value_name = "FILE", action(ArgAction::Append))]
+    file: Vec<String>,
     #[arg(short, long)]
     insensitive: bool,
+    /// Search directories recursively, skipping the symbolic links met on
+    /// the way
     #[arg(short, long)]
     recursive: bool,
+    /// Search directories recursively, following every symbolic link
+    #[arg(short('R'), long, conflicts_with("recursive"))]
+    dereference_recursive: bool,
     #[arg(short, long)]
     count: bool,
+    #[arg(long, requires("count"))]
+    total_count: bool,
+    #[arg(short('l'), long, conflicts_with_all(["count", "files_without_match"]))]
+    files_with_matches: bool,
+    #[arg(short('L'), long, conflicts_with("count"))]
+    files_without_match: bool,
+    #[arg(short('F'), long)]
+    fixed_strings: bool,
+    #[arg(short('P'), long, conflicts_with("fixed_strings"))]
+    perl_regexp: bool,
+    /// Read patterns as extended regular expressions, as they always are
+    #[arg(short('E'), long, conflicts_with_all(["fixed_strings", "perl_regexp"]))]
+    extended_regexp: bool,
+    /// Read patterns as POSIX basic regular expressions
+    #[arg(
+        short('G'),
+        long,
+        conflicts_with_all(["fixed_strings", "perl_regexp", "extended_regexp"])
+    )]
+    basic_regexp: bool,
+    /// Match only whole words, bounded by characters that aren't letters,
+    /// digits or underscores
+    #[arg(short('w'), long, conflicts_with("line_regexp"))]
+    word_regexp: bool,
+    /// Match only whole words, bounded as in Unicode text segmentation
+    /// (UAX #29), which also splits runs of CJK text that -w sees as one word
+    #[arg(short('W'), long, conflicts_with_all(["word_regexp", "line_regexp"]))]
+    unicode_word: bool,
+    #[arg(short('x'), long)]
+    line_regexp: bool,
+    #[arg(short('m'), long, value_name = "NUM")]
+    max_count: Option<usize>,
     #[arg(short('v'), long("invert-match"))]
     invert: bool,
+    #[arg(short('o'), long, conflicts_with_all(["count", "invert"]))]
+    only_matching: bool,
+    /// Print every line, with only the matching ones colored and marked by
+    /// `:` after the filename or line number rather than `-`
+    #[arg(
+        long,
+        conflicts_with_all(["count", "files_with_matches", "only_matching"])
+    )]
+    passthrough: bool,
+    #[arg(short('n'), long)]
+    line_number: bool,
+    #[arg(short('Z'), long)]
+    null: bool,
+    /// Put a tab between the filename or line number and the line, so the
+    /// lines can be lined up
+    #[arg(short('T'), long)]
+    initial_tab: bool,
+    /// Flush the output after every line, even when it goes to a pipe
+    #[arg(long)]
+    line_buffered: bool,
+    /// Print the filename with each match, even for a single file
+    #[arg(short('H'), long)]
+    with_filename: bool,
+    /// Never print filenames with matches
+    #[arg(short('h'), long, conflicts_with("with_filename"))]
+    no_filename: bool,
+    /// Show standard input as LABEL rather than as -
+    #[arg(long, value_name = "LABEL")]
+    label: Option<String>,
+    #[arg(short('q'), long, alias("silent"))]
+    quiet: bool,
+    #[arg(long)]
+    stats: bool,
+    /// Print the time each phase of the search took for each file
+    #[arg(long)]
+    profile: bool,
+    #[arg(long)]
+    zip: bool,
+    /// Search the files in .tar and .tar.gz archives
+    #[arg(long)]
+    tar: bool,
+    /// Skip the files in tar archives that are larger than BYTES
+    #[arg(
+        long,
+        value_name = "BYTES",
+        default_value_t = 100_000_000,
+        requires("tar")
+    )]
+    max_entry_size: u64,
+    #[arg(long, value_name = "WHEN", value_enum, default_value_t = ColorWhen::Auto)]
+    color: ColorWhen,
+    #[arg(long, value_name = "FORMAT", value_enum, default_value_t = OutputFormat::Text)]
+    output_format: OutputFormat,
+    /// Print matches, counts and files as records in the common JSON or CSV
+    /// schema, all at the end
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_enum,
+        conflicts_with("output_format")
+    )]
+    machine_readable: Option<MachineFormat>,
+    #[arg(long, value_name = "TYPE", value_enum, default_value_t = BinaryFiles::Binary)]
+    binary_files: BinaryFiles,
+    #[arg(
+        short('A'),
+        long,
+        value_name = "NUM",
+        conflicts_with_all(["count", "files_with_matches", "files_without_match"])
+    )]
+    after_context: Option<usize>,
+    #[arg(
+        short('B'),
+        long,
+        value_name = "NUM",
+        conflicts_with_all(["count", "files_with_matches", "files_without_match"])
+    )]
+    before_context: Option<usize>,
+    #[arg(
+        short('C'),
+        long,
+        value_name = "NUM",
+        conflicts_with_all(["count", "files_with_matches", "files_without_match"])
+    )]
+    context: Option<usize>,
+    #[arg(
+        long,
+        value_name = "SEP",
+        default_value = "--",
+        conflicts_with("no_group_separator")
+    )]
+    group_separator: String,
+    #[arg(long)]
+    no_group_separator: bool,
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser(Pattern::new),
+        action(ArgAction::Append)
+    )]
+    include: Vec<Pattern>,
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser(Pattern::new),
+        action(ArgAction::Append)
+    )]
+    exclude: Vec<Pattern>,
+    /// Read --include globs from FILE, one per line
+    #[arg(long, value_name = "FILE", action(ArgAction::Append))]
+    include_from: Vec<String>,
+    /// Read --exclude globs from FILE, one per line
+    #[arg(long, value_name = "FILE", action(ArgAction::Append))]
+    exclude_from: Vec<String>,
+    #[arg(
+        long,
+        value_name = "GLOB",
+        value_parser(Pattern::new),
+        action(ArgAction::Append)
+    )]
+    exclude_dir: Vec<Pattern>,
+    /// Map files into memory to search them, rather than reading them
+    /// through a buffer
+    #[arg(long)]
+    mmap: bool,
+    /// Read files smaller than BYTES through a buffer even with --mmap
+    #[arg(long, value_name = "BYTES", default_value_t = 1 << 20, requires("mmap"))]
+    mmap_threshold: u64,
+    /// Stop searching after SECONDS, with a warning and an exit status of 2
+    #[arg(long, value_name = "SECONDS", value_parser = parse_timeout)]
+    timeout: Option<Duration>,
+    /// Walk directories asynchronously; symbolic links are not followed
+    #[cfg(feature = "async")]
+    #[arg(long("async"), conflicts_with("dereference_recursive"))]
+    async_walk: bool,
+    /// Print help
+    #[arg(long, action = ArgAction::Help)]
+    help: Option<bool>,
 }
 
-fn main() {
-    if let Err(e) = run(Args::parse()) {
-        eprintln!("{e}");
-        std::process::exit(1);
+#[derive(Clone, Debug, Default)]
+struct WalkFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    exclude_dir: Vec<Pattern>,
+}
+
+impl WalkFilter {
+    fn is_match(patterns: &[Pattern], name: &str) -> bool {
+        patterns.iter().any(|pattern| pattern.matches(name))
+    }
+
+    fn allows_dir(&self, name: &str) -> bool {
+        !Self::is_match(&self.exclude_dir, name)
+    }
+
+    fn allows_file(&self, name: &str) -> bool {
+        (self.include.is_empty() || Self::is_match(&self.include, name))
+            && !Self::is_match(&self.exclude, name)
+    }
+}
+
+/// One way of matching a line. An enum rather than a trait, as
+/// `combine_regexes` has to pick out the plain regexes.
+#[derive(Debug)]
+enum Matcher {
+    Regex(Regex),
+    /// A regex with backtracking features such as lookaround and
+    /// backreferences
+    Perl(fancy_regex::Regex),
+    /// A literal needle, lowercased when matching is case-insensitive
+    Fixed {
+        needle: String,
+        insensitive: bool,
+    },
+    /// Several regexes, matched in one pass over the line by the set. Each
+    /// one is only run on its own to find where it matched.
+    Set {
+        set: RegexSet,
+        regexes: Vec<Regex>,
+    },
+    /// Matches that start and end on Unicode word boundaries, found by
+    /// trying a matcher anchored at the start of the text from each one
+    UnicodeWord(Box<Matcher>),
+}
+
+/// The pattern language, from `-F`, `-P` or `-G`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Syntax {
+    Regex,
+    Basic,
+    Fixed,
+    Perl,
+}
+
+/// How much of a line a match has to cover, from `-w`, `-W` or `-x`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Anchor {
+    None,
+    Word,
+    UnicodeWord,
+    Line,
+}
+
+impl Anchor {
+    fn wrap(self, pattern: &str) -> String {
+        match self {
+            Anchor::None => pattern.to_string(),
+            Anchor::Word => format!(r"\b(?:{pattern})\b"),
+            Anchor::UnicodeWord => format!("^(?:{pattern})"),
+            Anchor::Line => format!("^(?:{pattern})$"),
+        }
+    }
+}
+
+impl Matcher {
+    fn is_match(&self, line: &str) -> bool {
+        let line = line_content(line);
+        match self {
+            Matcher::Regex(re) => re.is_match(line),
+            Matcher::Set { set, .. } => set.is_match(line),
+            // a match that gives up after too much backtracking is no match
+            Matcher::Perl(re) => re.is_match(line).unwrap_or(false),
+            Matcher::Fixed {
+                needle,
+                insensitive: false,
+            } => line.contains(needle.as_str()),
+            Matcher::Fixed {
+                needle,
+                insensitive: true,
+            } => line.to_lowercase().contains(needle.as_str()),
+            Matcher::UnicodeWord(_) => !self.find_spans(line).is_empty(),
+        }
+    }
+
+    fn find_spans(&self, line: &str) -> Vec<Range<usize>> {
+        let line = line_content(line);
+        match self {
+            Matcher::Regex(re) => re
+                .find_iter(line)
+                .filter(|m| !m.is_empty())
+                .map(|m| m.range())
+                .collect(),
+            Matcher::Set { regexes, .. } => regexes
+                .iter()
+                .flat_map(|re| re.find_iter(line))
+                .filter(|m| !m.is_empty())
+                .map(|m| m.range())
+                .collect(),
+            Matcher::Perl(re) => re
+                .find_iter(line)
+                .flatten()
+                .filter(|m| m.start() != m.end())
+                .map(|m| m.range())
+                .collect(),
+            Matcher::Fixed { needle, .. } if needle.is_empty() => vec![],
+            Matcher::Fixed {
+                needle,
+                insensitive: false,
+            } => fixed_spans(line, needle),
+            Matcher::Fixed {
+                needle,
+                insensitive: true,
+            } => {
+                let lower = line.to_lowercase();
+                let offsets = lowercase_offsets(line);
+                // a match that takes only part of what a character
+                // lowercased to is no match in the original line
+                fixed_spans(&lower, needle)
+                    .into_iter()
+                    .filter_map(|span| Some(offsets[span.start]?..offsets[span.end]?))
+                    .collect()
+            }
+            Matcher::UnicodeWord(anchored) => {
+                let boundaries: Vec<usize> = line
+                    .split_word_bound_indices()
+                    .map(|(start, _)| start)
+                    .chain(iter::once(line.len()))
+                    .collect();
+                let mut spans = vec![];
+                for &start in &boundaries {
+                    if spans
+                        .last()
+                        .is_some_and(|span: &Range<usize>| start < span.end)
+                    {
+                        continue;
+                    }
+                    let Some(found) = anchored.find_spans(&line[start..]).into_iter().next() else {
+                        continue;
+                    };
+                    let end = start + found.end;
+                    if boundaries.binary_search(&end).is_ok() {
+                        spans.push(start..end);
+                    }
+                }
+                spans
+            }
+        }
+    }
+}
+
+/// A line without its line ending, which patterns never see, so that `$`
+/// and `-x` match at the end of the text. Offsets into it are also offsets
+/// into the whole line.
+fn line_content(line: &str) -> &str {
+    line.trim_end_matches(['\r', '\n'])
+}
+
+fn fixed_spans(line: &str, needle: &str) -> Vec<Range<usize>> {
+    line.match_indices(needle)
+        .map(|(start, m)| start..start + m.len())
+        .collect()
+}
+
+/// For each byte offset in the lowercased `line`, and the one at its end,
+/// the offset in `line` of the same place, or `None` inside what a single
+/// character lowercased to, as `İ` lowercases to two.
+fn lowercase_offsets(line: &str) -> Vec<Option<usize>> {
+    let mut offsets = vec![];
+    for (start, c) in line.char_indices() {
+        let len: usize = c.to_lowercase().map(char::len_utf8).sum();
+        offsets.push(Some(start));
+        offsets.extend(iter::repeat_n(None, len - 1));
+    }
+    offsets.push(Some(line.len()));
+    offsets
+}
+
+/// Decides where the separator goes between groups of lines that are not
+/// adjacent. Groups in different files are always separated.
+#[derive(Debug)]
+struct ContextPrinter {
+    separator: Option<String>,
+    last_line: Option<usize>,
+    printed_any: bool,
+}
+
+impl ContextPrinter {
+    fn new(separator: Option<String>) -> Self {
+        ContextPrinter {
+            separator,
+            last_line: None,
+            printed_any: false,
+        }
+    }
+
+    fn start_file(&mut self) {
+        self.last_line = None;
+    }
+
+    /// Records that line `line_num` is printed next, returning the
+    /// separator to print before it if it starts a new group
+    fn next_line(&mut self, line_num: usize) -> Option<&str> {
+        let new_group = self.printed_any && self.last_line.is_none_or(|last| line_num > last + 1);
+        self.last_line = Some(line_num);
+        self.printed_any = true;
+        self.separator.as_deref().filter(|_| new_group)
+    }
+}
+
+/// Times the phases of a search for `--profile`: finding the files,
+/// opening them, matching their lines and printing what matched. A phase
+/// that runs while a file is being searched counts towards that file;
+/// others only count towards the total. Nothing is timed unless enabled.
+#[derive(Debug, Default)]
+struct Profiler {
+    enabled: bool,
+    phase: Option<(&'static str, Instant)>,
+    files: Vec<(String, Vec<(&'static str, Duration)>)>,
+    in_file: bool,
+    other: Vec<(&'static str, Duration)>,
+}
+
+impl Profiler {
+    const PHASES: [&str; 4] = ["discovery", "open", "match", "output"];
+
+    fn new(enabled: bool) -> Self {
+        Profiler {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// Starts counting phases towards `name`.
+    fn start_file(&mut self, name: &str) {
+        if self.enabled {
+            self.files.push((name.to_string(), vec![]));
+            self.in_file = true;
+        }
+    }
+
+    fn end_file(&mut self) {
+        self.in_file = false;
+    }
+
+    fn start_phase(&mut self, name: &'static str) {
+        if self.enabled {
+            self.phase = Some((name, Instant::now()));
+        }
+    }
+
+    fn end_phase(&mut self) {
+        if let Some((name, started)) = self.phase.take() {
+            self.record(name, started.elapsed());
+        }
+    }
+
+    /// Adds `duration` to the phase `name`, for phases that can't be timed
+    /// in one go.
+    fn record(&mut self, name: &'static str, duration: Duration) {
+        if !self.enabled {
+            return;
+        }
+        match self.files.last_mut() {
+            Some((_, phases)) if self.in_file => phases.push((name, duration)),
+            _ => self.other.push((name, duration)),
+        }
+    }
+
+    /// Returns a table with a row for each file and one for the total, and
+    /// a column for each phase.
+    fn report(&self) -> String {
+        let all = || {
+            self.files
+                .iter()
+                .flat_map(|(_, phases)| phases)
+                .chain(&self.other)
+        };
+        let names = Self::PHASES;
+        let cells = |phases: Vec<&(&str, Duration)>| -> Vec<String> {
+            names
+                .iter()
+                .map(|name| {
+                    let times: Vec<_> = phases.iter().filter(|(n, _)| n == name).collect();
+                    if times.is_empty() {
+                        String::new()
+                    } else {
+                        format!("{:.1?}", times.iter().map(|(_, d)| *d).sum::<Duration>())
+                    }
+                })
+                .collect()
+        };
+        let mut rows: Vec<Vec<String>> = vec![
+            iter::once("file")
+                .chain(names.iter().copied())
+                .map(String::from)
+                .collect(),
+        ];
+        for (file, phases) in &self.files {
+            rows.push(
+                iter::once(file.clone())
+                    .chain(cells(phases.iter().collect()))
+                    .collect(),
+            );
+        }
+        rows.push(
+            iter::once("total".to_string())
+                .chain(cells(all().collect()))
+                .collect(),
+        );
+
+        let widths: Vec<_> = (0..=names.len())
+            .map(|col| {
+                rows.iter()
+                    .map(|row| row[col].chars().count())
+                    .max()
+                    .unwrap_or(0)
+            })
+            .collect();
+        let mut table = String::new();
+        for row in rows {
+            let mut line = format!("{:<width$}", row[0], width = widths[0]);
+            for (cell, width) in row[1..].iter().zip(&widths[1..]) {
+                line.push_str(&format!("  {cell:>width$}"));
+            }
+            table.push_str(line.trim_end());
+            table.push('\n');
+        }
+        table
+    }
+}
+
+/// A name to report, whether it looks binary, and the lines to search
+type Source = (String, bool, Input);
+
+/// A file to search, read through a buffer or mapped into memory
+enum Input {
+    Read(Box<dyn BufRead>),
+    Mapped(Mmap),
+}
+
+#[derive(Debug, PartialEq)]
+enum Line {
+    Match(usize, String),
+    Context(usize, String),
+    /// A line that doesn't match, let through by `--passthrough`
+    Passed(usize, String),
+}
+
+/// Whether any file had a matching line, which `-q` reports as the exit
+/// status, or whether --timeout cut the search short.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MatchResult {
+    Matched,
+    NoMatch,
+    TimedOut,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum ColorWhen {
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+/// How to treat files that look binary, as decided by `is_binary`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum BinaryFiles {
+    Binary,
+    Text,
+    WithoutMatch,
+}
+
+/// One line of `--output-format json` output
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct JsonRecord {
+    // only the --total-count record has no file
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    file: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_number: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    line_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    match_count: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    total_count: Option<usize>,
+}
+
+impl JsonRecord {
+    fn new(file: &str) -> Self {
+        JsonRecord {
+            file: file.to_string(),
+            line_number: None,
+            line_content: None,
+            match_count: None,
+            total_count: None,
+        }
+    }
+}
+
+const RESET_COLOR: &str = "\x1b[0m";
+
+/// The SGR codes that `--color` paints each part of the output with, such
+/// as `01;31` for bold red. An empty code leaves that part uncolored, and
+/// by default only matches are colored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ColorScheme {
+    filename: String,
+    line_number: String,
+    matched: String,
+    separator: String,
+}
+
+impl Default for ColorScheme {
+    fn default() -> Self {
+        ColorScheme {
+            filename: String::new(),
+            line_number: String::new(),
+            matched: "01;31".to_string(),
+            separator: String::new(),
+        }
+    }
+}
+
+impl ColorScheme {
+    /// Wraps `text` in the escape codes for `sgr`, if it has any.
+    fn paint(sgr: &str, text: &str) -> String {
+        if sgr.is_empty() || text.is_empty() {
+            text.to_string()
+        } else {
+            format!("\x1b[{sgr}m{text}{RESET_COLOR}")
+        }
+    }
+}
+
+/// Reads colors in the format of GNU grep's `GREP_COLORS`: `key=SGR`
+/// entries separated by colons, as in `fn=35:ln=32:mt=01;31`. The keys are
+/// `fn`, `ln`, `se`, and `mt` or `ms` for matches. Entries with an unknown
+/// key, no `=` or a code that isn't digits and semicolons are skipped, so
+/// the part they were for keeps its default.
+fn parse_grep_colors(s: &str) -> ColorScheme {
+    let mut colors = ColorScheme::default();
+    for entry in s.split(':') {
+        let Some((key, sgr)) = entry.split_once('=') else {
+            continue;
+        };
+        if !sgr.bytes().all(|b| b.is_ascii_digit() || b == b';') {
+            continue;
+        }
+        let field = match key {
+            "fn" => &mut colors.filename,
+            "ln" => &mut colors.line_number,
+            "mt" | "ms" => &mut colors.matched,
+            "se" => &mut colors.separator,
+            _ => continue,
+        };
+        *field = sgr.to_string();
+    }
+    colors
+}
+
+/// Reads a --timeout, in seconds that may have a fraction.
+fn parse_timeout(value: &str) -> Result<Duration, String> {
+    value
+        .parse()
+        .ok()
+        .and_then(|secs| Duration::try_from_secs_f64(secs).ok())
+        .filter(|timeout| !timeout.is_zero())
+        .ok_or_else(|| format!("invalid number of seconds '{value}'"))
+}
+
+fn main() -> ExitCode {
+    let args: Args = parse_args();
+    let quiet = args.quiet;
+    match run(args) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Ok(MatchResult::NoMatch) if quiet => ExitCode::FAILURE,
+        Ok(MatchResult::TimedOut) => ExitCode::from(2),
+        Ok(_) => ExitCode::SUCCESS,
+    }
+}
+
+fn run(args: Args) -> Result<MatchResult> {
+    let started = Instant::now();
+    let colors =
+        env::var("GREPR_COLORS").map_or_else(|_| ColorScheme::default(), |s| parse_grep_colors(&s));
+    // with -e or -f, every positional argument is a file to search
+    let (patterns, mut files) = if args.regexp.is_empty() && args.file.is_empty() {
+        (args.pattern.into_iter().collect(), args.files)
+    } else {
+        let files = args.pattern.into_iter().chain(args.files).collect();
+        (args.regexp, files)
+    };
+    if files.is_empty() {
+        files.push("-".to_string());
+    }
+
+    let syntax = if args.fixed_strings {
+        Syntax::Fixed
+    } else if args.perl_regexp {
+        Syntax::Perl
+    } else if args.basic_regexp {
+        Syntax::Basic
+    } else {
+        Syntax::Regex
+    };
+    let anchor = if args.word_regexp {
+        Anchor::Word
+    } else if args.unicode_word {
+        Anchor::UnicodeWord
+    } else if args.line_regexp {
+        Anchor::Line
+    } else {
+        Anchor::None
+    };
+    let mut patterns = patterns
+        .iter()
+        .map(|pattern| build_matcher(pattern, args.insensitive, syntax, anchor))
+        .collect::<Result<Vec<_>>>()?;
+    for filename in &args.file {
+        patterns.extend(load_patterns(filename, args.insensitive, syntax, anchor)?);
+    }
+    let patterns = combine_regexes(patterns, args.insensitive);
+
+    // -R walks directories as -r does, but follows symbolic links
+    let recursive = args.recursive || args.dereference_recursive;
+    let follow_links = args.dereference_recursive;
+    let mut include = args.include;
+    for filename in &args.include_from {
+        include.extend(load_patterns_from_file(filename)?);
+    }
+    let mut exclude = args.exclude;
+    for filename in &args.exclude_from {
+        exclude.extend(load_patterns_from_file(filename)?);
+    }
+    let filter = WalkFilter {
+        include,
+        exclude,
+        exclude_dir: args.exclude_dir,
+    };
+    // directories are walked lazily, so names are shown whenever a walk
+    // could turn up more than one file
+    let show_names = args.with_filename
+        || (!args.no_filename
+            && (files.len() > 1
+                || (recursive && files.iter().any(|f| f != "-" && Path::new(f).is_dir()))
+                || (args.zip && files.iter().any(|f| is_zip(f)))
+                || (args.tar && files.iter().any(|f| is_tar(f)))));
+    #[cfg(feature = "async")]
+    let mut entries: Box<dyn Iterator<Item = Result<String>>> = if args.async_walk {
+        Box::new(find_files_async(&files, recursive, &filter)?)
+    } else {
+        Box::new(find_files(&files, recursive, follow_links, &filter))
+    };
+    #[cfg(not(feature = "async"))]
+    let mut entries = find_files(&files, recursive, follow_links, &filter);
+    // with -Z, a NUL takes the place of whatever would follow a filename
+    let null_terminate = args.null;
+    let name_end = if null_terminate { '\0' } else { '\n' };
+    // --machine-readable gathers the same records as --output-format json
+    let json = args.output_format == OutputFormat::Json || args.machine_readable.is_some();
+    let mut machine_records = vec![];
+    let colorize = !json
+        && match args.color {
+            ColorWhen::Always => true,
+            ColorWhen::Never => false,
+            ColorWhen::Auto => io::stdout().is_terminal(),
+        };
+    let paint = |sgr: &str, text: &str| {
+        if colorize {
+            ColorScheme::paint(sgr, text)
+        } else {
+            text.to_string()
+        }
+    };
+    let print = |out: &mut dyn Write, fname: &str, sep: char, val: &str| {
+        if show_names {
+            let fname = paint(&colors.filename, fname);
+            let sep = if null_terminate {
+                "\0".to_string()
+            } else {
+                paint(&colors.separator, &sep.to_string())
+            };
+            write!(out, "{fname}{sep}{val}")
+        } else {
+            write!(out, "{val}")
+        }
+    };
+    // output goes out a block at a time, unless someone may be watching
+    // the lines as they are found. Printing the 248k lines of a 10 MB file
+    // that match "e" to /dev/null took 0.09s a line at a time; it now
+    // takes 0.04s.
+    let mut out: Box<dyn Write> = if args.line_buffered || io::stdout().is_terminal() {
+        Box::new(LineWriter::new(io::stdout()))
+    } else {
+        Box::new(BufWriter::new(io::stdout()))
+    };
+    // with -T, a tab follows whatever comes before the line itself
+    let tab = if args.initial_tab && (show_names || args.line_number) {
+        "\t"
+    } else {
+        ""
+    };
+    let format_line = |line_num: usize, sep: char, line: &str, highlighted: bool| {
+        let line = if colorize && highlighted {
+            highlight(line, &patterns, &colors.matched)
+        } else {
+            line.to_string()
+        };
+        if args.line_number {
+            let line_num = paint(&colors.line_number, &line_num.to_string());
+            let sep = paint(&colors.separator, &sep.to_string());
+            format!("{line_num}{sep}{tab}{line}")
+        } else {
+            format!("{tab}{line}")
+        }
+    };
+    // listing filenames, or -q, only requires knowing whether a file has
+    // any match
+    let limit = if args.quiet || args.files_with_matches || args.files_without_match {
+        Some(args.max_count.map_or(1, |max| max.min(1)))
+    } else {
+        args.max_count
+    };
+    let before = args.before_context.or(args.context).unwrap_or(0);
+    let after = if args.quiet {
+        0
+    } else {
+        args.after_context.or(args.context).unwrap_or(0)
+    };
+    // as in GNU grep, any context option turns on separators, even -A 0
+    let has_context =
+        args.before_context.is_some() || args.after_context.is_some() || args.context.is_some();
+    let mut context_printer = ContextPrinter::new(
+        (has_context && !args.no_group_separator).then_some(args.group_separator),
+    );
+    let mut result = MatchResult::NoMatch;
+    let mut total = 0;
+    let mut files_scanned = 0;
+    let mut files_matched = 0;
+    let mut profiler = Profiler::new(args.profile);
+    // the timer only raises a flag, which the search looks at as it goes
+    let timed_out = Arc::new(AtomicBool::new(false));
+    if let Some(timeout) = args.timeout {
+        let timed_out = Arc::clone(&timed_out);
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            timed_out.store(true, Ordering::Relaxed);
+        });
+    }
+    'files: loop {
+        profiler.end_file();
+        if timed_out.load(Ordering::Relaxed) {
+            result = MatchResult::TimedOut;
+            break;
+        }
+        // directories are walked as their files are wanted
+        profiler.start_phase("discovery");
+        let entry = entries.next();
+        profiler.end_phase();
+        let filename = match entry {
+            None => break,
+            Some(Err(e)) => {
+                eprintln!("{e}");
+                continue;
+            }
+            Some(Ok(filename)) => filename,
+        };
+        profiler.start_file(&filename);
+        profiler.start_phase("open");
+        let sources = if args.zip && is_zip(&filename) {
+            match File::open(&filename) {
+                Err(e) => vec![Err(anyhow!("{filename}: {e}"))],
+                Ok(file) => zip_entries(
+                    &filename,
+                    BufReader::new(file),
+                    recursive,
+                    args.binary_files,
+                )
+                .unwrap_or_else(|e| vec![Err(anyhow!("{filename}: {e}"))]),
+            }
+        } else if args.tar && is_tar(&filename) {
+            match File::open(&filename) {
+                Err(e) => vec![Err(anyhow!("{filename}: {e}"))],
+                Ok(file) => tar_entries(
+                    &filename,
+                    BufReader::new(file),
+                    args.max_entry_size,
+                    args.binary_files,
+                )
+                .unwrap_or_else(|e| vec![Err(anyhow!("{filename}: {e}"))]),
+            }
+        } else {
+            let binary = args.binary_files != BinaryFiles::Text
+                && filename != "-"
+                && is_binary(Path::new(&filename));
+            let threshold = args.mmap.then_some(args.mmap_threshold);
+            vec![
+                open_input(&filename, threshold)
+                    .map(|input| (filename.clone(), binary, input))
+                    .map_err(|e| anyhow!("{filename}: {e}")),
+            ]
+        };
+        profiler.end_phase();
+        for source in sources {
+            if timed_out.load(Ordering::Relaxed) {
+                result = MatchResult::TimedOut;
+                break 'files;
+            }
+            let (filename, binary, input) = match source {
+                Err(e) => {
+                    eprintln!("{e}");
+                    continue;
+                }
+                Ok(source) => source,
+            };
+            let filename = match &args.label {
+                Some(label) if filename == "-" => label.clone(),
+                _ => filename,
+            };
+            if binary && args.binary_files == BinaryFiles::WithoutMatch {
+                continue;
+            }
+            let map;
+            let file: Box<dyn LineSource> = match input {
+                Input::Read(file) => {
+                    let file = StopReader::new(file, &timed_out);
+                    if binary {
+                        Box::new(ReadLines::new(Lossy(file)))
+                    } else {
+                        Box::new(ReadLines::new(file))
+                    }
+                }
+                Input::Mapped(mapped) => {
+                    map = mapped;
+                    Box::new(MappedLines::new(&map, binary))
+                }
+            };
+            let file = Stoppable::new(file, &timed_out);
+            // matching lines are printed as they are found, so a search of
+            // a pipe that stays open shows them straight away
+            let streamed = !(args.quiet
+                || json
+                || args.files_with_matches
+                || args.files_without_match
+                || args.count
+                || binary);
+            // printing happens in the middle of matching when the lines are
+            // streamed, so the time it takes is kept apart as it goes
+            let mut output_time = Duration::ZERO;
+            let started = args.profile.then(Instant::now);
+            let found = if streamed {
+                context_printer.start_file();
+                let mut write_line = |line: Line| -> Result<()> {
+                    let (line_num, sep, text) = match &line {
+                        Line::Match(num, text) => (*num, ':', text),
+                        Line::Context(num, text) | Line::Passed(num, text) => (*num, '-', text),
+                    };
+                    if !args.only_matching {
+                        if let Some(separator) = context_printer.next_line(line_num) {
+                            writeln!(out, "{}", paint(&colors.separator, separator))?;
+                        }
+                        let highlighted = !matches!(line, Line::Passed(..));
+                        let text = format_line(line_num, sep, text, highlighted);
+                        print(&mut out, &filename, sep, &text)?;
+                        return Ok(());
+                    }
+                    // each match goes on its own line, without context
+                    if sep == '-' {
+                        return Ok(());
+                    }
+                    for span in match_spans(text, &patterns) {
+                        let text = &text[span];
+                        let text = paint(&colors.matched, text);
+                        let text = if args.line_number {
+                            let line_num = paint(&colors.line_number, &line_num.to_string());
+                            format!("{line_num}{}{tab}{text}", paint(&colors.separator, ":"))
+                        } else {
+                            format!("{tab}{text}")
+                        };
+                        print(&mut out, &filename, ':', &format!("{text}\n"))?;
+                    }
+                    Ok(())
+                };
+                let (invert, passthrough) = (args.invert, args.passthrough);
+                scan_lines(
+                    file,
+                    &patterns,
+                    invert,
+                    limit,
+                    before,
+                    after,
+                    passthrough,
+                    |line| {
+                        if !args.profile {
+                            return write_line(line);
+                        }
+                        let started = Instant::now();
+                        let result = write_line(line);
+                        output_time += started.elapsed();
+                        result
+                    },
+                )
+                .map(|num_matches| (num_matches, vec![]))
+            } else {
+                let (invert, passthrough) = (args.invert, args.passthrough);
+                find_lines(file, &patterns, invert, limit, before, after, passthrough).map(
+                    |lines| {
+                        let num_matches = lines
+                            .iter()
+                            .filter(|line| matches!(line, Line::Match(..)))
+                            .count();
+                        (num_matches, lines)
+                    },
+                )
+            };
+            if let Some(started) = started {
+                profiler.record("match", started.elapsed().saturating_sub(output_time));
+                profiler.record("output", output_time);
+            }
+            match found {
+                Err(e) if is_timeout(&e) => {
+                    result = MatchResult::TimedOut;
+                    break 'files;
+                }
+                Err(e) => eprintln!("{e}"),
+                Ok((num_matches, lines)) => {
+                    if num_matches > 0 {
+                        result = MatchResult::Matched;
+                        files_matched += 1;
+                    }
+                    files_scanned += 1;
+                    total += num_matches;
+                    profiler.start_phase("output");
+                    if args.quiet {
+                        // the first match settles the exit status
+                        if result == MatchResult::Matched {
+                            break 'files;
+                        }
+                    } else if json {
+                        let records = json_records(
+                            &filename,
+                            &lines,
+                            args.count,
+                            args.files_with_matches,
+                            args.files_without_match,
+                        );
+                        if args.machine_readable.is_some() {
+                            machine_records.extend(records.into_iter().map(grepr_record));
+                        } else {
+                            for record in records {
+                                writeln!(out, "{}", serde_json::to_string(&record)?)?;
+                            }
+                        }
+                    } else if args.files_with_matches || args.files_without_match {
+                        if (num_matches == 0) == args.files_without_match {
+                            write!(out, "{}{name_end}", paint(&colors.filename, &filename))?;
+                        }
+                    } else if args.count {
+                        print(&mut out, &filename, ':', &format!("{num_matches}\n"))?;
+                    } else if binary && num_matches > 0 {
+                        writeln!(out, "Binary file {filename} matches")?;
+                    }
+                    profiler.end_phase();
+                }
+            }
+        }
+    }
+    if let (MatchResult::TimedOut, Some(timeout)) = (result, args.timeout) {
+        eprintln!("warning: timed out after {timeout:?}, so the search is incomplete");
+    }
+    if args.total_count && !args.quiet {
+        if json {
+            let record = JsonRecord {
+                total_count: Some(total),
+                ..JsonRecord::new("")
+            };
+            if args.machine_readable.is_some() {
+                machine_records.push(grepr_record(record));
+            } else {
+                writeln!(out, "{}", serde_json::to_string(&record)?)?;
+            }
+        } else {
+            print(&mut out, "total", ':', &format!("{total}\n"))?;
+        }
+    }
+    if let (Some(format), false) = (args.machine_readable, args.quiet) {
+        MachineOutput::Grepr(machine_records).write(format, &mut out)?;
+    }
+    out.flush()?;
+    // the summary goes to stderr, so stdout reads the same with or without it
+    if args.stats {
+        eprintln!(
+            "{files_scanned} files scanned, {files_matched} files with matches, \
+             {total} total matches, elapsed {:.1?}",
+            started.elapsed()
+        );
+    }
+    if args.profile {
+        eprint!("{}", profiler.report());
+    }
+    Ok(result)
+}
+
+fn build_matcher(
+    pattern: &str,
+    insensitive: bool,
+    syntax: Syntax,
+    anchor: Anchor,
+) -> Result<Matcher> {
+    if syntax == Syntax::Fixed {
+        // an anchored fixed string is easiest matched as an escaped regex
+        if anchor != Anchor::None {
+            return build_matcher(&regex::escape(pattern), insensitive, Syntax::Regex, anchor);
+        }
+        let needle = if insensitive {
+            pattern.to_lowercase()
+        } else {
+            pattern.to_string()
+        };
+        return Ok(Matcher::Fixed {
+            needle,
+            insensitive,
+        });
+    }
+    let wrapped = if syntax == Syntax::Basic {
+        anchor.wrap(&bre_to_ere(pattern))
+    } else {
+        anchor.wrap(pattern)
+    };
+    let matcher = if syntax == Syntax::Perl {
+        fancy_regex::RegexBuilder::new(&wrapped)
+            .case_insensitive(insensitive)
+            .build()
+            .map(Matcher::Perl)
+            .ok()
+    } else {
+        RegexBuilder::new(&wrapped)
+            .case_insensitive(insensitive)
+            .build()
+            .map(Matcher::Regex)
+            .ok()
+    };
+    match matcher {
+        None => Err(anyhow!(r#"Invalid pattern ""{pattern}""#)),
+        Some(matcher) if anchor == Anchor::UnicodeWord => {
+            Ok(Matcher::UnicodeWord(Box::new(matcher)))
+        }
+        Some(matcher) => Ok(matcher),
+    }
+}
+
+/// Puts the plain regexes among `patterns` into one `RegexSet`, so that a
+/// line that doesn't match is only scanned once rather than once per
+/// pattern. Other matchers are left as they are.
+// Counting the lines of 10 MB of random words that match any of the 50
+// patterns in tests/patterns/words.txt took 0.23s one regex at a time and
+// 0.06s with the set, or 0.54s and 0.11s with -i, when almost no line
+// matched (benches/regex_set.rs times the matching alone). With most lines matching one of the first few patterns, it went
+// from 0.09s to 0.07s.
+fn combine_regexes(patterns: Vec<Matcher>, insensitive: bool) -> Vec<Matcher> {
+    let mut regexes = vec![];
+    let mut others = vec![];
+    for pattern in patterns {
+        match pattern {
+            Matcher::Regex(re) => regexes.push(re),
+            other => others.push(other),
+        }
+    }
+    if regexes.len() > 1 {
+        // a set too big to compile is left as separate regexes
+        let set = RegexSetBuilder::new(regexes.iter().map(Regex::as_str))
+            .case_insensitive(insensitive)
+            .build();
+        if let Ok(set) = set {
+            others.push(Matcher::Set { set, regexes });
+            return others;
+        }
+    }
+    others.extend(regexes.into_iter().map(Matcher::Regex));
+    others
+}
+
+/// Rewrites a POSIX basic regular expression, as `-G` reads them, in the
+/// extended syntax of the regex crate. `\(`, `\)`, `\{`, `\}`, and the GNU
+/// `\|`, `\+` and `\?`, lose their backslash, while the same characters
+/// without one are literals and gain it. A `*` that starts the pattern or a
+/// group, or follows a leading `^`, is a literal too.
+fn bre_to_ere(pattern: &str) -> String {
+    let mut ere = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    // whether a `*` here would have nothing to repeat, and whether a `^`
+    // would be an anchor
+    let mut at_start = true;
+    let mut can_anchor = true;
+    while let Some(c) = chars.next() {
+        let start = mem::replace(&mut at_start, false);
+        let anchor = mem::replace(&mut can_anchor, false);
+        match c {
+            '\\' => match chars.next() {
+                Some(c @ ('(' | '|')) => {
+                    ere.push(c);
+                    at_start = true;
+                    can_anchor = true;
+                }
+                Some(c @ (')' | '{' | '}' | '+' | '?')) => ere.push(c),
+                Some(c) => {
+                    ere.push('\\');
+                    ere.push(c);
+                }
+                // left for the regex crate to report
+                None => ere.push('\\'),
+            },
+            '(' | ')' | '{' | '}' | '|' | '+' | '?' => {
+                ere.push('\\');
+                ere.push(c);
+            }
+            '*' if start => ere.push_str("\\*"),
+            '^' if anchor => {
+                ere.push(c);
+                at_start = true;
+            }
+            '[' => copy_bracket(&mut chars, &mut ere),
+            _ => ere.push(c),
+        }
+    }
+    ere
+}
+
+/// Copies a bracket expression, whose opening `[` has been read, from
+/// `chars` to `ere`. Everything in one is literal in POSIX, so a backslash
+/// or a `[` that isn't part of a class such as `[:alpha:]` is escaped for
+/// the regex crate, which would read either as special.
+fn copy_bracket(chars: &mut std::str::Chars, ere: &mut String) {
+    ere.push('[');
+    let mut rest = chars.clone();
+    if rest.next() == Some('^') {
+        ere.push('^');
+        *chars = rest;
+    }
+    // a `]` straight after the opening is a member, not the end
+    let mut first = true;
+    while let Some(c) = chars.next() {
+        match c {
+            ']' if !first => {
+                ere.push(']');
+                return;
+            }
+            '[' if matches!(chars.clone().next(), Some(':' | '.' | '=')) => {
+                let delimiter = chars.next().unwrap_or(':');
+                ere.push('[');
+                ere.push(delimiter);
+                let mut previous = delimiter;
+                for c in chars.by_ref() {
+                    ere.push(c);
+                    if c == ']' && previous == delimiter {
+                        break;
+                    }
+                    previous = c;
+                }
+            }
+            '[' | '\\' => {
+                ere.push('\\');
+                ere.push(c);
+            }
+            _ => ere.push(c),
+        }
+        first = false;
+    }
+}
+
+fn load_patterns(
+    filename: &str,
+    insensitive: bool,
+    syntax: Syntax,
+    anchor: Anchor,
+) -> Result<Vec<Matcher>> {
+    let file = open(filename).map_err(|e| anyhow!("{filename}: {e}"))?;
+    let mut patterns = vec![];
+    for (line_num, line) in file.lines().enumerate() {
+        let line = line?;
+        if line.is_empty() {
+            continue;
+        }
+        let pattern = build_matcher(&line, insensitive, syntax, anchor)
+            .map_err(|e| anyhow!("{filename}:{}: {e}", line_num + 1))?;
+        patterns.push(pattern);
+    }
+    Ok(patterns)
+}
+
+/// Reads the globs for --include-from or --exclude-from, one per line.
+/// Blank lines and lines that start with `#` are skipped.
+fn load_patterns_from_file(path: &str) -> Result<Vec<Pattern>> {
+    let file = open(path).map_err(|e| anyhow!("{path}: {e}"))?;
+    let mut patterns = vec![];
+    for (line_num, line) in file.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let pattern = Pattern::new(&line).map_err(|e| anyhow!("{path}:{}: {e}", line_num + 1))?;
+        patterns.push(pattern);
+    }
+    Ok(patterns)
+}
+
+/// Reads lines with invalid UTF-8 replaced by U+FFFD instead of failing,
+/// so a binary file can still be searched for text.
+struct Lossy<R>(R);
+
+impl<R: Read> Read for Lossy<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<R: BufRead> BufRead for Lossy<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.0.consume(amount)
+    }
+
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        let mut bytes = vec![];
+        let num_bytes = self.0.read_until(b'\n', &mut bytes)?;
+        buf.push_str(&String::from_utf8_lossy(&bytes));
+        Ok(num_bytes)
+    }
+}
+
+/// The lines of an input, one at a time, each with its line ending.
+trait LineSource {
+    /// Returns the next line, or `None` at the end of the input.
+    fn next_line(&mut self) -> io::Result<Option<Cow<'_, str>>>;
+}
+
+impl<L: LineSource + ?Sized> LineSource for Box<L> {
+    fn next_line(&mut self) -> io::Result<Option<Cow<'_, str>>> {
+        (**self).next_line()
+    }
+}
+
+/// Lines read from a stream into a buffer that is reused for each one.
+struct ReadLines<R> {
+    reader: R,
+    line: String,
+}
+
+impl<R: BufRead> ReadLines<R> {
+    fn new(reader: R) -> Self {
+        ReadLines {
+            reader,
+            line: String::new(),
+        }
+    }
+}
+
+impl<R: BufRead> LineSource for ReadLines<R> {
+    fn next_line(&mut self) -> io::Result<Option<Cow<'_, str>>> {
+        self.line.clear();
+        if self.reader.read_line(&mut self.line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(Cow::Borrowed(&self.line)))
+    }
+}
+
+/// Lines sliced out of bytes already in memory, such as a mapped file,
+/// found with one pass of `memchr` over them for newlines. Lines are only
+/// copied when they have to be kept. With `lossy`, invalid UTF-8 is
+/// replaced as `Lossy` does; otherwise it fails as `read_line` would.
+struct MappedLines<'a> {
+    data: &'a [u8],
+    start: usize,
+    newlines: Memchr<'a>,
+    lossy: bool,
+}
+
+impl<'a> MappedLines<'a> {
+    fn new(data: &'a [u8], lossy: bool) -> Self {
+        MappedLines {
+            data,
+            start: 0,
+            newlines: memchr_iter(b'\n', data),
+            lossy,
+        }
+    }
+}
+
+impl LineSource for MappedLines<'_> {
+    fn next_line(&mut self) -> io::Result<Option<Cow<'_, str>>> {
+        if self.start == self.data.len() {
+            return Ok(None);
+        }
+        let end = self.newlines.next().map_or(self.data.len(), |i| i + 1);
+        let line = &self.data[self.start..end];
+        self.start = end;
+        if self.lossy {
+            return Ok(Some(String::from_utf8_lossy(line)));
+        }
+        match str::from_utf8(line) {
+            Ok(line) => Ok(Some(Cow::Borrowed(line))),
+            Err(_) => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "stream did not contain valid UTF-8",
+            )),
+        }
+    }
+}
+
+/// A reader that fails with `ErrorKind::TimedOut` once `stop` is set. The
+/// flag is looked at each time the buffer is filled, so even a line that
+/// never ends, as from /dev/zero, is cut short rather than read forever.
+struct StopReader<'a, R> {
+    reader: R,
+    stop: &'a AtomicBool,
+}
+
+impl<'a, R: BufRead> StopReader<'a, R> {
+    fn new(reader: R, stop: &'a AtomicBool) -> Self {
+        StopReader { reader, stop }
     }
 }
 
-fn run(args: Args) -> Result<()> {
-    let pattern = RegexBuilder::new(&args.pattern)
-        .case_insensitive(args.insensitive)
-        .build()
-        .map_err(|_| anyhow!(r#"Invalid pattern ""{}""#, args.pattern))?;
+impl<R: BufRead> Read for StopReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let amount = available.len().min(buf.len());
+        buf[..amount].copy_from_slice(&available[..amount]);
+        self.consume(amount);
+        Ok(amount)
+    }
+}
+
+impl<R: BufRead> BufRead for StopReader<'_, R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.stop.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+        }
+        self.reader.fill_buf()
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.reader.consume(amount)
+    }
+}
+
+/// How many lines go by between looks at whether --timeout has expired.
+const TIMEOUT_CHECK_LINES: usize = 1000;
+
+/// The lines of `lines` until `stop` is set, after which reading fails
+/// with `ErrorKind::TimedOut`. The flag is looked at before the first line
+/// and then every `TIMEOUT_CHECK_LINES` lines.
+struct Stoppable<'a, L> {
+    lines: L,
+    stop: &'a AtomicBool,
+    read: usize,
+}
+
+impl<'a, L: LineSource> Stoppable<'a, L> {
+    fn new(lines: L, stop: &'a AtomicBool) -> Self {
+        Stoppable {
+            lines,
+            stop,
+            read: 0,
+        }
+    }
+}
+
+impl<L: LineSource> LineSource for Stoppable<'_, L> {
+    fn next_line(&mut self) -> io::Result<Option<Cow<'_, str>>> {
+        if self.read.is_multiple_of(TIMEOUT_CHECK_LINES) && self.stop.load(Ordering::Relaxed) {
+            return Err(io::Error::new(io::ErrorKind::TimedOut, "timed out"));
+        }
+        self.read += 1;
+        self.lines.next_line()
+    }
+}
+
+/// Whether `e` is a search stopped by `Stoppable` or `StopReader`.
+fn is_timeout(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<io::Error>()
+        .is_some_and(|e| e.kind() == io::ErrorKind::TimedOut)
+}
+
+/// Opens `filename` to be searched, mapping it into memory when there is
+/// a `threshold` and it is a regular file of at least that many bytes.
+/// Standard input and pipes can't be mapped, and for small files the map
+/// costs more than it saves, so those are read through a buffer.
+fn open_input(filename: &str, threshold: Option<u64>) -> Result<Input> {
+    let Some(threshold) = threshold.filter(|_| filename != "-") else {
+        return Ok(Input::Read(open(filename)?));
+    };
+    let file = File::open(filename)?;
+    let metadata = file.metadata()?;
+    if !metadata.is_file() || metadata.len() < threshold {
+        return Ok(Input::Read(Box::new(BufReader::new(file))));
+    }
+    // Counting the lines of a 1 GB file that match a word found on none
+    // of them took 1.78s read a line at a time, and 1.24s mapped; printing
+    // the half that match another, 2.79s against 2.31s.
+    //
+    // SAFETY: the map is only read. As with read(2), a file changed while
+    // it is searched gives a mix of old and new lines, though one cut
+    // short under the map is a SIGBUS rather than an early end.
+    Ok(Input::Mapped(unsafe { Mmap::map(&file)? }))
+}
+
+/// A file is taken to be binary when its first 8 KiB contain a NUL byte.
+/// Only regular files are checked, since peeking at a pipe would consume
+/// its input. Files that cannot be read are not binary, so opening them
+/// later reports the error.
+fn is_binary(path: &Path) -> bool {
+    let mut buffer = [0; 8192];
+    if !fs::metadata(path).is_ok_and(|metadata| metadata.is_file()) {
+        return false;
+    }
+    let Ok(mut file) = File::open(path) else {
+        return false;
+    };
+    let mut filled = 0;
+    while filled < buffer.len() {
+        match file.read(&mut buffer[filled..]) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => filled += n,
+        }
+    }
+    buffer[..filled].contains(&0)
+}
+
+fn is_zip(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("zip"))
+}
+
+/// Reads an entry of the ZIP archive at `path` into memory, since entries
+/// can only be read one at a time and in order.
+fn open_zip_entry<R: Read>(path: &Path, entry: &mut ZipFile<'_, R>) -> Result<Box<dyn BufRead>> {
+    let mut contents = vec![];
+    if let Err(e) = entry.read_to_end(&mut contents) {
+        let name = entry.name().unwrap_or_default();
+        return Err(anyhow!("{}!{name}: {e}", path.display()));
+    }
+    Ok(Box::new(Cursor::new(contents)))
+}
+
+/// The files in a ZIP archive, each named `archive.zip!entry/path.txt`.
+/// With `recursive`, an archive inside the archive has its own entries
+/// searched, named `outer.zip!inner.zip!entry`, instead of being searched
+/// as it is.
+fn zip_entries(
+    name: &str,
+    archive: impl Read + Seek,
+    recursive: bool,
+    binary_files: BinaryFiles,
+) -> Result<Vec<Result<Source>>> {
+    let mut archive = ZipArchive::new(archive)?;
+    let mut sources = vec![];
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        if !entry.is_file() {
+            continue;
+        }
+        let entry_name = format!("{name}!{}", entry.name()?);
+        let mut file = match open_zip_entry(Path::new(name), &mut entry) {
+            Err(e) => {
+                sources.push(Err(e));
+                continue;
+            }
+            Ok(file) => file,
+        };
+        if recursive && is_zip(&entry_name) {
+            let mut contents = vec![];
+            file.read_to_end(&mut contents)?;
+            match zip_entries(&entry_name, Cursor::new(contents), recursive, binary_files) {
+                Err(e) => sources.push(Err(anyhow!("{entry_name}: {e}"))),
+                Ok(nested) => sources.extend(nested),
+            }
+            continue;
+        }
+        let binary = is_binary_entry(&mut file, binary_files);
+        sources.push(Ok((entry_name, binary, Input::Read(file))));
+    }
+    Ok(sources)
+}
+
+/// Checks an archive entry as `is_binary` checks a file, which costs
+/// nothing once the whole entry is in memory.
+fn is_binary_entry(file: &mut Box<dyn BufRead>, binary_files: BinaryFiles) -> bool {
+    binary_files != BinaryFiles::Text
+        && file
+            .fill_buf()
+            .is_ok_and(|buf| buf[..buf.len().min(8192)].contains(&0))
+}
+
+fn is_tar(name: &str) -> bool {
+    let name = name.to_lowercase();
+    [".tar", ".tar.gz", ".tgz"]
+        .iter()
+        .any(|ext| name.ends_with(ext))
+}
+
+/// Reads an entry of the tar archive at `path` into memory, since entries
+/// can only be read one at a time and in order.
+fn open_tar_entry(path: &Path, entry: &mut Entry<impl Read>) -> Result<Box<dyn BufRead>> {
+    let mut contents = Vec::with_capacity(entry.size() as usize);
+    if let Err(e) = entry.read_to_end(&mut contents) {
+        let name = entry.path().map(|name| name.display().to_string());
+        return Err(anyhow!(
+            "{}!{}: {e}",
+            path.display(),
+            name.unwrap_or_default()
+        ));
+    }
+    Ok(Box::new(Cursor::new(contents)))
+}
+
+/// The files in a tar archive, gzipped when its name ends in `.gz` or
+/// `.tgz`, each named `archive.tar.gz!entry/path.txt`. Files larger than
+/// `max_size` are skipped, with an error, rather than read into memory.
+fn tar_entries(
+    name: &str,
+    archive: impl Read,
+    max_size: u64,
+    binary_files: BinaryFiles,
+) -> Result<Vec<Result<Source>>> {
+    let lower = name.to_lowercase();
+    let archive: Box<dyn Read> = if lower.ends_with(".gz") || lower.ends_with(".tgz") {
+        Box::new(GzDecoder::new(archive))
+    } else {
+        Box::new(archive)
+    };
+    let mut archive = Archive::new(archive);
+    let mut sources = vec![];
+    for entry in archive.entries()? {
+        // an archive can't be read past a bad entry
+        let mut entry = match entry {
+            Err(e) => {
+                sources.push(Err(anyhow!("{name}: {e}")));
+                break;
+            }
+            Ok(entry) => entry,
+        };
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let entry_name = format!("{name}!{}", entry.path()?.display());
+        if entry.size() > max_size {
+            sources.push(Err(anyhow!(
+                "{entry_name}: skipped, as it is larger than {max_size} bytes"
+            )));
+            continue;
+        }
+        let mut file = match open_tar_entry(Path::new(name), &mut entry) {
+            Err(e) => {
+                sources.push(Err(e));
+                continue;
+            }
+            Ok(file) => file,
+        };
+        let binary = is_binary_entry(&mut file, binary_files);
+        sources.push(Ok((entry_name, binary, Input::Read(file))));
+    }
+    Ok(sources)
+}
+
+fn find_lines(
+    file: impl LineSource,
+    patterns: &[Matcher],
+    invert: bool,
+    limit: Option<usize>,
+    before: usize,
+    after: usize,
+    passthrough: bool,
+) -> Result<Vec<Line>> {
+    let mut lines = vec![];
+    scan_lines(
+        file,
+        patterns,
+        invert,
+        limit,
+        before,
+        after,
+        passthrough,
+        |line| {
+            lines.push(line);
+            Ok(())
+        },
+    )?;
+    Ok(lines)
+}
+
+/// Hands each matching line, and the context around it, to `found` as
+/// soon as it is read, and returns the number of matches. With
+/// `passthrough`, every other line is handed over too, until the limit.
+#[allow(clippy::too_many_arguments)]
+fn scan_lines(
+    mut file: impl LineSource,
+    patterns: &[Matcher],
+    invert: bool,
+    limit: Option<usize>,
+    before: usize,
+    after: usize,
+    passthrough: bool,
+    mut found: impl FnMut(Line) -> Result<()>,
+) -> Result<usize> {
+    let mut line_num = 0;
+    let mut num_matches = 0;
+    let mut previous: VecDeque<(usize, String)> = VecDeque::new();
+    let mut after_remaining = 0;
+
+    loop {
+        let limit_reached = limit.is_some_and(|max| num_matches >= max);
+        if limit_reached && after_remaining == 0 {
+            break;
+        }
+
+        let Some(line) = file.next_line()? else {
+            break;
+        };
+        line_num += 1;
+
+        let is_match = patterns.iter().any(|pattern| pattern.is_match(&line));
+        if !limit_reached && (is_match ^ invert) {
+            for (num, text) in previous.drain(..) {
+                found(Line::Context(num, text))?;
+            }
+            found(Line::Match(line_num, line.into_owned()))?;
+            num_matches += 1;
+            after_remaining = after;
+        } else if passthrough && !limit_reached {
+            found(Line::Passed(line_num, line.into_owned()))?;
+        } else if after_remaining > 0 {
+            found(Line::Context(line_num, line.into_owned()))?;
+            after_remaining -= 1;
+        } else if before > 0 {
+            if previous.len() == before {
+                previous.pop_front();
+            }
+            previous.push_back((line_num, line.into_owned()));
+        }
+    }
+    Ok(num_matches)
+}
+
+/// The `--machine-readable` form of a `--output-format json` record.
+fn grepr_record(record: JsonRecord) -> GreprRecord {
+    GreprRecord {
+        file: Some(record.file).filter(|file| !file.is_empty()),
+        line_number: record.line_number,
+        line: record.line_content,
+        count: record.match_count.or(record.total_count),
+    }
+}
+
+fn json_records(
+    filename: &str,
+    lines: &[Line],
+    count: bool,
+    files_with_matches: bool,
+    files_without_match: bool,
+) -> Vec<JsonRecord> {
+    let num_matches = lines
+        .iter()
+        .filter(|line| matches!(line, Line::Match(..)))
+        .count();
+    if files_with_matches || files_without_match {
+        if (num_matches == 0) == files_without_match {
+            vec![JsonRecord::new(filename)]
+        } else {
+            vec![]
+        }
+    } else if count {
+        vec![JsonRecord {
+            match_count: Some(num_matches),
+            ..JsonRecord::new(filename)
+        }]
+    } else {
+        lines
+            .iter()
+            .filter_map(|line| match line {
+                Line::Match(num, text) => Some(JsonRecord {
+                    line_number: Some(*num),
+                    line_content: Some(text.trim_end_matches(['\r', '\n']).to_string()),
+                    ..JsonRecord::new(filename)
+                }),
+                Line::Context(..) | Line::Passed(..) => None,
+            })
+            .collect()
+    }
+}
+
+/// The matches of all patterns in a line, in order. As with a single
+/// pattern, matches are leftmost and never overlap.
+fn match_spans(line: &str, patterns: &[Matcher]) -> Vec<Range<usize>> {
+    let mut spans: Vec<_> = patterns
+        .iter()
+        .flat_map(|pattern| pattern.find_spans(line))
+        .collect();
+    spans.sort_by_key(|span| (span.start, Reverse(span.end)));
+
+    // a match starting inside an earlier one is dropped, not cut short
+    let mut kept = vec![];
+    let mut last = 0;
+    for span in spans {
+        if span.start < last || span.end <= last {
+            continue;
+        }
+        last = span.end;
+        kept.push(span);
+    }
+    kept
+}
+
+fn highlight(line: &str, patterns: &[Matcher], sgr: &str) -> String {
+    let mut result = String::with_capacity(line.len());
+    let mut last = 0;
+    for span in match_spans(line, patterns) {
+        result.push_str(&line[last..span.start]);
+        result.push_str(&ColorScheme::paint(sgr, &line[span.clone()]));
+        last = span.end;
+    }
+    result.push_str(&line[last..]);
+    result
+}
+
+/// Resolves a path named on the command line, or returns `None` for a
+/// directory that should be walked.
+fn check_path(path: &str, recursive: bool) -> Option<Result<String>> {
+    if path == "-" {
+        return Some(Ok(path.to_string()));
+    }
+    match fs::metadata(path) {
+        Ok(metadata) if metadata.is_dir() && recursive => None,
+        Ok(metadata) if metadata.is_dir() => Some(Err(anyhow!("{path} is a directory"))),
+        Ok(_) => Some(Ok(path.to_string())),
+        Err(e) => Some(Err(anyhow!("{path}: {e}"))),
+    }
+}
+
+/// Finds the files to search. Symbolic links named in `paths` are always
+/// followed, but those met while walking a directory only with
+/// `follow_links`, as with `-R`; otherwise they are skipped, as with `-r`.
+// Directories are walked as the results are consumed, so searching starts
+// with the first file found. On a warm tree of 100k files, the first match
+// used to take 48ms to appear; it now takes 4ms.
+fn find_files<'a>(
+    paths: &'a [String],
+    recursive: bool,
+    follow_links: bool,
+    filter: &'a WalkFilter,
+) -> impl Iterator<Item = Result<String>> + 'a {
+    paths
+        .iter()
+        .flat_map(move |path| -> Box<dyn Iterator<Item = Result<String>>> {
+            match check_path(path, recursive) {
+                Some(result) => Box::new(iter::once(result)),
+                None => Box::new(
+                    WalkDir::new(path)
+                        .follow_links(follow_links)
+                        .into_iter()
+                        .filter_entry(|e| {
+                            // the starting directory itself is never pruned
+                            e.depth() == 0
+                                || !e.file_type().is_dir()
+                                || filter.allows_dir(&e.file_name().to_string_lossy())
+                        })
+                        .flatten()
+                        .filter(|e| {
+                            e.file_type().is_file()
+                                && filter.allows_file(&e.file_name().to_string_lossy())
+                        })
+                        .map(|e| Ok(e.path().display().to_string())),
+                ),
+            }
+        })
+}
+
+#[cfg(feature = "async")]
+fn find_files_stream(
+    paths: Vec<String>,
+    recursive: bool,
+    filter: Arc<WalkFilter>,
+) -> impl Stream<Item = Result<String>> + Send {
+    use async_walkdir::Filtering;
+
+    stream::iter(paths).flat_map(move |path| match check_path(&path, recursive) {
+        Some(result) => stream::once(result).boxed(),
+        None => {
+            let filter = Arc::clone(&filter);
+            async_walkdir::WalkDir::new(&path)
+                .filter(move |entry| {
+                    let filter = Arc::clone(&filter);
+                    async move {
+                        let name = entry.file_name().to_string_lossy().into_owned();
+                        match entry.file_type().await {
+                            Ok(t) if t.is_dir() && !filter.allows_dir(&name) => {
+                                Filtering::IgnoreDir
+                            }
+                            Ok(t) if t.is_file() && filter.allows_file(&name) => {
+                                Filtering::Continue
+                            }
+                            _ => Filtering::Ignore,
+                        }
+                    }
+                })
+                .filter_map(|entry| entry.ok().map(|e| Ok(e.path().display().to_string())))
+                .boxed()
+        }
+    })
+}
+
+/// Drives `find_files_stream` on a background runtime, handing the
+/// results back to the synchronous search loop as they arrive.
+// On the same 100k-file tree, the first match appears after 13ms, but the
+// whole search takes 2.2s against 0.5s for the synchronous walk.
+#[cfg(feature = "async")]
+fn find_files_async(
+    paths: &[String],
+    recursive: bool,
+    filter: &WalkFilter,
+) -> Result<impl Iterator<Item = Result<String>>> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .build()?;
+    let (tx, mut rx) = tokio::sync::mpsc::channel(1024);
+    let mut files = find_files_stream(paths.to_vec(), recursive, Arc::new(filter.clone()));
+    runtime.spawn(async move {
+        while let Some(file) = files.next().await {
+            if tx.send(file).await.is_err() {
+                break;
+            }
+        }
+    });
+    // the runtime lives as long as the iterator that reads from it
+    Ok(iter::from_fn(move || {
+        let _runtime = &runtime;
+        rx.blocking_recv()
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Anchor, BinaryFiles, ColorScheme, ContextPrinter, Input, JsonRecord, Line, LineSource,
+        Lossy, MappedLines, Matcher, Profiler, ReadLines, StopReader, Stoppable, Syntax,
+        TIMEOUT_CHECK_LINES, WalkFilter, bre_to_ere, build_matcher, combine_regexes, find_files,
+        find_lines, highlight, is_binary, is_tar, is_timeout, is_zip, json_records, load_patterns,
+        load_patterns_from_file, lowercase_offsets, match_spans, open_input, parse_grep_colors,
+        parse_timeout, tar_entries, zip_entries,
+    };
+    use glob::Pattern;
+    use proptest::prelude::*;
+    use rand::{Rng, distributions::Alphanumeric};
+    use regex::{Regex, RegexBuilder};
+    use std::{
+        fs,
+        io::{self, BufRead, Cursor, Write},
+        path::Path,
+        slice,
+        sync::atomic::{AtomicBool, Ordering},
+        thread,
+        time::Duration,
+    };
 
-    let entries = find_files(&args.files, args.recursive);
-    let num_files = entries.len();
-    let print = |fname: &str, val: &str| {
-        if num_files > 1 {
-            print!("{fname}:{val}");
-        } else {
-            print!("{val}");
-        }
-    };
-    for entry in entries {
-        match entry {
-            Err(e) => eprintln!("{e}"),
-            Ok(filename) => match open(&filename) {
-                Err(e) => eprintln!("{filename}: {e}"),
-                Ok(file) => match find_lines(file, &pattern, args.invert) {
-                    Err(e) => eprintln!("{e}"),
-                    Ok(matches) => {
-                        if args.count {
-                            print(&filename, &format!("{}\n", matches.len()));
-                        } else {
-                            for line in &matches {
-                                print(&filename, line);
-                            }
-                        }
+    #[test]
+    fn test_find_lines() {
+        let text = b"Lorem\nIpsum\r\nDOLOR";
+        let lines = || ReadLines::new(Cursor::new(&text));
+
+        // the pattern _or_ should match the one line, "Lorem"
+        let re1 = Matcher::Regex(Regex::new("or").unwrap());
+        let matches = find_lines(lines(), slice::from_ref(&re1), false, None, 0, 0, false);
+        assert!(matches.is_ok());
+        assert_eq!(
+            matches.unwrap(),
+            vec![Line::Match(1, "Lorem\n".to_string())]
+        );
+
+        // when inverted, the function should match the other two lines
+        let matches = find_lines(lines(), slice::from_ref(&re1), true, None, 0, 0, false);
+        assert!(matches.is_ok());
+        assert_eq!(
+            matches.unwrap(),
+            vec![
+                Line::Match(2, "Ipsum\r\n".to_string()),
+                Line::Match(3, "DOLOR".to_string())
+            ]
+        );
+
+        // this regex will be case-insensitive
+        let re2 = Matcher::Regex(
+            RegexBuilder::new("or")
+                .case_insensitive(true)
+                .build()
+                .unwrap(),
+        );
+
+        // the two lines "Lorem" and "DOLOR" should match
+        let matches = find_lines(lines(), slice::from_ref(&re2), false, None, 0, 0, false);
+        assert!(matches.is_ok());
+        assert_eq!(
+            matches.unwrap(),
+            vec![
+                Line::Match(1, "Lorem\n".to_string()),
+                Line::Match(3, "DOLOR".to_string())
+            ]
+        );
+
+        // a limit stops the search once enough lines have matched
+        let matches = find_lines(lines(), slice::from_ref(&re2), false, Some(1), 0, 0, false);
+        assert!(matches.is_ok());
+        assert_eq!(
+            matches.unwrap(),
+            vec![Line::Match(1, "Lorem\n".to_string())]
+        );
+
+        // context lines are reported around each match without repeats
+        let matches = find_lines(lines(), slice::from_ref(&re1), false, None, 1, 1, false);
+        assert!(matches.is_ok());
+        assert_eq!(
+            matches.unwrap(),
+            vec![
+                Line::Match(1, "Lorem\n".to_string()),
+                Line::Context(2, "Ipsum\r\n".to_string())
+            ]
+        );
+
+        let re3 = Matcher::Regex(Regex::new("D").unwrap());
+        let matches = find_lines(lines(), slice::from_ref(&re3), false, None, 2, 0, false);
+        assert!(matches.is_ok());
+        assert_eq!(
+            matches.unwrap(),
+            vec![
+                Line::Context(1, "Lorem\n".to_string()),
+                Line::Context(2, "Ipsum\r\n".to_string()),
+                Line::Match(3, "DOLOR".to_string())
+            ]
+        );
+
+        // with passthrough, the lines that don't match come through too,
+        // in their place, until the limit is reached
+        let matches = find_lines(lines(), slice::from_ref(&re3), false, None, 1, 0, true);
+        assert!(matches.is_ok());
+        assert_eq!(
+            matches.unwrap(),
+            vec![
+                Line::Passed(1, "Lorem\n".to_string()),
+                Line::Passed(2, "Ipsum\r\n".to_string()),
+                Line::Match(3, "DOLOR".to_string())
+            ]
+        );
+        let matches = find_lines(lines(), slice::from_ref(&re1), false, Some(1), 0, 0, true);
+        assert_eq!(
+            matches.unwrap(),
+            vec![Line::Match(1, "Lorem\n".to_string())]
+        );
+
+        // a line matches when any one of several patterns matches
+        let matches = find_lines(lines(), &[re1, re3], false, None, 0, 0, false);
+        assert!(matches.is_ok());
+        assert_eq!(
+            matches.unwrap(),
+            vec![
+                Line::Match(1, "Lorem\n".to_string()),
+                Line::Match(3, "DOLOR".to_string())
+            ]
+        );
+    }
+
+    /// Random text, or text made mostly of a few letters and line endings,
+    /// so that lines repeat and patterns often match
+    fn corpus() -> impl Strategy<Value = String> {
+        prop_oneof![any::<String>(), "[abAB é\r\n]{0,40}"]
+    }
+
+    /// Random text escaped into a regex, or a regex made of a few letters
+    /// and the operators that can match empty strings or line endings
+    fn pattern() -> impl Strategy<Value = String> {
+        prop_oneof![
+            any::<String>().prop_map(|s| regex::escape(&s)),
+            "([abAB é.|^$\n]|\\n|[*?+]){0,6}",
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn find_lines_matches_pattern(text in corpus(), pattern in pattern()) {
+            let lines: Vec<&str> = text.split_inclusive('\n').collect();
+            for insensitive in [false, true] {
+                let Ok(re) = RegexBuilder::new(&pattern).case_insensitive(insensitive).build()
+                else {
+                    continue;
+                };
+                let matcher = build_matcher(&pattern, insensitive, Syntax::Regex, Anchor::None)
+                    .unwrap();
+                let find = |invert| {
+                    let source = ReadLines::new(Cursor::new(text.as_bytes()));
+                    find_lines(source, slice::from_ref(&matcher), invert, None, 0, 0, false).unwrap()
+                };
+                let mut seen = vec![false; lines.len()];
+                for invert in [false, true] {
+                    for line in find(invert) {
+                        let Line::Match(num, found) = line else {
+                            panic!("context line without asking for context");
+                        };
+                        // each line is matched without its line ending
+                        let content = found.trim_end_matches(['\r', '\n']);
+                        prop_assert_eq!(re.is_match(content), !invert, "{:?}", found);
+                        prop_assert_eq!(found.as_str(), lines[num - 1]);
+                        prop_assert!(!seen[num - 1], "line {} found twice", num);
+                        seen[num - 1] = true;
                     }
-                },
-            },
+                }
+                // the lines that match and those that don't make up the text
+                prop_assert!(seen.iter().all(|&seen| seen));
+            }
         }
     }
-    Ok(())
-}
 
-fn open(filename: &str) -> Result<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+    fn collect_lines(mut lines: impl LineSource) -> Vec<String> {
+        let mut collected = vec![];
+        while let Some(line) = lines.next_line().unwrap() {
+            collected.push(line.into_owned());
+        }
+        collected
     }
-}
 
-fn find_lines<T: BufRead>(mut file: T, pattern: &Regex, invert: bool) -> Result<Vec<String>> {
-    let mut matches = vec![];
-    let mut line = String::new();
+    #[test]
+    fn test_stoppable() {
+        let text = "line\n".repeat(TIMEOUT_CHECK_LINES * 3);
+        let stop = AtomicBool::new(false);
+        let mut lines = Stoppable::new(ReadLines::new(Cursor::new(&text)), &stop);
+        for _ in 0..TIMEOUT_CHECK_LINES + 1 {
+            assert!(lines.next_line().unwrap().is_some());
+        }
+
+        // the flag is only seen at the next check
+        stop.store(true, Ordering::Relaxed);
+        for _ in 0..TIMEOUT_CHECK_LINES - 1 {
+            assert!(lines.next_line().unwrap().is_some());
+        }
+        let err = lines.next_line().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(is_timeout(&err.into()));
+        assert!(!is_timeout(&io::Error::other("other").into()));
 
-    loop {
-        let bytes = file.read_line(&mut line)?;
-        if bytes == 0 {
-            break;
+        // a flag that is already set stops the search before it starts
+        let mut lines = Stoppable::new(ReadLines::new(Cursor::new(&text)), &stop);
+        assert!(lines.next_line().is_err());
+        let matches = find_lines(
+            Stoppable::new(ReadLines::new(Cursor::new(&text)), &stop),
+            &[Matcher::Regex(Regex::new("line").unwrap())],
+            false,
+            None,
+            0,
+            0,
+            false,
+        );
+        assert!(is_timeout(&matches.unwrap_err()));
+    }
+
+    #[test]
+    fn test_stop_reader() {
+        let stop = AtomicBool::new(false);
+        let mut lines =
+            ReadLines::new(StopReader::new(io::BufReader::new(io::repeat(b'x')), &stop));
+        // a line that never ends is still cut short once the flag is set
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                thread::sleep(Duration::from_millis(50));
+                stop.store(true, Ordering::Relaxed);
+            });
+            let err = lines.next_line().unwrap_err();
+            assert!(is_timeout(&err.into()));
+        });
+
+        let stop = AtomicBool::new(false);
+        let mut reader = StopReader::new(Cursor::new("one\ntwo\n"), &stop);
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(line, "one\n");
+        stop.store(true, Ordering::Relaxed);
+        let err = reader.read_line(&mut line).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    #[test]
+    fn test_parse_timeout() {
+        assert_eq!(parse_timeout("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_timeout("0.25"), Ok(Duration::from_millis(250)));
+        for bad in ["0", "-1", "inf", "NaN", "", "1s"] {
+            assert_eq!(
+                parse_timeout(bad),
+                Err(format!("invalid number of seconds '{bad}'"))
+            );
         }
+    }
 
-        if pattern.is_match(&line) ^ invert {
-            matches.push(mem::take(&mut line));
+    #[test]
+    fn test_mapped_lines() {
+        // lines in memory are split just as they are when read
+        for text in [
+            &b"Lorem\nIpsum\r\nDOLOR"[..],
+            b"a\n\nb\n",
+            b"\n",
+            b"",
+            "çé\n€".as_bytes(),
+        ] {
+            assert_eq!(
+                collect_lines(MappedLines::new(text, false)),
+                collect_lines(ReadLines::new(Cursor::new(text))),
+            );
         }
-        line.clear();
+
+        // invalid UTF-8 fails as read_line does, unless it is replaced
+        let text = b"ok\nbad \xff\n";
+        let mut lines = MappedLines::new(text, false);
+        assert_eq!(lines.next_line().unwrap().unwrap(), "ok\n");
+        assert_eq!(
+            lines.next_line().unwrap_err().to_string(),
+            ReadLines::new(Cursor::new(&b"\xff"[..]))
+                .next_line()
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            collect_lines(MappedLines::new(text, true)),
+            collect_lines(ReadLines::new(Lossy(Cursor::new(text)))),
+        );
+        assert_eq!(
+            collect_lines(MappedLines::new(text, true))[1],
+            "bad \u{fffd}\n"
+        );
+
+        let re = Matcher::Regex(Regex::new("or").unwrap());
+        let text = b"Lorem\nIpsum\r\nDOLOR";
+        let matches = find_lines(
+            MappedLines::new(text, false),
+            &[re],
+            true,
+            None,
+            0,
+            0,
+            false,
+        );
+        assert_eq!(
+            matches.unwrap(),
+            vec![
+                Line::Match(2, "Ipsum\r\n".to_string()),
+                Line::Match(3, "DOLOR".to_string())
+            ]
+        );
     }
-    Ok(matches)
-}
 
-fn find_files(paths: &[String], recursive: bool) -> Vec<Result<String>> {
-    let mut results = vec![];
+    #[test]
+    fn test_open_input() {
+        let mapped = |name, threshold| matches!(open_input(name, threshold), Ok(Input::Mapped(_)));
+        // fox.txt is 45 bytes
+        assert!(mapped("tests/inputs/fox.txt", Some(45)));
+        assert!(!mapped("tests/inputs/fox.txt", Some(46)));
+        assert!(!mapped("tests/inputs/fox.txt", None));
+        assert!(!mapped("-", Some(0)));
+        assert!(open_input("tests/inputs/no-such-file", Some(0)).is_err());
+    }
 
-    for path in paths {
-        match path.as_str() {
-            "-" => results.push(Ok(path.to_string())),
-            _ => match fs::metadata(path) {
-                Ok(metadata) => {
-                    if metadata.is_dir() {
-                        if recursive {
-                            for entry in WalkDir::new(path)
-                                .into_iter()
-                                .flatten()
-                                .filter(|e| e.file_type().is_file())
-                            {
-                                results.push(Ok(entry.path().display().to_string()));
-                            }
-                        } else {
-                            results.push(Err(anyhow!("{path} is a directory")));
-                        }
-                    } else if metadata.is_file() {
-                        results.push(Ok(path.to_string()));
-                    }
+    #[test]
+    fn test_profiler() {
+        let mut profiler = Profiler::new(true);
+        profiler.record("discovery", Duration::from_micros(5));
+        profiler.start_file("a.txt");
+        profiler.record("open", Duration::from_micros(12));
+        profiler.record("match", Duration::from_millis(3));
+        profiler.record("match", Duration::from_millis(1));
+        profiler.record("output", Duration::from_nanos(250));
+        profiler.end_file();
+        profiler.record("discovery", Duration::from_micros(2));
+        profiler.start_file("longer.txt");
+        profiler.record("open", Duration::from_nanos(800));
+        profiler.end_file();
+        assert_eq!(
+            profiler.report(),
+            "file        discovery     open  match   output\n\
+             a.txt                   12.0µs  4.0ms  250.0ns\n\
+             longer.txt             800.0ns\n\
+             total           7.0µs   12.8µs  4.0ms  250.0ns\n"
+        );
+
+        // a phase that is started is timed when it ends
+        profiler.start_phase("output");
+        assert!(profiler.phase.is_some());
+        profiler.end_phase();
+        assert!(profiler.phase.is_none());
+        assert_eq!(profiler.other.len(), 3);
+
+        let mut profiler = Profiler::new(false);
+        profiler.start_file("a.txt");
+        profiler.start_phase("open");
+        profiler.end_phase();
+        profiler.record("match", Duration::from_millis(1));
+        assert!(profiler.files.is_empty() && profiler.other.is_empty());
+    }
+
+    #[test]
+    fn test_is_binary() {
+        assert!(!is_binary(Path::new("tests/inputs/fox.txt")));
+        assert!(!is_binary(Path::new("tests/inputs/empty.txt")));
+        assert!(!is_binary(Path::new("tests/inputs/no-such-file")));
+
+        // only a NUL within the first 8 KiB counts
+        let dir = tempfile::tempdir().unwrap();
+        let early = dir.path().join("early.bin");
+        fs::write(&early, b"text\0more text\n").unwrap();
+        assert!(is_binary(&early));
+
+        let late = dir.path().join("late.bin");
+        let mut contents = vec![b'a'; 8192];
+        contents.push(0);
+        fs::write(&late, contents).unwrap();
+        assert!(!is_binary(&late));
+
+        // invalid UTF-8 alone does not make a file binary
+        let latin1 = dir.path().join("latin1.txt");
+        fs::write(&latin1, b"caf\xe9\n").unwrap();
+        assert!(!is_binary(&latin1));
+    }
+
+    #[test]
+    fn test_context_printer() {
+        let mut printer = ContextPrinter::new(Some("--".to_string()));
+        assert_eq!(printer.next_line(3), None);
+        assert_eq!(printer.next_line(4), None);
+        assert_eq!(printer.next_line(6), Some("--"));
+
+        // the first group in the next file is separated from the last one
+        printer.start_file();
+        assert_eq!(printer.next_line(1), Some("--"));
+        assert_eq!(printer.next_line(2), None);
+
+        let mut printer = ContextPrinter::new(None);
+        assert_eq!(printer.next_line(1), None);
+        assert_eq!(printer.next_line(5), None);
+    }
+
+    #[test]
+    fn test_load_patterns() {
+        // blank lines are skipped
+        let patterns = load_patterns(
+            "tests/patterns/words.txt",
+            false,
+            Syntax::Regex,
+            Anchor::None,
+        );
+        assert!(patterns.is_ok());
+        assert_eq!(patterns.unwrap().len(), 50);
+
+        // a bad pattern is reported along with its line number
+        let patterns = load_patterns("tests/patterns/bad.txt", false, Syntax::Regex, Anchor::None);
+        assert!(patterns.is_err());
+        assert_eq!(
+            patterns.unwrap_err().to_string(),
+            r#"tests/patterns/bad.txt:3: Invalid pattern ""*foo""#
+        );
+    }
+
+    #[test]
+    fn test_load_patterns_from_file() {
+        // comments and blank lines are skipped
+        let patterns = load_patterns_from_file("tests/patterns/grepignore").unwrap();
+        assert_eq!(
+            patterns,
+            vec![Pattern::new("*.o").unwrap(), Pattern::new("*.md").unwrap()]
+        );
+
+        // a bad glob is reported along with its line number
+        let err = load_patterns_from_file("tests/patterns/bad_globs.txt").unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("tests/patterns/bad_globs.txt:2: ")
+        );
+
+        assert!(load_patterns_from_file("tests/patterns/missing").is_err());
+    }
+
+    #[test]
+    fn test_combine_regexes() {
+        let build = |pattern, syntax| build_matcher(pattern, true, syntax, Anchor::None).unwrap();
+        let patterns = combine_regexes(
+            vec![
+                build("fox", Syntax::Regex),
+                build("a.c", Syntax::Fixed),
+                build(r"d\w+", Syntax::Regex),
+            ],
+            true,
+        );
+        assert_eq!(patterns.len(), 2);
+        assert!(matches!(patterns[0], Matcher::Fixed { .. }));
+        let Matcher::Set { regexes, .. } = &patterns[1] else {
+            panic!("expected a set, got {:?}", patterns[1]);
+        };
+        assert_eq!(regexes.len(), 2);
+
+        // the set keeps -i, and each regex is still found on its own
+        let set = &patterns[1];
+        assert!(set.is_match("The FOX\n"));
+        assert!(set.is_match("lazy Dog\n"));
+        assert!(!set.is_match("a.c\n"));
+        assert_eq!(set.find_spans("a dog, a fox\n"), vec![9..12, 2..5]);
+        assert_eq!(match_spans("a dog, a fox\n", &patterns), vec![2..5, 9..12]);
+
+        // a lone regex is left alone
+        let patterns = combine_regexes(vec![build("fox", Syntax::Regex)], true);
+        assert!(matches!(patterns[..], [Matcher::Regex(_)]));
+    }
+
+    #[test]
+    fn test_bre_to_ere() {
+        for (bre, ere) in [
+            (r"a\(b\)*c", "a(b)*c"),
+            (r"a\{2,3\}", "a{2,3}"),
+            (r"a\|b", "a|b"),
+            (r"a\+b\?", "a+b?"),
+            // the same characters unescaped are literals
+            ("f(x)", r"f\(x\)"),
+            ("a{2}", r"a\{2\}"),
+            ("a|b+c?", r"a\|b\+c\?"),
+            // a `*` with nothing before it to repeat
+            ("*a", r"\*a"),
+            ("^*a", r"^\*a"),
+            (r"\(*a\)", r"(\*a)"),
+            (r"a\|*b", r"a|\*b"),
+            ("a**", "a**"),
+            // other escapes are kept
+            (r"a\.b\\c\<d\>", r"a\.b\\c\<d\>"),
+            // everything in brackets is literal
+            ("[(|)]", "[(|)]"),
+            (r"[\]", r"[\\]"),
+            ("[[a]", r"[\[a]"),
+            ("[]a]*", "[]a]*"),
+            ("[^]a]", "[^]a]"),
+            ("[[:alpha:](]+", r"[[:alpha:](]\+"),
+        ] {
+            assert_eq!(bre_to_ere(bre), ere, "{bre}");
+        }
+    }
+
+    #[test]
+    fn test_bre_to_ere_idempotent() {
+        // patterns made only of what BRE and ERE share are left as they are,
+        // so translating them again changes nothing
+        let atoms = [
+            "a",
+            "Z",
+            "0",
+            " ",
+            ".",
+            "^",
+            "$",
+            "\\.",
+            "\\*",
+            "\\\\",
+            "\\[",
+            "[ab]",
+            "[^x-z]",
+            "[[:digit:]]",
+            "[]y]",
+        ];
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let mut ere = String::new();
+            for _ in 0..rng.gen_range(1..12) {
+                ere.push_str(atoms[rng.gen_range(0..atoms.len())]);
+                if rng.gen_bool(0.2) {
+                    ere.push('*');
                 }
-                Err(e) => results.push(Err(anyhow!("{path}: {e}"))),
-            },
+            }
+            // a leading `*` only means something in BRE
+            if ere.starts_with('*') || ere.starts_with("^*") {
+                continue;
+            }
+            let once = bre_to_ere(&ere);
+            assert_eq!(once, ere);
+            assert_eq!(bre_to_ere(&once), once);
+            assert!(Regex::new(&once).is_ok(), "{once}");
         }
     }
 
-    results
-}
+    #[test]
+    fn test_basic_regexp() {
+        let basic = |pattern| build_matcher(pattern, false, Syntax::Basic, Anchor::None).unwrap();
+        assert!(basic(r"\(ab\)\{2\}").is_match("xababx"));
+        assert!(!basic(r"\(ab\)\{2\}").is_match("abx"));
+        assert!(basic("f(x)").is_match("y = f(x)"));
+        assert!(!basic("f(x)").is_match("fx"));
+        assert!(basic("*").is_match("a*b"));
+        assert!(!basic("*").is_match("ab"));
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::find_files;
-    use rand::{Rng, distributions::Alphanumeric};
+    // Counting "eternity" in a 100 MB file of words from tests/inputs, as
+    // benches/fixed.rs does, took 264ms as a regex and 272ms with -F, 289ms
+    // with -i and 430ms with -Fi. The regex engine already finds plain
+    // literals quickly and -Fi pays for lowercasing each line, so -F is mainly
+    // for patterns full of metacharacters that would otherwise need escaping.
+    #[test]
+    fn test_fixed_strings() {
+        // regex metacharacters are matched literally
+        let fixed = build_matcher("a.c", false, Syntax::Fixed, Anchor::None).unwrap();
+        assert!(fixed.is_match("xa.cx"));
+        assert!(!fixed.is_match("abc"));
+        assert_eq!(fixed.find_spans("a.c a.c"), vec![0..3, 4..7]);
+
+        // a pattern that is not a valid regex is fine as a fixed string
+        let fixed = build_matcher("*foo", false, Syntax::Fixed, Anchor::None).unwrap();
+        assert!(fixed.is_match("**foo"));
+
+        // case-insensitive matching lowercases both needle and haystack
+        let fixed = build_matcher("ThE", true, Syntax::Fixed, Anchor::None).unwrap();
+        assert!(fixed.is_match("tHe end"));
+        assert!(!fixed.is_match("thorn"));
+        assert_eq!(fixed.find_spans("The THE"), vec![0..3, 4..7]);
+
+        // offsets carry over when lowercasing changes the length of a line
+        let fixed = build_matcher("FOO", true, Syntax::Fixed, Anchor::None).unwrap();
+        assert_eq!(fixed.find_spans("İx foo\n"), vec![4..7]);
+        assert_eq!(fixed.find_spans("ẞ FOO\n"), vec![4..7]);
+        let fixed = build_matcher("I", true, Syntax::Fixed, Anchor::None).unwrap();
+        assert!(fixed.find_spans("İ\n").is_empty());
+        assert_eq!(
+            lowercase_offsets("İx"),
+            [Some(0), None, None, Some(2), Some(3)]
+        );
+
+        // the empty needle matches every line but has nothing to highlight
+        let fixed = build_matcher("", false, Syntax::Fixed, Anchor::None).unwrap();
+        assert!(fixed.is_match("anything"));
+        assert!(fixed.find_spans("anything").is_empty());
+    }
+
+    #[test]
+    fn test_perl_regexp() {
+        // lookbehind and backreferences are not supported by the regex crate
+        assert!(build_matcher(r"(?<=up )the", false, Syntax::Regex, Anchor::None).is_err());
+        assert!(build_matcher(r"(.)\1", false, Syntax::Regex, Anchor::None).is_err());
+
+        let lookbehind = build_matcher(r"(?<=up )the", false, Syntax::Perl, Anchor::None).unwrap();
+        assert!(lookbehind.is_match("sweeping up the heart\n"));
+        assert!(!lookbehind.is_match("the heart\n"));
+        assert_eq!(lookbehind.find_spans("up the the\n"), vec![3..6]);
+
+        let lookahead = build_matcher(r"the(?= heart)", false, Syntax::Perl, Anchor::None).unwrap();
+        assert!(lookahead.is_match("up the heart\n"));
+        assert!(!lookahead.is_match("the end\n"));
+        let backreference = build_matcher(r"(\w)\1", true, Syntax::Perl, Anchor::Word).unwrap();
+        assert!(backreference.is_match("Ee\n"));
+        assert!(!backreference.is_match("eel\n"));
+
+        let bad = build_matcher("(?<=a", false, Syntax::Perl, Anchor::None);
+        assert_eq!(bad.unwrap_err().to_string(), r#"Invalid pattern ""(?<=a""#);
+    }
+
+    #[test]
+    fn test_anchors() {
+        // -w rejects a pattern that is only part of a word
+        let word = build_matcher("the", false, Syntax::Regex, Anchor::Word).unwrap();
+        assert!(word.is_match("up the heart\n"));
+        assert!(word.is_match("the"));
+        assert!(word.is_match("(the)"));
+        assert!(!word.is_match("them\n"));
+        assert!(!word.is_match("bathe"));
+        assert_eq!(word.find_spans("the theme, the\n"), vec![0..3, 11..14]);
+
+        // alternatives are wrapped as a whole
+        let word = build_matcher("up|the", false, Syntax::Regex, Anchor::Word).unwrap();
+        assert!(word.is_match("the"));
+        assert!(!word.is_match("supper"));
+
+        // -x ignores the line ending but nothing else
+        let line = build_matcher("The|Until", true, Syntax::Regex, Anchor::Line).unwrap();
+        assert!(line.is_match("the\n"));
+        assert!(line.is_match("UNTIL\r\n"));
+        assert!(!line.is_match("The end\n"));
+        assert_eq!(line.find_spans("the\n"), vec![0..3]);
+
+        let empty = build_matcher("", false, Syntax::Regex, Anchor::Line).unwrap();
+        assert!(empty.is_match("\n"));
+        assert!(!empty.is_match(" \n"));
+
+        // fixed strings are escaped before they are anchored
+        let fixed = build_matcher("a.c", false, Syntax::Fixed, Anchor::Line).unwrap();
+        assert!(fixed.is_match("a.c\n"));
+        assert!(!fixed.is_match("abc\n"));
+        let fixed = build_matcher("A.C", true, Syntax::Fixed, Anchor::Word).unwrap();
+        assert!(fixed.is_match("x a.c y"));
+        assert!(!fixed.is_match("xa.c"));
+    }
+
+    #[test]
+    fn test_unicode_word() {
+        // -w sees the katakana as more word characters, while the Unicode
+        // rules break between scripts
+        let word = build_matcher("バー", false, Syntax::Regex, Anchor::Word).unwrap();
+        let unicode = build_matcher("バー", false, Syntax::Regex, Anchor::UnicodeWord).unwrap();
+        assert!(!word.is_match("fooバーbaz\n"));
+        assert!(unicode.is_match("fooバーbaz\n"));
+        assert_eq!(unicode.find_spans("fooバーbaz\n"), vec![3..9]);
+
+        // underscores join words under both
+        assert!(!word.is_match("foo_バー_baz\n"));
+        assert!(!unicode.is_match("foo_バー_baz\n"));
+
+        // each ideograph is a word of its own
+        let unicode = build_matcher("日本", false, Syntax::Regex, Anchor::UnicodeWord).unwrap();
+        assert!(unicode.is_match("日本語\n"));
+        assert!(
+            !build_matcher("日本", false, Syntax::Regex, Anchor::Word)
+                .unwrap()
+                .is_match("日本語\n")
+        );
+
+        // a match that ends inside a word doesn't hide a later one
+        let unicode = build_matcher("ab", true, Syntax::Fixed, Anchor::UnicodeWord).unwrap();
+        assert!(!unicode.is_match("abc"));
+        assert_eq!(unicode.find_spans("abc AB ab"), vec![4..6, 7..9]);
+        assert!(build_matcher("(", false, Syntax::Regex, Anchor::UnicodeWord).is_err());
+    }
+
+    #[test]
+    fn test_json_records() {
+        let lines = vec![
+            Line::Context(1, "before\n".to_string()),
+            Line::Match(2, "a \"match\"\r\n".to_string()),
+        ];
+
+        // only matching lines are reported, without the line ending
+        let records = json_records("file.txt", &lines, false, false, false);
+        assert_eq!(
+            records,
+            vec![JsonRecord {
+                file: "file.txt".to_string(),
+                line_number: Some(2),
+                line_content: Some("a \"match\"".to_string()),
+                match_count: None,
+                total_count: None,
+            }]
+        );
+
+        // each record round-trips through its JSON form
+        let json = serde_json::to_string(&records[0]).unwrap();
+        assert_eq!(
+            json,
+            r#"{"file":"file.txt","line_number":2,"line_content":"a \"match\""}"#
+        );
+        let parsed: JsonRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, records[0]);
+
+        let records = json_records("file.txt", &lines, true, false, false);
+        assert_eq!(records[0].match_count, Some(1));
+        let json = serde_json::to_string(&records[0]).unwrap();
+        assert_eq!(json, r#"{"file":"file.txt","match_count":1}"#);
+        let parsed: JsonRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, records[0]);
+
+        assert_eq!(
+            json_records("file.txt", &lines, false, true, false),
+            vec![JsonRecord::new("file.txt")]
+        );
+        assert!(json_records("file.txt", &lines, false, false, true).is_empty());
+
+        // the --total-count record leaves out the file
+        let record = JsonRecord {
+            total_count: Some(3),
+            ..JsonRecord::new("")
+        };
+        let json = serde_json::to_string(&record).unwrap();
+        assert_eq!(json, r#"{"total_count":3}"#);
+        let parsed: JsonRecord = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, record);
+    }
+
+    #[test]
+    fn test_match_spans() {
+        let patterns = [
+            Matcher::Regex(Regex::new(r"\d+").unwrap()),
+            Matcher::Regex(Regex::new(r"2\.\d").unwrap()),
+        ];
+        assert_eq!(
+            match_spans("v1.22.3 and 4\n", &patterns),
+            vec![1..2, 3..5, 6..7, 12..13]
+        );
+        assert!(match_spans("none\n", &patterns).is_empty());
+    }
+
+    #[test]
+    fn test_match_spans_overlapping() {
+        // as GNU grep, `-e ab -e bc` finds only ab in abc
+        let patterns = [
+            Matcher::Regex(Regex::new("ab").unwrap()),
+            Matcher::Regex(Regex::new("bc").unwrap()),
+        ];
+        assert_eq!(match_spans("abc\n", &patterns), vec![0..2]);
+        assert_eq!(
+            highlight("abc\n", &patterns, "01;31"),
+            "\x1b[01;31mab\x1b[0mc\n"
+        );
+
+        // of matches starting together, the longest is kept
+        let patterns = [
+            Matcher::Regex(Regex::new("a").unwrap()),
+            Matcher::Regex(Regex::new("abc").unwrap()),
+        ];
+        assert_eq!(match_spans("abcd\n", &patterns), vec![0..3]);
+    }
+
+    #[test]
+    fn test_highlight() {
+        let re = Matcher::Regex(Regex::new("o").unwrap());
+        assert_eq!(
+            highlight("foo bar\n", slice::from_ref(&re), "01;31"),
+            "f\x1b[01;31mo\x1b[0m\x1b[01;31mo\x1b[0m bar\n"
+        );
+
+        // lines without a match are returned unchanged
+        assert_eq!(highlight("bar\n", &[re], "01;31"), "bar\n");
+
+        // a match overlapping an earlier one is not highlighted
+        let patterns = [
+            Matcher::Regex(Regex::new("oo b").unwrap()),
+            Matcher::Regex(Regex::new("ba").unwrap()),
+        ];
+        assert_eq!(
+            highlight("foo bar\n", &patterns, "01;31"),
+            "f\x1b[01;31moo b\x1b[0mar\n"
+        );
+
+        // empty matches are not wrapped in escape codes
+        let re = Matcher::Regex(Regex::new("").unwrap());
+        assert_eq!(highlight("bar\n", &[re], "01;31"), "bar\n");
+
+        // an empty code leaves matches as they are
+        let re = Matcher::Regex(Regex::new("a").unwrap());
+        assert_eq!(highlight("bar\n", &[re], ""), "bar\n");
+    }
+
+    #[test]
+    fn test_parse_grep_colors() {
+        let colors = parse_grep_colors("fn=35:ln=32:mt=01;31:se=36");
+        assert_eq!(
+            colors,
+            ColorScheme {
+                filename: "35".to_string(),
+                line_number: "32".to_string(),
+                matched: "01;31".to_string(),
+                separator: "36".to_string(),
+            }
+        );
+        assert_eq!(parse_grep_colors("ms=04").matched, "04");
+
+        // anything not understood leaves the defaults in place
+        assert_eq!(parse_grep_colors(""), ColorScheme::default());
+        assert_eq!(parse_grep_colors("::"), ColorScheme::default());
+        assert_eq!(parse_grep_colors("xx=35:ne:rv"), ColorScheme::default());
+        assert_eq!(parse_grep_colors("mt=red"), ColorScheme::default());
+        assert_eq!(parse_grep_colors("mt"), ColorScheme::default());
+
+        // and doesn't stop the entries around it from being read
+        let colors = parse_grep_colors(":fn=35::bad:mt=1;x:xx=1:ln=32:");
+        assert_eq!(colors.filename, "35");
+        assert_eq!(colors.line_number, "32");
+        assert_eq!(colors.matched, "01;31");
+
+        // an empty code turns a color off
+        assert_eq!(parse_grep_colors("mt=").matched, "");
+    }
+
+    #[test]
+    fn test_zip_entries() {
+        let archive = fs::File::open("tests/archives/nested.zip").unwrap();
+        let sources = zip_entries("nested.zip", archive, false, BinaryFiles::Binary).unwrap();
+        let names: Vec<_> = sources
+            .iter()
+            .map(|source| source.as_ref().unwrap().0.as_str())
+            .collect();
+        assert_eq!(names, vec!["nested.zip!fox.txt", "nested.zip!inner.zip"]);
+
+        // a nested archive is opened instead of searched
+        let archive = fs::File::open("tests/archives/nested.zip").unwrap();
+        let mut sources = zip_entries("nested.zip", archive, true, BinaryFiles::Binary).unwrap();
+        let (name, binary, input) = sources.pop().unwrap().unwrap();
+        assert_eq!(name, "nested.zip!inner.zip!bustle.txt");
+        assert!(!binary);
+        let Input::Read(file) = input else {
+            panic!("a ZIP entry is never mapped");
+        };
+        let lines = find_lines(
+            ReadLines::new(file),
+            &[Matcher::Regex(Regex::new("house").unwrap())],
+            false,
+            None,
+            0,
+            0,
+            false,
+        );
+        assert_eq!(
+            lines.unwrap(),
+            vec![Line::Match(1, "The bustle in a house\n".to_string())]
+        );
+
+        assert!(is_zip("a/b.ZIP"));
+        assert!(!is_zip("zip"));
+        assert!(!is_zip("b.zip.txt"));
+    }
+
+    /// Makes a tar archive of `files`, each a name and its contents, with
+    /// a directory entry before them.
+    fn make_tar(files: &[(&str, &[u8])]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(vec![]);
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_mode(0o755);
+        header.set_size(0);
+        builder
+            .append_data(&mut header, "dir/", io::empty())
+            .unwrap();
+        for (name, contents) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_mode(0o644);
+            header.set_size(contents.len() as u64);
+            builder.append_data(&mut header, name, *contents).unwrap();
+        }
+        builder.into_inner().unwrap()
+    }
+
+    #[test]
+    fn test_tar_entries() {
+        let archive = make_tar(&[
+            ("dir/a.txt", b"one\ntwo\n"),
+            ("dir/b.bin", b"\0\x01"),
+            ("big.txt", &[b'x'; 100]),
+        ]);
+        let sources = tar_entries("t.tar", &archive[..], 99, BinaryFiles::Binary).unwrap();
+        let [a, b, big] = &sources[..] else {
+            panic!("expected three entries, not {}", sources.len());
+        };
+        let (name, binary, _) = a.as_ref().unwrap();
+        assert_eq!((name.as_str(), *binary), ("t.tar!dir/a.txt", false));
+        let (name, binary, _) = b.as_ref().unwrap();
+        assert_eq!((name.as_str(), *binary), ("t.tar!dir/b.bin", true));
+        let Err(e) = big else {
+            panic!("an entry over the size is skipped");
+        };
+        assert_eq!(
+            e.to_string(),
+            "t.tar!big.txt: skipped, as it is larger than 99 bytes"
+        );
+
+        // a gzipped archive is known by its name
+        let mut encoder = flate2::write::GzEncoder::new(vec![], flate2::Compression::default());
+        encoder.write_all(&archive).unwrap();
+        let gzipped = encoder.finish().unwrap();
+        let mut sources = tar_entries("t.tgz", &gzipped[..], 100, BinaryFiles::Text).unwrap();
+        assert_eq!(sources.len(), 3);
+        let (name, binary, input) = sources.remove(0).unwrap();
+        assert_eq!(name, "t.tgz!dir/a.txt");
+        assert!(!binary);
+        let Input::Read(file) = input else {
+            panic!("a tar entry is never mapped");
+        };
+        let lines = find_lines(
+            ReadLines::new(file),
+            &[Matcher::Regex(Regex::new("two").unwrap())],
+            false,
+            None,
+            0,
+            0,
+            false,
+        );
+        assert_eq!(lines.unwrap(), vec![Line::Match(2, "two\n".to_string())]);
+
+        // an archive that isn't one gives an error in place of entries
+        let sources = tar_entries("t.tar.gz", &archive[..], 100, BinaryFiles::Text).unwrap();
+        assert!(matches!(sources[..], [Err(_)]));
+
+        assert!(is_tar("a/b.TAR"));
+        assert!(is_tar("b.tar.gz"));
+        assert!(is_tar("b.tgz"));
+        assert!(!is_tar("b.gz"));
+        assert!(!is_tar("b.tar.txt"));
+    }
 
     #[test]
     fn test_find_files() {
         // verify that function finds the file known to exist
-        let files = find_files(&["./tests/inputs/fox.txt".to_string()], false);
+        let files: Vec<_> = find_files(
+            &["./tests/inputs/fox.txt".to_string()],
+            false,
+            false,
+            &WalkFilter::default(),
+        )
+        .collect();
         assert_eq!(files.len(), 1);
         assert_eq!(files[0].as_ref().unwrap(), "./tests/inputs/fox.txt");
 
         // the function should reject a dir without a recursice option
-        let files = find_files(&["./tests/inputs".to_string()], false);
+        let files: Vec<_> = find_files(
+            &["./tests/inputs".to_string()],
+            false,
+            false,
+            &WalkFilter::default(),
+        )
+        .collect();
         assert_eq!(files.len(), 1);
         if let Err(e) = &files[0] {
             assert_eq!(e.to_string(), "./tests/inputs is a directory");
         }
 
         // verify the function recurses to find four files in the directory
-        let res = find_files(&["./tests/inputs".to_string()], true);
-        let mut files: Vec<String> = res
-            .iter()
-            .map(|r| r.as_ref().unwrap().replace("\\", "/"))
-            .collect();
+        let mut files: Vec<String> = find_files(
+            &["./tests/inputs".to_string()],
+            true,
+            false,
+            &WalkFilter::default(),
+        )
+        .map(|r| r.as_ref().unwrap().replace("\\", "/"))
+        .collect();
         files.sort();
         assert_eq!(files.len(), 4);
         assert_eq!(
@@ -172,8 +2999,48 @@ mod tests {
             .collect();
 
         // verify that the function returns the bad file as an error
-        let files = find_files(&[bad], false);
+        let files: Vec<_> = find_files(&[bad], false, false, &WalkFilter::default()).collect();
         assert_eq!(files.len(), 1);
         assert!(files[0].is_err());
     }
+
+    #[test]
+    fn test_find_files_filtered() {
+        let inputs = ["./tests/inputs".to_string()];
+        let find = |filter: &WalkFilter| {
+            let mut files: Vec<String> = find_files(&inputs, true, false, filter)
+                .map(|r| r.as_ref().unwrap().replace("\\", "/"))
+                .collect();
+            files.sort();
+            files
+        };
+
+        // only names matching an include pattern are kept
+        let filter = WalkFilter {
+            include: vec![Pattern::new("[bf]*.txt").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(
+            find(&filter),
+            vec!["./tests/inputs/bustle.txt", "./tests/inputs/fox.txt"]
+        );
+
+        // excludes win over includes
+        let filter = WalkFilter {
+            include: vec![Pattern::new("*.txt").unwrap()],
+            exclude: vec![Pattern::new("fox.*").unwrap(), Pattern::new("e*").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(
+            find(&filter),
+            vec!["./tests/inputs/bustle.txt", "./tests/inputs/nobody.txt"]
+        );
+
+        // the starting directory is not pruned by --exclude-dir
+        let filter = WalkFilter {
+            exclude_dir: vec![Pattern::new("inputs").unwrap()],
+            ..Default::default()
+        };
+        assert_eq!(find(&filter).len(), 4);
+    }
 }
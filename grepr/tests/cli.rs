@@ -12,6 +12,12 @@ const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const NOBODY: &str = "tests/inputs/nobody.txt";
 const INPUTS_DIR: &str = "tests/inputs";
+const DOCS_ZIP: &str = "tests/archives/docs.zip";
+const NESTED_ZIP: &str = "tests/archives/nested.zip";
+const BROKEN_ZIP: &str = "tests/archives/broken.zip";
+const DOCS_TAR: &str = "tests/archives/docs.tar";
+const DOCS_TAR_GZ: &str = "tests/archives/docs.tar.gz";
+const BROKEN_TAR: &str = "tests/archives/broken.tar";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -226,6 +232,40 @@ fn insensitive_count_multiple() -> Result<()> {
     )
 }
 
+// --------------------------------------------------
+#[test]
+fn total_count_multiple() -> Result<()> {
+    run(
+        &["-c", "--total-count", "The", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.capitalized.total.count",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn total_count_single() -> Result<()> {
+    // without filenames, the total is a bare count too
+    Command::cargo_bin(PRG)?
+        .args(["-c", "--total-count", "The", BUSTLE])
+        .assert()
+        .success()
+        .stdout("3\n3\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_total_count_without_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--total-count", "The", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the following required arguments were not provided",
+        ));
+    Ok(())
+}
+
 // --------------------------------------------------
 #[test]
 fn warns_dir_not_recursive() -> Result<()> {
@@ -281,3 +321,2063 @@ fn stdin_insensitive_count() -> Result<()> {
     assert_eq!(stdout, expected);
     Ok(())
 }
+
+// --------------------------------------------------
+#[test]
+fn label() -> Result<()> {
+    let fox = fs::read_to_string(FOX)?;
+    let line = fox.trim_end();
+    for (args, expected) in [
+        (
+            &["--label=pipe", "fox", "-", FOX][..],
+            format!("pipe:{line}\n{FOX}:{line}\n"),
+        ),
+        (
+            &["--label", "pipe", "-c", "fox", "-", EMPTY],
+            format!("pipe:1\n{EMPTY}:0\n"),
+        ),
+        (&["--label=pipe", "-l", "fox", "-"], "pipe\n".to_string()),
+        (&["--label=pipe", "-H", "fox"], format!("pipe:{line}\n")),
+        (&["--label=pipe", "fox"], format!("{line}\n")),
+        (&["fox", "-", FOX], format!("-:{line}\n{FOX}:{line}\n")),
+    ] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .write_stdin(fox.clone())
+            .assert()
+            .success()
+            .stdout(expected);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn label_leaves_files_alone() -> Result<()> {
+    run(
+        &["--label=pipe", "-i", "the", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.lowercase.insensitive",
+    )?;
+    run(
+        &["--label=pipe", "-il", "nobody", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.nobody.insensitive.l",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn with_filename() -> Result<()> {
+    for flag in ["-H", "--with-filename"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "-c", "fox", FOX])
+            .assert()
+            .success()
+            .stdout(format!("{FOX}:1\n"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn filename_prefix() -> Result<()> {
+    // by default, names are shown only for more than one file
+    let line = "The quick brown fox jumps over the lazy dog.";
+    Command::cargo_bin(PRG)?
+        .args(["fox", FOX])
+        .assert()
+        .success()
+        .stdout(format!("{line}\n"));
+    Command::cargo_bin(PRG)?
+        .args(["fox", FOX, EMPTY])
+        .assert()
+        .success()
+        .stdout(format!("{FOX}:{line}\n"));
+
+    Command::cargo_bin(PRG)?
+        .args(["-Hn", "fox", FOX])
+        .assert()
+        .success()
+        .stdout(format!("{FOX}:1:{line}\n"));
+    Command::cargo_bin(PRG)?
+        .args(["-hn", "fox", EMPTY, FOX, BUSTLE])
+        .assert()
+        .success()
+        .stdout(format!("1:{line}\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_filename() -> Result<()> {
+    for flag in ["-h", "--no-filename"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "-c", "fox", FOX, EMPTY])
+            .assert()
+            .success()
+            .stdout("1\n0\n");
+    }
+    Command::cargo_bin(PRG)?
+        .args(["-H", "-h", "fox", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    // help is still there without its short flag
+    Command::cargo_bin(PRG)?
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--no-filename"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_line_number() -> Result<()> {
+    run(
+        &["--line-number", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn nobody_insensitive_line_number() -> Result<()> {
+    run(
+        &["-in", "nobody", NOBODY],
+        "tests/expected/nobody.txt.insensitive.n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_line_number() -> Result<()> {
+    run(
+        &["-n", "The", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.capitalized.n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_line_number() -> Result<()> {
+    let input = fs::read_to_string(BUSTLE)?;
+    let expected = fs::read_to_string("tests/expected/bustle.txt.the.capitalized.n")?;
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["-n", "The"])
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["--color=always", "fox", FOX])
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        b"The quick brown \x1b[01;31mfox\x1b[0m jumps over the lazy dog.\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn color_always_line_number() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["--color", "always", "-n", "the", BUSTLE])
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        b"6:The sweeping up \x1b[01;31mthe\x1b[0m heart,\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn grepr_colors() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .env("GREPR_COLORS", "fn=35:ln=32:se=36:mt=01;32")
+        .args(["--color=always", "-Hn", "fox", FOX])
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "\x1b[35mtests/inputs/fox.txt\x1b[0m\x1b[36m:\x1b[0m\x1b[32m1\x1b[0m\x1b[36m:\x1b[0m\
+         The quick brown \x1b[01;32mfox\x1b[0m jumps over the lazy dog.\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn grepr_colors_context_separator() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .env("GREPR_COLORS", "se=36:mt=")
+        .args(["--color=always", "-A0", "^The", BUSTLE])
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "The bustle in a house\nThe morning after death\n\x1b[36m--\x1b[0m\nThe sweeping up the heart,\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn grepr_colors_malformed() -> Result<()> {
+    // entries that can't be read are skipped, leaving the default colors
+    let output = Command::cargo_bin(PRG)?
+        .env("GREPR_COLORS", "mt=red::xx=35:ln")
+        .args(["--color=always", "-n", "fox", FOX])
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        b"1:The quick brown \x1b[01;31mfox\x1b[0m jumps over the lazy dog.\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn grepr_colors_color_never() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .env("GREPR_COLORS", "fn=35:ln=32:se=36")
+        .args(["--color=never", "-Hn", "fox", FOX])
+        .assert()
+        .success()
+        .stdout("tests/inputs/fox.txt:1:The quick brown fox jumps over the lazy dog.\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn color_never() -> Result<()> {
+    run(
+        &["--color=never", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn color_auto_pipe() -> Result<()> {
+    // stdout is a pipe here, so "auto" must not emit escape codes
+    run(
+        &["--color=auto", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_color() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--color=sometimes", "fox", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'sometimes'"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files_with_matches() -> Result<()> {
+    run(
+        &["-l", "The", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.capitalized.l",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn files_without_match() -> Result<()> {
+    run(
+        &["--files-without-match", "The", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.capitalized.L",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn files_with_matches_insensitive() -> Result<()> {
+    run(
+        &["-il", "nobody", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.nobody.insensitive.l",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files_with_matches_and_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-l", "-c", "The", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files_with_and_without_match() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-l", "-L", "The", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files_with_matches_stops_at_first_match() -> Result<()> {
+    // the first line matches; everything after it is a large block of
+    // invalid UTF-8 that would fail to read if the scan continued
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("large.txt");
+    let mut contents = b"needle\n".to_vec();
+    contents.extend(std::iter::repeat_n(0xff, 10 * 1024 * 1024));
+    fs::write(&path, contents)?;
+    let path = path.display().to_string();
+
+    Command::cargo_bin(PRG)?
+        .args(["-l", "needle", &path])
+        .assert()
+        .success()
+        .stdout(format!("{path}\n"))
+        .stderr("");
+
+    Command::cargo_bin(PRG)?
+        .args(["needle", &path])
+        .assert()
+        .stderr(predicate::str::contains("valid UTF-8"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_context() -> Result<()> {
+    run(
+        &["-C", "1", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.C1",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_after_context_line_number() -> Result<()> {
+    run(
+        &["-n", "--after-context", "1", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.nA1",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn nobody_before_context_line_number() -> Result<()> {
+    run(
+        &["-niB2", "nobody", NOBODY],
+        "tests/expected/nobody.txt.insensitive.nB2",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn bustle_overlapping_context() -> Result<()> {
+    run(
+        &["-n", "-A1", "-B3", "us", BUSTLE],
+        "tests/expected/bustle.txt.us.nA1B3",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn huge_context() -> Result<()> {
+    // no room is set aside up front for this many lines
+    let huge = usize::MAX.to_string();
+    for (flag, pattern) in [("-B", "c"), ("-C", "b")] {
+        Command::cargo_bin(PRG)?
+            .args([flag, &huge, pattern])
+            .write_stdin("a\nb\nc\n")
+            .assert()
+            .success()
+            .stdout("a\nb\nc\n");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_context() -> Result<()> {
+    run(
+        &["-C1", "The", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.capitalized.C1",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_context_and_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "-C1", "The", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_context_and_files_with_matches() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-l", "-A1", "The", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_patterns() -> Result<()> {
+    run(
+        &["-e", "fox", "-e", "Nobody", FOX, NOBODY],
+        "tests/expected/fox.nobody.e",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_patterns_line_number() -> Result<()> {
+    run(
+        &["-n", "--regexp", "house", "--regexp", "heart", BUSTLE],
+        "tests/expected/bustle.txt.house.heart.e.n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_patterns_inverted() -> Result<()> {
+    run(
+        &["-v", "-e", "The", "-e", "the", BUSTLE],
+        "tests/expected/bustle.txt.the.e.v",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_flag_stdin() -> Result<()> {
+    let input = fs::read_to_string(BUSTLE)?;
+    let expected = fs::read_to_string("tests/expected/bustle.txt.the.capitalized")?;
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["-e", "The"])
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_second_pattern() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-e", "fox", "-e", "*foo", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(r#"Invalid pattern ""*foo""#));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file() -> Result<()> {
+    run(
+        &["-f", "tests/patterns/words.txt", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.words.f",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file_insensitive() -> Result<()> {
+    run(
+        &[
+            "-i",
+            "--file",
+            "tests/patterns/words.txt",
+            BUSTLE,
+            EMPTY,
+            FOX,
+            NOBODY,
+        ],
+        "tests/expected/all.words.f.insensitive",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn pattern_file_stdin() -> Result<()> {
+    let patterns = fs::read_to_string("tests/patterns/words.txt")?;
+    let expected = fs::read_to_string("tests/expected/all.words.f")?;
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["-f", "-", BUSTLE, EMPTY, FOX, NOBODY])
+        .write_stdin(patterns)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_pattern_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-e", "Until", "-f", "tests/patterns/bad.txt", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"tests/patterns/bad.txt:3: Invalid pattern ""*foo""#,
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_missing_pattern_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args(["-f", &bad, BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn make_tree() -> Result<tempfile::TempDir> {
+    let dir = tempfile::tempdir()?;
+    for name in [
+        "README.md",
+        "src/main.rs",
+        "src/lib.o",
+        "src/util/mod.rs",
+        ".git/config",
+        "target/debug/build.rs",
+    ] {
+        let path = dir.path().join(name);
+        fs::create_dir_all(path.parent().unwrap())?;
+        fs::write(path, "needle\n")?;
+    }
+    Ok(dir)
+}
+
+// --------------------------------------------------
+fn run_tree(args: &[&str], expected: &[&str]) -> Result<()> {
+    let dir = make_tree()?;
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .args(["-rl", "needle", "."])
+        .current_dir(dir.path())
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    let mut files: Vec<_> = stdout.lines().map(|l| l.replace("\\", "/")).collect();
+    files.sort();
+    assert_eq!(files, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_include() -> Result<()> {
+    run_tree(
+        &["--include", "*.rs"],
+        &[
+            "./src/main.rs",
+            "./src/util/mod.rs",
+            "./target/debug/build.rs",
+        ],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_exclude() -> Result<()> {
+    run_tree(
+        &["--exclude=*.o", "--exclude=*.md"],
+        &[
+            "./.git/config",
+            "./src/main.rs",
+            "./src/util/mod.rs",
+            "./target/debug/build.rs",
+        ],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_exclude_dir() -> Result<()> {
+    run_tree(
+        &["--exclude-dir", ".git", "--exclude-dir", "targ*"],
+        &[
+            "./README.md",
+            "./src/lib.o",
+            "./src/main.rs",
+            "./src/util/mod.rs",
+        ],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_include_exclude_dir() -> Result<()> {
+    run_tree(
+        &[
+            "--include=*.rs",
+            "--exclude-dir=util",
+            "--exclude-dir=target",
+        ],
+        &["./src/main.rs"],
+    )
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn recursive_symlinks() -> Result<()> {
+    use std::os::unix::fs::symlink;
+
+    let dir = make_tree()?;
+    let outside = tempfile::tempdir()?;
+    fs::write(outside.path().join("linked.txt"), "needle\n")?;
+    symlink(outside.path(), dir.path().join("src/linked"))?;
+    symlink("README.md", dir.path().join("README.link"))?;
+
+    let search = |flag: &str, path: &str| -> Result<Vec<String>> {
+        let output = Command::cargo_bin(PRG)?
+            .args([flag, "-l", "--exclude-dir=.git", "--exclude-dir=target"])
+            .args(["needle", path])
+            .current_dir(dir.path())
+            .output()?;
+        assert!(output.status.success());
+        let mut files: Vec<_> = String::from_utf8(output.stdout)?
+            .lines()
+            .map(str::to_string)
+            .collect();
+        files.sort();
+        Ok(files)
+    };
+
+    // -r skips the links it finds on the way
+    assert_eq!(
+        search("-r", ".")?,
+        [
+            "./README.md",
+            "./src/lib.o",
+            "./src/main.rs",
+            "./src/util/mod.rs"
+        ]
+    );
+    // -R follows them into the directory outside the tree
+    assert_eq!(
+        search("--dereference-recursive", ".")?,
+        [
+            "./README.link",
+            "./README.md",
+            "./src/lib.o",
+            "./src/linked/linked.txt",
+            "./src/main.rs",
+            "./src/util/mod.rs"
+        ]
+    );
+    // a link named on the command line is followed either way
+    assert_eq!(search("-r", "src/linked")?, ["src/linked/linked.txt"]);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_recursive_with_dereference_recursive() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-r", "-R", "fox", INPUTS_DIR])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_glob() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--include", "[", "-r", "fox", INPUTS_DIR])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value '['"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_metachar() -> Result<()> {
+    run(&["-F", ".", FOX, BUSTLE], "tests/expected/fox.bustle.dot.F")
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_insensitive() -> Result<()> {
+    run(
+        &["--fixed-strings", "-i", "nobody!", NOBODY],
+        "tests/expected/nobody.txt.nobody.Fi",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_insensitive_only_matching() -> Result<()> {
+    // İ lowercases to more bytes than it takes
+    Command::cargo_bin(PRG)?
+        .args(["-o", "-iF", "FOO"])
+        .write_stdin("İx foo\n")
+        .assert()
+        .success()
+        .stdout("foo\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_inverted() -> Result<()> {
+    run(&["-Fv", "the", BUSTLE], "tests/expected/bustle.txt.the.Fv")
+}
+
+// --------------------------------------------------
+#[test]
+fn fixed_strings_invalid_regex() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-F", "*foo", FOX])
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_multiple_files() -> Result<()> {
+    run(
+        &["-m1", "The", BUSTLE, NOBODY],
+        "tests/expected/bustle.nobody.the.m1",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_after_context() -> Result<()> {
+    run(
+        &["-n", "--max-count", "2", "-A1", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.nm2A1",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn max_count_many_matches() -> Result<()> {
+    let input = "a match\n".repeat(1000);
+    Command::cargo_bin(PRG)?
+        .args(["-m", "3", "match"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("a match\n".repeat(3));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn max_count_stops_reading() -> Result<()> {
+    use std::{
+        io::{self, Write},
+        process::{Command, Stdio},
+        sync::mpsc,
+        thread,
+        time::{Duration, Instant},
+    };
+
+    let dir = tempfile::tempdir()?;
+    let fifo = dir.path().join("fifo");
+    assert!(Command::new("mkfifo").arg(&fifo).status()?.success());
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!())
+        .args(["-m", "3", "match"])
+        .arg(&fifo)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    // the writer never closes the pipe, so grepr only exits if it stops
+    // reading once it has found enough matches
+    let (done, finished) = mpsc::channel::<()>();
+    let writer = thread::spawn(move || -> io::Result<()> {
+        let mut writer = fs::OpenOptions::new().write(true).open(&fifo)?;
+        writer.write_all("a match\n".repeat(5).as_bytes())?;
+        writer.flush()?;
+        let _ = finished.recv();
+        Ok(())
+    });
+
+    let started = Instant::now();
+    while child.try_wait()?.is_none() {
+        if started.elapsed() > Duration::from_secs(10) {
+            child.kill()?;
+            panic!("grepr kept reading after reaching --max-count");
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let output = child.wait_with_output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, "a match\n".repeat(3));
+    done.send(())?;
+    writer.join().unwrap()?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn line_buffered() -> Result<()> {
+    use std::{
+        io::{self, BufRead, BufReader, Write},
+        process::{Command, Stdio},
+        sync::mpsc,
+        thread,
+        time::Duration,
+    };
+
+    let dir = tempfile::tempdir()?;
+    let fifo = dir.path().join("fifo");
+    assert!(Command::new("mkfifo").arg(&fifo).status()?.success());
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!())
+        .args(["--line-buffered", "match"])
+        .arg(&fifo)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .spawn()?;
+
+    // the writer keeps the pipe open until the first match has been read,
+    // so it only shows up if grepr flushes it straight away
+    let (done, finished) = mpsc::channel::<()>();
+    let writer = thread::spawn(move || -> io::Result<()> {
+        let mut writer = fs::OpenOptions::new().write(true).open(&fifo)?;
+        writer.write_all(b"a match\nnothing\n")?;
+        writer.flush()?;
+        let _ = finished.recv();
+        writer.write_all(b"another match\n")
+    });
+
+    let (line_tx, line_rx) = mpsc::channel();
+    let stdout = child.stdout.take().unwrap();
+    let reader = thread::spawn(move || -> io::Result<()> {
+        for line in BufReader::new(stdout).lines() {
+            if line_tx.send(line?).is_err() {
+                break;
+            }
+        }
+        Ok(())
+    });
+
+    let first = line_rx.recv_timeout(Duration::from_secs(10));
+    if first.is_err() {
+        child.kill()?;
+    }
+    done.send(())?;
+    writer.join().unwrap()?;
+    assert_eq!(first?, "a match");
+    assert_eq!(
+        line_rx.recv_timeout(Duration::from_secs(10))?,
+        "another match"
+    );
+    assert!(child.wait()?.success());
+    reader.join().unwrap()?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn json_output() -> Result<()> {
+    run(
+        &["--output-format", "json", "The", BUSTLE, FOX],
+        "tests/expected/bustle.fox.the.json",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn json_output_count() -> Result<()> {
+    run(
+        &["--output-format=json", "-c", "The", BUSTLE, EMPTY],
+        "tests/expected/bustle.empty.the.count.json",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn machine_readable_json() -> Result<()> {
+    run(
+        &["--machine-readable", "json", "The", BUSTLE, FOX],
+        "tests/expected/bustle.fox.the.machine.json",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn machine_readable_csv() -> Result<()> {
+    run(
+        &[
+            "--machine-readable=csv",
+            "-c",
+            "--total-count",
+            "The",
+            BUSTLE,
+            EMPTY,
+        ],
+        "tests/expected/bustle.empty.the.total.count.csv",
+    )?;
+    Command::cargo_bin(PRG)?
+        .args([
+            "--machine-readable=csv",
+            "-l",
+            "-i",
+            "nobody",
+            BUSTLE,
+            NOBODY,
+        ])
+        .assert()
+        .success()
+        .stdout(format!("file,line_number,line,count\n{NOBODY},,,\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_machine_readable_and_output_format() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([
+            "--machine-readable=json",
+            "--output-format=json",
+            "The",
+            FOX,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn passthrough() -> Result<()> {
+    // every line comes through as it is
+    Command::cargo_bin(PRG)?
+        .args(["--passthrough", "The", BUSTLE])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string(BUSTLE)?);
+
+    // and only the matching ones are marked
+    let expected = "1:The bustle in a house\n2:The morning after death\n\
+                    3-Is solemnest of industries\n4-Enacted upon earth,—\n5-\n\
+                    6:The sweeping up the heart,\n7-And putting love away\n\
+                    8-We shall not want to use again\n9-Until eternity.\n";
+    Command::cargo_bin(PRG)?
+        .args(["--passthrough", "-n", "The", BUSTLE])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn passthrough_color() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["--passthrough", "--color=always", "-i", "the", BUSTLE])
+        .output()?;
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let input = fs::read_to_string(BUSTLE)?;
+    assert_eq!(stdout.lines().count(), input.lines().count());
+    for (line, original) in stdout.lines().zip(input.lines()) {
+        if original.to_lowercase().contains("the") {
+            assert!(line.contains("\x1b[01;31m"), "{line:?}");
+        } else {
+            assert_eq!(line, original);
+        }
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_passthrough_conflicts() -> Result<()> {
+    for flag in ["-c", "-l", "-o"] {
+        Command::cargo_bin(PRG)?
+            .args(["--passthrough", flag, "The", BUSTLE])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "the argument '--passthrough' cannot be used with",
+            ));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stats() -> Result<()> {
+    use regex::Regex;
+    use std::process::Command;
+
+    let files = [BUSTLE, EMPTY, FOX, NOBODY];
+    let plain = Command::new(assert_cmd::cargo::cargo_bin!())
+        .arg("The")
+        .args(files)
+        .output()?;
+    let output = Command::new(assert_cmd::cargo::cargo_bin!())
+        .args(["--stats", "The"])
+        .args(files)
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, plain.stdout);
+
+    let summary = Regex::new(
+        r"^(\d+) files scanned, (\d+) files with matches, (\d+) total matches, elapsed [\d.]+(ns|µs|ms|s)\n$",
+    )?;
+    let stderr = String::from_utf8(output.stderr)?;
+    let Some(caps) = summary.captures(&stderr) else {
+        panic!("unexpected summary: {stderr:?}");
+    };
+    assert_eq!((&caps[1], &caps[2], &caps[3]), ("4", "3", "5"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn json_output_total_count() -> Result<()> {
+    run(
+        &[
+            "--output-format=json",
+            "-c",
+            "--total-count",
+            "The",
+            BUSTLE,
+            EMPTY,
+        ],
+        "tests/expected/bustle.empty.the.total.count.json",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn json_output_parses() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args([
+            "--output-format=json",
+            "--color=always",
+            "-i",
+            "nobody",
+            NOBODY,
+        ])
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    let records = stdout
+        .lines()
+        .map(serde_json::from_str::<serde_json::Value>)
+        .collect::<Result<Vec<_>, _>>()?;
+    assert_eq!(records.len(), 2);
+    assert_eq!(records[0]["file"], NOBODY);
+    assert_eq!(records[0]["line_number"], 1);
+    assert_eq!(records[0]["line_content"], "I'm Nobody! Who are you?");
+    assert_eq!(records[1]["line_number"], 2);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_files() -> Result<()> {
+    // the compiled grepr executable is a real binary that contains the
+    // text of its own error messages
+    let binary = assert_cmd::cargo::cargo_bin!("grepr").display().to_string();
+
+    for args in [
+        vec!["Invalid pattern", binary.as_str()],
+        vec!["--binary-files=binary", "Invalid pattern", binary.as_str()],
+    ] {
+        Command::cargo_bin(PRG)?
+            .args(args)
+            .assert()
+            .success()
+            .stdout(format!("Binary file {binary} matches\n"));
+    }
+
+    Command::cargo_bin(PRG)?
+        .args(["--binary-files=binary", "no such text anywhere", &binary])
+        .assert()
+        .success()
+        .stdout("");
+
+    Command::cargo_bin(PRG)?
+        .args([
+            "--binary-files=without-match",
+            "Invalid pattern",
+            &binary,
+            FOX,
+        ])
+        .assert()
+        .success()
+        .stdout("");
+
+    // a count still reports the matching lines of a binary file
+    Command::cargo_bin(PRG)?
+        .args(["-c", "--binary-files=binary", "Invalid pattern", &binary])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match("^[1-9][0-9]*\n$")?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_files_as_text() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("nul.txt");
+    fs::write(&path, b"first match\n\0\nsecond match\n")?;
+    let path = path.display().to_string();
+
+    Command::cargo_bin(PRG)?
+        .args(["match", &path])
+        .assert()
+        .success()
+        .stdout(format!("Binary file {path} matches\n"));
+
+    Command::cargo_bin(PRG)?
+        .args(["--binary-files=text", "match", &path])
+        .assert()
+        .success()
+        .stdout("first match\nsecond match\n");
+
+    Command::cargo_bin(PRG)?
+        .args(["--binary-files=without-match", "-l", "match", &path])
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_binary_files() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--binary-files=foo", "The", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value 'foo' for '--binary-files <TYPE>'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn null_files_with_matches() -> Result<()> {
+    for flag in ["-Z", "--null"] {
+        let output = Command::cargo_bin(PRG)?
+            .args(["-l", flag, "The", BUSTLE, EMPTY, FOX, NOBODY])
+            .output()?;
+        assert!(output.status.success());
+        assert_eq!(
+            output.stdout,
+            format!("{BUSTLE}\0{FOX}\0{NOBODY}\0").as_bytes()
+        );
+    }
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["-LZ", "The", BUSTLE, EMPTY, FOX, NOBODY])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, format!("{EMPTY}\0").as_bytes());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn null_after_filename() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["-Zn", "fox", EMPTY, FOX])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        format!("{FOX}\x001:The quick brown fox jumps over the lazy dog.\n").as_bytes()
+    );
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["-Zc", "fox", EMPTY, FOX])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        format!("{EMPTY}\x000\n{FOX}\x001\n").as_bytes()
+    );
+
+    // without a filename to terminate, the output is unchanged
+    let output = Command::cargo_bin(PRG)?.args(["-Z", "fox", FOX]).output()?;
+    assert!(output.status.success());
+    assert_eq!(
+        output.stdout,
+        b"The quick brown fox jumps over the lazy dog.\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp_rejects_part_of_word() -> Result<()> {
+    // "us" only appears inside "bustle", "industries" and "use"
+    run(&["-w", "us", BUSTLE], "tests/expected/bustle.txt.us.w")?;
+    run(
+        &["--word-regexp", "us", BUSTLE],
+        "tests/expected/bustle.txt.us.w",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp_insensitive() -> Result<()> {
+    run(
+        &["-wi", "the", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.insensitive.w",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn word_regexp_multiple_patterns() -> Result<()> {
+    run(
+        &["-wn", "-e", "tell", "-e", "you", NOBODY],
+        "tests/expected/nobody.txt.tell.you.wn",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn unicode_word() -> Result<()> {
+    let input = "foo_バー_baz\nfooバーbaz\nfoo バー\n";
+    // -w needs a non-word character on each side
+    Command::cargo_bin(PRG)?
+        .args(["-w", "バー"])
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout("foo バー\n");
+    // -W also finds the word where the script changes, but not where an
+    // underscore joins it to the rest
+    for flag in ["-W", "--unicode-word"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "バー"])
+            .write_stdin(input)
+            .assert()
+            .success()
+            .stdout("fooバーbaz\nfoo バー\n");
+    }
+    Command::cargo_bin(PRG)?
+        .args(["-Wo", "日本"])
+        .write_stdin("日本語\n")
+        .assert()
+        .success()
+        .stdout("日本\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_unicode_word_with_word_regexp() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-w", "-W", "foo", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn line_regexp() -> Result<()> {
+    // the line ending, including a CR, is not part of the line
+    run(
+        &[
+            "-xi",
+            "-e",
+            "how public—like a frog—",
+            "-e",
+            "until eternity.",
+            BUSTLE,
+            NOBODY,
+        ],
+        "tests/expected/all.x.e",
+    )?;
+    run(
+        &["--line-regexp", "Until", BUSTLE],
+        "tests/expected/empty.foo",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_word_regexp_and_line_regexp() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-w", "-x", "The", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--word-regexp' cannot be used with '--line-regexp'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn group_separator() -> Result<()> {
+    run(
+        &["-C", "1", "--group-separator", "***", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.C1.sep",
+    )?;
+    run(
+        &["-C", "1", "--group-separator=", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.C1.sep_empty",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn no_group_separator() -> Result<()> {
+    run(
+        &["-C", "1", "--no-group-separator", "The", BUSTLE],
+        "tests/expected/bustle.txt.the.capitalized.C1.nosep",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn group_separator_zero_context() -> Result<()> {
+    // an explicit context of zero still separates every group, across files
+    run(
+        &[
+            "-A",
+            "0",
+            "--group-separator=~~",
+            "-e",
+            "The",
+            "-e",
+            "Until",
+            BUSTLE,
+            FOX,
+        ],
+        "tests/expected/all.the.until.A0.sep",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_group_separator_and_no_group_separator() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([
+            "-C",
+            "1",
+            "--group-separator=x",
+            "--no-group-separator",
+            "The",
+            BUSTLE,
+        ])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn perl_regexp_lookbehind() -> Result<()> {
+    // lookbehind is not supported without -P
+    Command::cargo_bin(PRG)?
+        .args(["(?<=up )the", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"Invalid pattern ""(?<=up )the""#,
+        ));
+
+    run(
+        &["-P", "(?<=up )the", BUSTLE],
+        "tests/expected/bustle.txt.lookbehind.P",
+    )?;
+    run(
+        &["--perl-regexp", "(?<=up )the", BUSTLE],
+        "tests/expected/bustle.txt.lookbehind.P",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn perl_regexp_backreference() -> Result<()> {
+    run(
+        &["-Pin", r"(\w)\1", BUSTLE, FOX, NOBODY],
+        "tests/expected/all.backreference.Pin",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_perl_regexp_and_fixed_strings() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-P", "-F", "The", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--perl-regexp' cannot be used with '--fixed-strings'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn extended_regexp() -> Result<()> {
+    // patterns are extended regular expressions with or without -E
+    for flag in ["-E", "--extended-regexp"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "^The (bustle|morning)", BUSTLE])
+            .assert()
+            .success()
+            .stdout("The bustle in a house\nThe morning after death\n");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn basic_regexp() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-G", r"up \(the\) he\{0,1\}a", BUSTLE])
+        .assert()
+        .success()
+        .stdout("The sweeping up the heart,\n");
+
+    // with -G, `?` is a literal rather than making the `u` optional
+    Command::cargo_bin(PRG)?
+        .args(["--basic-regexp", "you?", NOBODY])
+        .assert()
+        .success()
+        .stdout("I'm Nobody! Who are you?\r\n");
+    Command::cargo_bin(PRG)?
+        .args(["-c", "you?", NOBODY])
+        .assert()
+        .success()
+        .stdout("3\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_basic_regexp_and_extended_regexp() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-G", "-E", "The", BUSTLE])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--basic-regexp' cannot be used with '--extended-regexp'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching() -> Result<()> {
+    run(
+        &["-o", r"\w*th\w*", BUSTLE],
+        "tests/expected/bustle.txt.th.o",
+    )?;
+    run(
+        &["--only-matching", r"\w*th\w*", BUSTLE],
+        "tests/expected/bustle.txt.th.o",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_line_number() -> Result<()> {
+    run(
+        &["-oni", "-e", "the", "-e", "you", BUSTLE, EMPTY, FOX, NOBODY],
+        "tests/expected/all.the.you.oni",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_color() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-o", "--color=always", "o[gx]", FOX])
+        .assert()
+        .success()
+        .stdout("\x1b[01;31mox\x1b[0m\n\x1b[01;31mog\x1b[0m\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn only_matching_overlapping_patterns() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-o", "-e", "ab", "-e", "bc"])
+        .write_stdin("abc\n")
+        .assert()
+        .success()
+        .stdout("ab\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_only_matching_and_count_or_invert() -> Result<()> {
+    for flag in ["-c", "-v"] {
+        Command::cargo_bin(PRG)?
+            .args(["-o", flag, "The", BUSTLE])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains(
+                "the argument '--only-matching' cannot be used with",
+            ));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_names_single_file_in_dir() -> Result<()> {
+    // a directory could hold any number of files, so names are always shown
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("fox.txt");
+    fs::copy(FOX, &path)?;
+    Command::cargo_bin(PRG)?
+        .args(["-r", "fox"])
+        .arg(dir.path())
+        .assert()
+        .success()
+        .stdout(format!("{}:{}", path.display(), fs::read_to_string(FOX)?));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(feature = "async")]
+#[test]
+fn async_matches_sync() -> Result<()> {
+    let sorted = |out: Vec<u8>| -> Result<Vec<String>> {
+        let mut lines: Vec<String> = String::from_utf8(out)?.lines().map(String::from).collect();
+        lines.sort();
+        Ok(lines)
+    };
+    for args in [
+        vec!["-ri", "the", INPUTS_DIR, FOX],
+        vec!["-rc", "--exclude", "f*", "the", INPUTS_DIR],
+        vec![
+            "-rl",
+            "--include",
+            "*.txt",
+            "--exclude-dir",
+            "inputs",
+            "the",
+            "tests",
+        ],
+    ] {
+        let sync = Command::cargo_bin(PRG)?.args(&args).output()?;
+        let async_walk = Command::cargo_bin(PRG)?
+            .arg("--async")
+            .args(&args)
+            .output()?;
+        assert!(async_walk.status.success());
+        assert_eq!(sorted(async_walk.stdout)?, sorted(sync.stdout)?);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn quiet_match() -> Result<()> {
+    for flag in ["-q", "--quiet", "--silent"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "The", BUSTLE])
+            .assert()
+            .code(0)
+            .stdout("");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn quiet_no_match() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-q", "xyzzy", BUSTLE, EMPTY, FOX])
+        .assert()
+        .code(1)
+        .stdout("");
+
+    // without -q, finding nothing is not an error
+    Command::cargo_bin(PRG)?
+        .args(["xyzzy", BUSTLE])
+        .assert()
+        .code(0)
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn quiet_overrides_output() -> Result<()> {
+    for flag in ["-c", "-l", "-n", "-C1", "--output-format=json"] {
+        Command::cargo_bin(PRG)?
+            .args(["-q", flag, "-i", "the", BUSTLE, FOX])
+            .assert()
+            .code(0)
+            .stdout("");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn quiet_stops_at_first_match() -> Result<()> {
+    // the bad file is never reached, so it isn't reported
+    Command::cargo_bin(PRG)?
+        .args(["-q", "fox", FOX, &gen_bad_file()])
+        .assert()
+        .code(0)
+        .stdout("")
+        .stderr("");
+
+    Command::cargo_bin(PRG)?
+        .args(["-q", "fox", &gen_bad_file(), FOX])
+        .assert()
+        .code(0)
+        .stderr(predicate::str::contains("No such file or directory"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn zip() -> Result<()> {
+    // entries are named after the archive, even when it is the only file
+    run(&["--zip", "The", DOCS_ZIP], "tests/expected/docs.zip.the")
+}
+
+// --------------------------------------------------
+#[test]
+fn zip_with_files() -> Result<()> {
+    run(
+        &["--zip", "-c", "The", DOCS_ZIP, FOX],
+        "tests/expected/docs.zip.fox.the.count",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn zip_nested() -> Result<()> {
+    // without --recursive, an archive inside the archive is searched as is
+    run(
+        &["--zip", "The", NESTED_ZIP],
+        "tests/expected/nested.zip.the",
+    )?;
+    run(
+        &["--zip", "--recursive", "The", NESTED_ZIP],
+        "tests/expected/nested.zip.the.recursive",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn zip_skips_bad_archive() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--zip", "The", BROKEN_ZIP, DOCS_ZIP])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string("tests/expected/docs.zip.the")?)
+        .stderr(predicate::str::starts_with(format!(
+            "{BROKEN_ZIP}: invalid Zip archive"
+        )));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_exclude_from() -> Result<()> {
+    let ignore = fs::canonicalize("tests/patterns/grepignore")?;
+    run_tree(
+        &["--exclude-from", ignore.to_str().unwrap()],
+        &[
+            "./.git/config",
+            "./src/main.rs",
+            "./src/util/mod.rs",
+            "./target/debug/build.rs",
+        ],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn recursive_include_from() -> Result<()> {
+    let include = fs::canonicalize("tests/patterns/include.txt")?;
+    run_tree(
+        &[
+            &format!("--include-from={}", include.display()),
+            "--exclude=build.rs",
+        ],
+        &["./src/main.rs", "./src/util/mod.rs"],
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_exclude_from() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--exclude-from", "tests/patterns/bad_globs.txt"])
+        .args(["-r", "fox", "tests/inputs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with(
+            "tests/patterns/bad_globs.txt:2: ",
+        ));
+
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args(["--include-from", &bad, "-r", "fox", "tests/inputs"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with(format!("{bad}: ")));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap_matches_read() -> Result<()> {
+    // every file is mapped with a threshold of 0, so each search must
+    // print what it does when the files are read
+    let binary = assert_cmd::cargo::cargo_bin!("grepr").display().to_string();
+    for args in [
+        vec!["-i", "the", BUSTLE, EMPTY, FOX, NOBODY],
+        vec!["-nvC1", "the", BUSTLE, NOBODY],
+        vec!["-c", "-e", "o", "-e", "Nobody", NOBODY, FOX],
+        vec!["-o", "--color=always", "[a-z]+s", BUSTLE],
+        vec!["-rl", "fox", INPUTS_DIR],
+        vec!["--binary-files=text", "-c", "Invalid pattern", &binary],
+        vec!["Invalid pattern", &binary],
+    ] {
+        let read = Command::cargo_bin(PRG)?.args(&args).output()?;
+        let mapped = Command::cargo_bin(PRG)?
+            .args(["--mmap", "--mmap-threshold", "0"])
+            .args(&args)
+            .output()?;
+        assert!(mapped.status.success());
+        assert_eq!(
+            String::from_utf8_lossy(&mapped.stdout),
+            String::from_utf8_lossy(&read.stdout)
+        );
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap_stdin() -> Result<()> {
+    // standard input can't be mapped, so it is read as usual
+    Command::cargo_bin(PRG)?
+        .args(["--mmap", "--mmap-threshold", "0", "fox"])
+        .write_stdin(fs::read_to_string(FOX)?)
+        .assert()
+        .success()
+        .stdout(fs::read_to_string(FOX)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_mmap_threshold_without_mmap() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--mmap-threshold", "0", "fox", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--mmap"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn tar() -> Result<()> {
+    run(&["--tar", "The", DOCS_TAR], "tests/expected/docs.tar.the")?;
+    run(
+        &["--tar", "The", DOCS_TAR_GZ],
+        "tests/expected/docs.tar.gz.the",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn tar_without_flag() -> Result<()> {
+    // without --tar, an archive is searched as it is
+    Command::cargo_bin(PRG)?
+        .args(["The", DOCS_TAR])
+        .assert()
+        .success()
+        .stdout(format!("Binary file {DOCS_TAR} matches\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn tar_max_entry_size() -> Result<()> {
+    // bustle.txt is 193 bytes and nobody.txt 248
+    Command::cargo_bin(PRG)?
+        .args(["--tar", "--max-entry-size", "200", "-c", "The"])
+        .args([DOCS_TAR_GZ, FOX])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string(
+            "tests/expected/docs.tar.gz.fox.the.max200.count",
+        )?)
+        .stderr(format!(
+            "{DOCS_TAR_GZ}!poems/nobody.txt: skipped, as it is larger than 200 bytes\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn tar_skips_bad_archive() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--tar", "The", BROKEN_TAR, DOCS_TAR])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string("tests/expected/docs.tar.the")?)
+        .stderr(predicate::str::starts_with(format!("{BROKEN_TAR}: ")));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_max_entry_size_without_tar() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--max-entry-size", "10", "The", DOCS_TAR])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--tar"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn profile() -> Result<()> {
+    // the table goes to stderr, leaving the output as it was
+    let output = Command::cargo_bin(PRG)?
+        .args(["--profile", "-n", "The", BUSTLE, FOX])
+        .output()?;
+    assert!(output.status.success());
+    let expected = Command::cargo_bin(PRG)?
+        .args(["-n", "The", BUSTLE, FOX])
+        .output()?;
+    assert_eq!(output.stdout, expected.stdout);
+
+    let stderr = String::from_utf8(output.stderr)?;
+    let lines: Vec<_> = stderr.lines().collect();
+    assert_eq!(lines.len(), 4, "{stderr}");
+    assert!(predicate::str::is_match(r"^file +discovery +open +match +output$")?.eval(lines[0]));
+    assert!(lines[1].starts_with(&format!("{BUSTLE} ")));
+    assert!(lines[2].starts_with(&format!("{FOX} ")));
+    assert!(lines[3].starts_with("total "));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn initial_tab() -> Result<()> {
+    for flag in ["-T", "--initial-tab"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "-n", "-i", "the", FOX, BUSTLE])
+            .assert()
+            .success()
+            .stdout(format!(
+                "{FOX}:1:\tThe quick brown fox jumps over the lazy dog.\n\
+                 {BUSTLE}:1:\tThe bustle in a house\n\
+                 {BUSTLE}:2:\tThe morning after death\n\
+                 {BUSTLE}:6:\tThe sweeping up the heart,\n"
+            ));
+    }
+
+    // the tab follows whichever of the filename and line number is last
+    Command::cargo_bin(PRG)?
+        .args(["-T", "fox", FOX, BUSTLE])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{FOX}:\tThe quick brown fox jumps over the lazy dog.\n"
+        ));
+    Command::cargo_bin(PRG)?
+        .args(["-Tn", "-C1", "morning", BUSTLE])
+        .assert()
+        .success()
+        .stdout(
+            "1-\tThe bustle in a house\n\
+             2:\tThe morning after death\n\
+             3-\tIs solemnest of industries\n",
+        );
+    Command::cargo_bin(PRG)?
+        .args(["-Tno", "sweeping", BUSTLE])
+        .assert()
+        .success()
+        .stdout("6:\tsweeping\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn initial_tab_without_prefix() -> Result<()> {
+    // with nothing before the line, and for counts, there is no tab
+    Command::cargo_bin(PRG)?
+        .args(["-T", "fox", FOX])
+        .assert()
+        .success()
+        .stdout("The quick brown fox jumps over the lazy dog.\n");
+    Command::cargo_bin(PRG)?
+        .args(["-Tc", "fox", FOX, EMPTY])
+        .assert()
+        .success()
+        .stdout(format!("{FOX}:1\n{EMPTY}:0\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn timeout() -> Result<()> {
+    use std::{
+        io::{self, Write},
+        process::{Command, Stdio},
+        thread,
+        time::{Duration, Instant},
+    };
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!())
+        .args(["--timeout", "0.5", "-c", "never"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    // the input is one line of zeros that never ends, so only the timeout
+    // stops grepr, even partway through reading that line
+    let mut stdin = child.stdin.take().unwrap();
+    let writer = thread::spawn(move || -> io::Result<()> {
+        let zeros = [0; 65536];
+        loop {
+            stdin.write_all(&zeros)?;
+        }
+    });
+
+    let started = Instant::now();
+    while child.try_wait()?.is_none() {
+        if started.elapsed() > Duration::from_secs(10) {
+            child.kill()?;
+            panic!("grepr kept searching after its --timeout");
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    assert!(started.elapsed() >= Duration::from_millis(500));
+
+    let output = child.wait_with_output()?;
+    assert_eq!(output.status.code(), Some(2));
+    assert_eq!(
+        String::from_utf8(output.stderr)?,
+        "warning: timed out after 500ms, so the search is incomplete\n"
+    );
+    // the writer stops once grepr closes the pipe
+    assert!(writer.join().unwrap().is_err());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn timeout_not_reached() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--timeout", "60", "-i", "the", BUSTLE])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string(
+            "tests/expected/bustle.txt.the.lowercase.insensitive",
+        )?)
+        .stderr("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_timeout() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--timeout", "0", "fox", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid number of seconds '0'"));
+    Ok(())
+}
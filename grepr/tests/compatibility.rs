@@ -0,0 +1,119 @@
+//! Runs grepr and the system grep with the same arguments and checks that
+//! they print the same thing. These are ignored by default, as they depend
+//! on which grep is installed; run them with `cargo test -- --include-ignored`.
+//!
+//! Only output is compared: grepr exits with success when nothing matches
+//! unless `-q` is given, where grep exits with 1.
+
+use anyhow::Result;
+use assert_cmd::cargo::cargo_bin;
+use pretty_assertions::assert_eq;
+use std::process::Command;
+
+const BUSTLE: &str = "tests/inputs/bustle.txt";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const FOX: &str = "tests/inputs/fox.txt";
+const NOBODY: &str = "tests/inputs/nobody.txt";
+const INPUTS: &[&str] = &[BUSTLE, EMPTY, FOX, NOBODY];
+
+// --------------------------------------------------
+fn has_grep() -> bool {
+    Command::new("grep").arg("--version").output().is_ok()
+}
+
+// --------------------------------------------------
+/// Compares the output of both for each input alone, where no filenames
+/// are printed, and for all of them together, where they are.
+fn compare(args: &[&str]) -> Result<()> {
+    if !has_grep() {
+        eprintln!("skipped, as grep is not available");
+        return Ok(());
+    }
+    let mut runs: Vec<Vec<&str>> = INPUTS.iter().map(|input| vec![*input]).collect();
+    runs.push(INPUTS.to_vec());
+
+    for files in runs {
+        let grep = Command::new("grep").args(args).args(&files).output()?;
+        let grepr = Command::new(cargo_bin!("grepr"))
+            .args(args)
+            .args(&files)
+            .output()?;
+        assert!(grepr.status.success(), "grepr {args:?} {files:?} failed");
+        assert_eq!(
+            String::from_utf8(grepr.stdout)?,
+            String::from_utf8(grep.stdout)?,
+            "grepr {args:?} {files:?}"
+        );
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn matches() -> Result<()> {
+    compare(&["The"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn insensitive() -> Result<()> {
+    compare(&["-i", "the"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn invert() -> Result<()> {
+    compare(&["-v", "the"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn count_insensitive_invert() -> Result<()> {
+    compare(&["-civ", "the"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn line_number_no_filename() -> Result<()> {
+    compare(&["-n", "-h", "-i", "nobody"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn files_with_matches() -> Result<()> {
+    compare(&["-l", "-i", "nobody"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn only_matching() -> Result<()> {
+    compare(&["-o", "-i", "the"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn word_regexp() -> Result<()> {
+    compare(&["-w", "the"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn context() -> Result<()> {
+    compare(&["-C1", "-n", "morning"])
+}
+
+// --------------------------------------------------
+#[test]
+#[ignore = "needs the system grep"]
+fn max_count_extended() -> Result<()> {
+    compare(&["-m1", "-E", "T(he|hen)"])
+}
@@ -0,0 +1,15 @@
+tests/inputs/bustle.txt:The bustle in a house
+tests/inputs/bustle.txt:The morning after death
+tests/inputs/bustle.txt:Is solemnest of industries
+tests/inputs/bustle.txt:Enacted upon earth,—
+tests/inputs/bustle.txt:The sweeping up the heart,
+tests/inputs/bustle.txt:And putting love away
+tests/inputs/bustle.txt:We shall not want to use again
+tests/inputs/bustle.txt:Until eternity.
+tests/inputs/fox.txt:The quick brown fox jumps over the lazy dog.
+tests/inputs/nobody.txt:Then there's a pair of us!
+tests/inputs/nobody.txt:Don't tell! they'd advertise—you know!
+tests/inputs/nobody.txt:How dreary—to be—Somebody!
+tests/inputs/nobody.txt:How public—like a Frog—
+tests/inputs/nobody.txt:To tell one's name—the livelong June—
+tests/inputs/nobody.txt:To an admiring Bog!
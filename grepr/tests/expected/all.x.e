@@ -0,0 +1,2 @@
+tests/inputs/bustle.txt:Until eternity.
+tests/inputs/nobody.txt:How public—like a Frog—
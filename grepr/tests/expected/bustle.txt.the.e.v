@@ -0,0 +1,6 @@
+Is solemnest of industries
+Enacted upon earth,—
+
+And putting love away
+We shall not want to use again
+Until eternity.
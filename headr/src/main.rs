@@ -1,53 +1,43 @@
 use anyhow::Result;
 use clap::Parser;
+use cli_utils::{exit_with_error, parse_args};
 use std::fs::File;
-use std::io::{self, BufRead, BufReader};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::num::NonZeroUsize;
 
 #[derive(Parser, Debug)]
 #[command(author, version, about)]
-
 struct Args {
     #[arg(default_value = "-", value_name = "FILE")]
     files: Vec<String>,
-    #[arg(
-        short('n'),
-        long,
-        value_name = "LINES",
-        value_parser(clap::value_parser!(u64).range(1..)),
-        default_value = "10"
-    )]
-    lines: u64,
-    #[arg(
-        short('c'),
-        long,
-        value_name = "BYTES",
-        conflicts_with("lines"),
-        value_parser(clap::value_parser!(u64).range(1..))
-    )]
-    bytes: Option<u64>,
+    #[arg(short('n'), long, value_name = "LINES", default_value = "10")]
+    lines: NonZeroUsize,
+    #[arg(short('c'), long, value_name = "BYTES", conflicts_with("lines"))]
+    bytes: Option<NonZeroUsize>,
 }
 
 fn run(args: Args) -> Result<()> {
     let num_files = args.files.len();
     for (file_num, filename) in args.files.iter().enumerate() {
-        match open(&filename) {
+        match open(filename) {
             Err(err) => eprintln!("{filename}: {err}"),
-            Ok(mut file) => {
+            Ok(file) => {
                 if num_files > 1 {
                     println!("{}==> {filename} <==", if file_num > 0 { "\n" } else { "" },);
                 }
                 if let Some(num_bytes) = args.bytes {
-                    let mut buffer = vec![0; num_bytes as usize];
-                    let bytes_read = file.read(&mut buffer)?;
-                    print!("{}", String::from_utf8_lossy(&buffer[..bytes_read]));
+                    // Copy the raw bytes so binary input comes out unchanged
+                    let mut head = file.take(num_bytes.get() as u64);
+                    io::copy(&mut head, &mut io::stdout())?;
                 } else {
-                    let mut line = String::new();
-                    for _ in 0..args.lines {
-                        let bytes = file.read_line(&mut line)?;
+                    let mut file = BufReader::new(file);
+                    let mut line = vec![];
+                    for _ in 0..args.lines.get() {
+                        let bytes = file.read_until(b'\n', &mut line)?;
                         if bytes == 0 {
                             break;
                         }
-                        print!("{line}");
+                        io::stdout().write_all(&line)?;
                         line.clear();
                     }
                 }
@@ -57,16 +47,13 @@ fn run(args: Args) -> Result<()> {
     Ok(())
 }
 
-fn open(filename: &str) -> Result<Box<dyn BufRead>> {
+fn open(filename: &str) -> Result<Box<dyn Read>> {
     match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+        "-" => Ok(Box::new(io::stdin())),
+        _ => Ok(Box::new(File::open(filename)?)),
     }
 }
 
 fn main() {
-    if let Err(e) = run(Args::parse()) {
-        eprintln!("{e}");
-        std::process::exit(1);
-    }
+    exit_with_error!(run(parse_args()));
 }
@@ -13,10 +13,11 @@ const ONE: &str = "./tests/inputs/one.txt";
 const TWO: &str = "./tests/inputs/two.txt";
 const THREE: &str = "./tests/inputs/three.txt";
 const TWELVE: &str = "./tests/inputs/twelve.txt";
+const BINARY: &str = "./tests/inputs/binary.bin";
 
 // --------------------------------------------------
 fn random_string() -> String {
-    rand::thread_rng()
+    rand::rng()
         .sample_iter(&Alphanumeric)
         .take(7)
         .map(char::from)
@@ -420,3 +421,88 @@ fn multiple_files_c4() -> Result<()> {
         "tests/expected/all.c4.out",
     )
 }
+
+// --------------------------------------------------
+fn run_bytes(args: &[&str], expected_file: &str) -> Result<()> {
+    // Compare raw bytes, binary input must come through untouched
+    let expected = fs::read(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_zero_lines() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "0", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '0' for '--lines <LINES>'",
+        ));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_zero_bytes() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "0", EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '0' for '--bytes <BYTES>'",
+        ));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn binary_c5() -> Result<()> {
+    run_bytes(&[BINARY, "-c", "5"], "tests/expected/binary.bin.c5.out")
+}
+
+#[test]
+fn binary_c10() -> Result<()> {
+    // Stops in the middle of invalid UTF-8 and a run of NULs
+    run_bytes(&[BINARY, "-c", "10"], "tests/expected/binary.bin.c10.out")
+}
+
+#[test]
+fn binary_c100() -> Result<()> {
+    run_bytes(&[BINARY, "-c", "100"], "tests/expected/binary.bin.c100.out")
+}
+
+#[test]
+fn binary_n2() -> Result<()> {
+    run_bytes(&[BINARY, "-n", "2"], "tests/expected/binary.bin.n2.out")
+}
+
+#[test]
+fn binary_n4() -> Result<()> {
+    run_bytes(&[BINARY, "-n", "4"], "tests/expected/binary.bin.n4.out")
+}
+
+#[test]
+fn binary_one_c3() -> Result<()> {
+    run_bytes(
+        &["-c", "3", BINARY, ONE],
+        "tests/expected/binary.one.c3.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
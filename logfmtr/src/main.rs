@@ -0,0 +1,247 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{exit_with_error, open, parse_args};
+use serde_json::{Map, Value};
+use std::env;
+use std::io::{self, BufRead, Write};
+
+const BLUE: &str = "34";
+const GREEN: &str = "32";
+const YELLOW: &str = "33";
+const RED: &str = "31";
+const DIM: &str = "2";
+
+/// Other names loggers commonly give the fields shown first, which are
+/// looked for when the field itself is missing
+const ALIASES: &[(&str, &[&str])] = &[
+    ("timestamp", &["time", "ts"]),
+    ("level", &["lvl", "severity"]),
+    ("message", &["msg"]),
+    ("error", &["err"]),
+];
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    /// Fields to show first, in this order; the rest follow as key=value
+    #[arg(
+        long,
+        value_name = "FIELDS",
+        value_delimiter = ',',
+        default_value = "timestamp,level,message,error"
+    )]
+    field_order: Vec<String>,
+
+    /// Print without colors, as does setting LOGFMTR_NO_COLOR
+    #[arg(long)]
+    no_color: bool,
+}
+
+/// Wraps `text` in the escape codes for the SGR `code` when `color` is set.
+fn paint(code: &str, text: &str, color: bool) -> String {
+    if color && !code.is_empty() {
+        format!("\x1b[{code}m{text}\x1b[0m")
+    } else {
+        text.to_string()
+    }
+}
+
+/// The color of a level, whatever its case and spelling.
+fn level_color(level: &str) -> &'static str {
+    match level.to_lowercase().as_str() {
+        "trace" | "debug" => BLUE,
+        "info" => GREEN,
+        "warn" | "warning" => YELLOW,
+        "error" | "fatal" | "critical" | "panic" => RED,
+        _ => "",
+    }
+}
+
+/// A value as it is shown: strings without their quotes, and anything else
+/// as compact JSON.
+fn plain(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        _ => value.to_string(),
+    }
+}
+
+/// The value of a key=value pair: a string, quoted as JSON only when it
+/// would otherwise be hard to tell where it ends, or anything else as
+/// compact JSON.
+fn pair_value(value: &Value) -> String {
+    match value {
+        Value::String(s)
+            if !s.is_empty()
+                && !s.contains(|c: char| c.is_whitespace() || c == '=' || c == '"') =>
+        {
+            s.clone()
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Removes the field `name`, or the first of its aliases there is, from
+/// `object`.
+fn take_field(object: &mut Map<String, Value>, name: &str) -> Option<Value> {
+    object.remove(name).or_else(|| {
+        let (_, aliases) = ALIASES.iter().find(|(field, _)| *field == name)?;
+        aliases.iter().find_map(|alias| object.remove(*alias))
+    })
+}
+
+/// Formats a log line that holds a JSON object: the fields in `order`
+/// first, with the level colored by its severity and the error in red,
+/// then the others, in order of their names, as dim key=value pairs.
+/// Returns `None` for any other line, which is printed as it is.
+fn format_line(line: &[u8], order: &[String], color: bool) -> Option<String> {
+    let Ok(Value::Object(mut object)) = serde_json::from_slice(line) else {
+        return None;
+    };
+    let mut parts = vec![];
+    for name in order {
+        let Some(value) = take_field(&mut object, name) else {
+            continue;
+        };
+        parts.push(match name.as_str() {
+            "level" => {
+                let level = plain(&value).to_uppercase();
+                paint(level_color(&level), &format!("{level:<5}"), color)
+            }
+            "error" => paint(RED, &format!("error={}", pair_value(&value)), color),
+            _ => plain(&value),
+        });
+    }
+    for (key, value) in object {
+        parts.push(paint(DIM, &format!("{key}={}", pair_value(&value)), color));
+    }
+    Some(parts.join(" "))
+}
+
+fn run(args: Args) -> Result<()> {
+    let color = !args.no_color && env::var_os("LOGFMTR_NO_COLOR").is_none_or(|v| v.is_empty());
+    let mut stdout = io::stdout().lock();
+    let mut line = vec![];
+
+    for filename in &args.files {
+        match open(filename) {
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(mut file) => loop {
+                line.clear();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+                match format_line(&line, &args.field_order, color) {
+                    Some(formatted) => writeln!(stdout, "{formatted}")?,
+                    None => stdout.write_all(&line)?,
+                }
+            },
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_line, level_color, pair_value};
+    use serde_json::json;
+
+    fn order(fields: &[&str]) -> Vec<String> {
+        fields.iter().map(|field| field.to_string()).collect()
+    }
+
+    #[test]
+    fn test_level_color() {
+        assert_eq!(level_color("DEBUG"), "34");
+        assert_eq!(level_color("info"), "32");
+        assert_eq!(level_color("Warning"), "33");
+        assert_eq!(level_color("WARN"), "33");
+        assert_eq!(level_color("error"), "31");
+        assert_eq!(level_color("fatal"), "31");
+        assert_eq!(level_color("notice"), "");
+    }
+
+    #[test]
+    fn test_pair_value() {
+        assert_eq!(pair_value(&json!("abc")), "abc");
+        assert_eq!(pair_value(&json!("42")), "42");
+        assert_eq!(pair_value(&json!("")), r#""""#);
+        assert_eq!(pair_value(&json!("a b")), r#""a b""#);
+        assert_eq!(pair_value(&json!("a=b")), r#""a=b""#);
+        assert_eq!(pair_value(&json!(r#"say "hi""#)), r#""say \"hi\"""#);
+        // anything but a string is left as JSON
+        assert_eq!(pair_value(&json!(42)), "42");
+        assert_eq!(pair_value(&json!({"id": 7})), r#"{"id":7}"#);
+    }
+
+    #[test]
+    fn test_format_line() {
+        let default = order(&["timestamp", "level", "message", "error"]);
+        let line = r#"{"user":"ann","level":"info","message":"logged in","timestamp":"T1","n":3}"#;
+        assert_eq!(
+            format_line(line.as_bytes(), &default, false).unwrap(),
+            "T1 INFO  logged in n=3 user=ann"
+        );
+        assert_eq!(
+            format_line(line.as_bytes(), &default, true).unwrap(),
+            "T1 \x1b[32mINFO \x1b[0m logged in \x1b[2mn=3\x1b[0m \x1b[2muser=ann\x1b[0m"
+        );
+
+        // missing fields are left out, and aliases stand in for them
+        let line = r#"{"msg":"disk full","lvl":"error","err":"ENOSPC"}"#;
+        assert_eq!(
+            format_line(line.as_bytes(), &default, false).unwrap(),
+            "ERROR disk full error=ENOSPC"
+        );
+        assert_eq!(
+            format_line(line.as_bytes(), &default, true).unwrap(),
+            "\x1b[31mERROR\x1b[0m disk full \x1b[31merror=ENOSPC\x1b[0m"
+        );
+
+        // values that aren't strings are shown as JSON
+        let line = r#"{"message":"x","tags":["a","b"],"ok":true,"none":null}"#;
+        assert_eq!(
+            format_line(line.as_bytes(), &default, false).unwrap(),
+            r#"x none=null ok=true tags=["a","b"]"#
+        );
+    }
+
+    #[test]
+    fn test_format_line_order() {
+        let line = r#"{"level":"warn","message":"slow","ms":950,"timestamp":"T2"}"#;
+        assert_eq!(
+            format_line(line.as_bytes(), &order(&["message", "ms", "level"]), false).unwrap(),
+            "slow 950 WARN  timestamp=T2"
+        );
+        assert_eq!(
+            format_line(line.as_bytes(), &order(&[]), false).unwrap(),
+            "level=warn message=slow ms=950 timestamp=T2"
+        );
+    }
+
+    #[test]
+    fn test_format_line_not_json() {
+        let default = order(&["timestamp", "level", "message", "error"]);
+        for line in [
+            &b"plain text\n"[..],
+            b"",
+            b"{not json\n",
+            b"[1, 2]\n",
+            b"42",
+            br#""a string""#,
+            b"{\"message\":\"\xff\"}\n",
+        ] {
+            assert_eq!(format_line(line, &default, true), None);
+        }
+        // a JSON object may end with any line ending
+        let line = b"{\"message\":\"x\"}\r\n";
+        assert_eq!(format_line(line, &default, false), Some("x".to_string()));
+    }
+}
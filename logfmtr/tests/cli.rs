@@ -0,0 +1,137 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "logfmtr";
+const MIXED: &str = "tests/inputs/mixed.log";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read(expected_file)?;
+    let output = Command::cargo_bin(PRG)?
+        .env_remove("LOGFMTR_NO_COLOR")
+        .args(args)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args([&bad, MIXED])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed() -> Result<()> {
+    run(&[MIXED], "tests/expected/mixed.log.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed_stdin() -> Result<()> {
+    let input = fs::read(MIXED)?;
+    let expected = fs::read("tests/expected/mixed.log.out")?;
+    Command::cargo_bin(PRG)?
+        .env_remove("LOGFMTR_NO_COLOR")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_color() -> Result<()> {
+    run(
+        &["--no-color", MIXED],
+        "tests/expected/mixed.log.no-color.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn no_color_env() -> Result<()> {
+    let expected = fs::read("tests/expected/mixed.log.no-color.out")?;
+    Command::cargo_bin(PRG)?
+        .env("LOGFMTR_NO_COLOR", "1")
+        .arg(MIXED)
+        .assert()
+        .success()
+        .stdout(expected);
+
+    // an empty value doesn't count
+    let expected = fs::read("tests/expected/mixed.log.out")?;
+    Command::cargo_bin(PRG)?
+        .env("LOGFMTR_NO_COLOR", "")
+        .arg(MIXED)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn field_order() -> Result<()> {
+    run(
+        &["--no-color", "--field-order", "level,message,route", MIXED],
+        "tests/expected/mixed.log.order.out",
+    )?;
+    run(
+        &["--no-color", "--field-order=level,message,route", MIXED],
+        "tests/expected/mixed.log.order.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn not_json_unchanged() -> Result<()> {
+    let input = "plain text\r\n{broken\n[1]\n\n  indented\n";
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(input);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,156 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use cli_utils::{open, parse_args};
+use std::io::BufRead;
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    /// Read MD5 sums from the FILEs and check them
+    #[arg(short('c'), long)]
+    check: bool,
+}
+
+/// Returns the MD5 digest of everything `file` holds, in hex.
+fn digest(mut file: impl BufRead) -> Result<String> {
+    let mut context = md5::Context::new();
+    loop {
+        let buf = file.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        context.consume(buf);
+        let len = buf.len();
+        file.consume(len);
+    }
+    Ok(format!("{:x}", context.finalize()))
+}
+
+/// Splits a line as `md5sum` prints it into the digest and the filename,
+/// which follows two spaces, or a space and a `*` for a file read in
+/// binary mode.
+fn parse_line(line: &str) -> Option<(&str, &str)> {
+    let (digest, rest) = line.split_once(' ')?;
+    let filename = rest.strip_prefix([' ', '*'])?;
+    let is_digest = digest.len() == 32 && digest.bytes().all(|b| b.is_ascii_hexdigit());
+    (is_digest && !filename.is_empty()).then_some((digest, filename))
+}
+
+/// Checks the files listed in `list` against their sums, and returns
+/// whether every one of them matched.
+fn check(list: &str) -> Result<bool> {
+    let file = open(list).map_err(|e| anyhow!("{list}: {e}"))?;
+    let mut all_ok = true;
+    let mut checked = 0;
+    for (line_num, line) in file.lines().enumerate() {
+        let line = line?;
+        let Some((expected, filename)) = parse_line(&line) else {
+            eprintln!(
+                "{list}: {}: improperly formatted MD5 checksum line",
+                line_num + 1
+            );
+            continue;
+        };
+        checked += 1;
+        match open(filename).and_then(digest) {
+            Err(e) => {
+                eprintln!("{filename}: {e}");
+                eprintln!("{filename}: FAILED open or read");
+                all_ok = false;
+            }
+            Ok(actual) if actual.eq_ignore_ascii_case(expected) => println!("{filename}: OK"),
+            Ok(_) => {
+                eprintln!("{filename}: FAILED");
+                all_ok = false;
+            }
+        }
+    }
+    if checked == 0 {
+        bail!("{list}: no properly formatted MD5 checksum lines found");
+    }
+    Ok(all_ok)
+}
+
+/// Prints the sum of each file, or checks the sums listed in each, and
+/// returns whether all went well.
+fn run(args: Args) -> Result<bool> {
+    let mut all_ok = true;
+    for filename in &args.files {
+        if args.check {
+            match check(filename) {
+                Err(e) => {
+                    eprintln!("{e}");
+                    all_ok = false;
+                }
+                Ok(ok) => all_ok &= ok,
+            }
+            continue;
+        }
+        match open(filename).and_then(digest) {
+            Err(e) => {
+                eprintln!("{filename}: {e}");
+                all_ok = false;
+            }
+            Ok(sum) => println!("{sum}  {filename}"),
+        }
+    }
+    Ok(all_ok)
+}
+
+fn main() -> ExitCode {
+    match run(parse_args()) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{digest, parse_line};
+    use std::io::Cursor;
+
+    #[test]
+    fn test_digest() {
+        // from the test suite of RFC 1321
+        for (text, expected) in [
+            ("", "d41d8cd98f00b204e9800998ecf8427e"),
+            ("a", "0cc175b9c0f1b6a831c399e269772661"),
+            ("abc", "900150983cd24fb0d6963f7d28e17f72"),
+            ("message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                "abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+        ] {
+            assert_eq!(digest(Cursor::new(text)).unwrap(), expected, "{text}");
+        }
+        // the digest doesn't depend on how the input is buffered
+        let text = "0123456789".repeat(10_000);
+        let small = std::io::BufReader::with_capacity(7, Cursor::new(&text));
+        assert_eq!(digest(small).unwrap(), format!("{:x}", md5::compute(&text)));
+    }
+
+    #[test]
+    fn test_parse_line() {
+        let sum = "d41d8cd98f00b204e9800998ecf8427e";
+        assert_eq!(parse_line(&format!("{sum}  a.txt")), Some((sum, "a.txt")));
+        assert_eq!(parse_line(&format!("{sum} *a.txt")), Some((sum, "a.txt")));
+        assert_eq!(parse_line(&format!("{sum}  a  b")), Some((sum, "a  b")));
+        assert_eq!(parse_line(&format!("{sum} a.txt")), None);
+        assert_eq!(parse_line(&format!("{sum}  ")), None);
+        assert_eq!(parse_line("d41d8cd9  a.txt"), None);
+        assert_eq!(
+            parse_line(&format!("{}  a.txt", sum.replace('d', "x"))),
+            None
+        );
+        assert_eq!(parse_line(""), None);
+    }
+}
@@ -0,0 +1,155 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "md5r";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const FOX: &str = "tests/inputs/fox.txt";
+const SUMS: &str = "tests/inputs/sums.md5";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+#[test]
+fn files() -> Result<()> {
+    // as printed by md5sum
+    Command::cargo_bin(PRG)?
+        .args([FOX, EMPTY])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string(SUMS)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .write_stdin(fs::read_to_string(FOX)?)
+        .assert()
+        .success()
+        .stdout("0d7006cd055e94cf614587e1d2ae0c8e  -\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args([&bad, EMPTY])
+        .assert()
+        .failure()
+        .stdout("d41d8cd98f00b204e9800998ecf8427e  tests/inputs/empty.txt\n")
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn check() -> Result<()> {
+    for flag in ["-c", "--check"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, SUMS])
+            .assert()
+            .success()
+            .stdout(format!("{FOX}: OK\n{EMPTY}: OK\n"))
+            .stderr("");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn check_round_trip() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("file.txt");
+    fs::write(&file, "some text\n")?;
+    let output = Command::cargo_bin(PRG)?.arg(&file).output()?;
+    assert!(output.status.success());
+    let sums = dir.path().join("sums.md5");
+    fs::write(&sums, output.stdout)?;
+
+    let file = file.display();
+    Command::cargo_bin(PRG)?
+        .arg("-c")
+        .arg(&sums)
+        .assert()
+        .success()
+        .stdout(format!("{file}: OK\n"));
+
+    // a file that has changed since no longer matches
+    fs::write(dir.path().join("file.txt"), "other text\n")?;
+    Command::cargo_bin(PRG)?
+        .arg("-c")
+        .arg(&sums)
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(format!("{file}: FAILED\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn check_missing_and_bad_lines() -> Result<()> {
+    let bad = gen_bad_file();
+    let list = format!(
+        "not a checksum line\n\
+         0d7006cd055e94cf614587e1d2ae0c8e  {FOX}\n\
+         d41d8cd98f00b204e9800998ecf8427e  {bad}\n"
+    );
+    Command::cargo_bin(PRG)?
+        .arg("-c")
+        .write_stdin(list)
+        .assert()
+        .failure()
+        .stdout(format!("{FOX}: OK\n"))
+        .stderr(predicate::str::starts_with(
+            "-: 1: improperly formatted MD5 checksum line\n",
+        ))
+        .stderr(predicate::str::ends_with(format!(
+            "{bad}: FAILED open or read\n"
+        )));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_checksum_lines() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-c", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::ends_with(format!(
+            "{FOX}: no properly formatted MD5 checksum lines found\n"
+        )));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
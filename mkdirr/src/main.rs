@@ -0,0 +1,159 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::parse_args;
+use std::fs::{self, DirBuilder};
+use std::io::{self, ErrorKind};
+use std::path::Path;
+use std::process::ExitCode;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "DIRECTORY", required = true)]
+    dirs: Vec<String>,
+
+    /// Make parent directories as needed, and don't mind ones that exist
+    #[arg(short('p'), long)]
+    parents: bool,
+
+    /// Set the permissions of the new directories to MODE, in octal
+    #[arg(short('m'), long, value_name = "MODE", value_parser = parse_mode)]
+    mode: Option<u32>,
+
+    /// Print the name of each directory made
+    #[arg(short('v'), long)]
+    verbose: bool,
+}
+
+/// Parses an octal mode such as `755` or `1777`.
+fn parse_mode(value: &str) -> Result<u32, String> {
+    match u32::from_str_radix(value, 8) {
+        Ok(mode) if mode <= 0o7777 && !value.starts_with('+') => Ok(mode),
+        _ => Err(format!("invalid mode '{value}'")),
+    }
+}
+
+/// Makes the directory `dir`, with exactly the permissions `mode` when
+/// there is one. Without one, they are those the umask leaves.
+fn make_dir(dir: &Path, mode: Option<u32>) -> io::Result<()> {
+    let mut builder = DirBuilder::new();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+
+        if let Some(mode) = mode {
+            builder.mode(mode);
+            builder.create(dir)?;
+            // the umask takes bits out of the mode given to mkdir(2), as
+            // it doesn't for chmod(2)
+            return fs::set_permissions(dir, fs::Permissions::from_mode(mode));
+        }
+    }
+    #[cfg(not(unix))]
+    let _ = mode;
+    builder.create(dir)
+}
+
+/// Makes `dir` and whichever of the directories above it are missing,
+/// from the top down, calling `made` with each one. Only `dir` itself
+/// gets `mode`, as with GNU `mkdir -p`.
+fn make_parents(dir: &Path, mode: Option<u32>, mut made: impl FnMut(&Path)) -> io::Result<()> {
+    let missing: Vec<_> = dir
+        .ancestors()
+        .take_while(|path| !path.as_os_str().is_empty() && !path.is_dir())
+        .collect();
+    for path in missing.into_iter().rev() {
+        let mode = if path == dir { mode } else { None };
+        match make_dir(path, mode) {
+            // someone else may have made it since, or it may be `..`
+            Err(e) if e.kind() == ErrorKind::AlreadyExists && path.is_dir() => {}
+            Err(e) => return Err(e),
+            Ok(()) => made(path),
+        }
+    }
+    Ok(())
+}
+
+/// Makes each DIRECTORY, printing what went wrong with those that can't
+/// be made, and returns whether they all were.
+fn run(args: Args) -> Result<bool> {
+    let mut all_ok = true;
+    let made = |path: &Path| {
+        if args.verbose {
+            println!("created directory '{}'", path.display());
+        }
+    };
+    for dir in &args.dirs {
+        let path = Path::new(dir);
+        let result = if args.parents {
+            make_parents(path, args.mode, made)
+        } else {
+            make_dir(path, args.mode).map(|()| made(path))
+        };
+        if let Err(e) = result {
+            eprintln!("cannot create directory '{dir}': {e}");
+            all_ok = false;
+        }
+    }
+    Ok(all_ok)
+}
+
+fn main() -> ExitCode {
+    match run(parse_args()) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{make_parents, parse_mode};
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode("755"), Ok(0o755));
+        assert_eq!(parse_mode("0700"), Ok(0o700));
+        assert_eq!(parse_mode("1777"), Ok(0o1777));
+        assert_eq!(parse_mode("0"), Ok(0));
+        for bad in ["", "8", "rwx", "u+x", "+7", "-7", "17777"] {
+            assert_eq!(parse_mode(bad), Err(format!("invalid mode '{bad}'")));
+        }
+    }
+
+    #[test]
+    fn test_make_parents() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("a/b/c");
+        let mut made = vec![];
+        make_parents(&path, None, |path| made.push(path.to_path_buf())).unwrap();
+        assert!(path.is_dir());
+        let expected: Vec<PathBuf> = ["a", "a/b", "a/b/c"]
+            .iter()
+            .map(|name| dir.path().join(name))
+            .collect();
+        assert_eq!(made, expected);
+
+        // nothing is made the second time, which is still fine
+        let mut made = vec![];
+        make_parents(&path, None, |path| made.push(path.to_path_buf())).unwrap();
+        assert!(made.is_empty());
+
+        // only the missing part of a path that goes back up is made
+        let path = dir.path().join("a/x/../y");
+        let mut made = vec![];
+        make_parents(&path, None, |path| made.push(path.to_path_buf())).unwrap();
+        assert_eq!(made, vec![dir.path().join("a/x"), path]);
+        assert!(dir.path().join("a/y").is_dir());
+
+        // a file in the way can't be made into a directory
+        let file = dir.path().join("file");
+        std::fs::write(&file, "").unwrap();
+        assert!(make_parents(&file.join("d"), None, |_| {}).is_err());
+        assert!(make_parents(&file, None, |_| {}).is_err());
+    }
+}
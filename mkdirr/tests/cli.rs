@@ -0,0 +1,193 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+
+const PRG: &str = "mkdirr";
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn makes_dirs() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["a", "b"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    assert!(dir.path().join("a").is_dir());
+    assert!(dir.path().join("b").is_dir());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_exists() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    fs::create_dir(dir.path().join("a"))?;
+    // the other directories are still made
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["a", "b"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::starts_with(
+            "cannot create directory 'a': File exists",
+        ));
+    assert!(dir.path().join("b").is_dir());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_missing_parent() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .arg("a/b/c")
+        .assert()
+        .code(1)
+        .stderr(predicate::str::starts_with(
+            "cannot create directory 'a/b/c': No such file or directory",
+        ));
+    assert!(!dir.path().join("a").exists());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn parents() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-p", "a/b/c"])
+        .assert()
+        .success()
+        .stdout("");
+    assert!(dir.path().join("a/b/c").is_dir());
+
+    // directories that exist are fine with -p
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["--parents", "a/b/c", "a", "a/b/d"])
+        .assert()
+        .success()
+        .stderr("");
+    assert!(dir.path().join("a/b/d").is_dir());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_parents_through_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    fs::write(dir.path().join("file"), "")?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-p", "file/a", "file"])
+        .assert()
+        .code(1)
+        .stderr(predicate::str::is_match(
+            "^cannot create directory 'file/a': .*\ncannot create directory 'file': File exists",
+        )?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn verbose() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-v", "a", "b"])
+        .assert()
+        .success()
+        .stdout("created directory 'a'\ncreated directory 'b'\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn verbose_parents() -> Result<()> {
+    // each directory made is printed, and none that already were
+    let dir = tempfile::tempdir()?;
+    fs::create_dir(dir.path().join("a"))?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-pv", "a/b/c/d", "a/b/e"])
+        .assert()
+        .success()
+        .stdout(
+            "created directory 'a/b'\n\
+             created directory 'a/b/c'\n\
+             created directory 'a/b/c/d'\n\
+             created directory 'a/b/e'\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn mode() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir()?;
+    let mode = |name| -> Result<u32> {
+        Ok(fs::metadata(dir.path().join(name))?.permissions().mode() & 0o7777)
+    };
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-m", "700", "private"])
+        .assert()
+        .success();
+    assert_eq!(mode("private")?, 0o700);
+
+    // the mode is set as given, whatever the umask, but only on the last
+    // directory of a path made with -p
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-p", "--mode=1777", "a/b/shared"])
+        .assert()
+        .success();
+    assert_eq!(mode("a/b/shared")?, 0o1777);
+    assert_ne!(mode("a/b")?, 0o1777);
+    assert_eq!(mode("a")?, mode("a/b")?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_mode() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    Command::cargo_bin(PRG)?
+        .current_dir(&dir)
+        .args(["-m", "u+rwx", "a"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid mode 'u+rwx'"));
+    assert!(!dir.path().join("a").exists());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
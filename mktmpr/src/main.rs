@@ -0,0 +1,164 @@
+use anyhow::{Result, anyhow};
+use clap::Parser;
+use cli_utils::parse_args;
+use std::env;
+use std::iter;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use tempfile::Builder;
+
+/// How many random characters go between the prefix and the suffix
+const RANDOM_CHARS: usize = 10;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    /// Make a directory rather than a file
+    #[arg(short('d'), long)]
+    directory: bool,
+
+    /// Make it in DIR rather than in $TMPDIR, or /tmp when that is unset
+    #[arg(short('p'), long, value_name = "DIR", num_args = 0..=1)]
+    tmpdir: Option<Option<PathBuf>>,
+
+    /// Start the name with PREFIX
+    #[arg(long, value_name = "PREFIX", default_value = "tmp.", value_parser = parse_affix)]
+    prefix: String,
+
+    /// End the name with SUFFIX
+    #[arg(long, value_name = "SUFFIX", default_value = "", value_parser = parse_affix)]
+    suffix: String,
+
+    /// Print a name without making anything, so another process may take
+    /// it first
+    #[arg(short('u'), long)]
+    dry_run: bool,
+
+    /// Print no error when nothing could be made
+    #[arg(short('q'), long)]
+    quiet: bool,
+}
+
+/// Checks that a prefix or suffix stays within the directory's name.
+fn parse_affix(value: &str) -> Result<String, String> {
+    if value.contains('/') {
+        Err(format!("invalid '{value}', which contains '/'"))
+    } else {
+        Ok(value.to_string())
+    }
+}
+
+/// A name such as `tmp.x7Rk2QpZ0b`, made the way `tempfile` makes them but
+/// without looking at the file system.
+fn random_name(prefix: &str, suffix: &str) -> String {
+    let chars: String = iter::repeat_with(fastrand::alphanumeric)
+        .take(RANDOM_CHARS)
+        .collect();
+    format!("{prefix}{chars}{suffix}")
+}
+
+/// Makes a file, or a directory, with a random name in `dir`, that only
+/// its owner may use, and keeps it.
+fn make_temp(dir: &Path, prefix: &str, suffix: &str, directory: bool) -> Result<PathBuf> {
+    let mut builder = Builder::new();
+    builder
+        .prefix(prefix)
+        .suffix(suffix)
+        .rand_bytes(RANDOM_CHARS)
+        .disable_cleanup(true);
+    // files already get 0600, but directories would follow the umask
+    #[cfg(unix)]
+    if directory {
+        use std::{fs::Permissions, os::unix::fs::PermissionsExt};
+
+        builder.permissions(Permissions::from_mode(0o700));
+    }
+    let kind = if directory { "directory" } else { "file" };
+    let made = if directory {
+        builder.tempdir_in(dir).map(|d| d.path().to_path_buf())
+    } else {
+        builder.tempfile_in(dir).map(|f| f.path().to_path_buf())
+    };
+    made.map_err(|e| anyhow!("failed to create {kind}: {e}"))
+}
+
+fn run(args: Args) -> Result<()> {
+    let dir = match args.tmpdir.flatten() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => env::temp_dir(),
+    };
+    let path = if args.dry_run {
+        dir.join(random_name(&args.prefix, &args.suffix))
+    } else {
+        make_temp(&dir, &args.prefix, &args.suffix, args.directory)?
+    };
+    println!("{}", path.display());
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Args = parse_args();
+    let quiet = args.quiet;
+    match run(args) {
+        Err(e) => {
+            if !quiet {
+                eprintln!("{e}");
+            }
+            ExitCode::FAILURE
+        }
+        Ok(()) => ExitCode::SUCCESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{RANDOM_CHARS, make_temp, parse_affix, random_name};
+
+    #[test]
+    fn test_parse_affix() {
+        assert_eq!(parse_affix("tmp."), Ok("tmp.".to_string()));
+        assert_eq!(parse_affix(""), Ok("".to_string()));
+        assert_eq!(
+            parse_affix("a/b"),
+            Err("invalid 'a/b', which contains '/'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_random_name() {
+        let name = random_name("pre-", ".txt");
+        assert!(name.starts_with("pre-"));
+        assert!(name.ends_with(".txt"));
+        assert_eq!(name.len(), "pre-".len() + RANDOM_CHARS + ".txt".len());
+        assert!(
+            name[4..4 + RANDOM_CHARS]
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric())
+        );
+        assert_ne!(random_name("", ""), random_name("", ""));
+    }
+
+    #[test]
+    fn test_make_temp() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = make_temp(dir.path(), "a.", ".log", false).unwrap();
+        assert!(file.is_file());
+        assert_eq!(file.parent(), Some(dir.path()));
+        let name = file.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("a.") && name.ends_with(".log"));
+
+        let sub = make_temp(dir.path(), "", "", true).unwrap();
+        assert!(sub.is_dir());
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = |path| std::fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            assert_eq!(mode(&file), 0o600);
+            assert_eq!(mode(&sub), 0o700);
+        }
+
+        let err = make_temp(&dir.path().join("missing"), "", "", false).unwrap_err();
+        assert!(err.to_string().starts_with("failed to create file: "));
+    }
+}
@@ -0,0 +1,186 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const PRG: &str = "mktmpr";
+
+// --------------------------------------------------
+/// Runs mktmpr with `args`, with $TMPDIR set to `tmpdir`, and returns the
+/// path it printed.
+fn make(tmpdir: &Path, args: &[&str]) -> Result<PathBuf> {
+    let output = Command::cargo_bin(PRG)?
+        .env("TMPDIR", tmpdir)
+        .args(args)
+        .output()?;
+    assert!(output.status.success());
+    assert!(output.stderr.is_empty());
+    let stdout = String::from_utf8(output.stdout)?;
+    let path = stdout.strip_suffix('\n').expect("no newline");
+    Ok(PathBuf::from(path))
+}
+
+/// The name of the last part of `path`.
+fn name(path: &Path) -> &str {
+    path.file_name().unwrap().to_str().unwrap()
+}
+
+// --------------------------------------------------
+#[test]
+fn makes_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = make(dir.path(), &[])?;
+    assert!(path.is_file());
+    assert_eq!(path.parent(), Some(dir.path()));
+    assert!(name(&path).starts_with("tmp."));
+    assert_eq!(name(&path).len(), "tmp.".len() + 10);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn makes_directory() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    for flag in ["-d", "--directory"] {
+        let path = make(dir.path(), &[flag])?;
+        assert!(path.is_dir());
+        assert_eq!(fs::read_dir(&path)?.count(), 0);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn different_paths() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let mut paths = vec![];
+    for args in [&[][..], &[], &["-d"], &["-u"], &["-u"]] {
+        paths.push(make(dir.path(), args)?);
+    }
+    let count = paths.len();
+    paths.sort();
+    paths.dedup();
+    assert_eq!(paths.len(), count);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn prefix_and_suffix() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = make(dir.path(), &["--prefix=report-", "--suffix", ".csv"])?;
+    assert!(path.is_file());
+    let name = name(&path);
+    assert!(name.starts_with("report-"));
+    assert!(name.ends_with(".csv"));
+    assert_eq!(name.len(), "report-".len() + 10 + ".csv".len());
+
+    let path = make(dir.path(), &["-d", "--prefix=", "--suffix=.d"])?;
+    assert!(path.is_dir());
+    assert_eq!(self::name(&path).len(), 10 + ".d".len());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn tmpdir() -> Result<()> {
+    let env_dir = tempfile::tempdir()?;
+    let dir = tempfile::tempdir()?;
+    let dir_name = dir.path().to_str().unwrap();
+    for args in [&["-p", dir_name][..], &["--tmpdir", dir_name]] {
+        let path = make(env_dir.path(), args)?;
+        assert_eq!(path.parent(), Some(dir.path()));
+        assert!(path.is_file());
+    }
+    let path = make(env_dir.path(), &[&format!("--tmpdir={dir_name}"), "-d"])?;
+    assert_eq!(path.parent(), Some(dir.path()));
+
+    // without a DIR, $TMPDIR is used
+    let path = make(env_dir.path(), &["--tmpdir"])?;
+    assert_eq!(path.parent(), Some(env_dir.path()));
+    assert_eq!(fs::read_dir(env_dir.path())?.count(), 1);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dry_run() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    for flag in ["-u", "--dry-run"] {
+        let path = make(dir.path(), &[flag, "-d", "--suffix=.x"])?;
+        assert_eq!(path.parent(), Some(dir.path()));
+        assert!(name(&path).starts_with("tmp."));
+        assert!(name(&path).ends_with(".x"));
+        assert!(!path.exists());
+    }
+    assert_eq!(fs::read_dir(dir.path())?.count(), 0);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_missing_dir() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let missing = dir.path().join("missing");
+    Command::cargo_bin(PRG)?
+        .arg("-p")
+        .arg(&missing)
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::starts_with("failed to create file: "));
+    Command::cargo_bin(PRG)?
+        .args(["-d", "-p"])
+        .arg(&missing)
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("failed to create directory: "));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn quiet() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    for flag in ["-q", "--quiet"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "-p"])
+            .arg(dir.path().join("missing"))
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr("");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_slash_in_prefix() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--prefix", "a/b", "-u"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid 'a/b', which contains '/'",
+        ));
+    Command::cargo_bin(PRG)?
+        .args(["--suffix=/x", "-u"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid '/x', which contains '/'"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,378 @@
+use anyhow::{Result, bail};
+use clap::{Parser, ValueEnum};
+use cli_utils::{exit_with_error, open, parse_args};
+use regex::bytes::Regex;
+use std::io::{self, BufRead, BufWriter, Write};
+
+/// Columns taken by a line number, as in GNU `nl` without `-w`
+const WIDTH: usize = 6;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about, disable_help_flag = true)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    #[arg(
+        short('b'),
+        long("body-numbering"),
+        value_name = "STYLE",
+        default_value = "t",
+        value_parser = parse_style
+    )]
+    body: Style,
+
+    #[arg(
+        short('d'),
+        long("section-delimiter"),
+        value_name = "CC",
+        default_value = r"\:"
+    )]
+    delimiter: String,
+
+    #[arg(
+        short('f'),
+        long("footer-numbering"),
+        value_name = "STYLE",
+        default_value = "n",
+        value_parser = parse_style
+    )]
+    footer: Style,
+
+    #[arg(
+        short('h'),
+        long("header-numbering"),
+        value_name = "STYLE",
+        default_value = "n",
+        value_parser = parse_style
+    )]
+    header: Style,
+
+    #[arg(
+        short('i'),
+        long("line-increment"),
+        value_name = "NUMBER",
+        default_value_t = 1,
+        allow_negative_numbers = true
+    )]
+    increment: i64,
+
+    #[arg(
+        short('l'),
+        long("join-blank-lines"),
+        value_name = "NUMBER",
+        default_value_t = 1,
+        value_parser(clap::value_parser!(u64).range(1..))
+    )]
+    join_blank: u64,
+
+    #[arg(
+        short('n'),
+        long("number-format"),
+        value_name = "FORMAT",
+        default_value = "rn"
+    )]
+    format: Format,
+
+    #[arg(
+        short('s'),
+        long("number-separator"),
+        value_name = "STRING",
+        default_value = "\t"
+    )]
+    separator: String,
+
+    #[arg(
+        short('v'),
+        long("starting-line-number"),
+        value_name = "NUMBER",
+        default_value_t = 1,
+        allow_negative_numbers = true
+    )]
+    start: i64,
+
+    /// Print help
+    #[arg(long, action = clap::ArgAction::Help)]
+    help: Option<bool>,
+}
+
+/// Which lines of a section get a number
+#[derive(Debug, Clone)]
+enum Style {
+    All,
+    NonEmpty,
+    None,
+    Pattern(Regex),
+}
+
+fn parse_style(value: &str) -> Result<Style, String> {
+    match value {
+        "a" => Ok(Style::All),
+        "t" => Ok(Style::NonEmpty),
+        "n" => Ok(Style::None),
+        _ => match value.strip_prefix('p') {
+            Some(pattern) => Regex::new(pattern)
+                .map(Style::Pattern)
+                .map_err(|e| e.to_string()),
+            None => Err(format!("invalid numbering style: '{value}'")),
+        },
+    }
+}
+
+/// Where the number sits in its columns
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Format {
+    Ln,
+    Rn,
+    Rz,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Section {
+    Header,
+    Body,
+    Footer,
+}
+
+/// Numbers lines a logical page at a time. A line holding only the
+/// delimiter three, two or one times starts a header, body or footer,
+/// prints as an empty line, and restarts the numbering.
+#[derive(Debug)]
+struct Numberer {
+    header: Style,
+    body: Style,
+    footer: Style,
+    delimiter: Vec<u8>,
+    format: Format,
+    separator: String,
+    start: i64,
+    increment: i64,
+    join_blank: u64,
+    section: Section,
+    number: i64,
+    blank_lines: u64,
+}
+
+impl Numberer {
+    fn new(args: Args) -> Self {
+        // as in GNU nl, a lone character is followed by the default ':'
+        let mut delimiter = args.delimiter.into_bytes();
+        if delimiter.len() == 1 {
+            delimiter.push(b':');
+        }
+        Self {
+            header: args.header,
+            body: args.body,
+            footer: args.footer,
+            delimiter,
+            format: args.format,
+            separator: args.separator,
+            start: args.start,
+            increment: args.increment,
+            join_blank: args.join_blank,
+            section: Section::Body,
+            number: args.start,
+            blank_lines: 0,
+        }
+    }
+
+    fn section_start(&self, text: &[u8]) -> Option<Section> {
+        let len = self.delimiter.len();
+        if len == 0
+            || !text.len().is_multiple_of(len)
+            || !text.chunks(len).all(|c| c == self.delimiter)
+        {
+            return None;
+        }
+        match text.len() / len {
+            3 => Some(Section::Header),
+            2 => Some(Section::Body),
+            1 => Some(Section::Footer),
+            _ => None,
+        }
+    }
+
+    fn style(&self) -> &Style {
+        match self.section {
+            Section::Header => &self.header,
+            Section::Body => &self.body,
+            Section::Footer => &self.footer,
+        }
+    }
+
+    /// Whether `text` gets a number. With `-l`, only every Nth of a run of
+    /// blank lines does under the `a` style.
+    fn numbered(&mut self, text: &[u8]) -> bool {
+        match self.style() {
+            Style::All if text.is_empty() && self.join_blank > 1 => {
+                self.blank_lines += 1;
+                if self.blank_lines == self.join_blank {
+                    self.blank_lines = 0;
+                    true
+                } else {
+                    false
+                }
+            }
+            Style::All => {
+                self.blank_lines = 0;
+                true
+            }
+            Style::NonEmpty => !text.is_empty(),
+            Style::None => false,
+            Style::Pattern(regex) => regex.is_match(text),
+        }
+    }
+
+    fn format_number(&self) -> String {
+        let number = self.number;
+        match self.format {
+            Format::Ln => format!("{number:<WIDTH$}"),
+            Format::Rn => format!("{number:>WIDTH$}"),
+            Format::Rz => format!("{number:0WIDTH$}"),
+        }
+    }
+
+    /// Appends `line` to `output` with its number, or with blanks the width
+    /// of one, always ending in a newline.
+    fn number_line(&mut self, line: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        let text = line.strip_suffix(b"\n").unwrap_or(line);
+        if let Some(section) = self.section_start(text) {
+            self.section = section;
+            self.number = self.start;
+            self.blank_lines = 0;
+            output.push(b'\n');
+            return Ok(());
+        }
+
+        if self.numbered(text) {
+            output.extend(self.format_number().as_bytes());
+            output.extend(self.separator.as_bytes());
+            self.number = match self.number.checked_add(self.increment) {
+                Some(number) => number,
+                None => bail!("line number overflow"),
+            };
+        } else {
+            let blank = WIDTH + self.separator.len();
+            output.extend(std::iter::repeat_n(b' ', blank));
+        }
+        output.extend(text);
+        output.push(b'\n');
+        Ok(())
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let files = args.files.clone();
+    let mut numberer = Numberer::new(args);
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut line = vec![];
+    let mut output = vec![];
+
+    // numbering carries on from one file into the next
+    for filename in &files {
+        match open(filename) {
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(mut file) => loop {
+                line.clear();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+                output.clear();
+                numberer.number_line(&line, &mut output)?;
+                stdout.write_all(&output)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Args, Numberer, Section, Style, parse_style};
+    use clap::Parser;
+
+    fn number(args: &[&str], input: &str) -> String {
+        let args = Args::parse_from(["nlr"].iter().chain(args));
+        let mut numberer = Numberer::new(args);
+        let mut output = vec![];
+        for line in input.split_inclusive('\n') {
+            numberer.number_line(line.as_bytes(), &mut output).unwrap();
+        }
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_parse_style() {
+        assert!(matches!(parse_style("a"), Ok(Style::All)));
+        assert!(matches!(parse_style("t"), Ok(Style::NonEmpty)));
+        assert!(matches!(parse_style("n"), Ok(Style::None)));
+        assert!(matches!(parse_style("p^a"), Ok(Style::Pattern(_))));
+        assert!(parse_style("p[").is_err());
+        assert_eq!(
+            parse_style("x").unwrap_err(),
+            "invalid numbering style: 'x'"
+        );
+    }
+
+    #[test]
+    fn test_section_start() {
+        let numberer = Numberer::new(Args::parse_from(["nlr"]));
+        assert_eq!(numberer.section_start(br"\:\:\:"), Some(Section::Header));
+        assert_eq!(numberer.section_start(br"\:\:"), Some(Section::Body));
+        assert_eq!(numberer.section_start(br"\:"), Some(Section::Footer));
+        assert_eq!(numberer.section_start(br"\:\:\:\:"), None);
+        assert_eq!(numberer.section_start(br"\:\"), None);
+        assert_eq!(numberer.section_start(b""), None);
+
+        let numberer = Numberer::new(Args::parse_from(["nlr", "-d", "@"]));
+        assert_eq!(numberer.section_start(b"@:@:"), Some(Section::Body));
+        assert_eq!(numberer.section_start(b"@@"), None);
+
+        let numberer = Numberer::new(Args::parse_from(["nlr", "-d", ""]));
+        assert_eq!(numberer.section_start(b""), None);
+    }
+
+    #[test]
+    fn test_number_line() {
+        assert_eq!(number(&[], "a\n\nb"), "     1\ta\n       \n     2\tb\n");
+        assert_eq!(number(&["-ba"], "a\n\n"), "     1\ta\n     2\t\n");
+        assert_eq!(number(&["-bn"], "a\n"), "       a\n");
+        assert_eq!(number(&["-bp^b", "-s:"], "a\nb\n"), "       a\n     1:b\n");
+        assert_eq!(number(&["-nln"], "a\n"), "1     \ta\n");
+        assert_eq!(number(&["-nrz", "-v-2"], "a\n"), "-00002\ta\n");
+        assert_eq!(number(&["-i5", "-v10"], "a\nb\n"), "    10\ta\n    15\tb\n");
+        // each section starts counting again
+        assert_eq!(
+            number(&["-ha"], "a\n\\:\\:\\:\nb\n\\:\\:\nc\n"),
+            "     1\ta\n\n     1\tb\n\n     1\tc\n"
+        );
+    }
+
+    #[test]
+    fn test_join_blank_lines() {
+        assert_eq!(
+            number(&["-ba", "-l2"], "\n\n\na\n\n"),
+            "       \n     1\t\n       \n     2\ta\n       \n"
+        );
+        // only the a style counts blank lines at all
+        assert_eq!(number(&["-l2"], "\n\n"), "       \n       \n");
+    }
+
+    #[test]
+    fn test_overflow() {
+        let args = Args::parse_from(["nlr", "-v", &i64::MAX.to_string()]);
+        let mut numberer = Numberer::new(args);
+        let mut output = vec![];
+        assert_eq!(
+            numberer
+                .number_line(b"a\n", &mut output)
+                .unwrap_err()
+                .to_string(),
+            "line number overflow"
+        );
+    }
+}
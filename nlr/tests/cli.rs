@@ -0,0 +1,229 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "nlr";
+const DELIMS: &str = "tests/inputs/delims.txt";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const FOX: &str = "tests/inputs/fox.txt";
+const SECTIONS: &str = "tests/inputs/sections.txt";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_style() -> Result<()> {
+    for flag in ["-b", "-f", "-h"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "x", SECTIONS])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("invalid numbering style: 'x'"));
+    }
+    Command::cargo_bin(PRG)?
+        .args(["-b", "p[", SECTIONS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("regex parse error"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_format() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-n", "xx", SECTIONS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value 'xx' for '--number-format <FORMAT>'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_join_blank_lines() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-l", "0", SECTIONS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "invalid value '0' for '--join-blank-lines <NUMBER>'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args([FOX, &bad, FOX])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string("tests/expected/all.out")?)
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn sections() -> Result<()> {
+    run(&[SECTIONS], "tests/expected/sections.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn number_all_sections() -> Result<()> {
+    run(
+        &["-ha", "-fa", "-ba", SECTIONS],
+        "tests/expected/sections.txt.ha_fa_ba.out",
+    )?;
+    run(
+        &[
+            "--header-numbering=a",
+            "--footer-numbering=a",
+            "--body-numbering=a",
+            SECTIONS,
+        ],
+        "tests/expected/sections.txt.ha_fa_ba.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn number_non_empty() -> Result<()> {
+    run(
+        &["-h", "t", "-f", "t", "-b", "n", SECTIONS],
+        "tests/expected/sections.txt.ht_ft_bn.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn number_pattern() -> Result<()> {
+    run(
+        &["-bp^[A-Z]", "-hpheader", SECTIONS],
+        "tests/expected/sections.txt.bp_hp.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn join_blank_lines() -> Result<()> {
+    run(
+        &["-ba", "-l", "2", SECTIONS],
+        "tests/expected/sections.txt.ba_l2.out",
+    )?;
+    run(
+        &["-ba", "--join-blank-lines", "2", SECTIONS],
+        "tests/expected/sections.txt.ba_l2.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn start_and_increment() -> Result<()> {
+    run(
+        &["-v", "10", "-i", "5", SECTIONS],
+        "tests/expected/sections.txt.v10_i5.out",
+    )?;
+    run(
+        &["--starting-line-number=10", "--line-increment=5", SECTIONS],
+        "tests/expected/sections.txt.v10_i5.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn number_formats() -> Result<()> {
+    run(
+        &["-n", "ln", SECTIONS],
+        "tests/expected/sections.txt.n_ln.out",
+    )?;
+    run(
+        &["--number-format", "rz", SECTIONS],
+        "tests/expected/sections.txt.n_rz.out",
+    )?;
+    run(&["-n", "rn", SECTIONS], "tests/expected/sections.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn separator() -> Result<()> {
+    run(&["-s", ": ", SECTIONS], "tests/expected/sections.txt.s.out")?;
+    run(
+        &["--number-separator=: ", SECTIONS],
+        "tests/expected/sections.txt.s.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn section_delimiter() -> Result<()> {
+    // with another delimiter, the default one is just text
+    run(
+        &["-d", "@#", "-ha", "-fa", DELIMS],
+        "tests/expected/delims.txt.d.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files() -> Result<()> {
+    // numbering runs on from one file into the next
+    run(&[FOX, EMPTY, FOX], "tests/expected/all.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin() -> Result<()> {
+    let input = fs::read_to_string(SECTIONS)?;
+    let expected = fs::read_to_string("tests/expected/sections.txt.out")?;
+    Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
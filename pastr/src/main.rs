@@ -0,0 +1,197 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use cli_utils::{exit_with_error, open, parse_args};
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    #[arg(short('d'), long, value_name = "LIST", default_value = "\t")]
+    delimiters: String,
+
+    #[arg(short('s'), long)]
+    serial: bool,
+}
+
+/// Splits a delimiter list into the delimiters to cycle through, turning
+/// `\n`, `\t` and `\\` into their characters and `\0` into no delimiter.
+fn parse_delimiters(list: &str) -> Result<Vec<String>> {
+    let mut delimiters = vec![];
+    let mut chars = list.chars();
+    while let Some(c) = chars.next() {
+        let delimiter = match c {
+            '\\' => match chars.next() {
+                Some('n') => "\n".to_string(),
+                Some('t') => "\t".to_string(),
+                Some('0') => "".to_string(),
+                Some(c) => c.to_string(),
+                None => bail!("delimiter list ends with an unescaped backslash: {list}"),
+            },
+            c => c.to_string(),
+        };
+        delimiters.push(delimiter);
+    }
+    // as in GNU paste, an empty list joins lines with nothing between them
+    if delimiters.is_empty() {
+        delimiters.push("".to_string());
+    }
+    Ok(delimiters)
+}
+
+fn trim_newline(line: &[u8]) -> &[u8] {
+    line.strip_suffix(b"\n").unwrap_or(line)
+}
+
+/// Joins line N of every input into output line N, until all of them run
+/// out. `sources` maps each input to its reader, so that `-` given twice
+/// reads alternate lines from the one stdin.
+fn paste(
+    readers: &mut [Box<dyn BufRead>],
+    sources: &[usize],
+    delimiters: &[String],
+    mut output: impl Write,
+) -> Result<()> {
+    let mut line = vec![];
+    let mut row = vec![];
+    loop {
+        row.clear();
+        let mut found = false;
+        for (i, &source) in sources.iter().enumerate() {
+            if i > 0 {
+                row.extend(delimiters[(i - 1) % delimiters.len()].as_bytes());
+            }
+            // an input that has run out leaves an empty field
+            line.clear();
+            if readers[source].read_until(b'\n', &mut line)? > 0 {
+                found = true;
+                row.extend(trim_newline(&line));
+            }
+        }
+        if !found {
+            break;
+        }
+        row.push(b'\n');
+        output.write_all(&row)?;
+    }
+    Ok(())
+}
+
+/// Joins all the lines of one input into a single output line.
+fn paste_serial(
+    reader: &mut dyn BufRead,
+    delimiters: &[String],
+    mut output: impl Write,
+) -> Result<()> {
+    let mut line = vec![];
+    let mut row = vec![];
+    let mut num_lines = 0;
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        if num_lines > 0 {
+            row.extend(delimiters[(num_lines - 1) % delimiters.len()].as_bytes());
+        }
+        row.extend(trim_newline(&line));
+        num_lines += 1;
+    }
+    row.push(b'\n');
+    output.write_all(&row)?;
+    Ok(())
+}
+
+fn run(args: Args) -> Result<()> {
+    let delimiters = parse_delimiters(&args.delimiters)?;
+
+    // every file is opened up front, so a bad one fails before any output
+    let mut readers = vec![];
+    let mut stdin = None;
+    let mut sources = vec![];
+    for filename in &args.files {
+        if filename == "-" && stdin.is_some() {
+            sources.extend(stdin);
+            continue;
+        }
+        readers.push(open(filename).map_err(|e| anyhow!("{filename}: {e}"))?);
+        sources.push(readers.len() - 1);
+        if filename == "-" {
+            stdin = Some(readers.len() - 1);
+        }
+    }
+
+    let mut stdout = io::stdout().lock();
+    if args.serial {
+        for source in sources {
+            paste_serial(readers[source].as_mut(), &delimiters, &mut stdout)?;
+        }
+    } else {
+        paste(&mut readers, &sources, &delimiters, &mut stdout)?;
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_delimiters, paste, paste_serial};
+    use std::io::{BufRead, Cursor};
+
+    fn reader(text: &'static str) -> Box<dyn BufRead> {
+        Box::new(Cursor::new(text))
+    }
+
+    #[test]
+    fn test_parse_delimiters() {
+        assert_eq!(parse_delimiters("\t").unwrap(), ["\t"]);
+        assert_eq!(parse_delimiters(",;").unwrap(), [",", ";"]);
+        assert_eq!(
+            parse_delimiters(r"\n\t\\\0\x").unwrap(),
+            ["\n", "\t", "\\", "", "x"]
+        );
+        assert_eq!(parse_delimiters("é|").unwrap(), ["é", "|"]);
+        assert_eq!(parse_delimiters("").unwrap(), [""]);
+        assert_eq!(
+            parse_delimiters(r"a\").unwrap_err().to_string(),
+            r"delimiter list ends with an unescaped backslash: a\"
+        );
+    }
+
+    #[test]
+    fn test_paste() {
+        let mut readers = vec![reader("a\nb\nc\n"), reader("1\n2"), reader("")];
+        let mut output = vec![];
+        paste(&mut readers, &[0, 1, 2], &[",".to_string()], &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a,1,\nb,2,\nc,,\n");
+
+        // one reader shared by two inputs supplies alternate lines
+        let mut readers = vec![reader("1\n2\n3\n")];
+        let mut output = vec![];
+        paste(&mut readers, &[0, 0], &["\t".to_string()], &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "1\t2\n3\t\n");
+
+        // nothing is printed once every input is empty
+        let mut readers = vec![reader(""), reader("")];
+        let mut output = vec![];
+        paste(&mut readers, &[0, 1], &["\t".to_string()], &mut output).unwrap();
+        assert!(output.is_empty());
+    }
+
+    #[test]
+    fn test_paste_serial() {
+        let delimiters = [",".to_string(), ";".to_string()];
+        let mut output = vec![];
+        paste_serial(&mut Cursor::new("a\nb\nc\nd"), &delimiters, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "a,b;c,d\n");
+
+        let mut output = vec![];
+        paste_serial(&mut Cursor::new(""), &delimiters, &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "\n");
+    }
+}
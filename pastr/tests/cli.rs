@@ -0,0 +1,186 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "pastr";
+const COLORS: &str = "tests/inputs/colors.txt";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const GREEK: &str = "tests/inputs/greek.txt";
+const LOREM: &str = "tests/inputs/lorem.txt";
+const NUMBERS: &str = "tests/inputs/numbers.txt";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(input_file: &str, args: &[&str], expected_file: &str) -> Result<()> {
+    let input = fs::read_to_string(input_file)?;
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_file() -> Result<()> {
+    // nothing is printed when any file can't be opened
+    let bad = gen_bad_file();
+    Command::cargo_bin(PRG)?
+        .args([NUMBERS, &bad])
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr(predicate::str::is_match(format!(
+            "^{bad}: .* [(]os error 2[)]"
+        ))?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_trailing_backslash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-d", r"a\", NUMBERS])
+        .assert()
+        .failure()
+        .stderr("delimiter list ends with an unescaped backslash: a\\\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn two_files() -> Result<()> {
+    run(&[NUMBERS, GREEK], "tests/expected/numbers.greek.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn three_files() -> Result<()> {
+    // the last line of colors.txt has no newline
+    run(
+        &[NUMBERS, GREEK, COLORS],
+        "tests/expected/numbers.greek.colors.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn four_files() -> Result<()> {
+    run(
+        &[COLORS, EMPTY, GREEK, NUMBERS],
+        "tests/expected/colors.empty.greek.numbers.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn empty_files() -> Result<()> {
+    run(&[EMPTY, EMPTY], "tests/expected/empty.empty.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_delimiters() -> Result<()> {
+    run(
+        &["-d", ",;|", NUMBERS, GREEK, COLORS, LOREM],
+        "tests/expected/all.d_multi.out",
+    )?;
+    run(
+        &["--delimiters=,;|", NUMBERS, GREEK, COLORS, LOREM],
+        "tests/expected/all.d_multi.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn escaped_delimiters() -> Result<()> {
+    run(
+        &["-d", r"\n\t\\\0", NUMBERS, GREEK, COLORS, LOREM, NUMBERS],
+        "tests/expected/all.d_escapes.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn empty_delimiter_list() -> Result<()> {
+    run(
+        &["-d", "", NUMBERS, GREEK],
+        "tests/expected/numbers.greek.d_empty.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn serial() -> Result<()> {
+    run(
+        &["-s", NUMBERS, EMPTY, COLORS],
+        "tests/expected/numbers.empty.colors.s.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn serial_delimiters() -> Result<()> {
+    run(
+        &["--serial", "-d", ", ", GREEK, LOREM],
+        "tests/expected/greek.lorem.s_d.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_repeated() -> Result<()> {
+    // each "-" takes the next line of the one stdin
+    run_stdin(GREEK, &["-", "-", "-"], "tests/expected/greek.stdin3.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_default() -> Result<()> {
+    run_stdin(NUMBERS, &[], NUMBERS)
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,192 @@
+use anyhow::{Result, bail};
+use clap::Parser;
+use cli_utils::parse_args;
+use std::process::ExitCode;
+
+// The limits of Linux, which are what the files named here will mostly
+// live under
+const PATH_MAX: usize = 4096;
+const NAME_MAX: usize = 255;
+
+// The smallest limits POSIX allows a system, _POSIX_PATH_MAX less its NUL
+// and _POSIX_NAME_MAX, which are the ones a portable name keeps within
+const POSIX_PATH_MAX: usize = 255;
+const POSIX_NAME_MAX: usize = 14;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "NAME", required = true)]
+    names: Vec<String>,
+
+    /// Keep to the POSIX portable characters and the smallest POSIX limits
+    #[arg(short('p'), long)]
+    portability: bool,
+
+    /// Reject empty names and components that start with '-'
+    #[arg(short('P'), long)]
+    posix_special: bool,
+}
+
+/// Whether `c` is in the POSIX portable filename character set.
+fn is_portable(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-')
+}
+
+/// Checks `name` as GNU `pathchk` does, failing at the first problem found:
+/// with `portable`, for characters outside the portable set and names or
+/// components too long for the smallest POSIX limits, otherwise for ones
+/// too long for this system; and with `special`, for components that start
+/// with `-`. A name may never be empty or contain a NUL.
+fn check(name: &str, portable: bool, special: bool) -> Result<()> {
+    if name.is_empty() {
+        bail!("empty file name");
+    }
+    if name.contains('\0') {
+        bail!("NUL in file name '{name}'");
+    }
+    if special && name.split('/').any(|part| part.starts_with('-')) {
+        bail!("leading '-' in a component of file name '{name}'");
+    }
+    if portable && let Some(c) = name.chars().find(|&c| c != '/' && !is_portable(c)) {
+        bail!("nonportable character '{c}' in file name '{name}'");
+    }
+
+    let (path_max, name_max) = if portable {
+        (POSIX_PATH_MAX, POSIX_NAME_MAX)
+    } else {
+        (PATH_MAX - 1, NAME_MAX)
+    };
+    if name.len() > path_max {
+        bail!(
+            "limit {path_max} exceeded by length {} of file name '{name}'",
+            name.len()
+        );
+    }
+    if let Some(part) = name.split('/').find(|part| part.len() > name_max) {
+        bail!(
+            "limit {name_max} exceeded by length {} of file name component '{part}'",
+            part.len()
+        );
+    }
+    Ok(())
+}
+
+/// Checks each NAME, printing what is wrong with those that fail, and
+/// returns whether they all passed.
+fn run(args: Args) -> Result<bool> {
+    let mut all_ok = true;
+    for name in &args.names {
+        if let Err(e) = check(name, args.portability, args.posix_special) {
+            eprintln!("{e}");
+            all_ok = false;
+        }
+    }
+    Ok(all_ok)
+}
+
+fn main() -> ExitCode {
+    match run(parse_args()) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{NAME_MAX, PATH_MAX, check};
+
+    fn error(name: &str, portable: bool, special: bool) -> String {
+        check(name, portable, special).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn test_valid() {
+        for name in [
+            "a", "a/b.c", "/usr/lib", "a_b-c.d", "./a/../b", "dir/", "//a",
+        ] {
+            for (portable, special) in [(false, false), (true, false), (false, true), (true, true)]
+            {
+                assert!(check(name, portable, special).is_ok(), "{name}");
+            }
+        }
+        // the portable limits are the largest lengths allowed
+        assert!(check(&"a".repeat(14), true, false).is_ok());
+        assert!(check(&"a/".repeat(127), true, false).is_ok());
+    }
+
+    #[test]
+    fn test_empty() {
+        assert_eq!(error("", false, false), "empty file name");
+        assert_eq!(error("", true, true), "empty file name");
+    }
+
+    #[test]
+    fn test_nonportable() {
+        // anything goes without -p
+        assert!(check("a b/ç*?", false, false).is_ok());
+        assert_eq!(
+            error("a b", true, false),
+            "nonportable character ' ' in file name 'a b'"
+        );
+        assert_eq!(
+            error("dir/façade", true, false),
+            "nonportable character 'ç' in file name 'dir/façade'"
+        );
+    }
+
+    #[test]
+    fn test_leading_hyphen() {
+        // only -P minds a leading hyphen, in any component
+        assert!(check("-a", true, false).is_ok());
+        assert!(check("a-/b-", false, true).is_ok());
+        assert_eq!(
+            error("-a", false, true),
+            "leading '-' in a component of file name '-a'"
+        );
+        assert_eq!(
+            error("a/-b/c", false, true),
+            "leading '-' in a component of file name 'a/-b/c'"
+        );
+    }
+
+    #[test]
+    fn test_name_too_long() {
+        let name = "a/".repeat(128);
+        assert_eq!(
+            error(&name, true, false),
+            format!("limit 255 exceeded by length 256 of file name '{name}'")
+        );
+        assert!(check(&name, false, false).is_ok());
+
+        let name = "a/".repeat(PATH_MAX / 2);
+        assert_eq!(
+            error(&name, false, false),
+            format!("limit 4095 exceeded by length 4096 of file name '{name}'")
+        );
+    }
+
+    #[test]
+    fn test_component_too_long() {
+        assert_eq!(
+            error("dir/abcdefghijklmno", true, false),
+            "limit 14 exceeded by length 15 of file name component 'abcdefghijklmno'"
+        );
+        assert!(check("dir/abcdefghijklmno", false, false).is_ok());
+
+        let part = "x".repeat(NAME_MAX + 1);
+        assert_eq!(
+            error(&format!("/tmp/{part}"), false, false),
+            format!("limit 255 exceeded by length 256 of file name component '{part}'")
+        );
+    }
+
+    #[test]
+    fn test_nul() {
+        assert_eq!(error("a\0b", false, false), "NUL in file name 'a\0b'");
+    }
+}
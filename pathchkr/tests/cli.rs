@@ -0,0 +1,144 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const PRG: &str = "pathchkr";
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn valid() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-pP", "a", "dir/file.txt", "/usr/local/lib"])
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn spaces() -> Result<()> {
+    // a space is fine for this system, but isn't portable
+    Command::cargo_bin(PRG)?
+        .arg("my file.txt")
+        .assert()
+        .success();
+    Command::cargo_bin(PRG)?
+        .args(["--portability", "my file.txt"])
+        .assert()
+        .code(1)
+        .stderr("nonportable character ' ' in file name 'my file.txt'\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn leading_hyphen() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-p", "--", "-rf", "a/-b"])
+        .assert()
+        .success();
+    Command::cargo_bin(PRG)?
+        .args(["--posix-special", "--", "-rf", "ok", "a/-b"])
+        .assert()
+        .code(1)
+        .stderr(
+            "leading '-' in a component of file name '-rf'\n\
+             leading '-' in a component of file name 'a/-b'\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-P", ""])
+        .assert()
+        .code(1)
+        .stderr("empty file name\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn component_too_long() -> Result<()> {
+    let long = "a".repeat(15);
+    Command::cargo_bin(PRG)?
+        .args(["-p", &format!("dir/{long}")])
+        .assert()
+        .code(1)
+        .stderr(format!(
+            "limit 14 exceeded by length 15 of file name component '{long}'\n"
+        ));
+
+    let long = "a".repeat(256);
+    Command::cargo_bin(PRG)?
+        .arg(&long)
+        .assert()
+        .code(1)
+        .stderr(format!(
+            "limit 255 exceeded by length 256 of file name component '{long}'\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn name_too_long() -> Result<()> {
+    let long = "dir/".repeat(64);
+    Command::cargo_bin(PRG)?
+        .args(["-p", &long])
+        .assert()
+        .code(1)
+        .stderr(format!(
+            "limit 255 exceeded by length 256 of file name '{long}'\n"
+        ));
+
+    let long = "dir/".repeat(1024);
+    Command::cargo_bin(PRG)?
+        .arg(&long)
+        .assert()
+        .code(1)
+        .stderr(format!(
+            "limit 4095 exceeded by length 4096 of file name '{long}'\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn some_fail() -> Result<()> {
+    // every name is checked, and one failure fails the lot
+    Command::cargo_bin(PRG)?
+        .args(["-p", "a b", "ok", "c*d"])
+        .assert()
+        .code(1)
+        .stderr(
+            "nonportable character ' ' in file name 'a b'\n\
+             nonportable character '*' in file name 'c*d'\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
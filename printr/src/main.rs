@@ -0,0 +1,747 @@
+use anyhow::{Result, bail};
+use clap::Parser;
+use cli_utils::parse_args;
+use std::io::{self, Write};
+use std::process::ExitCode;
+
+/// Field widths and precisions past this are refused, as GNU printf does
+const MAX_WIDTH: usize = i32::MAX as usize;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FORMAT")]
+    format: String,
+
+    #[arg(
+        value_name = "ARGUMENT",
+        allow_hyphen_values = true,
+        trailing_var_arg = true
+    )]
+    arguments: Vec<String>,
+}
+
+/// One `%` conversion, such as `%-8.3f`
+#[derive(Debug, Default, Clone, PartialEq)]
+struct Spec {
+    left: bool,
+    plus: bool,
+    space: bool,
+    zero: bool,
+    alt: bool,
+    width: usize,
+    precision: Option<usize>,
+    conversion: u8,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Piece {
+    Text(Vec<u8>),
+    Spec(Spec),
+    /// A `\c`, after which nothing more is printed
+    Stop,
+}
+
+/// Reads the escape that follows a backslash at the start of `rest`,
+/// returning the bytes it stands for, or `None` for `\c`, and how much of
+/// `rest` it took. Octal escapes are `\NNN` in a format, and `\0NNN` as
+/// well in a `%b` argument, as with `echo -e`.
+fn escape(rest: &[u8], octal_0: bool) -> Result<(Option<Vec<u8>>, usize)> {
+    let Some(&c) = rest.first() else {
+        // a trailing backslash stands for itself
+        return Ok((Some(vec![b'\\']), 0));
+    };
+    let byte = match c {
+        b'\\' => b'\\',
+        b'"' => b'"',
+        b'a' => 0x07,
+        b'b' => 0x08,
+        b'e' => 0x1b,
+        b'f' => 0x0c,
+        b'n' => b'\n',
+        b'r' => b'\r',
+        b't' => b'\t',
+        b'v' => 0x0b,
+        b'c' => return Ok((None, 1)),
+        b'x' => {
+            let (value, len) = take_digits(&rest[1..], 16, 2);
+            if len == 0 {
+                bail!("missing hexadecimal number in escape");
+            }
+            return Ok((Some(vec![value]), 1 + len));
+        }
+        b'0'..=b'7' => {
+            let start = usize::from(octal_0 && c == b'0');
+            let (value, len) = take_digits(&rest[start..], 8, 3);
+            return Ok((Some(vec![value]), start + len));
+        }
+        // unknown escapes are printed as they are
+        _ => return Ok((Some(vec![b'\\', c]), 1)),
+    };
+    Ok((Some(vec![byte]), 1))
+}
+
+/// Reads up to `max` digits in the given radix from the start of `bytes`,
+/// returning their value truncated to a byte and how many there were.
+fn take_digits(bytes: &[u8], radix: u32, max: usize) -> (u8, usize) {
+    let mut value: u32 = 0;
+    let mut len = 0;
+    for digit in bytes
+        .iter()
+        .take(max)
+        .map_while(|&b| char::from(b).to_digit(radix))
+    {
+        value = value * radix + digit;
+        len += 1;
+    }
+    (value as u8, len)
+}
+
+/// Splits a format into literal text, with its escapes expanded, and the
+/// conversions between.
+fn parse_format(format: &[u8]) -> Result<Vec<Piece>> {
+    let mut pieces = vec![];
+    let mut text = vec![];
+    let mut i = 0;
+    while i < format.len() {
+        match format[i] {
+            b'\\' => {
+                let (bytes, len) = escape(&format[i + 1..], false)?;
+                i += 1 + len;
+                match bytes {
+                    Some(bytes) => text.extend(bytes),
+                    None => {
+                        pieces.push(Piece::Text(text));
+                        pieces.push(Piece::Stop);
+                        return Ok(pieces);
+                    }
+                }
+            }
+            b'%' if format.get(i + 1) == Some(&b'%') => {
+                text.push(b'%');
+                i += 2;
+            }
+            b'%' => {
+                let (spec, len) = parse_spec(&format[i..])?;
+                pieces.push(Piece::Text(std::mem::take(&mut text)));
+                pieces.push(Piece::Spec(spec));
+                i += len;
+            }
+            byte => {
+                text.push(byte);
+                i += 1;
+            }
+        }
+    }
+    pieces.push(Piece::Text(text));
+    Ok(pieces)
+}
+
+/// Reads the conversion at the start of `format`, which begins with `%`,
+/// returning it and its length.
+fn parse_spec(format: &[u8]) -> Result<(Spec, usize)> {
+    let mut spec = Spec::default();
+    let mut i = 1;
+    while let Some(&flag) = format.get(i) {
+        match flag {
+            b'-' => spec.left = true,
+            b'+' => spec.plus = true,
+            b' ' => spec.space = true,
+            b'0' => spec.zero = true,
+            b'#' => spec.alt = true,
+            _ => break,
+        }
+        i += 1;
+    }
+    let number = |i: &mut usize| -> Result<usize> {
+        let start = *i;
+        while format.get(*i).is_some_and(u8::is_ascii_digit) {
+            *i += 1;
+        }
+        let digits = std::str::from_utf8(&format[start..*i])?;
+        match digits.parse::<usize>() {
+            Ok(value) if value <= MAX_WIDTH => Ok(value),
+            Err(_) if digits.is_empty() => Ok(0),
+            _ => bail!("{digits}: invalid field width or precision"),
+        }
+    };
+    spec.width = number(&mut i)?;
+    if format.get(i) == Some(&b'.') {
+        i += 1;
+        spec.precision = Some(number(&mut i)?);
+    }
+    match format.get(i) {
+        Some(&c) if b"diouxXfFeEgGcsb".contains(&c) => spec.conversion = c,
+        _ => {
+            let end = (i + 1).min(format.len());
+            bail!(
+                "{}: invalid conversion specification",
+                String::from_utf8_lossy(&format[..end])
+            )
+        }
+    }
+    Ok((spec, i + 1))
+}
+
+/// Reads the number at the start of `arg` the way C's `strtol` does: an
+/// optional sign, then hex after `0x`, octal after `0`, or decimal. A
+/// leading quote gives the code of the character after it. Returns the
+/// value, saturated to `i128`, and how much of `arg` was used.
+fn parse_int(arg: &str) -> (i128, usize) {
+    let trimmed = arg.trim_start();
+    let skipped = arg.len() - trimmed.len();
+    if let Some(rest) = trimmed.strip_prefix(['\'', '"']) {
+        return match rest.chars().next() {
+            Some(c) => (i128::from(u32::from(c)), skipped + 1 + c.len_utf8()),
+            None => (0, 0),
+        };
+    }
+    let (negative, unsigned) = match trimmed.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, trimmed.strip_prefix('+').unwrap_or(trimmed)),
+    };
+    let sign_len = trimmed.len() - unsigned.len();
+    let lower = unsigned.to_ascii_lowercase();
+    let (radix, prefix_len) =
+        if lower.starts_with("0x") && lower[2..].starts_with(|c: char| c.is_ascii_hexdigit()) {
+            (16, 2)
+        } else if lower.starts_with('0') {
+            (8, 0)
+        } else {
+            (10, 0)
+        };
+    let mut value: i128 = 0;
+    let mut digits = 0;
+    for digit in unsigned[prefix_len..]
+        .chars()
+        .map_while(|c| c.to_digit(radix))
+    {
+        value = value
+            .saturating_mul(radix.into())
+            .saturating_add(digit.into());
+        digits += 1;
+    }
+    if digits == 0 {
+        return (0, 0);
+    }
+    let value = if negative { -value } else { value };
+    (value, skipped + sign_len + prefix_len + digits)
+}
+
+/// Reads the longest floating-point number at the start of `arg`, or the
+/// code of a quoted character, returning it and how much of `arg` was used.
+fn parse_float(arg: &str) -> (f64, usize) {
+    let trimmed = arg.trim_start();
+    let skipped = arg.len() - trimmed.len();
+    if trimmed.starts_with(['\'', '"']) {
+        let (value, len) = parse_int(trimmed);
+        return (value as f64, skipped + len);
+    }
+    (1..=trimmed.len())
+        .rev()
+        .filter(|&end| trimmed.is_char_boundary(end))
+        .find_map(|end| {
+            let value = trimmed[..end].parse::<f64>().ok()?;
+            Some((value, skipped + end))
+        })
+        .unwrap_or((0.0, 0))
+}
+
+/// Formats `value`, which is not negative, like C's `%e`, with at least
+/// two exponent digits.
+fn format_exp(value: f64, precision: usize, alt: bool) -> String {
+    let formatted = format!("{value:.precision$e}");
+    let (mantissa, exponent) = formatted.split_once('e').unwrap();
+    let exponent: i32 = exponent.parse().unwrap();
+    let point = if alt && precision == 0 { "." } else { "" };
+    let sign = if exponent < 0 { '-' } else { '+' };
+    format!("{mantissa}{point}e{sign}{:02}", exponent.abs())
+}
+
+/// Formats `value`, which is not negative, like C's `%g`: `%e` for very
+/// large or small values and `%f` otherwise, with `precision` significant
+/// digits and, unless `alt`, no trailing zeros.
+fn format_general(value: f64, precision: usize, alt: bool) -> String {
+    let precision = precision.max(1);
+    let exponent: i32 = if value == 0.0 {
+        0
+    } else {
+        let formatted = format!("{value:.*e}", precision - 1);
+        formatted.split_once('e').unwrap().1.parse().unwrap()
+    };
+    let formatted = if exponent < -4 || exponent >= precision as i32 {
+        format_exp(value, precision - 1, alt)
+    } else {
+        let decimals = (precision as i32 - 1 - exponent) as usize;
+        let point = if alt && decimals == 0 { "." } else { "" };
+        format!("{value:.decimals$}{point}")
+    };
+    if alt {
+        return formatted;
+    }
+    let (number, exp) = match formatted.find('e') {
+        Some(i) => formatted.split_at(i),
+        None => (formatted.as_str(), ""),
+    };
+    let number = if number.contains('.') {
+        number.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        number
+    };
+    format!("{number}{exp}")
+}
+
+/// Lays out a formatted value in its field. The sign and prefix stay in
+/// front of any zero padding.
+fn pad(spec: &Spec, sign: &str, prefix: &str, body: &[u8], zero_pad: bool, output: &mut Vec<u8>) {
+    let len = sign.len() + prefix.len() + body.len();
+    let fill = spec.width.saturating_sub(len);
+    if spec.left {
+        output.extend(sign.bytes().chain(prefix.bytes()));
+        output.extend(body);
+        output.extend(std::iter::repeat_n(b' ', fill));
+    } else if spec.zero && zero_pad {
+        output.extend(sign.bytes().chain(prefix.bytes()));
+        output.extend(std::iter::repeat_n(b'0', fill));
+        output.extend(body);
+    } else {
+        output.extend(std::iter::repeat_n(b' ', fill));
+        output.extend(sign.bytes().chain(prefix.bytes()));
+        output.extend(body);
+    }
+}
+
+/// Fills in a format from the arguments, reporting any argument that
+/// doesn't fit its conversion and carrying on
+#[derive(Debug)]
+struct Printer<'a> {
+    arguments: &'a [String],
+    next: usize,
+    ok: bool,
+    output: Vec<u8>,
+}
+
+impl<'a> Printer<'a> {
+    fn new(arguments: &'a [String]) -> Self {
+        Self {
+            arguments,
+            next: 0,
+            ok: true,
+            output: vec![],
+        }
+    }
+
+    fn next_arg(&mut self) -> Option<&'a str> {
+        let arg = self.arguments.get(self.next)?;
+        self.next += 1;
+        Some(arg)
+    }
+
+    fn error(&mut self, arg: &str, message: &str) {
+        eprintln!("'{arg}': {message}");
+        self.ok = false;
+    }
+
+    fn check_used(&mut self, arg: &str, used: usize) {
+        if used == 0 {
+            self.error(arg, "expected a numeric value");
+        } else if used < arg.len() {
+            self.error(arg, "value not completely converted");
+        }
+    }
+
+    /// The next argument as an integer in `min..=max`; a missing one is 0
+    fn int_arg(&mut self, min: i128, max: i128) -> i128 {
+        let Some(arg) = self.next_arg() else {
+            return 0;
+        };
+        let (value, used) = parse_int(arg);
+        if !(min..=max).contains(&value) {
+            self.error(arg, "Numerical result out of range");
+            return value.clamp(min, max);
+        }
+        self.check_used(arg, used);
+        value
+    }
+
+    fn float_arg(&mut self) -> f64 {
+        let Some(arg) = self.next_arg() else {
+            return 0.0;
+        };
+        let (value, used) = parse_float(arg);
+        self.check_used(arg, used);
+        value
+    }
+
+    /// Prints one conversion, returning false when a `%b` argument holds a
+    /// `\c`.
+    fn convert(&mut self, spec: &Spec) -> Result<bool> {
+        let mut output = std::mem::take(&mut self.output);
+        let mut more = true;
+        match spec.conversion {
+            b'd' | b'i' => {
+                let value = self.int_arg(i64::MIN.into(), i64::MAX.into());
+                let sign = if value < 0 {
+                    "-"
+                } else if spec.plus {
+                    "+"
+                } else if spec.space {
+                    " "
+                } else {
+                    ""
+                };
+                let digits = int_digits(spec, value.unsigned_abs().to_string());
+                pad(
+                    spec,
+                    sign,
+                    "",
+                    &digits,
+                    spec.precision.is_none(),
+                    &mut output,
+                );
+            }
+            c @ (b'o' | b'u' | b'x' | b'X') => {
+                // as with strtoumax, a negative number wraps around
+                let value = self.int_arg(-i128::from(u64::MAX), u64::MAX.into());
+                let value = value.rem_euclid(1 << 64);
+                let digits = match c {
+                    b'o' => format!("{value:o}"),
+                    b'u' => value.to_string(),
+                    b'x' => format!("{value:x}"),
+                    _ => format!("{value:X}"),
+                };
+                let mut digits = int_digits(spec, digits);
+                let prefix = match c {
+                    b'o' if spec.alt && !digits.starts_with(b"0") => {
+                        digits.insert(0, b'0');
+                        ""
+                    }
+                    b'x' if spec.alt && value != 0 => "0x",
+                    b'X' if spec.alt && value != 0 => "0X",
+                    _ => "",
+                };
+                pad(
+                    spec,
+                    "",
+                    prefix,
+                    &digits,
+                    spec.precision.is_none(),
+                    &mut output,
+                );
+            }
+            c @ (b'f' | b'F' | b'e' | b'E' | b'g' | b'G') => {
+                let value = self.float_arg();
+                let sign = if value.is_sign_negative() {
+                    "-"
+                } else if spec.plus {
+                    "+"
+                } else if spec.space {
+                    " "
+                } else {
+                    ""
+                };
+                let precision = spec.precision.unwrap_or(6);
+                let body = if !value.is_finite() {
+                    if value.is_nan() { "nan" } else { "inf" }.to_string()
+                } else {
+                    match c.to_ascii_lowercase() {
+                        b'f' => {
+                            let point = if spec.alt && precision == 0 { "." } else { "" };
+                            format!("{:.precision$}{point}", value.abs())
+                        }
+                        b'e' => format_exp(value.abs(), precision, spec.alt),
+                        _ => format_general(value.abs(), precision, spec.alt),
+                    }
+                };
+                let body = if c.is_ascii_uppercase() {
+                    body.to_ascii_uppercase()
+                } else {
+                    body
+                };
+                pad(
+                    spec,
+                    sign,
+                    "",
+                    body.as_bytes(),
+                    value.is_finite(),
+                    &mut output,
+                );
+            }
+            b'c' => {
+                // like GNU printf, the first byte, or a NUL for nothing
+                let arg = self.next_arg().unwrap_or_default();
+                let byte = arg.bytes().next().unwrap_or(0);
+                pad(spec, "", "", &[byte], false, &mut output);
+            }
+            b's' => {
+                let arg = self.next_arg().unwrap_or_default().as_bytes();
+                let len = spec.precision.map_or(arg.len(), |p| p.min(arg.len()));
+                pad(spec, "", "", &arg[..len], false, &mut output);
+            }
+            _ => {
+                let arg = self.next_arg().unwrap_or_default().as_bytes();
+                let (mut text, stop) = expand_escapes(arg)?;
+                text.truncate(spec.precision.unwrap_or(text.len()));
+                pad(spec, "", "", &text, false, &mut output);
+                more = !stop;
+            }
+        }
+        self.output = output;
+        Ok(more)
+    }
+
+    /// Prints the format once, returning false if a `\c` ended the output.
+    fn print(&mut self, pieces: &[Piece]) -> Result<bool> {
+        for piece in pieces {
+            match piece {
+                Piece::Text(text) => self.output.extend(text),
+                Piece::Spec(spec) => {
+                    if !self.convert(spec)? {
+                        return Ok(false);
+                    }
+                }
+                Piece::Stop => return Ok(false),
+            }
+        }
+        Ok(true)
+    }
+}
+
+/// Zero-fills `digits` to an integer conversion's precision. A precision
+/// of 0 prints nothing at all for a 0.
+fn int_digits(spec: &Spec, digits: String) -> Vec<u8> {
+    match spec.precision {
+        Some(0) if digits == "0" => vec![],
+        Some(precision) => format!("{digits:0>precision$}").into_bytes(),
+        None => digits.into_bytes(),
+    }
+}
+
+/// Expands the escapes in a `%b` argument, as `echo -e` does. The flag is
+/// set when a `\c` cut the text short.
+fn expand_escapes(arg: &[u8]) -> Result<(Vec<u8>, bool)> {
+    let mut text = vec![];
+    let mut i = 0;
+    while i < arg.len() {
+        if arg[i] != b'\\' {
+            text.push(arg[i]);
+            i += 1;
+            continue;
+        }
+        let (bytes, len) = escape(&arg[i + 1..], true)?;
+        match bytes {
+            Some(bytes) => text.extend(bytes),
+            None => return Ok((text, true)),
+        }
+        i += 1 + len;
+    }
+    Ok((text, false))
+}
+
+/// Fills in `format`, reusing it for as long as arguments remain.
+/// Returns the output and whether every argument converted cleanly.
+fn printf(format: &str, arguments: &[String]) -> Result<(Vec<u8>, bool)> {
+    let pieces = parse_format(format.as_bytes())?;
+    let mut printer = Printer::new(arguments);
+    loop {
+        let start = printer.next;
+        if !printer.print(&pieces)? || printer.next == start {
+            break;
+        }
+        if printer.next >= arguments.len() {
+            break;
+        }
+    }
+    if printer.next == 0
+        && let Some(arg) = arguments.first()
+    {
+        eprintln!("warning: ignoring excess arguments, starting with '{arg}'");
+    }
+    Ok((printer.output, printer.ok))
+}
+
+fn run(args: Args) -> Result<bool> {
+    let (output, ok) = printf(&args.format, &args.arguments)?;
+    let mut stdout = io::stdout().lock();
+    stdout.write_all(&output)?;
+    stdout.flush()?;
+    Ok(ok)
+}
+
+fn main() -> ExitCode {
+    match run(parse_args()) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Ok(false) => ExitCode::FAILURE,
+        Ok(true) => ExitCode::SUCCESS,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Piece, Spec, expand_escapes, format_general, parse_float, parse_format, parse_int, printf,
+    };
+    use proptest::prelude::*;
+
+    fn print(format: &str, arguments: &[&str]) -> (String, bool) {
+        let arguments: Vec<String> = arguments.iter().map(|arg| arg.to_string()).collect();
+        let (output, ok) = printf(format, &arguments).unwrap();
+        (String::from_utf8_lossy(&output).into_owned(), ok)
+    }
+
+    #[test]
+    fn test_parse_format() {
+        assert_eq!(
+            parse_format(b"a%%b\\t%-08.3fc\\cd").unwrap(),
+            [
+                Piece::Text(b"a%b\t".to_vec()),
+                Piece::Spec(Spec {
+                    left: true,
+                    zero: true,
+                    width: 8,
+                    precision: Some(3),
+                    conversion: b'f',
+                    ..Spec::default()
+                }),
+                Piece::Text(b"c".to_vec()),
+                Piece::Stop,
+            ]
+        );
+        assert_eq!(
+            parse_format(b"%5%").unwrap_err().to_string(),
+            "%5%: invalid conversion specification"
+        );
+        assert_eq!(
+            parse_format(b"x%").unwrap_err().to_string(),
+            "%: invalid conversion specification"
+        );
+        assert_eq!(
+            parse_format(b"%9999999999d").unwrap_err().to_string(),
+            "9999999999: invalid field width or precision"
+        );
+        assert_eq!(
+            parse_format(b"\\xz").unwrap_err().to_string(),
+            "missing hexadecimal number in escape"
+        );
+    }
+
+    #[test]
+    fn test_escapes() {
+        // octal is \NNN in a format, and \0NNN too in a %b argument
+        assert_eq!(
+            parse_format(b"\\101\\0101").unwrap(),
+            [Piece::Text(b"A\x081".to_vec())]
+        );
+        assert_eq!(
+            expand_escapes(b"\\101\\0101").unwrap(),
+            (b"AA".to_vec(), false)
+        );
+        assert_eq!(
+            expand_escapes(b"\\x41\\q\\").unwrap(),
+            (b"A\\q\\".to_vec(), false)
+        );
+        assert_eq!(expand_escapes(b"a\\cb").unwrap(), (b"a".to_vec(), true));
+    }
+
+    #[test]
+    fn test_parse_int() {
+        assert_eq!(parse_int("42"), (42, 2));
+        assert_eq!(parse_int(" -42"), (-42, 4));
+        assert_eq!(parse_int("+0x1F"), (31, 5));
+        assert_eq!(parse_int("010"), (8, 3));
+        assert_eq!(parse_int("0x"), (0, 1));
+        assert_eq!(parse_int("12abc"), (12, 2));
+        assert_eq!(parse_int("'A"), (65, 2));
+        assert_eq!(parse_int("abc"), (0, 0));
+        assert_eq!(parse_int(""), (0, 0));
+    }
+
+    #[test]
+    fn test_parse_float() {
+        assert_eq!(parse_float("1.5"), (1.5, 3));
+        assert_eq!(parse_float("-.5e1x"), (-5.0, 5));
+        assert_eq!(parse_float("'a"), (97.0, 2));
+        assert_eq!(parse_float("x"), (0.0, 0));
+    }
+
+    #[test]
+    fn test_format_general() {
+        assert_eq!(format_general(0.0001, 6, false), "0.0001");
+        assert_eq!(format_general(0.00001, 6, false), "1e-05");
+        assert_eq!(format_general(123456789.0, 6, false), "1.23457e+08");
+        assert_eq!(format_general(100000.0, 6, false), "100000");
+        assert_eq!(format_general(1.5, 6, true), "1.50000");
+        assert_eq!(format_general(0.0, 6, false), "0");
+    }
+
+    #[test]
+    fn test_printf() {
+        assert_eq!(
+            print("%d-%s\\n", &["1", "a", "2"]),
+            ("1-a\n2-\n".to_string(), true)
+        );
+        assert_eq!(
+            print("%5.2f|%-4d|%04x\\n", &["3.14159", "7", "255"]),
+            (" 3.14|7   |00ff\n".to_string(), true)
+        );
+        assert_eq!(
+            print("%d\\n", &["12abc", "x"]),
+            ("12\n0\n".to_string(), false)
+        );
+        assert_eq!(print("%s\\c%s", &["a", "b"]), ("a".to_string(), true));
+        assert_eq!(print("%b%s", &["x\\cy", "z"]), ("x".to_string(), true));
+        assert_eq!(
+            print("%x %u", &["-1", "99999999999999999999"]),
+            ("ffffffffffffffff 18446744073709551615".to_string(), false)
+        );
+        // a format without conversions is printed just once
+        assert_eq!(print("hi\\n", &["a", "b"]), ("hi\n".to_string(), true));
+    }
+
+    proptest! {
+        #[test]
+        fn plain_text_is_unchanged(text in "[^%\\\\]*") {
+            prop_assert_eq!(print(&text, &[]), (text, true));
+        }
+
+        #[test]
+        fn random_formats_never_panic(
+            format in r"([^%\\]|%[-+ #0]{0,3}[0-9]{0,3}(\.[0-9]{0,3})?[a-zA-Z%]|\\.){0,10}",
+            arguments in prop::collection::vec(".{0,6}", 0..4),
+        ) {
+            let _ = printf(&format, &arguments);
+        }
+
+        #[test]
+        fn ints_match_std(n: i64, width in 0usize..30, precision in 1usize..25) {
+            prop_assert_eq!(print(&format!("%{width}d"), &[&n.to_string()]).0, format!("{n:width$}"));
+            prop_assert_eq!(print(&format!("%-{width}d|"), &[&n.to_string()]).0, format!("{n:<width$}|"));
+            prop_assert_eq!(
+                print(&format!("%.{precision}d"), &[&n.to_string()]).0,
+                format!("{}{:0precision$}", if n < 0 { "-" } else { "" }, n.unsigned_abs())
+            );
+        }
+
+        #[test]
+        fn unsigned_match_std(n: u64, width in 0usize..30) {
+            let arg = n.to_string();
+            prop_assert_eq!(print(&format!("%0{width}u"), &[&arg]).0, format!("{n:0width$}"));
+            prop_assert_eq!(print("%x %X %o", &[&arg, &arg, &arg]).0, format!("{n:x} {n:X} {n:o}"));
+            prop_assert_eq!(print("%#x", &[&arg]).0, if n == 0 { "0".to_string() } else { format!("{n:#x}") });
+        }
+
+        #[test]
+        fn floats_match_std(x in -1e12f64..1e12, precision in 0usize..12, width in 0usize..30) {
+            prop_assert_eq!(print(&format!("%{width}.{precision}f"), &[&x.to_string()]).0, format!("{x:width$.precision$}"));
+        }
+
+        #[test]
+        fn strings_match_std(s in "[a-z ]{0,20}", width in 0usize..30, precision in 0usize..30) {
+            prop_assert_eq!(print(&format!("%{width}.{precision}s"), &[&s]).0, format!("{s:>width$.precision$}"));
+            prop_assert_eq!(print(&format!("%-{width}s|"), &[&s]).0, format!("{s:<width$}|"));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+
+const PRG: &str = "printr";
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_conversion() -> Result<()> {
+    // nothing is printed when the format itself is wrong
+    for (format, message) in [
+        ("a%z", "%z: invalid conversion specification"),
+        ("a%", "%: invalid conversion specification"),
+        ("%5%", "%5%: invalid conversion specification"),
+        (r"\xg", "missing hexadecimal number in escape"),
+    ] {
+        Command::cargo_bin(PRG)?
+            .args([format, "1"])
+            .assert()
+            .failure()
+            .stdout("")
+            .stderr(format!("{message}\n"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn bad_arguments() -> Result<()> {
+    // a bad argument is reported, and printing carries on
+    Command::cargo_bin(PRG)?
+        .args([r"%d %f %x\n", "abc", "1.5x", "99999999999999999999"])
+        .assert()
+        .failure()
+        .stdout("0 1.500000 ffffffffffffffff\n")
+        .stderr(
+            "'abc': expected a numeric value\n\
+             '1.5x': value not completely converted\n\
+             '99999999999999999999': Numerical result out of range\n",
+        );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn integers() -> Result<()> {
+    run(
+        &[
+            r"%d|%i|%5d|%-5d|%05d|%+d|% d|%.3d|%d|%d\n",
+            "42",
+            "-7",
+            "42",
+            "42",
+            "-42",
+            "5",
+            "5",
+            "7",
+            "0x1F",
+            "'A",
+        ],
+        "tests/expected/integers.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn unsigned() -> Result<()> {
+    run(
+        &[
+            r"%u %o %x %X %#o %#x %#X %u\n",
+            "42",
+            "8",
+            "255",
+            "255",
+            "8",
+            "255",
+            "255",
+            "-1",
+        ],
+        "tests/expected/unsigned.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn floats() -> Result<()> {
+    run(
+        &[
+            r"%f|%.2f|%10.3f|%-10.1f|%e|%.2E|%g|%g|%G|%+.1f\n",
+            "3.14159",
+            "2.5",
+            "-1.5",
+            "1",
+            "12345.678",
+            "0.000123",
+            "0.0001",
+            "123456789",
+            "1e-10",
+            "2",
+        ],
+        "tests/expected/floats.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn strings() -> Result<()> {
+    run(
+        &[
+            r"[%s][%5s][%-5s][%.2s][%c][%3c]\n",
+            "hello",
+            "ab",
+            "ab",
+            "abcdef",
+            "xyz",
+            "q",
+        ],
+        "tests/expected/strings.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn escapes() -> Result<()> {
+    run(
+        &[r#"tab\there\nbell\a oct\101 hex\x42 quote\" back\\ %%\n"#],
+        "tests/expected/escapes.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn escaped_arguments() -> Result<()> {
+    run(&[r"%b|%b\n", r"a\tb\0101", r"c\nd"], "tests/expected/b.txt")
+}
+
+// --------------------------------------------------
+#[test]
+fn stop_output() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([r"%s\c%s", "a", "b"])
+        .assert()
+        .success()
+        .stdout("a");
+    Command::cargo_bin(PRG)?
+        .args(["%b%s", r"x\cy", "z"])
+        .assert()
+        .success()
+        .stdout("x");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn reuses_format() -> Result<()> {
+    // missing arguments in the last round count as empty or zero
+    run(
+        &[r"%s=%d\n", "a", "1", "b", "2", "c"],
+        "tests/expected/cycle.txt",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn negative_arguments() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([r"%d %s\n", "-5", "-x"])
+        .assert()
+        .success()
+        .stdout("-5 -x\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn excess_arguments() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([r"hi\n", "a", "b"])
+        .assert()
+        .success()
+        .stdout("hi\n")
+        .stderr("warning: ignoring excess arguments, starting with 'a'\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,282 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use cli_utils::parse_args;
+use std::env;
+use std::fs;
+use std::io::{self, BufWriter, ErrorKind, Write};
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitCode;
+
+// As on Linux, a path may go through at most 40 symbolic links
+const MAX_LINKS: usize = 40;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", required = true)]
+    files: Vec<String>,
+
+    /// Print paths relative to DIR
+    #[arg(long, value_name = "DIR")]
+    relative_to: Option<String>,
+
+    /// Print paths below DIR relative to it, and others in full
+    #[arg(long, value_name = "DIR")]
+    relative_base: Option<String>,
+
+    /// Let any component of a path be missing, not just the last
+    #[arg(short('m'), long)]
+    canonicalize_missing: bool,
+
+    /// Print nothing about paths that can't be resolved
+    #[arg(short('q'), long)]
+    quiet: bool,
+
+    /// End each path with NUL rather than newline
+    #[arg(short('z'), long)]
+    zero: bool,
+}
+
+/// Resolves `path` to an absolute path with no `.`, `..` or symbolic links
+/// in it. As with GNU `realpath`, the last component may be missing, but
+/// the directory it would be in must exist; with `missing`, any part of it
+/// may be missing.
+fn resolve(path: &Path, missing: bool) -> io::Result<PathBuf> {
+    if missing {
+        return resolve_missing(path);
+    }
+    match fs::canonicalize(path) {
+        Err(e) if e.kind() == ErrorKind::NotFound => {
+            let Some(Component::Normal(name)) = path.components().next_back() else {
+                return Err(e);
+            };
+            let dir = match path.parent() {
+                Some(dir) if dir != Path::new("") => dir,
+                _ => Path::new("."),
+            };
+            let dir = fs::canonicalize(dir).map_err(|_| e)?;
+            match fs::read_link(path) {
+                // a dangling link stands for the file it would lead to
+                Ok(target) => resolve(&dir.join(target), false),
+                Err(_) => Ok(dir.join(name)),
+            }
+        }
+        result => result,
+    }
+}
+
+/// Resolves `path` one component at a time, following the symbolic links
+/// that exist and taking the components that don't as they stand, so that
+/// a `..` after a link goes up from where the link leads.
+fn resolve_missing(path: &Path) -> io::Result<PathBuf> {
+    let mut rest = env::current_dir()?.join(path);
+    let mut resolved = PathBuf::new();
+    let mut links = 0;
+    loop {
+        let mut components = rest.components();
+        let Some(component) = components.next() else {
+            break;
+        };
+        let remaining = components.as_path().to_path_buf();
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::Normal(name) => {
+                let next = resolved.join(name);
+                if fs::symlink_metadata(&next).is_ok_and(|m| m.file_type().is_symlink()) {
+                    links += 1;
+                    if links > MAX_LINKS {
+                        return Err(io::Error::other("Too many levels of symbolic links"));
+                    }
+                    // a relative target starts from the directory of the
+                    // link, which is what has been resolved so far
+                    rest = fs::read_link(&next)?.join(remaining);
+                    continue;
+                }
+                resolved = next;
+            }
+            Component::RootDir | Component::Prefix(_) => resolved.push(component),
+        }
+        rest = remaining;
+    }
+    Ok(resolved)
+}
+
+/// Returns the path that leads from `base` to `path`, both of them
+/// resolved: up out of `base` to the directory they share, and then down
+/// into `path`.
+fn relative(path: &Path, base: &Path) -> PathBuf {
+    let mut path_parts = path.components().peekable();
+    let mut base_parts = base.components().peekable();
+    while path_parts.peek().is_some() && path_parts.peek() == base_parts.peek() {
+        path_parts.next();
+        base_parts.next();
+    }
+    let relative: PathBuf = base_parts
+        .map(|_| Component::ParentDir)
+        .chain(path_parts)
+        .collect();
+    if relative.as_os_str().is_empty() {
+        PathBuf::from(".")
+    } else {
+        relative
+    }
+}
+
+/// Returns `path` as it is printed: relative to `to`, unless there is a
+/// `base` that it isn't below.
+fn display(path: &Path, to: Option<&Path>, base: Option<&Path>) -> PathBuf {
+    match to {
+        Some(to) if base.is_none_or(|base| path.starts_with(base)) => relative(path, to),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// Resolves a directory given to `--relative-to` or `--relative-base`.
+fn resolve_dir(dir: &str, missing: bool) -> Result<PathBuf> {
+    let resolved = resolve(Path::new(dir), missing).map_err(|e| anyhow!("{dir}: {e}"))?;
+    if !missing && !resolved.is_dir() {
+        bail!("{dir}: Not a directory");
+    }
+    Ok(resolved)
+}
+
+/// Prints each FILE resolved, and returns whether they all could be.
+fn run(args: Args) -> Result<bool> {
+    let missing = args.canonicalize_missing;
+    let mut to = args
+        .relative_to
+        .as_deref()
+        .map(|dir| resolve_dir(dir, missing))
+        .transpose()?;
+    let base = args
+        .relative_base
+        .as_deref()
+        .map(|dir| resolve_dir(dir, missing))
+        .transpose()?;
+    // as in GNU realpath, paths are only relative to a DIR of --relative-to
+    // that is below that of --relative-base, and otherwise all are in full
+    match (&to, &base) {
+        (None, Some(base)) => to = Some(base.clone()),
+        (Some(dir), Some(base)) if !dir.starts_with(base) => to = None,
+        _ => {}
+    }
+
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut resolved_all = true;
+    for file in &args.files {
+        match resolve(Path::new(file), missing) {
+            Err(e) => {
+                if !args.quiet {
+                    stdout.flush()?;
+                    eprintln!("{file}: {e}");
+                }
+                resolved_all = false;
+            }
+            Ok(path) => {
+                let path = display(&path, to.as_deref(), base.as_deref());
+                stdout.write_all(path.as_os_str().as_encoded_bytes())?;
+                stdout.write_all(if args.zero { b"\0" } else { b"\n" })?;
+            }
+        }
+    }
+    stdout.flush()?;
+    Ok(resolved_all)
+}
+
+fn main() -> ExitCode {
+    match run(parse_args()) {
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{display, relative, resolve, resolve_missing};
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn test_relative() {
+        let relative = |path: &str, base: &str| relative(Path::new(path), Path::new(base));
+        assert_eq!(relative("/a/b/c", "/a/b"), Path::new("c"));
+        assert_eq!(relative("/a/b", "/a/b/c/d"), Path::new("../.."));
+        assert_eq!(relative("/a/b/c", "/a/x/y"), Path::new("../../b/c"));
+        assert_eq!(relative("/a/b", "/a/b"), Path::new("."));
+        assert_eq!(relative("/", "/a/b"), Path::new("../.."));
+        assert_eq!(relative("/a/b", "/"), Path::new("a/b"));
+        // components are compared whole
+        assert_eq!(relative("/a/bc", "/a/b"), Path::new("../bc"));
+    }
+
+    #[test]
+    fn test_display() {
+        let path = Path::new("/a/b/c");
+        let display = |to: Option<&str>, base: Option<&str>| {
+            display(path, to.map(Path::new), base.map(Path::new))
+        };
+        assert_eq!(display(None, None), path);
+        assert_eq!(display(Some("/a/x"), None), Path::new("../b/c"));
+        assert_eq!(display(Some("/a/x"), Some("/a")), Path::new("../b/c"));
+        assert_eq!(display(Some("/a/x"), Some("/x")), path);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_resolve() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let dir = fs::canonicalize(dir.path()).unwrap();
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        symlink("a/b", dir.join("one")).unwrap();
+        symlink("one", dir.join("two")).unwrap();
+        symlink(dir.join("two"), dir.join("a/three")).unwrap();
+        symlink("nowhere/x", dir.join("dangling")).unwrap();
+        symlink("a/new", dir.join("new")).unwrap();
+        symlink("loop", dir.join("loop")).unwrap();
+
+        for path in ["a/b", "one", "two", "a/three", "a/../two/.", "one/../b"] {
+            assert_eq!(
+                resolve(&dir.join(path), false).unwrap(),
+                dir.join("a/b"),
+                "{path}"
+            );
+        }
+        // only the last component may be missing
+        assert_eq!(
+            resolve(&dir.join("two/new"), false).unwrap(),
+            dir.join("a/b/new")
+        );
+        assert_eq!(resolve(&dir.join("new"), false).unwrap(), dir.join("a/new"));
+        assert!(resolve(&dir.join("none/new"), false).is_err());
+        assert!(resolve(&dir.join("dangling"), false).is_err());
+        assert!(resolve(&dir.join("loop"), false).is_err());
+
+        // with missing, links are followed wherever they lead
+        assert_eq!(resolve_missing(&dir.join("two")).unwrap(), dir.join("a/b"));
+        assert_eq!(
+            resolve_missing(&dir.join("none/../none/x")).unwrap(),
+            dir.join("none/x")
+        );
+        assert_eq!(
+            resolve_missing(&dir.join("two/../x")).unwrap(),
+            dir.join("a/x")
+        );
+        assert_eq!(
+            resolve_missing(&dir.join("dangling/y/../z")).unwrap(),
+            dir.join("nowhere/x/z")
+        );
+        assert_eq!(
+            resolve_missing(&dir.join("loop")).unwrap_err().to_string(),
+            "Too many levels of symbolic links"
+        );
+    }
+}
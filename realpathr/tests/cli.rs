@@ -0,0 +1,208 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use std::fs;
+use std::path::PathBuf;
+use tempfile::TempDir;
+
+const PRG: &str = "realpathr";
+
+// --------------------------------------------------
+/// A tree with a chain of links: `two` leads to `one`, which leads to
+/// `a/b`, and `a/b/up` leads back out to `a`
+fn make_tree() -> Result<(TempDir, PathBuf)> {
+    let dir = tempfile::tempdir()?;
+    let root = fs::canonicalize(dir.path())?;
+    fs::create_dir_all(root.join("a/b"))?;
+    fs::write(root.join("a/b/file.txt"), "")?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::symlink;
+        symlink("a/b", root.join("one"))?;
+        symlink("one", root.join("two"))?;
+        symlink("..", root.join("a/b/up"))?;
+    }
+    Ok((dir, root))
+}
+
+// --------------------------------------------------
+fn run(root: &PathBuf, args: &[&str], expected: &str) -> Result<()> {
+    let expected = expected.replace("ROOT", &root.display().to_string());
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .current_dir(root)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn relative_paths() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    run(
+        &root,
+        &[".", "a", "a/b/file.txt", "./a/./b/"],
+        "ROOT\nROOT/a\nROOT/a/b/file.txt\nROOT/a/b\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn parent_components() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    run(
+        &root,
+        &["a/b/..", "a/../a/b/../b/file.txt"],
+        "ROOT/a\nROOT/a/b/file.txt\n",
+    )
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn symlinks() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    // each link in the chain is followed, and .. goes up from where a link
+    // leads rather than from the link
+    run(
+        &root,
+        &[
+            "two",
+            "two/file.txt",
+            "two/..",
+            "one/up/b/up",
+            "a/b/up/../one",
+        ],
+        "ROOT/a/b\nROOT/a/b/file.txt\nROOT/a\nROOT/a\nROOT/a/b\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn missing_last_component() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    run(&root, &["a/new"], "ROOT/a/new\n")?;
+    Command::cargo_bin(PRG)?
+        .args(["a", "new/new", "a/b"])
+        .current_dir(&root)
+        .assert()
+        .failure()
+        .stdout(format!("{0}/a\n{0}/a/b\n", root.display()))
+        .stderr(predicate::str::starts_with("new/new: "));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn canonicalize_missing() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    for flag in ["-m", "--canonicalize-missing"] {
+        run(
+            &root,
+            &[flag, "new/new", "a/x/../y", "a/b/file.txt/z"],
+            "ROOT/new/new\nROOT/a/y\nROOT/a/b/file.txt/z\n",
+        )?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn quiet() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    for flag in ["-q", "--quiet"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, "new/new", "a"])
+            .current_dir(&root)
+            .assert()
+            .failure()
+            .stdout(format!("{}/a\n", root.display()))
+            .stderr("");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn zero() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    for flag in ["-z", "--zero"] {
+        run(&root, &[flag, "a", "a/b"], "ROOT/a\0ROOT/a/b\0")?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn relative_to() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    run(
+        &root,
+        &["--relative-to=a/b", ".", "a", "a/b", "a/b/file.txt"],
+        "../..\n..\n.\nfile.txt\n",
+    )?;
+    #[cfg(unix)]
+    run(&root, &["--relative-to", "two", "one/up"], "..\n")?;
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn relative_base() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    // paths below the base are relative to it
+    run(
+        &root,
+        &["--relative-base=a", "a/b/file.txt", "a", "."],
+        "b/file.txt\n.\nROOT\n",
+    )?;
+    // and to --relative-to, when that is below the base too
+    run(
+        &root,
+        &["--relative-base=.", "--relative-to=a/b", "a", "/"],
+        "..\n/\n",
+    )?;
+    // otherwise every path is in full
+    run(
+        &root,
+        &["--relative-base=a/b", "--relative-to=a", "a/b/file.txt"],
+        "ROOT/a/b/file.txt\n",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_relative_to() -> Result<()> {
+    let (_dir, root) = make_tree()?;
+    Command::cargo_bin(PRG)?
+        .args(["--relative-to=a/b/file.txt", "a"])
+        .current_dir(&root)
+        .assert()
+        .failure()
+        .stdout("")
+        .stderr("a/b/file.txt: Not a directory\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
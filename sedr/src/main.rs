@@ -0,0 +1,750 @@
+use anyhow::{Result, anyhow, bail};
+use clap::{ArgAction, Parser};
+use cli_utils::{exit_with_error, open, parse_args};
+use regex::{Regex, RegexBuilder};
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufWriter, Write};
+use std::iter;
+use std::path::Path;
+use tempfile::NamedTempFile;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "SCRIPT", required_unless_present_any(["expression", "file"]))]
+    script: Option<String>,
+
+    #[arg(value_name = "FILE")]
+    files: Vec<String>,
+
+    #[arg(short('n'), long, alias("silent"))]
+    quiet: bool,
+
+    #[arg(short('e'), long, value_name = "SCRIPT", action(ArgAction::Append))]
+    expression: Vec<String>,
+
+    #[arg(
+        short('f'),
+        long,
+        value_name = "SCRIPT_FILE",
+        action(ArgAction::Append)
+    )]
+    file: Vec<String>,
+
+    #[arg(short('i'), long)]
+    in_place: bool,
+}
+
+/// Selects a line by its number, as the last line, or by a pattern
+#[derive(Debug)]
+enum Address {
+    Line(usize),
+    Last,
+    Regex(Regex),
+}
+
+impl Address {
+    fn matches(&self, line_num: usize, last: bool, text: &str) -> bool {
+        match self {
+            Address::Line(n) => line_num == *n,
+            Address::Last => last,
+            Address::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Command {
+    Substitute(Substitution),
+    Delete,
+    Print,
+    Quit,
+    LineNumber,
+    Transliterate(HashMap<char, char>),
+}
+
+/// An `s` command. The replacement is a template for `Captures::expand`.
+#[derive(Debug)]
+struct Substitution {
+    regex: Regex,
+    replacement: String,
+    occurrence: usize,
+    global: bool,
+    print: bool,
+}
+
+impl Substitution {
+    /// `text` with the matches replaced, or `None` when there was nothing
+    /// to replace. With `N`, matching starts at the Nth match, and with `g`
+    /// carries on past it.
+    fn apply(&self, text: &str) -> Option<String> {
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        let mut replaced = false;
+        for caps in self.regex.captures_iter(text).skip(self.occurrence - 1) {
+            let whole = caps.get(0)?;
+            result.push_str(&text[last..whole.start()]);
+            caps.expand(&self.replacement, &mut result);
+            last = whole.end();
+            replaced = true;
+            if !self.global {
+                break;
+            }
+        }
+        result.push_str(&text[last..]);
+        replaced.then_some(result)
+    }
+}
+
+/// A command and the lines it applies to. A range is open from a line
+/// that matches `start` until one that matches `end`.
+#[derive(Debug)]
+struct Instruction {
+    start: Option<Address>,
+    end: Option<Address>,
+    command: Command,
+    active: bool,
+}
+
+impl Instruction {
+    fn selects(&mut self, line_num: usize, last: bool, text: &str) -> bool {
+        let Some(start) = &self.start else {
+            return true;
+        };
+        let Some(end) = &self.end else {
+            return start.matches(line_num, last, text);
+        };
+        if self.active {
+            self.active = !end.matches(line_num, last, text);
+            true
+        } else if start.matches(line_num, last, text) {
+            // as in GNU sed, the end is only looked for from the next line,
+            // unless it is a line number that has already gone by
+            self.active = match end {
+                Address::Line(n) => *n > line_num,
+                Address::Last => !last,
+                Address::Regex(_) => true,
+            };
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Parses a script into instructions. Commands are separated by `;` or
+/// newlines, and `#` starts a comment.
+#[derive(Debug)]
+struct ScriptParser {
+    chars: Vec<char>,
+    pos: usize,
+}
+
+impl ScriptParser {
+    fn new(script: &str) -> Self {
+        Self {
+            chars: script.chars().collect(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek();
+        if c.is_some() {
+            self.pos += 1;
+        }
+        c
+    }
+
+    fn skip_blanks(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+    }
+
+    fn parse(&mut self) -> Result<Vec<Instruction>> {
+        let mut instructions = vec![];
+        loop {
+            while matches!(self.peek(), Some(' ' | '\t' | '\n' | ';')) {
+                self.pos += 1;
+            }
+            match self.peek() {
+                None => return Ok(instructions),
+                Some('#') => while !matches!(self.next_char(), None | Some('\n')) {},
+                Some(_) => instructions.push(self.parse_instruction()?),
+            }
+        }
+    }
+
+    fn parse_instruction(&mut self) -> Result<Instruction> {
+        let start = self.parse_address()?;
+        let end = match start {
+            Some(_) if self.peek() == Some(',') => {
+                self.pos += 1;
+                self.skip_blanks();
+                match self.parse_address()? {
+                    Some(end) => Some(end),
+                    None => bail!("unexpected `,'"),
+                }
+            }
+            _ => None,
+        };
+        self.skip_blanks();
+        let command = match self.next_char() {
+            None | Some('\n' | ';') => bail!("missing command"),
+            Some('s') => Command::Substitute(self.parse_substitution()?),
+            Some('y') => Command::Transliterate(self.parse_transliteration()?),
+            Some('d') => Command::Delete,
+            Some('p') => Command::Print,
+            Some('q') if end.is_some() => bail!("command only uses one address"),
+            Some('q') => Command::Quit,
+            Some('=') => Command::LineNumber,
+            Some(name) => bail!("unknown command: `{name}'"),
+        };
+        self.skip_blanks();
+        if !matches!(self.peek(), None | Some(';' | '\n' | '#')) {
+            self.pos += 1;
+            bail!("extra characters after command");
+        }
+        Ok(Instruction {
+            start,
+            end,
+            command,
+            active: false,
+        })
+    }
+
+    fn parse_address(&mut self) -> Result<Option<Address>> {
+        match self.peek() {
+            Some('$') => {
+                self.pos += 1;
+                Ok(Some(Address::Last))
+            }
+            Some(c) if c.is_ascii_digit() => match self.parse_number()? {
+                0 => bail!("invalid usage of line address 0"),
+                n => Ok(Some(Address::Line(n))),
+            },
+            // `\cREGEXc` allows another delimiter than `/`
+            Some(c @ ('/' | '\\')) => {
+                self.pos += 1;
+                let delimiter = match c {
+                    '/' => '/',
+                    _ => match self.next_char() {
+                        None | Some('\n' | '\\') => bail!("unterminated address regex"),
+                        Some(delimiter) => delimiter,
+                    },
+                };
+                let pattern = self.read_delimited(delimiter, "unterminated address regex")?;
+                Ok(Some(Address::Regex(build_regex(
+                    &pattern, delimiter, false,
+                )?)))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<usize> {
+        let mut number: usize = 0;
+        while let Some(digit) = self.peek().and_then(|c| c.to_digit(10)) {
+            self.pos += 1;
+            number = number
+                .checked_mul(10)
+                .and_then(|n| n.checked_add(digit as usize))
+                .ok_or_else(|| anyhow!("number too large"))?;
+        }
+        Ok(number)
+    }
+
+    fn parse_delimiter(&mut self, name: char) -> Result<char> {
+        match self.next_char() {
+            None | Some('\n' | '\\') => bail!("unterminated `{name}' command"),
+            Some(delimiter) => Ok(delimiter),
+        }
+    }
+
+    /// Reads up to the next `delimiter` that isn't escaped. Escapes are left
+    /// for the caller to interpret.
+    fn read_delimited(&mut self, delimiter: char, error: &str) -> Result<String> {
+        let mut text = String::new();
+        loop {
+            match self.next_char() {
+                None | Some('\n') => bail!("{error}"),
+                Some(c) if c == delimiter => return Ok(text),
+                Some('\\') => match self.next_char() {
+                    None => bail!("{error}"),
+                    Some(c) => {
+                        text.push('\\');
+                        text.push(c);
+                    }
+                },
+                Some(c) => text.push(c),
+            }
+        }
+    }
+
+    fn parse_substitution(&mut self) -> Result<Substitution> {
+        let error = "unterminated `s' command";
+        let delimiter = self.parse_delimiter('s')?;
+        let pattern = self.read_delimited(delimiter, error)?;
+        let replacement = self.read_delimited(delimiter, error)?;
+
+        let mut occurrence = None;
+        let mut global = false;
+        let mut insensitive = false;
+        let mut print = false;
+        loop {
+            match self.peek() {
+                Some(c) if c.is_ascii_digit() => {
+                    if occurrence.is_some() {
+                        bail!("multiple number options to `s' command");
+                    }
+                    match self.parse_number()? {
+                        0 => bail!("number option to `s' command may not be zero"),
+                        n => occurrence = Some(n),
+                    }
+                    continue;
+                }
+                Some('g') => global = true,
+                Some('i' | 'I') => insensitive = true,
+                Some('p') => print = true,
+                None | Some(' ' | '\t' | ';' | '\n' | '#') => break,
+                Some(_) => {
+                    self.pos += 1;
+                    bail!("unknown option to `s'");
+                }
+            }
+            self.pos += 1;
+        }
+
+        let regex = build_regex(&pattern, delimiter, insensitive)?;
+        let replacement = parse_replacement(&replacement, regex.captures_len() - 1)?;
+        Ok(Substitution {
+            regex,
+            replacement,
+            occurrence: occurrence.unwrap_or(1),
+            global,
+            print,
+        })
+    }
+
+    fn parse_transliteration(&mut self) -> Result<HashMap<char, char>> {
+        let error = "unterminated `y' command";
+        let delimiter = self.parse_delimiter('y')?;
+        let from = unescape(&self.read_delimited(delimiter, error)?);
+        let to = unescape(&self.read_delimited(delimiter, error)?);
+        if from.len() != to.len() {
+            bail!("strings for `y' command are different lengths");
+        }
+        Ok(from.into_iter().zip(to).collect())
+    }
+}
+
+/// Compiles a pattern from a script, where an escaped delimiter stands for
+/// itself. Patterns use the syntax of the regex crate, which is close to
+/// the extended syntax of `sed -E`.
+fn build_regex(pattern: &str, delimiter: char, insensitive: bool) -> Result<Regex> {
+    let mut translated = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            translated.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some(escaped) if escaped == delimiter => {
+                translated.push_str(&regex::escape(&escaped.to_string()))
+            }
+            Some(escaped) => {
+                translated.push('\\');
+                translated.push(escaped);
+            }
+            None => translated.push('\\'),
+        }
+    }
+    RegexBuilder::new(&translated)
+        .case_insensitive(insensitive)
+        .build()
+        .map_err(|_| anyhow!("invalid regular expression: '{pattern}'"))
+}
+
+/// Turns the replacement of an `s` command into a template for
+/// `Captures::expand`: `&` and `\0` stand for the whole match, `\1` to `\9`
+/// for groups, and a backslash makes anything else literal.
+fn parse_replacement(replacement: &str, groups: usize) -> Result<String> {
+    let mut template = String::with_capacity(replacement.len());
+    let mut chars = replacement.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '&' => template.push_str("${0}"),
+            '$' => template.push_str("$$"),
+            '\\' => match chars.next() {
+                Some(digit @ '0'..='9') => {
+                    let group = digit as usize - '0' as usize;
+                    if group > groups {
+                        bail!("invalid reference \\{group} on `s' command's RHS");
+                    }
+                    template.push_str(&format!("${{{group}}}"));
+                }
+                Some('n') => template.push('\n'),
+                Some('$') => template.push_str("$$"),
+                Some(c) => template.push(c),
+                None => template.push('\\'),
+            },
+            c => template.push(c),
+        }
+    }
+    Ok(template)
+}
+
+/// The characters of a `y` string, where `\n` is a newline and a backslash
+/// makes anything else literal
+fn unescape(text: &str) -> Vec<char> {
+    let mut chars = text.chars();
+    let mut unescaped = vec![];
+    while let Some(c) = chars.next() {
+        unescaped.push(match c {
+            '\\' => match chars.next() {
+                Some('n') => '\n',
+                Some(c) => c,
+                None => '\\',
+            },
+            c => c,
+        });
+    }
+    unescaped
+}
+
+fn parse_script(script: &str, source: &str) -> Result<Vec<Instruction>> {
+    let mut parser = ScriptParser::new(script);
+    parser
+        .parse()
+        .map_err(|e| anyhow!("{source}, char {}: {e}", parser.pos))
+}
+
+/// Writes lines out. As in GNU sed, a last line without a newline is
+/// written without one, unless more output comes after it.
+#[derive(Debug)]
+struct Output<W: Write> {
+    writer: W,
+    missing_newline: bool,
+}
+
+impl<W: Write> Output<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer,
+            missing_newline: false,
+        }
+    }
+
+    fn write_line(&mut self, text: &str, newline: bool) -> io::Result<()> {
+        if self.missing_newline {
+            self.writer.write_all(b"\n")?;
+        }
+        self.writer.write_all(text.as_bytes())?;
+        if newline {
+            self.writer.write_all(b"\n")?;
+        }
+        self.missing_newline = !newline;
+        Ok(())
+    }
+}
+
+/// Runs a script over lines of input, a cycle at a time: each line is put
+/// in the pattern space, the instructions that select it change or print
+/// it, and then it is printed unless `-n` was given.
+#[derive(Debug)]
+struct Editor {
+    instructions: Vec<Instruction>,
+    quiet: bool,
+    line_num: usize,
+}
+
+impl Editor {
+    fn new(instructions: Vec<Instruction>, quiet: bool) -> Self {
+        Self {
+            instructions,
+            quiet,
+            line_num: 0,
+        }
+    }
+
+    /// Starts again from line 1 with every range closed
+    fn reset(&mut self) {
+        self.line_num = 0;
+        for instruction in &mut self.instructions {
+            instruction.active = false;
+        }
+    }
+
+    /// Edits every line, returning whether `q` stopped the editing early
+    fn edit(
+        &mut self,
+        lines: impl Iterator<Item = io::Result<String>>,
+        output: &mut Output<impl Write>,
+    ) -> Result<bool> {
+        let mut lines = lines.peekable();
+        while let Some(line) = lines.next() {
+            let line = line?;
+            self.line_num += 1;
+            let line_num = self.line_num;
+            let last = lines.peek().is_none();
+            let newline = line.ends_with('\n');
+            let mut text = line.strip_suffix('\n').unwrap_or(&line).to_string();
+
+            let mut deleted = false;
+            let mut quit = false;
+            for instruction in &mut self.instructions {
+                if !instruction.selects(line_num, last, &text) {
+                    continue;
+                }
+                match &instruction.command {
+                    Command::Substitute(substitution) => {
+                        if let Some(replaced) = substitution.apply(&text) {
+                            text = replaced;
+                            if substitution.print {
+                                output.write_line(&text, newline)?;
+                            }
+                        }
+                    }
+                    Command::Delete => {
+                        deleted = true;
+                        break;
+                    }
+                    Command::Print => output.write_line(&text, newline)?,
+                    Command::Quit => {
+                        quit = true;
+                        break;
+                    }
+                    Command::LineNumber => output.write_line(&line_num.to_string(), true)?,
+                    Command::Transliterate(map) => {
+                        text = text.chars().map(|c| *map.get(&c).unwrap_or(&c)).collect();
+                    }
+                }
+            }
+            if !deleted && !self.quiet {
+                output.write_line(&text, newline)?;
+            }
+            if quit {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+}
+
+/// The lines of `file`, each with its newline
+fn read_lines(mut file: Box<dyn BufRead>) -> impl Iterator<Item = io::Result<String>> {
+    iter::from_fn(move || {
+        let mut line = String::new();
+        match file.read_line(&mut line) {
+            Ok(0) => None,
+            Ok(_) => Some(Ok(line)),
+            Err(e) => Some(Err(e)),
+        }
+    })
+}
+
+/// Puts the finished `temp_file` in place of `filename`, keeping the
+/// permissions of the original. Renaming over a file isn't atomic on
+/// Windows, so there the output is copied over the original instead.
+fn replace_file(temp_file: NamedTempFile, filename: &str) -> Result<()> {
+    let permissions = fs::metadata(filename)?.permissions();
+    fs::set_permissions(temp_file.path(), permissions)?;
+    #[cfg(not(windows))]
+    temp_file.persist(filename)?;
+    #[cfg(windows)]
+    io::copy(&mut temp_file.reopen()?, &mut fs::File::create(filename)?)?;
+    Ok(())
+}
+
+fn run(args: Args) -> Result<()> {
+    // with -e or -f, every positional argument is a file to edit
+    let (instructions, mut files) = if args.expression.is_empty() && args.file.is_empty() {
+        let script = args.script.unwrap_or_default();
+        (parse_script(&script, "-e expression #1")?, args.files)
+    } else {
+        let mut instructions = vec![];
+        for (i, script) in args.expression.iter().enumerate() {
+            instructions.extend(parse_script(script, &format!("-e expression #{}", i + 1))?);
+        }
+        for filename in &args.file {
+            let script = fs::read_to_string(filename).map_err(|e| anyhow!("{filename}: {e}"))?;
+            instructions.extend(parse_script(&script, &format!("file {filename}"))?);
+        }
+        let files = args.script.into_iter().chain(args.files).collect();
+        (instructions, files)
+    };
+    let mut editor = Editor::new(instructions, args.quiet);
+
+    if !args.in_place {
+        if files.is_empty() {
+            files.push("-".to_string());
+        }
+        // the files are edited as one stream, so `$` is the very last line
+        let lines = files
+            .iter()
+            .filter_map(|filename| match open(filename) {
+                Err(e) => {
+                    eprintln!("{filename}: {e}");
+                    None
+                }
+                Ok(file) => Some(read_lines(file)),
+            })
+            .flatten();
+        let mut output = Output::new(BufWriter::new(io::stdout().lock()));
+        editor.edit(lines, &mut output)?;
+        output.writer.flush()?;
+        return Ok(());
+    }
+
+    if files.is_empty() || files.iter().any(|filename| filename == "-") {
+        bail!("--in-place needs a FILE to edit");
+    }
+    // each file is a stream of its own, written beside the original and
+    // only put in its place once done
+    for filename in &files {
+        let file = match open(filename) {
+            Err(e) => {
+                eprintln!("{filename}: {e}");
+                continue;
+            }
+            Ok(file) => file,
+        };
+        let dir = match Path::new(filename).parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        let temp_file = NamedTempFile::new_in(dir)?;
+        let mut output = Output::new(BufWriter::new(temp_file.as_file()));
+        editor.reset();
+        let quit = editor.edit(read_lines(file), &mut output)?;
+        output.writer.flush()?;
+        drop(output);
+        replace_file(temp_file, filename)?;
+        if quit {
+            break;
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Editor, Output, parse_replacement, parse_script};
+
+    fn edit(script: &str, quiet: bool, input: &str) -> String {
+        let mut editor = Editor::new(parse_script(script, "-e").unwrap(), quiet);
+        let lines = input.split_inclusive('\n').map(|line| Ok(line.to_string()));
+        let mut output = Output::new(vec![]);
+        editor.edit(lines, &mut output).unwrap();
+        String::from_utf8(output.writer).unwrap()
+    }
+
+    fn parse_error(script: &str) -> String {
+        parse_script(script, "-e expression #1")
+            .unwrap_err()
+            .to_string()
+    }
+
+    #[test]
+    fn test_parse_replacement() {
+        assert_eq!(parse_replacement(r"[&]", 0).unwrap(), "[${0}]");
+        assert_eq!(parse_replacement(r"\2-\1\0", 2).unwrap(), "${2}-${1}${0}");
+        assert_eq!(parse_replacement(r"\&\\$1\n", 0).unwrap(), "&\\$$1\n");
+        assert_eq!(
+            parse_replacement(r"\1", 0).unwrap_err().to_string(),
+            r"invalid reference \1 on `s' command's RHS"
+        );
+    }
+
+    #[test]
+    fn test_parse_script() {
+        assert_eq!(parse_script("", "-e").unwrap().len(), 0);
+        assert_eq!(parse_script(" p ; # note\n\n2d;", "-e").unwrap().len(), 2);
+        assert_eq!(
+            parse_error("1,"),
+            "-e expression #1, char 2: unexpected `,'"
+        );
+        assert_eq!(
+            parse_error("1"),
+            "-e expression #1, char 1: missing command"
+        );
+        assert_eq!(
+            parse_error("0p"),
+            "-e expression #1, char 1: invalid usage of line address 0"
+        );
+        assert_eq!(
+            parse_error("/a"),
+            "-e expression #1, char 2: unterminated address regex"
+        );
+        assert_eq!(
+            parse_error("s/a/b/gx"),
+            "-e expression #1, char 8: unknown option to `s'"
+        );
+        assert_eq!(
+            parse_error("s/a/b/2g3"),
+            "-e expression #1, char 8: multiple number options to `s' command"
+        );
+        assert_eq!(
+            parse_error("s/a/b/0"),
+            "-e expression #1, char 7: number option to `s' command may not be zero"
+        );
+        assert_eq!(
+            parse_error("dp"),
+            "-e expression #1, char 2: extra characters after command"
+        );
+    }
+
+    #[test]
+    fn test_substitute() {
+        assert_eq!(edit("s/o/0/", false, "foo\n"), "f0o\n");
+        assert_eq!(edit("s/o/0/g", false, "foo\n"), "f00\n");
+        assert_eq!(edit("s/o/0/2", false, "fooo\n"), "fo0o\n");
+        assert_eq!(edit("s/o/0/2g", false, "fooo\n"), "fo00\n");
+        assert_eq!(edit("s/o/0/3", false, "foo\n"), "foo\n");
+        assert_eq!(edit("s/O/0/gI", false, "foo\n"), "f00\n");
+        assert_eq!(edit(r"s/(.)(.)/\2\1/", false, "ab\n"), "ba\n");
+        assert_eq!(edit("s/x*/-/g", false, "abc\n"), "-a-b-c-\n");
+        // an escaped delimiter is matched literally, even a metacharacter
+        assert_eq!(edit(r"s/\//|/", false, "a/b\n"), "a|b\n");
+        assert_eq!(edit(r"s.\..-.", false, "ab.c\n"), "ab-c\n");
+        assert_eq!(edit("s/o/0/p", true, "foo\nbar\n"), "f0o\n");
+    }
+
+    #[test]
+    fn test_addresses() {
+        let input = "1\n2\n3\n4\n5\n";
+        assert_eq!(edit("2d", false, input), "1\n3\n4\n5\n");
+        assert_eq!(edit("$d", false, input), "1\n2\n3\n4\n");
+        assert_eq!(edit("2,4d", false, input), "1\n5\n");
+        assert_eq!(edit("4,2d", false, input), "1\n2\n3\n5\n");
+        assert_eq!(edit("3,$d", false, input), "1\n2\n");
+        assert_eq!(edit("/2/,/[0-9]/d", false, input), "1\n4\n5\n");
+        // a range left open runs to the end
+        assert_eq!(edit("/4/,/x/d", false, input), "1\n2\n3\n");
+        assert_eq!(edit(r"\,3,p", true, input), "3\n");
+    }
+
+    #[test]
+    fn test_commands() {
+        assert_eq!(edit("2q", false, "a\nb\nc\n"), "a\nb\n");
+        assert_eq!(edit("2q", true, "a\nb\nc\n"), "");
+        assert_eq!(edit("$=", true, "a\nb\nc\n"), "3\n");
+        assert_eq!(edit("=", false, "a\nb"), "1\na\n2\nb");
+        assert_eq!(edit("y/ab/ba/", false, "abc\n"), "bac\n");
+        assert_eq!(edit(r"y/a\/\n/\\_ /", false, "a/b\n"), "\\_b\n");
+        assert_eq!(edit("p", false, "a\nb"), "a\na\nb\nb");
+        // nothing after d runs for that line
+        assert_eq!(edit("d;p", false, "a\n"), "");
+    }
+}
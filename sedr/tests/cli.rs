@@ -0,0 +1,314 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "sedr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const FOX: &str = "tests/inputs/fox.txt";
+const NAMES: &str = "tests/inputs/names.txt";
+const NUMBERS: &str = "tests/inputs/numbers.txt";
+const UPPER: &str = "tests/inputs/upper.sed";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_script() -> Result<()> {
+    for (script, expected) in [
+        (
+            "s/a/b",
+            "-e expression #1, char 5: unterminated `s' command",
+        ),
+        (
+            "s/(a)/\\2/",
+            "-e expression #1, char 9: invalid reference \\2",
+        ),
+        (
+            "2,3q",
+            "-e expression #1, char 4: command only uses one address",
+        ),
+        (
+            "y/ab/c/",
+            "-e expression #1, char 7: strings for `y' command",
+        ),
+        ("k", "-e expression #1, char 1: unknown command: `k'"),
+    ] {
+        Command::cargo_bin(PRG)?
+            .args([script, FOX])
+            .assert()
+            .failure()
+            .stderr(predicate::str::starts_with(expected));
+    }
+
+    // each -e is counted on its own
+    Command::cargo_bin(PRG)?
+        .args(["-e", "p", "-e", "s/[/x/", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with(
+            "-e expression #2, char 6: invalid regular expression",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args(["p", FOX, &bad])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string(FOX)?.repeat(2))
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn substitute_backreferences() -> Result<()> {
+    run(
+        &[r"s/(\w+), (\w+)/\2 \1/", NAMES],
+        "tests/expected/names.txt.swap.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn substitute_global() -> Result<()> {
+    run(&["s/e/E/g", NUMBERS], "tests/expected/numbers.txt.g.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn substitute_occurrence() -> Result<()> {
+    // from the second match on
+    run(&["s/e/E/2g", NUMBERS], "tests/expected/numbers.txt.2g.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn substitute_insensitive_print() -> Result<()> {
+    run(&["-n", "s/THE/a/ip", FOX], "tests/expected/fox.txt.ip.out")?;
+    run(
+        &["--quiet", "s/THE/a/Ip", FOX],
+        "tests/expected/fox.txt.ip.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn delete_line_range() -> Result<()> {
+    run(&["3,5d", NUMBERS], "tests/expected/numbers.txt.3_5d.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn delete_regex_range() -> Result<()> {
+    run(
+        &["/two/,/four/d", NUMBERS],
+        "tests/expected/numbers.txt.regex_d.out",
+    )?;
+    // a range whose end has gone by covers just the line that opened it,
+    // and a later match opens it again
+    run(
+        &["/^t/,3d", NUMBERS],
+        "tests/expected/numbers.txt.regex_line_d.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn delete_last_line() -> Result<()> {
+    run(&["$d", NUMBERS], "tests/expected/numbers.txt.last_d.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn print() -> Result<()> {
+    run(
+        &["-n", "2p;$p", NUMBERS],
+        "tests/expected/numbers.txt.2p.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn quit() -> Result<()> {
+    run(&["3q", NUMBERS], "tests/expected/numbers.txt.3q.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn line_number() -> Result<()> {
+    run(
+        &["/e$/=", NUMBERS],
+        "tests/expected/numbers.txt.line_number.out",
+    )?;
+    // the files make one stream
+    run(
+        &["-n", "$=", NUMBERS, EMPTY, NAMES],
+        "tests/expected/all.count.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn transliterate() -> Result<()> {
+    run(&["y/abc/xyz/", NAMES], "tests/expected/names.txt.y.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn expressions() -> Result<()> {
+    run(
+        &["-e", r"s/(\w+), (\w+)/\2 \1/", "-e", "/Alan/d", NAMES],
+        "tests/expected/names.txt.e.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn script_file() -> Result<()> {
+    run(&["-f", UPPER, NAMES], "tests/expected/names.txt.f.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin() -> Result<()> {
+    let expected = fs::read_to_string("tests/expected/numbers.txt.3q.out")?;
+    Command::cargo_bin(PRG)?
+        .arg("3q")
+        .write_stdin(fs::read_to_string(NUMBERS)?)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn missing_newline() -> Result<()> {
+    // only the very end of the output goes without a newline
+    Command::cargo_bin(PRG)?
+        .args(["p"])
+        .write_stdin("a\nb")
+        .assert()
+        .success()
+        .stdout("a\na\nb\nb");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn in_place() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let names = dir.path().join("names.txt");
+    let numbers = dir.path().join("numbers.txt");
+    fs::copy(NAMES, &names)?;
+    fs::copy(NUMBERS, &numbers)?;
+
+    // each file is edited on its own, so $ is the last line of each
+    Command::cargo_bin(PRG)?
+        .args(["-i", "$d"])
+        .args([&names, &numbers])
+        .assert()
+        .success()
+        .stdout("");
+    let mut expected = fs::read_to_string(NAMES)?;
+    expected.truncate(expected.trim_end().rfind('\n').unwrap() + 1);
+    assert_eq!(fs::read_to_string(&names)?, expected);
+    assert_eq!(
+        fs::read_to_string(&numbers)?,
+        fs::read_to_string("tests/expected/numbers.txt.last_d.out")?
+    );
+
+    // nothing but the rewritten files is left behind
+    assert_eq!(fs::read_dir(dir.path())?.count(), 2);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn in_place_keeps_permissions() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("fox.txt");
+    fs::copy(FOX, &path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640))?;
+    Command::cargo_bin(PRG)?
+        .arg("--in-place")
+        .arg("s/fox/cat/")
+        .arg(&path)
+        .assert()
+        .success();
+    assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o640);
+    assert_eq!(
+        fs::read_to_string(&path)?,
+        "The quick brown cat jumps over the lazy dog.\n"
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_in_place_stdin() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-i", "p"])
+        .write_stdin("a\n")
+        .assert()
+        .failure()
+        .stderr("--in-place needs a FILE to edit\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{exit_with_error, open, parse_args};
+use std::cmp::Ordering;
+use std::io::{self, BufRead, Write};
+use std::ops::Range;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    #[arg(short('n'), long("numeric-sort"))]
+    numeric: bool,
+
+    #[arg(short('r'), long)]
+    reverse: bool,
+
+    #[arg(short('u'), long)]
+    unique: bool,
+
+    #[arg(short('s'), long)]
+    stable: bool,
+
+    #[arg(short('k'), long("key"), value_name = "COL[,COL]", value_parser = parse_key)]
+    keys: Vec<SortKey>,
+
+    #[arg(short('t'), long, value_name = "SEP")]
+    field_separator: Option<char>,
+}
+
+/// The part of a line that a comparison looks at. Field numbers are
+/// 1-based and inclusive; without an end the key runs to the end of line.
+#[derive(Debug, Clone, PartialEq)]
+enum SortKey {
+    Line,
+    Fields { start: usize, end: Option<usize> },
+}
+
+fn parse_key(value: &str) -> Result<SortKey, String> {
+    let field = |num: &str| match num.parse::<usize>() {
+        Ok(0) => Err(format!("field number is zero: '{value}'")),
+        Ok(n) => Ok(n),
+        Err(_) => Err(format!("invalid number: '{value}'")),
+    };
+    let (start, end) = match value.split_once(',') {
+        Some((start, end)) => (field(start)?, Some(field(end)?)),
+        None => (field(value)?, None),
+    };
+    Ok(SortKey::Fields { start, end })
+}
+
+/// Byte ranges of each field in `line`. With a separator the fields are
+/// the text between separators; otherwise each field is a run of blanks
+/// followed by non-blanks, so the leading blanks belong to the field.
+fn field_spans(line: &str, separator: Option<char>) -> Vec<Range<usize>> {
+    let mut spans = vec![];
+    match separator {
+        Some(sep) => {
+            let mut start = 0;
+            for (i, _) in line.match_indices(sep) {
+                spans.push(start..i);
+                start = i + sep.len_utf8();
+            }
+            spans.push(start..line.len());
+        }
+        None => {
+            let is_blank = |c: char| c == ' ' || c == '\t';
+            let mut start = 0;
+            while start < line.len() {
+                let rest = &line[start..];
+                let word = rest.len() - rest.trim_start_matches(is_blank).len();
+                let end = rest[word..]
+                    .find(is_blank)
+                    .map_or(line.len(), |i| start + word + i);
+                spans.push(start..end);
+                start = end;
+            }
+        }
+    }
+    spans
+}
+
+fn key_text<'a>(line: &'a str, key: &SortKey, separator: Option<char>) -> &'a str {
+    let SortKey::Fields { start, end } = key else {
+        return line;
+    };
+    let spans = field_spans(line, separator);
+    let Some(first) = spans.get(start - 1) else {
+        return "";
+    };
+    let last = match end {
+        Some(end) if end < start => return "",
+        Some(end) => spans
+            .get(end - 1)
+            .or(spans.last())
+            .map_or(line.len(), |s| s.end),
+        None => line.len(),
+    };
+    &line[first.start..last]
+}
+
+/// The leading number of `text` as `sort -n` reads it, or 0 if there is none.
+fn numeric_value(text: &str) -> f64 {
+    let text = text.trim_start_matches([' ', '\t']);
+    let mut end = 0;
+    let mut seen_point = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            '-' if i == 0 => {}
+            '.' if !seen_point => seen_point = true,
+            '0'..='9' => {}
+            _ => break,
+        }
+        end = i + 1;
+    }
+    text[..end].parse().unwrap_or(0.0)
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut lines = vec![];
+    for filename in &args.files {
+        match open(filename) {
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(file) => {
+                for line in file.lines() {
+                    lines.push(line?);
+                }
+            }
+        }
+    }
+
+    let keys = if args.keys.is_empty() {
+        vec![SortKey::Line]
+    } else {
+        args.keys.clone()
+    };
+    // Lines with equal keys are compared as a whole, as GNU sort does,
+    // unless the input order should be kept
+    let last_resort = !(args.stable || args.unique);
+    let compare = |a: &String, b: &String| -> Ordering {
+        let ordering = keys
+            .iter()
+            .map(|key| {
+                let a = key_text(a, key, args.field_separator);
+                let b = key_text(b, key, args.field_separator);
+                if args.numeric {
+                    numeric_value(a).total_cmp(&numeric_value(b))
+                } else {
+                    a.cmp(b)
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal);
+        let ordering = if ordering.is_eq() && last_resort {
+            a.cmp(b)
+        } else {
+            ordering
+        };
+        if args.reverse {
+            ordering.reverse()
+        } else {
+            ordering
+        }
+    };
+
+    lines.sort_by(compare);
+    if args.unique {
+        lines.dedup_by(|line, kept| compare(kept, line).is_eq());
+    }
+
+    let mut stdout = io::stdout().lock();
+    for line in lines {
+        writeln!(stdout, "{line}")?;
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SortKey, field_spans, key_text, numeric_value, parse_key};
+    use std::ops::Range;
+
+    #[test]
+    fn test_parse_key() {
+        assert_eq!(
+            parse_key("2"),
+            Ok(SortKey::Fields {
+                start: 2,
+                end: None
+            })
+        );
+        assert_eq!(
+            parse_key("2,3"),
+            Ok(SortKey::Fields {
+                start: 2,
+                end: Some(3)
+            })
+        );
+        assert!(parse_key("0").is_err());
+        assert!(parse_key("1,0").is_err());
+        assert!(parse_key("").is_err());
+        assert!(parse_key("a").is_err());
+        assert!(parse_key("1,").is_err());
+        assert!(parse_key("1,2,3").is_err());
+    }
+
+    #[test]
+    fn test_field_spans() {
+        assert_eq!(field_spans("a,,b", Some(',')), vec![0..1, 2..2, 3..4]);
+        assert_eq!(field_spans("", Some(',')), vec![0..0]);
+        assert_eq!(field_spans("  a b\tc", None), vec![0..3, 3..5, 5..7]);
+        assert_eq!(field_spans("a  ", None), vec![0..1, 1..3]);
+        assert_eq!(field_spans("", None), Vec::<Range<usize>>::new());
+    }
+
+    #[test]
+    fn test_key_text() {
+        let key = |start, end| SortKey::Fields { start, end };
+        let line = "bob  42 paris";
+        assert_eq!(key_text(line, &SortKey::Line, None), line);
+        assert_eq!(key_text(line, &key(2, Some(2)), None), "  42");
+        assert_eq!(key_text(line, &key(2, None), None), "  42 paris");
+        assert_eq!(key_text(line, &key(1, Some(9)), None), line);
+        assert_eq!(key_text(line, &key(4, None), None), "");
+        assert_eq!(key_text(line, &key(3, Some(2)), None), "");
+
+        let line = "bob,42,paris";
+        assert_eq!(key_text(line, &key(2, Some(2)), Some(',')), "42");
+        assert_eq!(key_text(line, &key(2, None), Some(',')), "42,paris");
+    }
+
+    #[test]
+    fn test_numeric_value() {
+        assert_eq!(numeric_value("42"), 42.0);
+        assert_eq!(numeric_value("  -3.5kg"), -3.5);
+        assert_eq!(numeric_value(".5"), 0.5);
+        assert_eq!(numeric_value("1.2.3"), 1.2);
+        assert_eq!(numeric_value("abc"), 0.0);
+        assert_eq!(numeric_value("-"), 0.0);
+        assert_eq!(numeric_value("4-2"), 4.0);
+        assert_eq!(numeric_value(""), 0.0);
+    }
+}
@@ -0,0 +1,264 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "sortr";
+const NUMBERS: &str = "tests/inputs/numbers.txt";
+const PEOPLE: &str = "tests/inputs/people.csv";
+const TABLE: &str = "tests/inputs/table.txt";
+const EMPTY: &str = "tests/inputs/empty.txt";
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename: String = rand::thread_rng()
+            .sample_iter(&Alphanumeric)
+            .take(7)
+            .map(char::from)
+            .collect();
+
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn dies(args: &[&str], expected: &str) -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args([NUMBERS, &bad])
+        .assert()
+        .success()
+        .stdout(fs::read_to_string("tests/expected/numbers.txt.out")?)
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_key() -> Result<()> {
+    dies(&["-k", "a", NUMBERS], "invalid number: 'a'")?;
+    dies(&["-k", "0", NUMBERS], "field number is zero: '0'")?;
+    dies(&["-k", "1,0", NUMBERS], "field number is zero: '1,0'")
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_separator() -> Result<()> {
+    dies(
+        &["-t", ",,", NUMBERS],
+        "invalid value ',,' for '--field-separator <SEP>'",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_numeric() -> Result<()> {
+    run(&["-n", NUMBERS], "tests/expected/numbers.txt.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_lexical() -> Result<()> {
+    run(&[NUMBERS], "tests/expected/numbers.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_reverse() -> Result<()> {
+    run(&["-r", NUMBERS], "tests/expected/numbers.txt.r.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_numeric_reverse() -> Result<()> {
+    run(&["-n", "-r", NUMBERS], "tests/expected/numbers.txt.nr.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_unique() -> Result<()> {
+    run(&["-u", NUMBERS], "tests/expected/numbers.txt.u.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_numeric_unique() -> Result<()> {
+    run(&["-n", "-u", NUMBERS], "tests/expected/numbers.txt.nu.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_numeric_reverse_unique() -> Result<()> {
+    run(
+        &["-n", "-r", "-u", NUMBERS],
+        "tests/expected/numbers.txt.nru.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_numeric_key() -> Result<()> {
+    run(
+        &["-t", ",", "-k", "2,2", "-n", PEOPLE],
+        "tests/expected/people.csv.t,k2,2n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_secondary_key() -> Result<()> {
+    run(
+        &["-t", ",", "-k", "3,3", "-k", "1,1", PEOPLE],
+        "tests/expected/people.csv.t,k3,3k1,1.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_secondary_key_numeric_reverse() -> Result<()> {
+    run(
+        &["-t", ",", "-k", "3,3", "-k", "2,2", "-n", "-r", PEOPLE],
+        "tests/expected/people.csv.t,k3,3k2,2nr.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_numeric_key_unique() -> Result<()> {
+    run(
+        &["-t", ",", "-k", "2,2", "-n", "-u", PEOPLE],
+        "tests/expected/people.csv.t,k2,2nu.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn csv_key_to_end_stable() -> Result<()> {
+    run(
+        &["-t", ",", "-k", "3", "-s", PEOPLE],
+        "tests/expected/people.csv.t,k3s.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn table_numeric_key() -> Result<()> {
+    run(
+        &["-k", "2,2", "-n", TABLE],
+        "tests/expected/table.txt.k2,2n.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn table_numeric_key_stable() -> Result<()> {
+    run(
+        &["-k", "2,2", "-n", "-s", TABLE],
+        "tests/expected/table.txt.k2,2ns.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn table_numeric_key_reverse_stable() -> Result<()> {
+    run(
+        &["-k", "2,2", "-n", "-r", "-s", TABLE],
+        "tests/expected/table.txt.k2,2nrs.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn table_numeric_key_reverse_unique() -> Result<()> {
+    run(
+        &["-k", "2,2", "-n", "-r", "-u", TABLE],
+        "tests/expected/table.txt.k2,2nru.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn table_secondary_key() -> Result<()> {
+    run(
+        &["-k", "3,3", "-k", "1,1", TABLE],
+        "tests/expected/table.txt.k3,3k1,1.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn table_key_unique() -> Result<()> {
+    run(
+        &["-k", "3,3", "-u", TABLE],
+        "tests/expected/table.txt.k3,3u.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> Result<()> {
+    run(
+        &["-n", "-r", "-u", EMPTY],
+        "tests/expected/empty.txt.nru.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files() -> Result<()> {
+    run(&["-n", TABLE, NUMBERS], "tests/expected/all.n.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn numbers_stdin() -> Result<()> {
+    let input = fs::read_to_string(NUMBERS)?;
+    let expected = fs::read_to_string("tests/expected/numbers.txt.nr.out")?;
+    let output = Command::cargo_bin(PRG)?
+        .args(["-n", "-r"])
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use anyhow::{Result, bail};
+use clap::Parser;
+use cli_utils::{exit_with_error, parse_args};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    #[arg(
+        short('n'),
+        long,
+        value_name = "LINES",
+        default_value = "10",
+        allow_hyphen_values = true,
+        value_parser = parse_take
+    )]
+    lines: TakeValue,
+
+    #[arg(
+        short('c'),
+        long,
+        value_name = "BYTES",
+        conflicts_with("lines"),
+        allow_hyphen_values = true,
+        value_parser = parse_take
+    )]
+    bytes: Option<TakeValue>,
+
+    #[arg(short('f'), long)]
+    follow: bool,
+}
+
+/// Which part of the input to print: the last `K` lines or bytes, or all
+/// of them starting from the `K`th (counting from 1, as `tail +K` does).
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum TakeValue {
+    Last(usize),
+    From(usize),
+}
+
+fn parse_take(value: &str) -> Result<TakeValue, String> {
+    let (from, num) = match value.strip_prefix('+') {
+        Some(num) => (true, num),
+        None => (false, value.strip_prefix('-').unwrap_or(value)),
+    };
+    let num = num.parse::<usize>().map_err(|e| e.to_string())?;
+    Ok(if from {
+        TakeValue::From(num)
+    } else {
+        TakeValue::Last(num)
+    })
+}
+
+fn tail_lines(mut file: impl BufRead, take: TakeValue, out: &mut impl Write) -> Result<()> {
+    let mut line = vec![];
+    match take {
+        TakeValue::Last(0) => {}
+        TakeValue::Last(num) => {
+            let mut last: VecDeque<Vec<u8>> = VecDeque::new();
+            while file.read_until(b'\n', &mut line)? > 0 {
+                if last.len() == num {
+                    last.pop_front();
+                }
+                last.push_back(line.clone());
+                line.clear();
+            }
+            for line in last {
+                out.write_all(&line)?;
+            }
+        }
+        TakeValue::From(num) => {
+            for _ in 1..num {
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    return Ok(());
+                }
+                line.clear();
+            }
+            io::copy(&mut file, out)?;
+        }
+    }
+    Ok(())
+}
+
+fn tail_bytes(mut file: impl Read, take: TakeValue, out: &mut impl Write) -> Result<()> {
+    match take {
+        TakeValue::Last(0) => {}
+        TakeValue::Last(num) => {
+            let mut last: VecDeque<u8> = VecDeque::new();
+            let mut buffer = [0; 8192];
+            loop {
+                let bytes_read = file.read(&mut buffer)?;
+                if bytes_read == 0 {
+                    break;
+                }
+                last.extend(&buffer[..bytes_read]);
+                if last.len() > num {
+                    last.drain(..last.len() - num);
+                }
+            }
+            let (front, back) = last.as_slices();
+            out.write_all(front)?;
+            out.write_all(back)?;
+        }
+        TakeValue::From(num) => {
+            let skip = num.saturating_sub(1) as u64;
+            io::copy(&mut (&mut file).take(skip), &mut io::sink())?;
+            io::copy(&mut file, out)?;
+        }
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> Result<()> {
+    if args.follow {
+        // TODO: keep reading as the files grow instead of stopping at EOF
+        bail!("--follow not yet implemented");
+    }
+
+    let mut stdout = io::stdout().lock();
+    let num_files = args.files.len();
+    for (file_num, filename) in args.files.iter().enumerate() {
+        match open(filename) {
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(file) => {
+                if num_files > 1 {
+                    writeln!(
+                        stdout,
+                        "{}==> {} <==",
+                        if file_num > 0 { "\n" } else { "" },
+                        if filename == "-" {
+                            "standard input"
+                        } else {
+                            filename
+                        }
+                    )?;
+                }
+                match args.bytes {
+                    Some(take) => tail_bytes(file, take, &mut stdout)?,
+                    None => tail_lines(BufReader::new(file), args.lines, &mut stdout)?,
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn open(filename: &str) -> Result<Box<dyn Read>> {
+    match filename {
+        "-" => Ok(Box::new(io::stdin())),
+        _ => Ok(Box::new(File::open(filename)?)),
+    }
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TakeValue::*, parse_take, tail_bytes, tail_lines};
+    use std::io::Cursor;
+
+    const TEXT: &str = "one\ntwo\nthree\nfour\nfive";
+
+    fn lines(input: &str, take: super::TakeValue) -> String {
+        let mut out = vec![];
+        tail_lines(Cursor::new(input), take, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn bytes(input: &str, take: super::TakeValue) -> String {
+        let mut out = vec![];
+        tail_bytes(Cursor::new(input), take, &mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    #[test]
+    fn test_parse_take() {
+        assert_eq!(parse_take("3"), Ok(Last(3)));
+        assert_eq!(parse_take("-3"), Ok(Last(3)));
+        assert_eq!(parse_take("+3"), Ok(From(3)));
+        assert_eq!(parse_take("0"), Ok(Last(0)));
+        assert_eq!(parse_take("+0"), Ok(From(0)));
+        assert!(parse_take("").is_err());
+        assert!(parse_take("+").is_err());
+        assert!(parse_take("3.5").is_err());
+        assert!(parse_take("--3").is_err());
+        assert!(parse_take("+-3").is_err());
+        assert!(parse_take("foo").is_err());
+    }
+
+    #[test]
+    fn test_tail_lines_last() {
+        assert_eq!(lines(TEXT, Last(2)), "four\nfive");
+        assert_eq!(lines(TEXT, Last(1)), "five");
+        assert_eq!(lines(TEXT, Last(0)), "");
+        assert_eq!(lines(TEXT, Last(100)), TEXT);
+        assert_eq!(lines("", Last(3)), "");
+    }
+
+    #[test]
+    fn test_tail_lines_from() {
+        assert_eq!(lines(TEXT, From(4)), "four\nfive");
+        assert_eq!(lines(TEXT, From(1)), TEXT);
+        assert_eq!(lines(TEXT, From(0)), TEXT);
+        assert_eq!(lines(TEXT, From(6)), "");
+        assert_eq!(lines(TEXT, From(100)), "");
+    }
+
+    #[test]
+    fn test_tail_bytes_last() {
+        assert_eq!(bytes(TEXT, Last(4)), "five");
+        assert_eq!(bytes(TEXT, Last(0)), "");
+        assert_eq!(bytes(TEXT, Last(100)), TEXT);
+
+        // more than one read's worth of input
+        let big = "x".repeat(20_000) + "tail";
+        assert_eq!(bytes(&big, Last(6)), "xxtail");
+    }
+
+    #[test]
+    fn test_tail_bytes_from() {
+        assert_eq!(bytes(TEXT, From(20)), "five");
+        assert_eq!(bytes(TEXT, From(1)), TEXT);
+        assert_eq!(bytes(TEXT, From(0)), TEXT);
+        assert_eq!(bytes(TEXT, From(100)), "");
+    }
+}
@@ -0,0 +1,422 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::{Rng, distributions::Alphanumeric};
+use std::fs;
+
+const PRG: &str = "tailr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const ONE: &str = "tests/inputs/one.txt";
+const TWO: &str = "tests/inputs/two.txt";
+const THREE: &str = "tests/inputs/three.txt";
+const TWELVE: &str = "tests/inputs/twelve.txt";
+
+// --------------------------------------------------
+fn random_string() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(7)
+        .map(char::from)
+        .collect()
+}
+
+// --------------------------------------------------
+fn gen_bad_file() -> String {
+    loop {
+        let filename = random_string();
+        if fs::metadata(&filename).is_err() {
+            return filename;
+        }
+    }
+}
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_stdin(args: &[&str], input_file: &str, expected_file: &str) -> Result<()> {
+    let input = fs::read(input_file)?;
+    let expected = fs::read(expected_file)?;
+    let output = Command::cargo_bin(PRG)?
+        .write_stdin(input)
+        .args(args)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, expected);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_lines() -> Result<()> {
+    let bad = random_string();
+    let expected = format!(
+        "invalid value '{bad}' for \
+        '--lines <LINES>': invalid digit found in string"
+    );
+    Command::cargo_bin(PRG)?
+        .args(["-n", &bad, EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_bytes() -> Result<()> {
+    let bad = random_string();
+    let expected = format!(
+        "invalid value '{bad}' for \
+        '--bytes <BYTES>': invalid digit found in string"
+    );
+    Command::cargo_bin(PRG)?
+        .args(["-c", &bad, EMPTY])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bytes_and_lines() -> Result<()> {
+    let msg = "the argument '--lines <LINES>' cannot be \
+               used with '--bytes <BYTES>'";
+
+    Command::cargo_bin(PRG)?
+        .args(["-n", "1", "-c", "2"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(msg));
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_follow() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--follow", ONE])
+        .assert()
+        .failure()
+        .stderr("--follow not yet implemented\n");
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args([ONE, &bad, TWO])
+        .assert()
+        .success()
+        .stderr(predicate::str::is_match(expected)?);
+
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> Result<()> {
+    run(&[EMPTY], "tests/expected/empty.txt.out")
+}
+
+#[test]
+fn empty_n3() -> Result<()> {
+    run(&["-n", "3", EMPTY], "tests/expected/empty.txt.n3.out")
+}
+
+#[test]
+fn empty_c_plus5() -> Result<()> {
+    run(&["-c", "+5", EMPTY], "tests/expected/empty.txt.c+5.out")
+}
+
+#[test]
+fn empty_stdin() -> Result<()> {
+    run_stdin(&[], EMPTY, "tests/expected/empty.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn one() -> Result<()> {
+    run(&[ONE], "tests/expected/one.txt.out")
+}
+
+#[test]
+fn one_n3() -> Result<()> {
+    run(&["-n", "3", ONE], "tests/expected/one.txt.n3.out")
+}
+
+#[test]
+fn one_n_plus3() -> Result<()> {
+    run(&["-n", "+3", ONE], "tests/expected/one.txt.n+3.out")
+}
+
+#[test]
+fn one_n0() -> Result<()> {
+    run(&["-n", "0", ONE], "tests/expected/one.txt.n0.out")
+}
+
+#[test]
+fn one_n_minus1() -> Result<()> {
+    run(&["-n", "-1", ONE], "tests/expected/one.txt.n-1.out")
+}
+
+#[test]
+fn one_c5() -> Result<()> {
+    run(&["-c", "5", ONE], "tests/expected/one.txt.c5.out")
+}
+
+#[test]
+fn one_c_plus5() -> Result<()> {
+    run(&["-c", "+5", ONE], "tests/expected/one.txt.c+5.out")
+}
+
+#[test]
+fn one_n3_stdin() -> Result<()> {
+    run_stdin(&["-n", "3"], ONE, "tests/expected/one.txt.n3.out")
+}
+
+#[test]
+fn one_n_plus3_stdin() -> Result<()> {
+    run_stdin(&["-n", "+3"], ONE, "tests/expected/one.txt.n+3.out")
+}
+
+#[test]
+fn one_c5_stdin() -> Result<()> {
+    run_stdin(&["-c", "5"], ONE, "tests/expected/one.txt.c5.out")
+}
+
+#[test]
+fn one_c_plus5_stdin() -> Result<()> {
+    run_stdin(&["-c", "+5"], ONE, "tests/expected/one.txt.c+5.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn two() -> Result<()> {
+    run(&[TWO], "tests/expected/two.txt.out")
+}
+
+#[test]
+fn two_n3() -> Result<()> {
+    run(&["-n", "3", TWO], "tests/expected/two.txt.n3.out")
+}
+
+#[test]
+fn two_n_plus3() -> Result<()> {
+    run(&["-n", "+3", TWO], "tests/expected/two.txt.n+3.out")
+}
+
+#[test]
+fn two_n0() -> Result<()> {
+    run(&["-n", "0", TWO], "tests/expected/two.txt.n0.out")
+}
+
+#[test]
+fn two_n_minus1() -> Result<()> {
+    run(&["-n", "-1", TWO], "tests/expected/two.txt.n-1.out")
+}
+
+#[test]
+fn two_c5() -> Result<()> {
+    run(&["-c", "5", TWO], "tests/expected/two.txt.c5.out")
+}
+
+#[test]
+fn two_c_plus5() -> Result<()> {
+    run(&["-c", "+5", TWO], "tests/expected/two.txt.c+5.out")
+}
+
+#[test]
+fn two_n3_stdin() -> Result<()> {
+    run_stdin(&["-n", "3"], TWO, "tests/expected/two.txt.n3.out")
+}
+
+#[test]
+fn two_n_plus3_stdin() -> Result<()> {
+    run_stdin(&["-n", "+3"], TWO, "tests/expected/two.txt.n+3.out")
+}
+
+#[test]
+fn two_c5_stdin() -> Result<()> {
+    run_stdin(&["-c", "5"], TWO, "tests/expected/two.txt.c5.out")
+}
+
+#[test]
+fn two_c_plus5_stdin() -> Result<()> {
+    run_stdin(&["-c", "+5"], TWO, "tests/expected/two.txt.c+5.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn three() -> Result<()> {
+    run(&[THREE], "tests/expected/three.txt.out")
+}
+
+#[test]
+fn three_n3() -> Result<()> {
+    run(&["-n", "3", THREE], "tests/expected/three.txt.n3.out")
+}
+
+#[test]
+fn three_n_plus3() -> Result<()> {
+    run(&["-n", "+3", THREE], "tests/expected/three.txt.n+3.out")
+}
+
+#[test]
+fn three_n0() -> Result<()> {
+    run(&["-n", "0", THREE], "tests/expected/three.txt.n0.out")
+}
+
+#[test]
+fn three_n_minus1() -> Result<()> {
+    run(&["-n", "-1", THREE], "tests/expected/three.txt.n-1.out")
+}
+
+#[test]
+fn three_c5() -> Result<()> {
+    run(&["-c", "5", THREE], "tests/expected/three.txt.c5.out")
+}
+
+#[test]
+fn three_c_plus5() -> Result<()> {
+    run(&["-c", "+5", THREE], "tests/expected/three.txt.c+5.out")
+}
+
+#[test]
+fn three_n3_stdin() -> Result<()> {
+    run_stdin(&["-n", "3"], THREE, "tests/expected/three.txt.n3.out")
+}
+
+#[test]
+fn three_n_plus3_stdin() -> Result<()> {
+    run_stdin(&["-n", "+3"], THREE, "tests/expected/three.txt.n+3.out")
+}
+
+#[test]
+fn three_c5_stdin() -> Result<()> {
+    run_stdin(&["-c", "5"], THREE, "tests/expected/three.txt.c5.out")
+}
+
+#[test]
+fn three_c_plus5_stdin() -> Result<()> {
+    run_stdin(&["-c", "+5"], THREE, "tests/expected/three.txt.c+5.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn twelve() -> Result<()> {
+    run(&[TWELVE], "tests/expected/twelve.txt.out")
+}
+
+#[test]
+fn twelve_n3() -> Result<()> {
+    run(&["-n", "3", TWELVE], "tests/expected/twelve.txt.n3.out")
+}
+
+#[test]
+fn twelve_n_plus3() -> Result<()> {
+    run(&["-n", "+3", TWELVE], "tests/expected/twelve.txt.n+3.out")
+}
+
+#[test]
+fn twelve_n0() -> Result<()> {
+    run(&["-n", "0", TWELVE], "tests/expected/twelve.txt.n0.out")
+}
+
+#[test]
+fn twelve_n_minus1() -> Result<()> {
+    run(&["-n", "-1", TWELVE], "tests/expected/twelve.txt.n-1.out")
+}
+
+#[test]
+fn twelve_c5() -> Result<()> {
+    run(&["-c", "5", TWELVE], "tests/expected/twelve.txt.c5.out")
+}
+
+#[test]
+fn twelve_c_plus5() -> Result<()> {
+    run(&["-c", "+5", TWELVE], "tests/expected/twelve.txt.c+5.out")
+}
+
+#[test]
+fn twelve_n3_stdin() -> Result<()> {
+    run_stdin(&["-n", "3"], TWELVE, "tests/expected/twelve.txt.n3.out")
+}
+
+#[test]
+fn twelve_n_plus3_stdin() -> Result<()> {
+    run_stdin(&["-n", "+3"], TWELVE, "tests/expected/twelve.txt.n+3.out")
+}
+
+#[test]
+fn twelve_c5_stdin() -> Result<()> {
+    run_stdin(&["-c", "5"], TWELVE, "tests/expected/twelve.txt.c5.out")
+}
+
+#[test]
+fn twelve_c_plus5_stdin() -> Result<()> {
+    run_stdin(&["-c", "+5"], TWELVE, "tests/expected/twelve.txt.c+5.out")
+}
+
+#[test]
+fn twelve_huge_counts() -> Result<()> {
+    // no room is set aside up front for this many lines or bytes
+    let huge = usize::MAX.to_string();
+    run(&["-n", &huge, TWELVE], TWELVE)?;
+    run(&["-c", &huge, TWELVE], TWELVE)
+}
+
+// --------------------------------------------------
+#[test]
+fn multiple_files_n2() -> Result<()> {
+    run(
+        &["-n", "2", EMPTY, ONE, TWO, THREE, TWELVE],
+        "tests/expected/all.n2.out",
+    )
+}
+
+#[test]
+fn multiple_files_c_plus3() -> Result<()> {
+    run(
+        &["-c", "+3", EMPTY, ONE, TWO, THREE, TWELVE],
+        "tests/expected/all.c+3.out",
+    )
+}
+
+#[test]
+fn multiple_files_stdin_header() -> Result<()> {
+    run_stdin(
+        &["-n", "1", ONE, "-"],
+        TWO,
+        "tests/expected/one.stdin.n1.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,98 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{exit_with_error, parse_args};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Write};
+
+const BUFFER_SIZE: usize = 8192;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE")]
+    files: Vec<String>,
+
+    #[arg(short('a'), long)]
+    append: bool,
+
+    #[arg(short('i'), long)]
+    ignore_interrupts: bool,
+}
+
+fn create(filename: &str, append: bool) -> io::Result<File> {
+    OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(append)
+        .truncate(!append)
+        .open(filename)
+}
+
+/// Copies `input` to `stdout` and to every file, a chunk at a time. A file
+/// that can't be written is reported and dropped, and copying carries on.
+fn tee(mut input: impl Read, mut stdout: impl Write, mut files: Vec<(&str, File)>) -> Result<()> {
+    let mut buffer = [0; BUFFER_SIZE];
+    loop {
+        let bytes = match input.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.into()),
+        };
+        let chunk = &buffer[..bytes];
+        stdout.write_all(chunk)?;
+        stdout.flush()?;
+        files.retain_mut(|(filename, file)| match file.write_all(chunk) {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("{filename}: {e}");
+                false
+            }
+        });
+    }
+    Ok(())
+}
+
+fn run(args: Args) -> Result<()> {
+    if args.ignore_interrupts {
+        ctrlc::set_handler(|| {})?;
+    }
+
+    let mut files = vec![];
+    for filename in &args.files {
+        match create(filename, args.append) {
+            Err(e) => eprintln!("{filename}: {e}"),
+            Ok(file) => files.push((filename.as_str(), file)),
+        }
+    }
+    tee(io::stdin().lock(), io::stdout().lock(), files)
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BUFFER_SIZE, tee};
+    use std::{fs, io::Cursor};
+
+    #[test]
+    fn test_tee() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = [dir.path().join("one"), dir.path().join("two")];
+        let files = paths
+            .iter()
+            .map(|path| ("file", fs::File::create(path).unwrap()))
+            .collect();
+
+        // more than one buffer's worth, ending mid-buffer
+        let input: Vec<u8> = (0..BUFFER_SIZE * 2 + 100).map(|i| i as u8).collect();
+        let mut stdout = vec![];
+        tee(Cursor::new(&input), &mut stdout, files).unwrap();
+        assert_eq!(stdout, input);
+        for path in paths {
+            assert_eq!(fs::read(path).unwrap(), input);
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use rand::Rng;
+use std::fs;
+
+const PRG: &str = "teer";
+
+// --------------------------------------------------
+fn gen_input(len: usize) -> Vec<u8> {
+    rand::thread_rng()
+        .sample_iter(rand::distributions::Standard)
+        .take(len)
+        .collect()
+}
+
+// --------------------------------------------------
+#[test]
+fn copies_to_stdout_and_files() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let paths: Vec<_> = ["one", "two", "three"]
+        .iter()
+        .map(|name| dir.path().join(name))
+        .collect();
+
+    // arbitrary bytes, spanning many reads
+    let input = gen_input(100_000);
+    let output = Command::cargo_bin(PRG)?
+        .args(&paths)
+        .write_stdin(input.clone())
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, input);
+    for path in &paths {
+        assert_eq!(fs::read(path)?, input);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_files() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .write_stdin("stdout only\n")
+        .assert()
+        .success()
+        .stdout("stdout only\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty_input() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("out");
+    fs::write(&path, "old contents\n")?;
+    Command::cargo_bin(PRG)?
+        .arg(&path)
+        .write_stdin("")
+        .assert()
+        .success()
+        .stdout("");
+    assert_eq!(fs::read_to_string(&path)?, "");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn truncates_by_default() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("out");
+    fs::write(&path, "a much longer line of old contents\n")?;
+    Command::cargo_bin(PRG)?
+        .arg(&path)
+        .write_stdin("new\n")
+        .assert()
+        .success()
+        .stdout("new\n");
+    assert_eq!(fs::read_to_string(&path)?, "new\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn append() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let existing = dir.path().join("existing");
+    let new = dir.path().join("new");
+    fs::write(&existing, "old\n")?;
+    for flag in ["-a", "--append"] {
+        Command::cargo_bin(PRG)?
+            .arg(flag)
+            .args([&existing, &new])
+            .write_stdin(format!("{flag}\n"))
+            .assert()
+            .success()
+            .stdout(format!("{flag}\n"));
+    }
+    assert_eq!(fs::read_to_string(&existing)?, "old\n-a\n--append\n");
+    assert_eq!(fs::read_to_string(&new)?, "-a\n--append\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn skips_bad_file() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let bad = dir.path().join("missing").join("out");
+    let good = dir.path().join("out");
+    Command::cargo_bin(PRG)?
+        .args([&bad, &good])
+        .write_stdin("hello\n")
+        .assert()
+        .success()
+        .stdout("hello\n")
+        .stderr(predicate::str::is_match(format!(
+            "^{}: .* [(]os error 2[)]",
+            bad.display()
+        ))?);
+    assert_eq!(fs::read_to_string(&good)?, "hello\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+// Sends SIGINT once the first chunk has made it through, then writes a
+// second one and returns how the process ended and what it printed.
+#[cfg(unix)]
+fn interrupt(args: &[&str]) -> Result<(std::process::ExitStatus, Vec<u8>)> {
+    use std::{
+        io::{Read, Write},
+        process::{Command, Stdio},
+    };
+
+    let mut child = Command::new(assert_cmd::cargo::cargo_bin!())
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().unwrap();
+    let mut stdout = child.stdout.take().unwrap();
+
+    // echoing the first chunk shows that any handler is in place
+    stdin.write_all(b"first\n")?;
+    let mut first = [0; 6];
+    stdout.read_exact(&mut first)?;
+    assert!(
+        Command::new("kill")
+            .args(["-INT", &child.id().to_string()])
+            .status()?
+            .success()
+    );
+    let _ = stdin.write_all(b"second\n");
+    drop(stdin);
+
+    let mut rest = vec![];
+    stdout.read_to_end(&mut rest)?;
+    Ok((child.wait()?, [&first[..], &rest].concat()))
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn ignore_interrupts() -> Result<()> {
+    use std::os::unix::process::ExitStatusExt;
+
+    for flag in ["-i", "--ignore-interrupts"] {
+        let (status, stdout) = interrupt(&[flag])?;
+        assert!(status.success());
+        assert_eq!(stdout, b"first\nsecond\n");
+    }
+
+    let (status, stdout) = interrupt(&[])?;
+    assert_eq!(status.signal(), Some(2));
+    assert_eq!(stdout, b"first\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
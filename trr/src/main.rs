@@ -0,0 +1,353 @@
+use anyhow::{Result, anyhow, bail};
+use clap::Parser;
+use cli_utils::{exit_with_error, parse_args};
+use std::collections::{HashMap, HashSet};
+use std::io::{self, BufRead, Write};
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "SET1")]
+    set1: String,
+
+    #[arg(value_name = "SET2")]
+    set2: Option<String>,
+
+    #[arg(short('c'), short_alias('C'), long)]
+    complement: bool,
+
+    #[arg(short('d'), long)]
+    delete: bool,
+
+    #[arg(short('s'), long("squeeze-repeats"))]
+    squeeze: bool,
+}
+
+/// The ASCII members of a POSIX class, in order, as in the C locale.
+fn class_chars(name: &str) -> Option<Vec<char>> {
+    let test: fn(&char) -> bool = match name {
+        "alnum" => |c| c.is_ascii_alphanumeric(),
+        "alpha" => |c| c.is_ascii_alphabetic(),
+        "blank" => |c| matches!(c, ' ' | '\t'),
+        "cntrl" => |c| c.is_ascii_control(),
+        "digit" => |c| c.is_ascii_digit(),
+        "graph" => |c| c.is_ascii_graphic(),
+        "lower" => |c| c.is_ascii_lowercase(),
+        "print" => |c| c.is_ascii_graphic() || *c == ' ',
+        "punct" => |c| c.is_ascii_punctuation(),
+        "space" => |c| matches!(c, ' ' | '\t' | '\n' | '\x0b' | '\x0c' | '\r'),
+        "upper" => |c| c.is_ascii_uppercase(),
+        "xdigit" => |c| c.is_ascii_hexdigit(),
+        _ => return None,
+    };
+    Some((0..=127u8).map(char::from).filter(test).collect())
+}
+
+/// Reads one possibly escaped character at `chars[*i]` and moves past it.
+/// Besides the usual `\n`-style escapes, `\NNN` is an octal code and any
+/// other escaped character stands for itself.
+fn take_char(chars: &[char], i: &mut usize) -> char {
+    let c = chars[*i];
+    *i += 1;
+    if c != '\\' || *i == chars.len() {
+        return c;
+    }
+    let escaped = chars[*i];
+    *i += 1;
+    match escaped {
+        'a' => '\x07',
+        'b' => '\x08',
+        'f' => '\x0c',
+        'n' => '\n',
+        'r' => '\r',
+        't' => '\t',
+        'v' => '\x0b',
+        '0'..='7' => {
+            let mut value = escaped.to_digit(8).unwrap_or(0);
+            for _ in 0..2 {
+                match chars.get(*i).and_then(|c| c.to_digit(8)) {
+                    Some(digit) if value * 8 + digit <= 0o377 => {
+                        value = value * 8 + digit;
+                        *i += 1;
+                    }
+                    _ => break,
+                }
+            }
+            char::from(value as u8)
+        }
+        other => other,
+    }
+}
+
+/// Expands the ranges (`a-z`), classes (`[:alpha:]`), equivalence classes
+/// (`[=c=]`) and repeats (`[x*N]`) in a set into the list of characters it
+/// stands for, in order. A repeat needs an explicit count: the `[x*]` form
+/// that pads SET2 to the length of SET1 is not supported.
+fn build_char_table(set: &str) -> Result<Vec<char>> {
+    let chars: Vec<char> = set.chars().collect();
+    let mut table = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let rest: String = chars[i..].iter().collect();
+        if let Some(inner) = rest.strip_prefix("[:")
+            && let Some(end) = inner.find(":]")
+        {
+            let name = &inner[..end];
+            match class_chars(name) {
+                Some(class) => table.extend(class),
+                None => bail!("invalid character class '{name}'"),
+            }
+            i += name.chars().count() + 4;
+            continue;
+        }
+        if let Some(inner) = rest.strip_prefix("[=")
+            && let Some(end) = inner.find("=]")
+            && inner[..end].chars().count() == 1
+        {
+            table.push(chars[i + 2]);
+            i += 5;
+            continue;
+        }
+        if chars[i] == '['
+            && i + 1 < chars.len()
+            && let Some(repeat) = take_repeat(&chars, i + 1)?
+        {
+            let (c, count, next) = repeat;
+            table.extend(std::iter::repeat_n(c, count));
+            i = next;
+            continue;
+        }
+
+        let start = take_char(&chars, &mut i);
+        if i + 1 < chars.len() && chars[i] == '-' {
+            i += 1;
+            let end = take_char(&chars, &mut i);
+            if end < start {
+                bail!(
+                    "range-endpoints of '{}-{}' are in reverse collating sequence order",
+                    start.escape_default(),
+                    end.escape_default()
+                );
+            }
+            table.extend(start..=end);
+        } else {
+            table.push(start);
+        }
+    }
+    Ok(table)
+}
+
+/// Parses the `x*N]` of a repeat starting at `chars[i]`, returning the
+/// character, the count and the position after the `]`. A count with a
+/// leading 0 is octal. Returns `None` when this is not a repeat at all.
+fn take_repeat(chars: &[char], mut i: usize) -> Result<Option<(char, usize, usize)>> {
+    let c = take_char(chars, &mut i);
+    if chars.get(i) != Some(&'*') {
+        return Ok(None);
+    }
+    let Some(len) = chars[i + 1..].iter().position(|&c| c == ']') else {
+        return Ok(None);
+    };
+    let digits: String = chars[i + 1..i + 1 + len].iter().collect();
+    if digits.chars().all(|c| c == '0') {
+        bail!("[{c}*] repeats without a count are not supported");
+    }
+    let radix = if digits.starts_with('0') { 8 } else { 10 };
+    let count = usize::from_str_radix(&digits, radix)
+        .map_err(|_| anyhow!("invalid repeat count '{digits}' in [c*n] construct"))?;
+    Ok(Some((c, count, i + len + 2)))
+}
+
+/// The characters of a set, or with `-c` every character not in it.
+#[derive(Debug)]
+struct CharSet {
+    chars: HashSet<char>,
+    complement: bool,
+}
+
+impl CharSet {
+    fn new(chars: &[char], complement: bool) -> Self {
+        Self {
+            chars: chars.iter().copied().collect(),
+            complement,
+        }
+    }
+
+    fn contains(&self, c: char) -> bool {
+        self.chars.contains(&c) != self.complement
+    }
+}
+
+#[derive(Debug)]
+enum Translation {
+    Map(HashMap<char, char>),
+    // with -c, every character outside the set becomes the given one
+    AllBut(HashSet<char>, char),
+}
+
+/// Applies the options in the order `tr` does: characters are deleted
+/// first, the rest are translated, and then repeats are squeezed. The
+/// last character written is kept so squeezing works across lines.
+#[derive(Debug)]
+struct Tr {
+    delete: Option<CharSet>,
+    translation: Option<Translation>,
+    squeeze: Option<CharSet>,
+    last: Option<char>,
+}
+
+impl Tr {
+    fn new(args: &Args) -> Result<Self> {
+        let set1 = build_char_table(&args.set1)?;
+        let set2 = args.set2.as_deref().map(build_char_table).transpose()?;
+        match &args.set2 {
+            Some(set2) if args.delete && !args.squeeze => bail!(
+                "extra operand '{set2}'\n\
+                 Only one string may be given when deleting without squeezing repeats."
+            ),
+            None if !args.delete && !args.squeeze => bail!(
+                "missing operand after '{}'\n\
+                 Two strings must be given when translating.",
+                args.set1
+            ),
+            None if args.delete && args.squeeze => bail!(
+                "missing operand after '{}'\n\
+                 Two strings must be given when both deleting and squeezing repeats.",
+                args.set1
+            ),
+            _ => {}
+        }
+
+        let translation = match &set2 {
+            Some(set2) if !args.delete => {
+                let Some(&last) = set2.last() else {
+                    bail!("when translating, SET2 must be non-empty");
+                };
+                Some(if args.complement {
+                    Translation::AllBut(set1.iter().copied().collect(), last)
+                } else {
+                    // SET2 is padded with its last character, and later
+                    // duplicates in SET1 win
+                    Translation::Map(
+                        set1.iter()
+                            .enumerate()
+                            .map(|(i, &c)| (c, *set2.get(i).unwrap_or(&last)))
+                            .collect(),
+                    )
+                })
+            }
+            _ => None,
+        };
+
+        Ok(Self {
+            delete: args.delete.then(|| CharSet::new(&set1, args.complement)),
+            translation,
+            squeeze: args.squeeze.then(|| match &set2 {
+                Some(set2) => CharSet::new(set2, false),
+                None => CharSet::new(&set1, args.complement),
+            }),
+            last: None,
+        })
+    }
+
+    fn translate(&self, c: char) -> char {
+        match &self.translation {
+            Some(Translation::Map(map)) => map.get(&c).copied().unwrap_or(c),
+            Some(Translation::AllBut(keep, to)) if !keep.contains(&c) => *to,
+            _ => c,
+        }
+    }
+
+    fn process(&mut self, input: &str, output: &mut String) {
+        for c in input.chars() {
+            if self.delete.as_ref().is_some_and(|set| set.contains(c)) {
+                continue;
+            }
+            let c = self.translate(c);
+            if self.last == Some(c) && self.squeeze.as_ref().is_some_and(|set| set.contains(c)) {
+                continue;
+            }
+            self.last = Some(c);
+            output.push(c);
+        }
+    }
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut tr = Tr::new(&args)?;
+    let mut stdin = io::stdin().lock();
+    let mut stdout = io::stdout().lock();
+    let mut line = String::new();
+    let mut output = String::new();
+    loop {
+        line.clear();
+        if stdin.read_line(&mut line)? == 0 {
+            break;
+        }
+        output.clear();
+        tr.process(&line, &mut output);
+        stdout.write_all(output.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{build_char_table, class_chars};
+
+    fn table(set: &str) -> String {
+        build_char_table(set).unwrap().into_iter().collect()
+    }
+
+    #[test]
+    fn test_class_chars() {
+        let class = |name| class_chars(name).map(String::from_iter);
+        let lower = "abcdefghijklmnopqrstuvwxyz";
+        let upper = "ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        let digit = "0123456789";
+        let punct = "!\"#$%&'()*+,-./:;<=>?@[\\]^_`{|}~";
+        assert_eq!(class("alnum").unwrap(), format!("{digit}{upper}{lower}"));
+        assert_eq!(class("alpha").unwrap(), format!("{upper}{lower}"));
+        assert_eq!(class("blank").unwrap(), "\t ");
+        assert_eq!(
+            class("cntrl").unwrap(),
+            (0..32u8).chain([127]).map(char::from).collect::<String>()
+        );
+        assert_eq!(class("digit").unwrap(), digit);
+        assert_eq!(class("graph").unwrap(), String::from_iter('!'..='~'));
+        assert_eq!(class("lower").unwrap(), lower);
+        assert_eq!(class("print").unwrap(), String::from_iter(' '..='~'));
+        assert_eq!(class("punct").unwrap(), punct);
+        assert_eq!(class("space").unwrap(), "\t\n\x0b\x0c\r ");
+        assert_eq!(class("upper").unwrap(), upper);
+        assert_eq!(class("xdigit").unwrap(), "0123456789ABCDEFabcdef");
+        assert_eq!(class("foo"), None);
+        assert_eq!(class(""), None);
+    }
+
+    #[test]
+    fn test_build_char_table() {
+        assert_eq!(table(""), "");
+        assert_eq!(table("abc"), "abc");
+        assert_eq!(table("a-e"), "abcde");
+        assert_eq!(table("a-cx-z"), "abcxyz");
+        assert_eq!(table("-a-"), "-a-");
+        assert_eq!(table("[:digit:]x"), "0123456789x");
+        assert_eq!(table("[:blank:][:upper:]").len(), 28);
+        assert_eq!(table("[=e=]"), "e");
+        assert_eq!(table("[x*3]y"), "xxxy");
+        assert_eq!(table("[x*010]"), "xxxxxxxx");
+        assert_eq!(table("[a]"), "[a]");
+        assert_eq!(table("\\n\\t\\\\\\-"), "\n\t\\-");
+        assert_eq!(table("\\101\\0"), "A\0");
+        assert_eq!(table("α-γ日"), "αβγ日");
+        assert!(build_char_table("z-a").is_err());
+        assert!(build_char_table("[:foo:]").is_err());
+        assert!(build_char_table("[x*]").is_err());
+        assert!(build_char_table("[x*y]").is_err());
+    }
+}
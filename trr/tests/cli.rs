@@ -0,0 +1,239 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+
+const PRG: &str = "trr";
+const EMPTY: &str = "tests/inputs/empty.txt";
+const FOX: &str = "tests/inputs/fox.txt";
+const MIXED: &str = "tests/inputs/mixed.txt";
+const UTF8: &str = "tests/inputs/utf8.txt";
+
+// --------------------------------------------------
+fn run(input_file: &str, args: &[&str], expected_file: &str) -> Result<()> {
+    let input = fs::read_to_string(input_file)?;
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+fn dies(args: &[&str], expected: &str) -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin("")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(expected));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    dies(&[], "Usage")
+}
+
+#[test]
+fn dies_missing_set2() -> Result<()> {
+    dies(&["a-z"], "Two strings must be given when translating")?;
+    dies(
+        &["-ds", "a-z"],
+        "Two strings must be given when both deleting",
+    )
+}
+
+#[test]
+fn dies_extra_set2() -> Result<()> {
+    dies(&["-d", "a", "b"], "extra operand 'b'")
+}
+
+#[test]
+fn dies_bad_class() -> Result<()> {
+    dies(&["[:foo:]", "x"], "invalid character class 'foo'")
+}
+
+#[test]
+fn dies_reverse_range() -> Result<()> {
+    dies(
+        &["z-a", "x"],
+        "'z-a' are in reverse collating sequence order",
+    )
+}
+
+#[test]
+fn dies_empty_set2() -> Result<()> {
+    dies(&["a", ""], "SET2 must be non-empty")
+}
+
+// --------------------------------------------------
+#[test]
+fn empty() -> Result<()> {
+    run(EMPTY, &["a-z", "A-Z"], "tests/expected/empty.txt.upper.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_upper() -> Result<()> {
+    run(FOX, &["a-z", "A-Z"], "tests/expected/fox.txt.upper.out")
+}
+
+#[test]
+fn fox_shift() -> Result<()> {
+    run(FOX, &["a-y", "b-z"], "tests/expected/fox.txt.shift.out")
+}
+
+#[test]
+fn fox_pad_set2() -> Result<()> {
+    run(FOX, &["a-z", "AB"], "tests/expected/fox.txt.pad.out")
+}
+
+#[test]
+fn fox_delete_space() -> Result<()> {
+    run(
+        FOX,
+        &["-d", "[:space:]"],
+        "tests/expected/fox.txt.d_space.out",
+    )
+}
+
+#[test]
+fn fox_complement() -> Result<()> {
+    run(
+        FOX,
+        &["-c", "[:alpha:]\\n", "_"],
+        "tests/expected/fox.txt.c_alpha.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn mixed_upper() -> Result<()> {
+    run(
+        MIXED,
+        &["[:lower:]", "[:upper:]"],
+        "tests/expected/mixed.txt.upper.out",
+    )
+}
+
+#[test]
+fn mixed_delete_digit() -> Result<()> {
+    run(
+        MIXED,
+        &["-d", "[:digit:]"],
+        "tests/expected/mixed.txt.d_digit.out",
+    )
+}
+
+#[test]
+fn mixed_squeeze_space() -> Result<()> {
+    run(
+        MIXED,
+        &["-s", "[:space:]"],
+        "tests/expected/mixed.txt.s_space.out",
+    )
+}
+
+#[test]
+fn mixed_squeeze_lower() -> Result<()> {
+    run(
+        MIXED,
+        &["-s", "a-z"],
+        "tests/expected/mixed.txt.s_lower.out",
+    )
+}
+
+#[test]
+fn mixed_delete_then_squeeze() -> Result<()> {
+    run(
+        MIXED,
+        &["-ds", "[:punct:]", "[:blank:]"],
+        "tests/expected/mixed.txt.ds.out",
+    )
+}
+
+#[test]
+fn mixed_complement_squeeze() -> Result<()> {
+    run(
+        MIXED,
+        &["-cs", "[:alnum:]", "\\n"],
+        "tests/expected/mixed.txt.cs_words.out",
+    )
+}
+
+#[test]
+fn mixed_complement_delete() -> Result<()> {
+    run(
+        MIXED,
+        &["--complement", "-d", "[:print:]\\n"],
+        "tests/expected/mixed.txt.cd_print.out",
+    )
+}
+
+#[test]
+fn mixed_repeat() -> Result<()> {
+    run(
+        MIXED,
+        &["[:digit:]", "[x*5][y*5]"],
+        "tests/expected/mixed.txt.repeat.out",
+    )
+}
+
+#[test]
+fn mixed_escape() -> Result<()> {
+    run(MIXED, &["\\t", " "], "tests/expected/mixed.txt.escape.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn utf8_upper() -> Result<()> {
+    run(UTF8, &["a-z", "A-Z"], "tests/expected/utf8.txt.upper.out")
+}
+
+#[test]
+fn utf8_delete_punct() -> Result<()> {
+    run(
+        UTF8,
+        &["-d", "[:punct:]"],
+        "tests/expected/utf8.txt.d_punct.out",
+    )
+}
+
+#[test]
+fn utf8_accents() -> Result<()> {
+    run(
+        UTF8,
+        &["éïöÜ", "eioU"],
+        "tests/expected/utf8.txt.accents.out",
+    )
+}
+
+#[test]
+fn utf8_squeeze_emoji() -> Result<()> {
+    run(UTF8, &["-s", "👍"], "tests/expected/utf8.txt.s_emoji.out")
+}
+
+#[test]
+fn utf8_delete_cjk() -> Result<()> {
+    run(UTF8, &["-d", "日本語"], "tests/expected/utf8.txt.d_cjk.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
@@ -0,0 +1,176 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{TabStops, exit_with_error, open, parse_args};
+use std::io::{self, BufRead, BufWriter, Write};
+use unicode_segmentation::UnicodeSegmentation;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    #[arg(value_name = "FILE", default_value = "-")]
+    files: Vec<String>,
+
+    #[arg(short('a'), long)]
+    all: bool,
+
+    #[arg(short('t'), long, value_name = "LIST", value_parser = TabStops::parse)]
+    tabs: Option<TabStops>,
+}
+
+/// Splits `line` into the pieces that each take a column: grapheme
+/// clusters, where invalid UTF-8 counts byte by byte.
+fn units(line: &[u8]) -> Vec<&[u8]> {
+    let mut units = vec![];
+    let mut rest = line;
+    for chunk in line.utf8_chunks() {
+        for grapheme in chunk.valid().graphemes(true) {
+            let (unit, tail) = rest.split_at(grapheme.len());
+            units.push(unit);
+            rest = tail;
+        }
+        for _ in chunk.invalid() {
+            let (unit, tail) = rest.split_at(1);
+            units.push(unit);
+            rest = tail;
+        }
+    }
+    units
+}
+
+/// Appends `line` to `output` with every run of blanks that reaches a tab
+/// stop replaced by a tab, as GNU `unexpand` does. Only the leading blanks
+/// are converted unless `all` is set.
+fn unexpand(line: &[u8], tabs: &TabStops, all: bool, output: &mut Vec<u8>) {
+    let mut column = 0;
+    let mut convert = true;
+    // blanks since the last stop, which become a tab if they reach the next
+    let mut pending = vec![];
+    // a single space just before a stop becomes a tab only if more blanks
+    // follow it
+    let mut lone_space = false;
+    for unit in units(line) {
+        let blank = unit == b" " || unit == b"\t";
+        if blank && convert {
+            match tabs.next_stop(column) {
+                // past the last stop of a list nothing more is converted
+                None => convert = false,
+                Some(next) => {
+                    if lone_space {
+                        output.push(b'\t');
+                        lone_space = false;
+                    }
+                    column = if unit == b"\t" { next } else { column + 1 };
+                    pending.extend(unit);
+                    if column == next {
+                        if pending == b" " {
+                            lone_space = true;
+                        } else {
+                            output.push(b'\t');
+                        }
+                        pending.clear();
+                    }
+                    continue;
+                }
+            }
+        }
+        if lone_space {
+            output.push(b' ');
+            lone_space = false;
+        }
+        output.append(&mut pending);
+        output.extend(unit);
+        column = match unit {
+            b"\t" => tabs.next_stop(column).unwrap_or(column + 1),
+            b"\x08" => column.saturating_sub(1),
+            _ => column + 1,
+        };
+        convert &= all || blank;
+    }
+    if lone_space {
+        output.push(b' ');
+    }
+    output.append(&mut pending);
+}
+
+fn run(args: Args) -> Result<()> {
+    // as with GNU unexpand, giving tab stops implies -a
+    let all = args.all || args.tabs.is_some();
+    let tabs = args.tabs.unwrap_or(TabStops::Every(8));
+    let mut stdout = BufWriter::new(io::stdout().lock());
+    let mut line = vec![];
+    let mut output = vec![];
+
+    for filename in &args.files {
+        match open(filename) {
+            Err(err) => eprintln!("{filename}: {err}"),
+            Ok(mut file) => loop {
+                line.clear();
+                if file.read_until(b'\n', &mut line)? == 0 {
+                    break;
+                }
+                output.clear();
+                unexpand(&line, &tabs, all, &mut output);
+                stdout.write_all(&output)?;
+            },
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::unexpand;
+    use cli_utils::TabStops;
+
+    fn unexpand_str(line: &str, tabs: TabStops, all: bool) -> String {
+        let mut output = vec![];
+        unexpand(line.as_bytes(), &tabs, all, &mut output);
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_leading() {
+        let tabs = || TabStops::Every(8);
+        assert_eq!(unexpand_str("        x\n", tabs(), false), "\tx\n");
+        assert_eq!(unexpand_str("          x", tabs(), false), "\t  x");
+        assert_eq!(unexpand_str("       \tx", tabs(), false), "\tx");
+        assert_eq!(unexpand_str("   x", tabs(), false), "   x");
+        // only the leading blanks change without -a
+        assert_eq!(
+            unexpand_str("x       y        z", tabs(), false),
+            "x       y        z"
+        );
+    }
+
+    #[test]
+    fn test_all() {
+        let tabs = || TabStops::Every(8);
+        assert_eq!(unexpand_str("ab      cd  e\n", tabs(), true), "ab\tcd  e\n");
+        assert_eq!(unexpand_str("  a  \n", tabs(), true), "  a  \n");
+        assert_eq!(unexpand_str("日本      x", tabs(), true), "日本\tx");
+    }
+
+    #[test]
+    fn test_lone_space() {
+        let tabs = || TabStops::Every(8);
+        // one space reaching a stop stays a space before text...
+        assert_eq!(unexpand_str("abcdefg x", tabs(), true), "abcdefg x");
+        // ...but becomes a tab when more blanks follow it
+        assert_eq!(
+            unexpand_str("abcdefg         x", tabs(), true),
+            "abcdefg\t\tx"
+        );
+        assert_eq!(unexpand_str("abcdefg  x", tabs(), true), "abcdefg\t x");
+        assert_eq!(unexpand_str("abcdefg \tx", tabs(), true), "abcdefg\t\tx");
+    }
+
+    #[test]
+    fn test_list() {
+        let tabs = TabStops::List(vec![4, 6]);
+        assert_eq!(unexpand_str("        x\n", tabs, true), "\t\t  x\n");
+    }
+}
@@ -1,13 +1,17 @@
 use anyhow::Result;
-use anyhow::anyhow;
-use clap::Parser;
+use anyhow::{anyhow, bail};
+use clap::{Parser, ValueEnum};
+use cli_utils::schema::{MachineFormat, MachineOutput, UniqrRecord};
+use cli_utils::{exit_with_error, open, parse_args};
 use std::{
-    fs::File,
-    io::{self, BufRead, BufReader, Write},
+    fs::{self, File},
+    io::{self, BufRead, Write},
+    path::Path,
 };
+use tempfile::NamedTempFile;
 
 #[derive(Debug, Parser)]
-#[command(author, version, author)]
+#[command(author, version, about)]
 struct Args {
     #[arg(value_name = "IN_FILE", default_value = "-")]
     in_file: String,
@@ -17,63 +21,348 @@ struct Args {
 
     #[arg(short, long)]
     count: bool,
+
+    /// With --count, separate each count from its line by a tab rather
+    /// than padding it
+    #[arg(short('T'), long)]
+    tab_separated: bool,
+
+    #[arg(short('d'), long)]
+    repeated: bool,
+
+    #[arg(short('i'), long)]
+    ignore_case: bool,
+
+    #[arg(short('f'), long, value_name = "N")]
+    skip_fields: Option<usize>,
+
+    /// Compare only field N (from 1) of each line, split on --delimiter,
+    /// but print whole lines
+    #[arg(
+        long,
+        value_name = "N",
+        value_parser = clap::value_parser!(u64).range(1..),
+        conflicts_with = "skip_fields"
+    )]
+    key_field: Option<u64>,
+
+    /// The character between fields for --key-field
+    #[arg(
+        long,
+        value_name = "CHAR",
+        default_value_t = '\t',
+        requires = "key_field"
+    )]
+    delimiter: char,
+
+    #[arg(short('s'), long, value_name = "N")]
+    skip_chars: Option<usize>,
+
+    #[arg(short('w'), long, value_name = "N")]
+    check_chars: Option<usize>,
+
+    #[arg(short('u'), long, conflicts_with_all(["repeated", "all_repeated"]))]
+    unique: bool,
+
+    #[arg(
+        short('D'),
+        long,
+        value_name = "METHOD",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "none",
+        conflicts_with_all(["count", "repeated"])
+    )]
+    all_repeated: Option<Delimit>,
+
+    #[arg(
+        long,
+        value_name = "METHOD",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "separate",
+        conflicts_with_all(["count", "repeated", "unique", "all_repeated"])
+    )]
+    group: Option<Group>,
+
+    #[arg(short('z'), long)]
+    zero_terminated: bool,
+
+    #[arg(long, conflicts_with("out_file"))]
+    in_place: bool,
+
+    /// Print nothing, but fail at the first line that repeats the one
+    /// before it
+    #[arg(
+        long,
+        conflicts_with_all([
+            "out_file", "in_place", "count", "repeated", "unique", "all_repeated", "group"
+        ])
+    )]
+    check: bool,
+
+    /// Print each line with its count as records in the common JSON or CSV
+    /// schema
+    #[arg(
+        long,
+        value_name = "FORMAT",
+        value_enum,
+        conflicts_with_all(["count", "tab_separated", "all_repeated", "group", "check"])
+    )]
+    machine_readable: Option<MachineFormat>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Delimit {
+    None,
+    Separate,
+}
+
+/// Where `--group` puts blank lines besides between groups
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+enum Group {
+    Separate,
+    Prepend,
+    Append,
+    Both,
 }
 
 fn run(args: Args) -> Result<()> {
-    let mut file = open(&args.in_file).map_err(|e| anyhow!("{}: {e}", args.in_file))?;
+    if args.tab_separated && !args.count {
+        eprintln!("warning: --tab-separated has no effect without --count");
+    }
+    let file = open(&args.in_file).map_err(|e| anyhow!("{}: {e}", args.in_file))?;
 
-    let mut out_file: Box<dyn Write> = match &args.out_file {
-        Some(out_name) => Box::new(File::create(out_name)?),
+    // --in-place writes beside IN_FILE, and only replaces it once done
+    let temp_file = if args.in_place {
+        if args.in_file == "-" {
+            bail!("--in-place needs an IN_FILE to replace");
+        }
+        let dir = match Path::new(&args.in_file).parent() {
+            Some(dir) if dir != Path::new("") => dir,
+            _ => Path::new("."),
+        };
+        Some(NamedTempFile::new_in(dir)?)
+    } else {
+        None
+    };
+    let out_file: Box<dyn Write> = match (&temp_file, &args.out_file) {
+        (Some(temp_file), _) => Box::new(temp_file.as_file()),
+        (_, Some(out_name)) => Box::new(File::create(out_name)?),
         _ => Box::new(io::stdout()),
     };
+    uniq(&args, file, out_file)?;
+    if let Some(temp_file) = temp_file {
+        replace_file(temp_file, &args.in_file)?;
+    }
+    Ok(())
+}
 
+/// Reads records from `file`, and writes each run of them with the same
+/// key to `out_file` as `args` says to.
+fn uniq(args: &Args, mut file: impl BufRead, mut out_file: impl Write) -> Result<()> {
+    let delimiter = if args.zero_terminated { b'\0' } else { b'\n' };
     let mut line = String::new();
     let mut previous = String::new();
     let mut count: u64 = 0;
+    let mut line_num: u64 = 0;
+    // Every copy of the current run, kept only for --all-repeated and --group
+    let keep_copies = args.all_repeated.is_some() || args.group.is_some();
+    let mut copies: Vec<String> = vec![];
+    let mut printed_group = false;
+    let mut records = vec![];
 
-    let mut print = |num: u64, text: &str| -> Result<()> {
-        if num > 0 {
-            if args.count {
-                write!(out_file, "{num:>4} {text}")?;
-            } else {
-                write!(out_file, "{text}")?;
+    let mut print = |num: u64, text: &str, copies: &[String]| -> Result<()> {
+        if num == 0
+            || args.check
+            || ((args.repeated || args.all_repeated.is_some()) && num == 1)
+            || (args.unique && num > 1)
+        {
+            return Ok(());
+        }
+        if args.machine_readable.is_some() {
+            let line = text.strip_suffix(char::from(delimiter)).unwrap_or(text);
+            records.push(UniqrRecord {
+                count: num,
+                line: line.to_string(),
+            });
+            return Ok(());
+        }
+        if let Some(method) = args.group {
+            if printed_group || matches!(method, Group::Prepend | Group::Both) {
+                out_file.write_all(&[delimiter])?;
+            }
+            for copy in copies {
+                write!(out_file, "{copy}")?;
             }
+            printed_group = true;
+            return Ok(());
+        }
+        match args.all_repeated {
+            Some(method) => {
+                if method == Delimit::Separate && printed_group {
+                    out_file.write_all(&[delimiter])?;
+                }
+                for copy in copies {
+                    write!(out_file, "{copy}")?;
+                }
+                printed_group = true;
+            }
+            None if args.count && args.tab_separated => write!(out_file, "{num}\t{text}")?,
+            None if args.count => write!(out_file, "{num:>4} {text}")?,
+            None => write!(out_file, "{text}")?,
         }
         Ok(())
     };
+    let key = |text: &str| -> String {
+        let text = text.strip_suffix(char::from(delimiter)).unwrap_or(text);
+        let text = text.trim_end();
+        let text = match args.key_field {
+            Some(n) => extract_field(text, args.delimiter, n as usize),
+            None => skip_fields(text, args.skip_fields.unwrap_or(0)),
+        };
+        let key: String = text
+            .chars()
+            .skip(args.skip_chars.unwrap_or(0))
+            .take(args.check_chars.unwrap_or(usize::MAX))
+            .collect();
+        if args.ignore_case {
+            key.to_lowercase()
+        } else {
+            key
+        }
+    };
     loop {
-        let bytes = file.read_line(&mut line)?;
+        let bytes = read_record(&mut file, delimiter, &mut line)?;
         if bytes == 0 {
             break;
         }
+        line_num += 1;
 
-        if line.trim_end() != previous.trim_end() {
+        if count == 0 || key(&line) != key(&previous) {
             if count > 0 {
-                print(count, &previous)?;
+                print(count, &previous, &copies)?;
             }
             previous = line.clone();
+            copies.clear();
             count = 0;
         }
+        if args.check && count > 0 {
+            let text = line.strip_suffix(char::from(delimiter)).unwrap_or(&line);
+            bail!("{}:{line_num}: duplicate: {text}", args.in_file);
+        }
         count += 1;
+        if keep_copies {
+            copies.push(line.clone());
+        }
         line.clear();
     }
 
     if count > 0 {
-        print(count, &previous)?;
+        print(count, &previous, &copies)?;
+    }
+    if printed_group && matches!(args.group, Some(Group::Append | Group::Both)) {
+        out_file.write_all(&[delimiter])?;
     }
+    if let Some(format) = args.machine_readable {
+        MachineOutput::Uniqr(records).write(format, &mut out_file)?;
+    }
+    Ok(())
+}
+
+/// Puts the finished `temp_file` in place of `filename`, keeping the
+/// permissions of the original. Renaming over a file isn't atomic on
+/// Windows, so there the output is copied over the original instead.
+fn replace_file(temp_file: NamedTempFile, filename: &str) -> Result<()> {
+    let permissions = fs::metadata(filename)?.permissions();
+    fs::set_permissions(temp_file.path(), permissions)?;
+    #[cfg(not(windows))]
+    temp_file.persist(filename)?;
+    #[cfg(windows)]
+    io::copy(&mut temp_file.reopen()?, &mut File::create(filename)?)?;
     Ok(())
 }
 
-fn open(filename: &str) -> Result<Box<dyn BufRead>> {
-    match filename {
-        "-" => Ok(Box::new(BufReader::new(io::stdin()))),
-        _ => Ok(Box::new(BufReader::new(File::open(filename)?))),
+/// Appends the next record, up to and including `delimiter`, to `record`.
+/// This is `read_line` with a choice of line ending.
+fn read_record(file: &mut impl BufRead, delimiter: u8, record: &mut String) -> Result<usize> {
+    let mut bytes = vec![];
+    let num_bytes = file.read_until(delimiter, &mut bytes)?;
+    record.push_str(std::str::from_utf8(&bytes)?);
+    Ok(num_bytes)
+}
+
+/// Drops the first `n` blank-separated fields from `line`. As with GNU
+/// `uniq`, the blanks before the next field are kept.
+fn skip_fields(line: &str, n: usize) -> &str {
+    let mut rest = line;
+    for _ in 0..n {
+        let field = rest.trim_start_matches([' ', '\t']);
+        let end = field.find([' ', '\t']).unwrap_or(field.len());
+        rest = &field[end..];
     }
+    rest
+}
+
+/// The `n`th field of `line` (from 1) split on `delim`, or "" when it has
+/// fewer fields.
+fn extract_field(line: &str, delim: char, n: usize) -> &str {
+    line.split(delim).nth(n - 1).unwrap_or("")
 }
 
 fn main() {
-    if let Err(e) = run(Args::parse()) {
-        eprintln!("{}", e);
-        std::process::exit(1);
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Args, uniq};
+    use clap::Parser;
+    use proptest::prelude::*;
+    use std::io::Cursor;
+
+    /// The most lines an input is made of
+    const MAX_LINES: usize = 64;
+
+    fn run_uniq(args: &[&str], input: &str) -> String {
+        let args = Args::parse_from(["uniqr"].iter().chain(args));
+        let mut output = vec![];
+        uniq(&args, Cursor::new(input), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    /// Lines of a few characters, so that many repeat, or of anything, with
+    /// or without a newline at the end
+    fn input() -> impl Strategy<Value = String> {
+        let line = prop_oneof!["[aAb \t]{0,3}", "[^\n]{0,20}"];
+        (prop::collection::vec(line, 0..MAX_LINES), any::<bool>()).prop_map(|(lines, newline)| {
+            let mut input = lines.join("\n");
+            if newline && !input.is_empty() {
+                input.push('\n');
+            }
+            input
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn uniq_is_idempotent(input in input()) {
+            for args in [&[][..], &["-i"]] {
+                let once = run_uniq(args, &input);
+                prop_assert_eq!(run_uniq(args, &once), once);
+            }
+        }
+
+        #[test]
+        fn counts_add_up_to_lines(input in input()) {
+            let output = run_uniq(&["-c"], &input);
+            let total: u64 = output
+                .split_inclusive('\n')
+                .map(|line| line.split_whitespace().next().unwrap().parse::<u64>().unwrap())
+                .sum();
+            prop_assert_eq!(total, input.split_inclusive('\n').count() as u64);
+        }
     }
 }
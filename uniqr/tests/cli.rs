@@ -617,3 +617,656 @@ fn t6_outfile_count() -> Result<()> {
 fn t6_stdin_outfile_count() -> Result<()> {
     run_stdin_outfile_count(&T6)
 }
+
+// --------------------------------------------------
+fn run_args(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output().expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn three_repeated() -> Result<()> {
+    run_args(
+        &["-d", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.d.out",
+    )?;
+    run_args(
+        &["--repeated", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.d.out",
+    )
+}
+
+#[test]
+fn t6_repeated() -> Result<()> {
+    run_args(
+        &["-d", "tests/inputs/t6.txt"],
+        "tests/expected/t6.txt.d.out",
+    )
+}
+
+#[test]
+fn three_all_repeated() -> Result<()> {
+    run_args(
+        &["-D", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.D.out",
+    )?;
+    run_args(
+        &["--all-repeated=none", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.D.out",
+    )
+}
+
+#[test]
+fn three_all_repeated_separate() -> Result<()> {
+    run_args(
+        &["--all-repeated=separate", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.D-separate.out",
+    )
+}
+
+#[test]
+fn dies_all_repeated_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-D", "-c", "tests/inputs/three.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn abc_unique() -> Result<()> {
+    run_args(
+        &["-u", "tests/inputs/abc.txt"],
+        "tests/expected/abc.txt.u.out",
+    )?;
+    run_args(
+        &["--unique", "tests/inputs/abc.txt"],
+        "tests/expected/abc.txt.u.out",
+    )
+}
+
+#[test]
+fn abc_unique_count() -> Result<()> {
+    run_args(
+        &["-u", "-c", "tests/inputs/abc.txt"],
+        "tests/expected/abc.txt.uc.out",
+    )
+}
+
+#[test]
+fn abc_repeated() -> Result<()> {
+    run_args(
+        &["-d", "tests/inputs/abc.txt"],
+        "tests/expected/abc.txt.d.out",
+    )
+}
+
+#[test]
+fn three_unique() -> Result<()> {
+    run_args(
+        &["-u", "tests/inputs/three.txt"],
+        "tests/expected/three.txt.u.out",
+    )
+}
+
+#[test]
+fn dies_unique_repeated() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-u", "-d", "tests/inputs/abc.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--unique' cannot be used with '--repeated'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn case_sensitive_by_default() -> Result<()> {
+    run_args(&["tests/inputs/case.txt"], "tests/expected/case.txt.out")
+}
+
+#[test]
+fn case_ignore_case() -> Result<()> {
+    run_args(
+        &["-i", "tests/inputs/case.txt"],
+        "tests/expected/case.txt.i.out",
+    )?;
+    run_args(
+        &["--ignore-case", "tests/inputs/case.txt"],
+        "tests/expected/case.txt.i.out",
+    )
+}
+
+#[test]
+fn case_ignore_case_count() -> Result<()> {
+    run_args(
+        &["-i", "-c", "tests/inputs/case.txt"],
+        "tests/expected/case.txt.ic.out",
+    )
+}
+
+#[test]
+fn case_ignore_case_repeated() -> Result<()> {
+    run_args(
+        &["-i", "-d", "tests/inputs/case.txt"],
+        "tests/expected/case.txt.id.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn blank_first_line() -> Result<()> {
+    run_args(&["tests/inputs/blank.txt"], "tests/expected/blank.txt.out")?;
+    run_args(
+        &["-c", "tests/inputs/blank.txt"],
+        "tests/expected/blank.txt.c.out",
+    )
+}
+
+#[test]
+fn records_skip_chars() -> Result<()> {
+    run_args(
+        &["-s", "4", "tests/inputs/records.txt"],
+        "tests/expected/records.txt.s4.out",
+    )?;
+    run_args(
+        &["--skip-chars=11", "tests/inputs/records.txt"],
+        "tests/expected/records.txt.s11.out",
+    )
+}
+
+#[test]
+fn records_skip_chars_past_end() -> Result<()> {
+    run_args(
+        &["-s", "100", "tests/inputs/records.txt"],
+        "tests/expected/records.txt.s100.out",
+    )
+}
+
+#[test]
+fn records_check_chars() -> Result<()> {
+    run_args(
+        &["--check-chars", "2", "tests/inputs/records.txt"],
+        "tests/expected/records.txt.w2.out",
+    )
+}
+
+#[test]
+fn records_skip_and_check_chars() -> Result<()> {
+    run_args(
+        &["-s4", "-w6", "tests/inputs/records.txt"],
+        "tests/expected/records.txt.s4w6.out",
+    )?;
+    run_args(
+        &["-s4", "-w6", "-i", "tests/inputs/records.txt"],
+        "tests/expected/records.txt.s4w6i.out",
+    )
+}
+
+#[test]
+fn dies_bad_skip_chars() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-s", "foo", "tests/inputs/records.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'foo'"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn log_skip_fields() -> Result<()> {
+    run_args(&["-f1", "tests/inputs/log.txt"], "tests/expected/log.txt.f1.out")?;
+    run_args(
+        &["--skip-fields", "2", "tests/inputs/log.txt"],
+        "tests/expected/log.txt.f2.out",
+    )
+}
+
+#[test]
+fn log_skip_fields_past_end() -> Result<()> {
+    run_args(&["-f5", "tests/inputs/log.txt"], "tests/expected/log.txt.f5.out")
+}
+
+#[test]
+fn log_skip_fields_then_chars() -> Result<()> {
+    // the blank before the second field is a space on one line and a tab
+    // on the next, so they only match once it is skipped with -s
+    run_args(
+        &["-f1", "-s1", "tests/inputs/log.txt"],
+        "tests/expected/log.txt.f1s1.out",
+    )
+}
+
+#[test]
+fn log_skip_fields_ignore_case() -> Result<()> {
+    run_args(
+        &["-f1", "-i", "tests/inputs/log.txt"],
+        "tests/expected/log.txt.f1i.out",
+    )
+}
+
+#[test]
+fn log_skip_fields_repeated() -> Result<()> {
+    run_args(
+        &["-f1", "-d", "tests/inputs/log.txt"],
+        "tests/expected/log.txt.f1d.out",
+    )
+}
+
+// --------------------------------------------------
+// NUL-separated paths, as printed by `find . -print0`; one name has a
+// newline in it
+const PRINT0: &[u8] = b"./a\0./b\0./b\0./c d\n\0./c d\n\0./a";
+
+fn run_zero(args: &[&str], expected: &[u8]) -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .write_stdin(PRINT0)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+    assert_eq!(output.stdout, expected);
+    Ok(())
+}
+
+#[test]
+fn zero_terminated() -> Result<()> {
+    run_zero(&["-z"], b"./a\0./b\0./c d\n\0./a")?;
+    run_zero(&["--zero-terminated"], b"./a\0./b\0./c d\n\0./a")
+}
+
+#[test]
+fn zero_terminated_count() -> Result<()> {
+    run_zero(
+        &["-z", "-c"],
+        b"   1 ./a\0   2 ./b\0   2 ./c d\n\0   1 ./a",
+    )
+}
+
+#[test]
+fn zero_terminated_all_repeated_separate() -> Result<()> {
+    run_zero(
+        &["-z", "--all-repeated=separate"],
+        b"./b\0./b\0\0./c d\n\0./c d\n\0",
+    )
+}
+
+#[test]
+fn nul_is_ordinary_text_by_default() -> Result<()> {
+    run_zero(&[], b"./a\0./b\0./b\0./c d\n\0./c d\n\0./a")
+}
+
+// --------------------------------------------------
+#[test]
+fn groups_group_separate() -> Result<()> {
+    run_args(
+        &["--group", "tests/inputs/groups.txt"],
+        "tests/expected/groups.txt.group-separate.out",
+    )?;
+    run_args(
+        &["--group=separate", "tests/inputs/groups.txt"],
+        "tests/expected/groups.txt.group-separate.out",
+    )
+}
+
+#[test]
+fn groups_group_prepend() -> Result<()> {
+    run_args(
+        &["--group=prepend", "tests/inputs/groups.txt"],
+        "tests/expected/groups.txt.group-prepend.out",
+    )
+}
+
+#[test]
+fn groups_group_append() -> Result<()> {
+    run_args(
+        &["--group=append", "tests/inputs/groups.txt"],
+        "tests/expected/groups.txt.group-append.out",
+    )
+}
+
+#[test]
+fn groups_group_both() -> Result<()> {
+    run_args(
+        &["--group=both", "tests/inputs/groups.txt"],
+        "tests/expected/groups.txt.group-both.out",
+    )
+}
+
+#[test]
+fn empty_group_both() -> Result<()> {
+    run_args(
+        &["--group=both", "tests/inputs/empty.txt"],
+        "tests/expected/empty.txt.out",
+    )
+}
+
+#[test]
+fn dies_group_repeated_unique() -> Result<()> {
+    for flag in ["-d", "-u"] {
+        Command::cargo_bin(PRG)?
+            .args(["--group", flag, "tests/inputs/groups.txt"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn check_no_duplicates() -> Result<()> {
+    for file in ["tests/inputs/empty.txt", "tests/inputs/one.txt", "tests/inputs/t6.txt"] {
+        Command::cargo_bin(PRG)?
+            .args(["--check", file])
+            .assert()
+            .success()
+            .stdout("")
+            .stderr("");
+    }
+    // the key options decide what counts as a duplicate
+    Command::cargo_bin(PRG)?
+        .args(["--check", "tests/inputs/case.txt"])
+        .assert()
+        .success();
+    Ok(())
+}
+
+#[test]
+fn check_duplicates() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--check", "tests/inputs/t5.txt"])
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("tests/inputs/t5.txt:3: duplicate: a\n");
+    // a last line without a newline still repeats the one before
+    Command::cargo_bin(PRG)?
+        .args(["--check", "tests/inputs/t2.txt"])
+        .assert()
+        .code(1)
+        .stderr("tests/inputs/t2.txt:2: duplicate: a\n");
+    Command::cargo_bin(PRG)?
+        .args(["--check", "-i"])
+        .write_stdin("Hello\nworld\nWORLD\nworld\n")
+        .assert()
+        .code(1)
+        .stderr("-:3: duplicate: WORLD\n");
+    Ok(())
+}
+
+#[test]
+fn dies_check_with_output() -> Result<()> {
+    for flag in ["-c", "-d", "-u", "-D", "--group", "--in-place"] {
+        Command::cargo_bin(PRG)?
+            .args(["--check", flag, "tests/inputs/t1.txt"])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
+
+// --------------------------------------------------
+fn run_in_place(test: &Test, args: &[&str], expected_file: &str) -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("input.txt");
+    fs::copy(test.input, &path)?;
+
+    Command::cargo_bin(PRG)?
+        .args(args)
+        .arg("--in-place")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("");
+    let expected = fs::read_to_string(expected_file)?;
+    assert_eq!(expected, fs::read_to_string(&path)?);
+
+    // nothing but the rewritten file is left behind
+    assert_eq!(fs::read_dir(dir.path())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn in_place() -> Result<()> {
+    for test in [EMPTY, ONE, THREE, T6] {
+        run_in_place(&test, &[], test.out)?;
+        run_in_place(&test, &["-c"], test.out_count)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn in_place_relative() -> Result<()> {
+    // a bare filename puts the temporary file in the working directory
+    let dir = tempfile::tempdir()?;
+    fs::copy(THREE.input, dir.path().join("three.txt"))?;
+    Command::cargo_bin(PRG)?
+        .current_dir(dir.path())
+        .args(["--in-place", "three.txt"])
+        .assert()
+        .success();
+    let expected = fs::read_to_string(THREE.out)?;
+    assert_eq!(expected, fs::read_to_string(dir.path().join("three.txt"))?);
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn in_place_keeps_permissions() -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("input.txt");
+    fs::copy(THREE.input, &path)?;
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o640))?;
+    Command::cargo_bin(PRG)?
+        .arg("--in-place")
+        .arg(&path)
+        .assert()
+        .success();
+    assert_eq!(fs::metadata(&path)?.permissions().mode() & 0o777, 0o640);
+    Ok(())
+}
+
+#[test]
+fn in_place_error_keeps_file() -> Result<()> {
+    // invalid UTF-8 stops uniqr partway, before the file is replaced
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("input.txt");
+    let contents = b"a\na\nb\n\xff\nc\n";
+    fs::write(&path, contents)?;
+    Command::cargo_bin(PRG)?
+        .arg("--in-place")
+        .arg(&path)
+        .assert()
+        .failure();
+    assert_eq!(fs::read(&path)?, contents);
+    assert_eq!(fs::read_dir(dir.path())?.count(), 1);
+    Ok(())
+}
+
+#[test]
+fn dies_in_place_with_out_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--in-place", THREE.input, "out.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+#[test]
+fn dies_in_place_stdin() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg("--in-place")
+        .write_stdin("a\na\n")
+        .assert()
+        .failure()
+        .stderr("--in-place needs an IN_FILE to replace\n");
+    Ok(())
+}
+
+#[test]
+fn tab_separated() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["-cT", THREE.input])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"2\ta\n2\tb\n1\ta\n3\tc\n1\ta\n4\td\n");
+    // each count starts its line, unpadded, with a tab straight after it
+    for line in output.stdout.split_inclusive(|&b| b == b'\n') {
+        assert_eq!(line.iter().position(|&b| b == b'\t'), Some(1));
+    }
+    Ok(())
+}
+
+#[test]
+fn tab_separated_wide_count() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["--count", "--tab-separated"])
+        .write_stdin(format!("{}y z\n", "x\n".repeat(12)))
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"12\tx\n1\ty z\n");
+    let tabs: Vec<_> = output
+        .stdout
+        .iter()
+        .enumerate()
+        .filter(|(_, b)| **b == b'\t')
+        .map(|(i, _)| i)
+        .collect();
+    assert_eq!(tabs, [2, 6]);
+    Ok(())
+}
+
+#[test]
+fn tab_separated_without_count() -> Result<()> {
+    let expected = fs::read_to_string(THREE.out)?;
+    Command::cargo_bin(PRG)?
+        .args(["-T", THREE.input])
+        .assert()
+        .success()
+        .stdout(expected)
+        .stderr("warning: --tab-separated has no effect without --count\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn key_field() -> Result<()> {
+    // only the fruit is compared, and the first line of each run is printed
+    run_args(
+        &["--key-field=2", "tests/inputs/fields.tsv"],
+        "tests/expected/fields.tsv.k2.out",
+    )?;
+    run_args(
+        &["--key-field=2", "-c", "tests/inputs/fields.tsv"],
+        "tests/expected/fields.tsv.k2.c.out",
+    )
+}
+
+#[test]
+fn key_field_missing() -> Result<()> {
+    // lines without a third field all have an empty key
+    run_args(
+        &["--key-field", "3", "tests/inputs/fields.tsv"],
+        "tests/expected/fields.tsv.k3.out",
+    )
+}
+
+#[test]
+fn key_field_delimiter() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--key-field=1", "--delimiter=,", "-i"])
+        .write_stdin("a,1\nA,2\nb,3\na\tb,4\n")
+        .assert()
+        .success()
+        .stdout("a,1\nb,3\na\tb,4\n");
+    Ok(())
+}
+
+#[test]
+fn dies_key_field_zero() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--key-field=0", "tests/inputs/fields.tsv"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value '0'"));
+    Ok(())
+}
+
+#[test]
+fn dies_delimiter_without_key_field() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--delimiter=,", "tests/inputs/fields.tsv"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--key-field"));
+    Ok(())
+}
+
+#[test]
+fn machine_readable_json() -> Result<()> {
+    let expected = concat!(
+        r#"{"schema_version":1,"tool":"uniqr","records":["#,
+        r#"{"count":3,"line":"Hello"},{"count":1,"line":"world"},"#,
+        r#"{"count":3,"line":"Foo"},{"count":3,"line":"bar"}]}"#,
+        "\n"
+    );
+    Command::cargo_bin(PRG)?
+        .args(["-i", "--machine-readable", "json", "tests/inputs/case.txt"])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+#[test]
+fn machine_readable_csv() -> Result<()> {
+    // -d and -u still choose which lines are printed
+    Command::cargo_bin(PRG)?
+        .args(["-u", "-i", "--machine-readable=csv", "tests/inputs/case.txt"])
+        .assert()
+        .success()
+        .stdout("count,line\n1,world\n");
+    Command::cargo_bin(PRG)?
+        .args(["--machine-readable=csv", "tests/inputs/empty.txt"])
+        .assert()
+        .success()
+        .stdout("count,line\n");
+    Ok(())
+}
+
+#[test]
+fn dies_machine_readable_and_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-c", "--machine-readable=json", "tests/inputs/case.txt"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
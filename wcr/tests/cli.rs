@@ -3,12 +3,19 @@ use assert_cmd::Command;
 use predicates::prelude::*;
 use pretty_assertions::assert_eq;
 use rand::{distributions::Alphanumeric, Rng};
-use std::fs;
+use std::{fs, io::Write};
 
 const PRG: &str = "wcr";
 const EMPTY: &str = "tests/inputs/empty.txt";
 const FOX: &str = "tests/inputs/fox.txt";
 const ATLAMAL: &str = "tests/inputs/atlamal.txt";
+const LENGTHS: &str = "tests/inputs/lengths.txt";
+const EMOJI: &str = "tests/inputs/emoji.txt";
+const PARAGRAPHS: &str = "tests/inputs/paragraphs.txt";
+const ONE_PARAGRAPH: &str = "tests/inputs/one_paragraph.txt";
+const PADDED: &str = "tests/inputs/padded.txt";
+const SENTENCES: &str = "tests/inputs/sentences.txt";
+const RECORDS: &str = "tests/inputs/records.bin";
 
 // --------------------------------------------------
 fn gen_bad_file() -> String {
@@ -218,3 +225,842 @@ fn test_all_words_lines() -> Result<()> {
 fn test_all_bytes_lines() -> Result<()> {
     run(&["-cl", EMPTY, FOX, ATLAMAL], "tests/expected/all.cl.out")
 }
+
+// --------------------------------------------------
+#[test]
+fn empty_max_line_length() -> Result<()> {
+    run(&["-L", EMPTY], "tests/expected/empty.txt.L.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_max_line_length() -> Result<()> {
+    run(&["--max-line-length", FOX], "tests/expected/fox.txt.L.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn lengths_max_line_length() -> Result<()> {
+    run(&["-L", LENGTHS], "tests/expected/lengths.txt.L.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_lines_words_max_line_length() -> Result<()> {
+    run(
+        &["-lwL", EMPTY, FOX, ATLAMAL, LENGTHS],
+        "tests/expected/all.lwL.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from_file() -> Result<()> {
+    run(
+        &["--files0-from", "tests/inputs/files0.txt"],
+        "tests/expected/all.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn files0_from_stdin() -> Result<()> {
+    let bad = gen_bad_file();
+    let input = format!("{FOX}\0{bad}\0-\0{ATLAMAL}");
+    let expected = fs::read_to_string("tests/expected/fox.atlamal.out")?;
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["--files0-from=-", "-l", "-w", "-c"])
+        .write_stdin(input)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+
+    let stderr = String::from_utf8(output.stderr).expect("invalid UTF-8");
+    assert!(predicate::str::is_match(format!("{bad}: .* [(]os error 2[)]"))?.eval(&stderr));
+    assert!(stderr.contains("no file name of '-' allowed"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files0_from_and_files() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", "tests/inputs/files0.txt", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files0_from_bad_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let expected = format!("{bad}: .* [(]os error 2[)]");
+    Command::cargo_bin(PRG)?
+        .args(["--files0-from", &bad])
+        .assert()
+        .failure()
+        .stderr(predicate::str::is_match(expected)?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_graphemes_and_chars() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-g", "-m"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--graphemes' cannot be used with '--chars'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_graphemes_and_bytes() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--graphemes", "--bytes"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--graphemes' cannot be used with '--bytes'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn emoji_graphemes() -> Result<()> {
+    run(&["--graphemes", EMOJI], "tests/expected/emoji.txt.g.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn emoji_chars() -> Result<()> {
+    run(&["-m", EMOJI], "tests/expected/emoji.txt.m.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn emoji_bytes() -> Result<()> {
+    run(&["-c", EMOJI], "tests/expected/emoji.txt.c.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn emoji_lines_words_graphemes() -> Result<()> {
+    run(&["-lwg", EMOJI], "tests/expected/emoji.txt.lwg.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn emoji_fox_graphemes() -> Result<()> {
+    run(&["-g", EMOJI, FOX], "tests/expected/emoji.fox.g.out")
+}
+
+// --------------------------------------------------
+#[cfg(feature = "rayon")]
+#[test]
+fn parallel_matches_sequential() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let mut files = vec![];
+    for i in 0..200 {
+        let path = dir.path().join(format!("{i:03}.txt"));
+        fs::write(&path, "word ".repeat(i) + &"\n".repeat(i % 7))?;
+        files.push(path.display().to_string());
+    }
+    files.push(gen_bad_file());
+
+    let sequential = Command::cargo_bin(PRG)?.args(&files).output()?;
+    let parallel = Command::cargo_bin(PRG)?
+        .arg("--parallel")
+        .args(&files)
+        .output()?;
+    assert!(parallel.status.success());
+    assert_eq!(
+        String::from_utf8(parallel.stdout)?,
+        String::from_utf8(sequential.stdout)?
+    );
+    assert_eq!(
+        String::from_utf8(parallel.stderr)?,
+        String::from_utf8(sequential.stderr)?
+    );
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_json() -> Result<()> {
+    run(&["--json", FOX], "tests/expected/fox.txt.json.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn test_all_json() -> Result<()> {
+    run(&["--json", EMPTY, FOX, ATLAMAL], "tests/expected/all.json.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn json_round_trip() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["--json", EMPTY, FOX, ATLAMAL])
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    let entries = value.as_array().expect("an array of entries");
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[1]["file"], FOX);
+    assert_eq!(entries[1]["lines"], 1);
+    assert_eq!(entries[1]["words"], 9);
+    assert_eq!(entries[1]["bytes"], 48);
+    assert_eq!(entries[1]["chars"], 48);
+
+    // the total entry sums every other entry
+    assert_eq!(entries[3]["file"], "total");
+    for key in ["lines", "words", "bytes", "chars"] {
+        let sum: u64 = entries[..3].iter().map(|e| e[key].as_u64().unwrap()).sum();
+        assert_eq!(entries[3][key], sum);
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_json_and_lines() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--json", "-l", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--json' cannot be used with '--lines'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn human_readable() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let kib = dir.path().join("kib.txt");
+    fs::write(&kib, "a\n".repeat(512))?;
+    let kib = kib.display().to_string();
+    let expected = format!("     512     512   1.0Ki {kib}\n");
+
+    for flag in ["-h", "--human-readable"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, &kib])
+            .assert()
+            .success()
+            .stdout(expected.clone());
+    }
+
+    Command::cargo_bin(PRG)?
+        .args(["-h", "--si", &kib])
+        .assert()
+        .success()
+        .stdout(format!("     512     512    1.0k {kib}\n"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn human_readable_small_counts_unchanged() -> Result<()> {
+    run(&["-h", ATLAMAL], "tests/expected/atlamal.txt.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn human_readable_json_is_raw() -> Result<()> {
+    let dir = tempfile::tempdir()?;
+    let path = dir.path().join("big.txt");
+    fs::write(&path, "word\n".repeat(1000))?;
+
+    let output = Command::cargo_bin(PRG)?
+        .args(["--json", "-h", "--si"])
+        .arg(&path)
+        .output()?;
+    assert!(output.status.success());
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(value["lines"], 1000);
+    assert_eq!(value["bytes"], 5000);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_si_without_human_readable() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--si", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--human-readable"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_name() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--stdin-name", "input"])
+        .write_stdin(fs::read_to_string(FOX)?)
+        .assert()
+        .success()
+        .stdout("       1       9      48 input\n");
+
+    // an empty name or - leave stdin unlabeled, as without the option
+    for name in ["", "-"] {
+        Command::cargo_bin(PRG)?
+            .arg(format!("--stdin-name={name}"))
+            .write_stdin(fs::read_to_string(FOX)?)
+            .assert()
+            .success()
+            .stdout("       1       9      48\n");
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_name_with_files() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--stdin-name=<stdin>", FOX, "-", ATLAMAL])
+        .write_stdin(fs::read_to_string(FOX)?)
+        .assert()
+        .success()
+        .stdout(fs::read_to_string("tests/expected/fox.stdin.atlamal.out")?);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn stdin_name_json() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--json", "--stdin-name", "input"])
+        .write_stdin(fs::read_to_string(FOX)?)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""file":"input""#));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn long_help() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("--human-readable"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_histogram() -> Result<()> {
+    run(
+        &["--histogram", FOX],
+        "tests/expected/fox.txt.histogram.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn fox_histogram_top_bar() -> Result<()> {
+    run(
+        &["--histogram", "--top", "5", "--bar", FOX],
+        "tests/expected/fox.txt.histogram.top5.bar.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn emoji_histogram_names() -> Result<()> {
+    run(
+        &["--histogram", "--top", "8", EMOJI],
+        "tests/expected/emoji.txt.histogram.top8.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn histogram_multiple_files() -> Result<()> {
+    run(
+        &["--histogram", "--bar", "--top", "4", ATLAMAL, FOX],
+        "tests/expected/atlamal.fox.histogram.top4.bar.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn empty_histogram() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--histogram", EMPTY])
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_top_without_histogram() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--top", "3", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--histogram"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_histogram_and_json() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--histogram", "--json", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--histogram' cannot be used with '--json'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files_from_file() -> Result<()> {
+    let bad = gen_bad_file();
+    let mut list = tempfile::NamedTempFile::new()?;
+    write!(list, "{EMPTY}\n\n{FOX}  \n{bad}\n{ATLAMAL}\n")?;
+    let list = list.path().display().to_string();
+
+    for flag in ["-T", "--files-from"] {
+        let output = Command::cargo_bin(PRG)?
+            .args([flag, &list])
+            .output()
+            .expect("fail");
+        assert!(output.status.success());
+
+        let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+        assert_eq!(stdout, fs::read_to_string("tests/expected/all.out")?);
+
+        let stderr = String::from_utf8(output.stderr).expect("invalid UTF-8");
+        assert!(predicate::str::is_match(format!("{bad}: .* [(]os error 2[)]"))?.eval(&stderr));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn files_from_stdin() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["--files-from=-", "-l", "-w", "-c"])
+        .write_stdin(format!("{FOX}\n-\n{ATLAMAL}\n"))
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, fs::read_to_string("tests/expected/fox.atlamal.out")?);
+
+    let stderr = String::from_utf8(output.stderr).expect("invalid UTF-8");
+    assert!(stderr.contains("no file name of '-' allowed"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_files_from_and_files() -> Result<()> {
+    for other in [FOX, "--files0-from=tests/inputs/files0.txt"] {
+        Command::cargo_bin(PRG)?
+            .args(["--files-from", "tests/inputs/files0.txt", other])
+            .assert()
+            .failure()
+            .stderr(predicate::str::contains("cannot be used with"));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn paragraphs() -> Result<()> {
+    // -p shows lines, words and paragraphs in place of bytes
+    run(&["-p", PARAGRAPHS], "tests/expected/paragraphs.txt.p.out")?;
+    run(
+        &["--paragraph", PARAGRAPHS],
+        "tests/expected/paragraphs.txt.p.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn paragraphs_no_blank_lines() -> Result<()> {
+    run(
+        &["-p", ONE_PARAGRAPH],
+        "tests/expected/one_paragraph.txt.p.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn paragraphs_padded() -> Result<()> {
+    // blank lines at the start and end add no paragraphs
+    run(&["-p", PADDED], "tests/expected/padded.txt.p.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn paragraphs_with_other_counts() -> Result<()> {
+    run(
+        &["-p", "-c", PARAGRAPHS],
+        "tests/expected/paragraphs.txt.pc.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn paragraphs_total() -> Result<()> {
+    run(
+        &["-p", PARAGRAPHS, ONE_PARAGRAPH, PADDED, EMPTY],
+        "tests/expected/paragraphs.all.p.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
+
+// --------------------------------------------------
+// Counts a gzipped copy of each input and checks them against the
+// expected output for the uncompressed file, renamed.
+fn run_gzip(args: &[&str], inputs: &[&str], expected_file: &str) -> Result<()> {
+    let mut expected = fs::read_to_string(expected_file)?;
+    let mut files = vec![];
+    for input in inputs {
+        let gz = format!("{input}.gz");
+        expected = expected.replace(&format!(" {input}\n"), &format!(" {gz}\n"));
+        files.push(gz);
+    }
+    let output = Command::cargo_bin(PRG)?
+        .args(args)
+        .args(&files)
+        .output()
+        .expect("fail");
+    assert!(output.status.success());
+
+    let stdout = String::from_utf8(output.stdout).expect("invalid UTF-8");
+    assert_eq!(stdout, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn gzip_by_extension() -> Result<()> {
+    run_gzip(&[], &[FOX], "tests/expected/fox.txt.out")?;
+    run_gzip(&[], &[EMPTY], "tests/expected/empty.txt.out")?;
+    run_gzip(&["-l"], &[ATLAMAL], "tests/expected/atlamal.txt.l.out")?;
+    run_gzip(&["-m"], &[ATLAMAL], "tests/expected/atlamal.txt.m.out")?;
+    run_gzip(&["-wc"], &[ATLAMAL], "tests/expected/atlamal.txt.wc.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn gzip_all() -> Result<()> {
+    for flag in ["-z", "--gzip"] {
+        run_gzip(&[flag], &[EMPTY, FOX, ATLAMAL], "tests/expected/all.out")?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn gzip_stdin() -> Result<()> {
+    let input = fs::read(format!("{ATLAMAL}.gz"))?;
+    let expected = fs::read_to_string("tests/expected/atlamal.txt.stdin.out")?;
+    Command::cargo_bin(PRG)?
+        .arg("-z")
+        .write_stdin(input)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn gzip_bad_magic() -> Result<()> {
+    // a file that isn't gzipped is reported, and counting carries on
+    Command::cargo_bin(PRG)?
+        .args(["-z", FOX, &format!("{FOX}.gz"), ATLAMAL])
+        .assert()
+        .success()
+        .stdout(
+            "       1       9      48 tests/inputs/fox.txt.gz\n       1       9      48 total\n",
+        )
+        .stderr(format!(
+            "{FOX}: invalid gzip header\n{ATLAMAL}: invalid gzip header\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn total_always() -> Result<()> {
+    run(&["--total=always", FOX], "tests/expected/fox.txt.total.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn total_never() -> Result<()> {
+    run(
+        &["--total", "never", FOX, ATLAMAL],
+        "tests/expected/fox.atlamal.nototal.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn total_auto() -> Result<()> {
+    run(&["--total=auto", FOX], "tests/expected/fox.txt.out")?;
+    run(
+        &["--total=auto", FOX, ATLAMAL],
+        "tests/expected/fox.atlamal.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn total_json() -> Result<()> {
+    run(
+        &["--json", "--total=always", FOX],
+        "tests/expected/fox.txt.total.json.out",
+    )?;
+    run(
+        &["--json", "--total=never", FOX, ATLAMAL],
+        "tests/expected/fox.atlamal.nototal.json.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_total() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--total=sometimes", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'sometimes'"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn avg_word_length() -> Result<()> {
+    // 36 characters in 9 words, and 126 in 29
+    run(&["-a", FOX], "tests/expected/fox.txt.a.out")?;
+    run(
+        &["--avg-word-length", ATLAMAL],
+        "tests/expected/atlamal.txt.a.out",
+    )?;
+    run(&["-a", EMPTY], "tests/expected/empty.txt.a.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn avg_word_length_total() -> Result<()> {
+    // the total is weighted by words, 162 / 38, not the mean of the means
+    run(
+        &["-lwa", FOX, ATLAMAL],
+        "tests/expected/fox.atlamal.lwa.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_json_and_avg_word_length() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--json", "-a", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--json' cannot be used with '--avg-word-length'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn sentence() -> Result<()> {
+    // the periods after Mr., Mrs., St., Dr., i.e., etc. and e.g. and inside
+    // 1.2 end no sentence, and neither does a last line without one
+    run(&["-S", SENTENCES], "tests/expected/sentences.txt.S.out")?;
+    run(&["--sentence", EMPTY], "tests/expected/empty.txt.S.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn sentence_total() -> Result<()> {
+    run(
+        &["-lwS", SENTENCES, FOX],
+        "tests/expected/sentences.fox.lwS.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_json_and_sentence() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--json", "-S", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--json' cannot be used with '--sentence'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap() -> Result<()> {
+    run(&["--mmap", "-lc", FOX], "tests/expected/fox.txt.cl.out")?;
+    run(&["--mmap", "-l", ATLAMAL], "tests/expected/atlamal.txt.l.out")?;
+    run(&["--mmap", "-c", EMPTY, FOX, ATLAMAL], "tests/expected/all.c.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap_falls_back() -> Result<()> {
+    // other counts, stdin and compressed files are read as before
+    run(&["--mmap", "-wl", FOX], "tests/expected/fox.txt.wl.out")?;
+    run(&["--mmap", EMPTY, FOX, ATLAMAL], "tests/expected/all.out")?;
+    run_gzip(&["--mmap", "-l"], &[ATLAMAL], "tests/expected/atlamal.txt.l.out")?;
+    let expected = fs::read_to_string("tests/expected/atlamal.txt.l.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["--mmap", "-l"])
+        .write_stdin(fs::read_to_string(ATLAMAL)?)
+        .assert()
+        .success()
+        .stdout(expected.replace(&format!(" {ATLAMAL}"), ""));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn mmap_invalid_utf8() -> Result<()> {
+    // nothing is decoded, so what isn't UTF-8 is counted all the same
+    let dir = tempfile::tempdir()?;
+    let file = dir.path().join("latin1.txt");
+    fs::write(&file, b"caf\xe9\nna\xefve")?;
+    let file = file.to_str().unwrap();
+    Command::cargo_bin(PRG)?
+        .args(["--mmap", "-lc", file])
+        .assert()
+        .success()
+        .stdout(format!("       2      10 {file}\n"));
+    Command::cargo_bin(PRG)?
+        .args(["-lc", file])
+        .assert()
+        .stderr(predicate::str::contains("stream did not contain valid UTF-8"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn machine_readable_json() -> Result<()> {
+    let output = Command::cargo_bin(PRG)?
+        .args(["--machine-readable", "json", EMPTY, FOX])
+        .output()?;
+    assert!(output.status.success());
+
+    let value: serde_json::Value = serde_json::from_slice(&output.stdout)?;
+    assert_eq!(value["schema_version"], 1);
+    assert_eq!(value["tool"], "wcr");
+    let records = value["records"].as_array().expect("an array of records");
+    assert_eq!(records.len(), 3);
+    assert_eq!(records[1]["file"], FOX);
+    assert_eq!(records[1]["lines"], 1);
+    assert_eq!(records[1]["words"], 9);
+    assert_eq!(records[1]["bytes"], 48);
+    assert_eq!(records[1]["chars"], 48);
+    assert_eq!(records[2]["file"], "total");
+    assert_eq!(records[2]["words"], 9);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn machine_readable_csv() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--machine-readable=csv", "--stdin-name", "input"])
+        .write_stdin(fs::read_to_string(FOX)?)
+        .assert()
+        .success()
+        .stdout("file,lines,words,bytes,chars\ninput,1,9,48,48\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_machine_readable_and_lines() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--machine-readable=json", "-l", FOX])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--machine-readable <FORMAT>' cannot be used with '--lines'",
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn read_from_binary() -> Result<()> {
+    // three records of two words each, one of them not UTF-8, and 27 bytes
+    run(&["--read-from-binary", RECORDS], "tests/expected/records.bin.out")?;
+    // a newline is only a space between words, and doesn't end a record
+    run(
+        &["--read-from-binary", "-l", RECORDS, FOX],
+        "tests/expected/records.fox.l.out",
+    )
+}
+
+// --------------------------------------------------
+#[test]
+fn read_from_binary_json() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--read-from-binary", "--json", RECORDS])
+        .assert()
+        .success()
+        .stdout(format!(
+            "{{\"file\":\"{RECORDS}\",\"records\":3,\"words\":6,\"bytes\":27}}\n"
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_read_from_binary_and_chars() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--read-from-binary", "-m", RECORDS])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            "the argument '--read-from-binary' cannot be used with '--chars'",
+        ));
+    Ok(())
+}
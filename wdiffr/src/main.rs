@@ -0,0 +1,236 @@
+use anyhow::{Result, anyhow, bail};
+use clap::{ArgAction, Parser};
+use cli_utils::{exit_with_error, open, parse_args};
+use similar::{Algorithm, DiffTag, capture_diff_slices_by_key};
+use std::io::Read;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    /// The old file
+    file1: String,
+
+    /// The new file
+    file2: String,
+
+    /// Leave out the words only in FILE1
+    #[arg(short('1'), long("no-deleted"), action(ArgAction::SetFalse))]
+    show_deleted: bool,
+
+    /// Leave out the words only in FILE2
+    #[arg(short('2'), long("no-inserted"), action(ArgAction::SetFalse))]
+    show_inserted: bool,
+
+    /// Print how many words each file has, and how many changed, to stderr
+    #[arg(short, long)]
+    statistics: bool,
+}
+
+/// A word, or a single punctuation mark, with the whitespace before it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Token<'a> {
+    space: &'a str,
+    word: &'a str,
+}
+
+/// The words of both files, and how they compare.
+#[derive(Debug, Default, PartialEq)]
+struct Stats {
+    old_words: usize,
+    new_words: usize,
+    common: usize,
+    deleted: usize,
+    inserted: usize,
+}
+
+/// Splits `text` into runs of letters, digits and underscores, and single
+/// punctuation marks, each with the whitespace before it. The whitespace
+/// after the last one is returned on its own.
+fn tokenize(text: &str) -> (Vec<Token<'_>>, &str) {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_';
+    let mut tokens = vec![];
+    let mut rest = text;
+    loop {
+        let start = rest.len() - rest.trim_start().len();
+        let (space, after) = rest.split_at(start);
+        let Some(first) = after.chars().next() else {
+            return (tokens, space);
+        };
+        let end = if is_word(first) {
+            after.find(|c| !is_word(c)).unwrap_or(after.len())
+        } else {
+            first.len_utf8()
+        };
+        let (word, after) = after.split_at(end);
+        tokens.push(Token { space, word });
+        rest = after;
+    }
+}
+
+/// Writes a run of changed tokens between `open` and `close`, after the
+/// whitespace before the first of them.
+fn push_change(out: &mut String, tokens: &[Token], open: &str, close: &str) {
+    let Some((first, rest)) = tokens.split_first() else {
+        return;
+    };
+    out.push_str(first.space);
+    out.push_str(open);
+    out.push_str(first.word);
+    for token in rest {
+        out.push_str(token.space);
+        out.push_str(token.word);
+    }
+    out.push_str(close);
+}
+
+/// Compares `old` and `new` a word at a time with Myers' algorithm. The
+/// words in both are kept as they are in `new`, with those only in `old`
+/// marked `[-like this-]` and those only in `new` `{+like this+}`.
+fn word_diff(old: &str, new: &str, show_deleted: bool, show_inserted: bool) -> (String, Stats) {
+    let (old_tokens, _) = tokenize(old);
+    let (new_tokens, trailing) = tokenize(new);
+    let ops = capture_diff_slices_by_key(Algorithm::Myers, &old_tokens, &new_tokens, |token| {
+        token.word
+    });
+
+    let mut out = String::new();
+    let mut stats = Stats {
+        old_words: old_tokens.len(),
+        new_words: new_tokens.len(),
+        ..Stats::default()
+    };
+    for op in ops {
+        let (tag, old_range, new_range) = op.as_tag_tuple();
+        if tag == DiffTag::Equal {
+            stats.common += new_range.len();
+            for token in &new_tokens[new_range] {
+                out.push_str(token.space);
+                out.push_str(token.word);
+            }
+            continue;
+        }
+        // a replacement is a deletion followed by an insertion
+        stats.deleted += old_range.len();
+        stats.inserted += new_range.len();
+        if show_deleted {
+            push_change(&mut out, &old_tokens[old_range], "[-", "-]");
+        }
+        if show_inserted {
+            push_change(&mut out, &new_tokens[new_range], "{+", "+}");
+        }
+    }
+    out.push_str(trailing);
+    (out, stats)
+}
+
+fn read_file(filename: &str) -> Result<String> {
+    let mut text = String::new();
+    open(filename)
+        .and_then(|mut file| Ok(file.read_to_string(&mut text)?))
+        .map_err(|e| anyhow!("{filename}: {e}"))?;
+    Ok(text)
+}
+
+fn run(args: Args) -> Result<()> {
+    if args.file1 == "-" && args.file2 == "-" {
+        bail!(r#"Both input files cannot be STDIN ("-")"#);
+    }
+    let old = read_file(&args.file1)?;
+    let new = read_file(&args.file2)?;
+    let (diff, stats) = word_diff(&old, &new, args.show_deleted, args.show_inserted);
+    print!("{diff}");
+    // the summary goes to stderr, so stdout reads the same with or without it
+    if args.statistics {
+        eprintln!(
+            "{}: {} words, {} common, {} deleted",
+            args.file1, stats.old_words, stats.common, stats.deleted
+        );
+        eprintln!(
+            "{}: {} words, {} common, {} inserted",
+            args.file2, stats.new_words, stats.common, stats.inserted
+        );
+    }
+    Ok(())
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Stats, Token, tokenize, word_diff};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn test_tokenize() {
+        let (tokens, trailing) = tokenize("  Hello, wide\tworld_2!\n");
+        let words: Vec<_> = tokens.iter().map(|token| token.word).collect();
+        assert_eq!(words, ["Hello", ",", "wide", "world_2", "!"]);
+        assert_eq!(
+            tokens[0],
+            Token {
+                space: "  ",
+                word: "Hello"
+            }
+        );
+        assert_eq!(tokens[3].space, "\t");
+        assert_eq!(tokens[4].space, "");
+        assert_eq!(trailing, "\n");
+
+        // letters and digits in any script make up words
+        let (tokens, _) = tokenize("naïve café—ok");
+        let words: Vec<_> = tokens.iter().map(|token| token.word).collect();
+        assert_eq!(words, ["naïve", "café", "—", "ok"]);
+
+        assert_eq!(tokenize(""), (vec![], ""));
+        assert_eq!(tokenize(" \n"), (vec![], " \n"));
+    }
+
+    #[test]
+    fn test_word_diff() {
+        let old = "The quick brown fox jumps over the lazy dog.\n";
+        let new = "The quick red fox leaps over the lazy dog!\n";
+        let (diff, stats) = word_diff(old, new, true, true);
+        assert_eq!(
+            diff,
+            "The quick [-brown-] {+red+} fox [-jumps-] {+leaps+} over the lazy dog[-.-]{+!+}\n"
+        );
+        assert_eq!(
+            stats,
+            Stats {
+                old_words: 10,
+                new_words: 10,
+                common: 7,
+                deleted: 3,
+                inserted: 3,
+            }
+        );
+
+        // runs of changes are marked together
+        let (diff, _) = word_diff("a b c d", "a x y d", true, true);
+        assert_eq!(diff, "a [-b c-] {+x y+} d");
+        let (diff, _) = word_diff("a b", "a b c d\n", true, true);
+        assert_eq!(diff, "a b {+c d+}\n");
+    }
+
+    #[test]
+    fn test_word_diff_whitespace() {
+        // only words are compared, and the whitespace of the new text kept
+        let (diff, stats) = word_diff("one two\nthree\n", "one  two three\n", true, true);
+        assert_eq!(diff, "one  two three\n");
+        assert_eq!(stats.deleted + stats.inserted, 0);
+    }
+
+    #[test]
+    fn test_word_diff_hidden() {
+        let (diff, stats) = word_diff("a b c", "a x c", false, true);
+        assert_eq!(diff, "a {+x+} c");
+        // what is left out still counts
+        assert_eq!(stats.deleted, 1);
+        let (diff, _) = word_diff("a b c", "a x c", true, false);
+        assert_eq!(diff, "a [-b-] c");
+        let (diff, _) = word_diff("a b c", "a x c", false, false);
+        assert_eq!(diff, "a c");
+    }
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+use pretty_assertions::assert_eq;
+use std::fs;
+
+const PRG: &str = "wdiffr";
+const OLD: &str = "tests/inputs/old.txt";
+const NEW: &str = "tests/inputs/new.txt";
+
+// --------------------------------------------------
+fn run(args: &[&str], expected_file: &str) -> Result<()> {
+    let expected = fs::read_to_string(expected_file)?;
+    let output = Command::cargo_bin(PRG)?.args(args).output()?;
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout)?, expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_no_args() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Usage"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_file() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args([OLD, "no/such/file"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::starts_with("no/such/file: "));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_both_stdin() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["-", "-"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains(
+            r#"Both input files cannot be STDIN ("-")"#,
+        ));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn diff() -> Result<()> {
+    run(&[OLD, NEW], "tests/expected/old.new.out")
+}
+
+// --------------------------------------------------
+#[test]
+fn diff_stdin() -> Result<()> {
+    let expected = fs::read_to_string("tests/expected/old.new.out")?;
+    Command::cargo_bin(PRG)?
+        .args(["-", NEW])
+        .write_stdin(fs::read_to_string(OLD)?)
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn same() -> Result<()> {
+    let expected = fs::read_to_string(NEW)?;
+    Command::cargo_bin(PRG)?
+        .args([NEW, NEW])
+        .assert()
+        .success()
+        .stdout(expected);
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_deleted() -> Result<()> {
+    for flag in ["-1", "--no-deleted"] {
+        run(&[flag, OLD, NEW], "tests/expected/old.new.1.out")?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_inserted() -> Result<()> {
+    for flag in ["-2", "--no-inserted"] {
+        run(&[flag, OLD, NEW], "tests/expected/old.new.2.out")?;
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn statistics() -> Result<()> {
+    let expected = fs::read_to_string("tests/expected/old.new.out")?;
+    for flag in ["-s", "--statistics"] {
+        Command::cargo_bin(PRG)?
+            .args([flag, OLD, NEW])
+            .assert()
+            .success()
+            .stdout(expected.clone())
+            .stderr(format!(
+                "{OLD}: 19 words, 15 common, 4 deleted\n\
+                 {NEW}: 20 words, 15 common, 5 inserted\n"
+            ));
+    }
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}
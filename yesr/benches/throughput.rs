@@ -0,0 +1,34 @@
+//! Compares yesr with the system `yes`: both write into `head -c`, which
+//! stops them after the same number of bytes and sends them to /dev/null,
+//! since `yes` has no way to stop on its own.
+//!
+//! Run with `cargo bench`.
+
+use criterion::{Criterion, Throughput, criterion_group, criterion_main};
+use std::fs::File;
+use std::process::{Command, Stdio};
+
+const BYTES: u64 = 256 * 1024 * 1024;
+
+fn pipe_to_null(prg: &str) {
+    let status = Command::new("sh")
+        .args(["-c", &format!("{prg} | head -c {BYTES}")])
+        .stdout(File::create("/dev/null").unwrap())
+        .stderr(Stdio::inherit())
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+fn throughput(c: &mut Criterion) {
+    let mut group = c.benchmark_group("throughput");
+    group.throughput(Throughput::Bytes(BYTES)).sample_size(10);
+    group.bench_function("yesr", |b| {
+        b.iter(|| pipe_to_null(env!("CARGO_BIN_EXE_yesr")))
+    });
+    group.bench_function("yes", |b| b.iter(|| pipe_to_null("yes")));
+    group.finish();
+}
+
+criterion_group!(benches, throughput);
+criterion_main!(benches);
@@ -0,0 +1,116 @@
+use anyhow::Result;
+use clap::Parser;
+use cli_utils::{exit_with_error, parse_args};
+use std::io::{self, BufWriter, ErrorKind, Write};
+
+// Printing a line at a time with println! managed about 3 MiB/s into a
+// pipe, as stdout flushes at every newline. Handing the kernel 64 KiB of
+// copies per write makes it 3.0 GiB/s, against 2.7 GiB/s for GNU yes
+// (see benches/throughput.rs).
+const BUF_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Parser)]
+#[command(author, version, about)]
+struct Args {
+    /// Text to print, joined with spaces; `y` if there is none
+    #[arg(value_name = "STRING")]
+    strings: Vec<String>,
+
+    /// Stop after printing N lines
+    #[arg(long, value_name = "N")]
+    count: Option<u64>,
+
+    /// Print STRING with no newline after it
+    #[arg(long)]
+    no_newline: bool,
+}
+
+/// Returns as many whole copies of `line` as fit in `BUF_SIZE` bytes, or a
+/// single one of a longer line, so that each write carries many lines.
+fn fill(line: &[u8]) -> Vec<u8> {
+    line.repeat((BUF_SIZE / line.len()).max(1))
+}
+
+/// Writes `line` to `out` `count` times, or for as long as it will take
+/// them when there is no count.
+fn repeat(out: &mut impl Write, line: &[u8], count: Option<u64>) -> io::Result<()> {
+    let buf = fill(line);
+    let Some(count) = count else {
+        loop {
+            out.write_all(&buf)?;
+        }
+    };
+    let copies = (buf.len() / line.len()) as u64;
+    for _ in 0..count / copies {
+        out.write_all(&buf)?;
+    }
+    out.write_all(&buf[..(count % copies) as usize * line.len()])?;
+    out.flush()
+}
+
+fn run(args: Args) -> Result<()> {
+    let mut line = if args.strings.is_empty() {
+        "y".to_string()
+    } else {
+        args.strings.join(" ")
+    }
+    .into_bytes();
+    if !args.no_newline {
+        line.push(b'\n');
+    }
+    // an empty STRING with --no-newline leaves nothing to repeat
+    if line.is_empty() {
+        return Ok(());
+    }
+
+    let mut stdout = BufWriter::with_capacity(BUF_SIZE, io::stdout().lock());
+    match repeat(&mut stdout, &line, args.count) {
+        // the reader has seen all it wanted, as with `yesr | head`
+        Err(e) if e.kind() == ErrorKind::BrokenPipe => Ok(()),
+        result => Ok(result?),
+    }
+}
+
+fn main() {
+    exit_with_error!(run(parse_args()));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BUF_SIZE, fill, repeat};
+
+    #[test]
+    fn test_fill() {
+        let buf = fill(b"y\n");
+        assert_eq!(buf.len(), BUF_SIZE);
+        assert!(buf.chunks(2).all(|chunk| chunk == b"y\n"));
+
+        // only whole copies go in
+        let buf = fill(b"abc\n");
+        assert_eq!(buf.len(), BUF_SIZE / 4 * 4);
+        let buf = fill(b"abcde\n");
+        assert_eq!(buf.len(), BUF_SIZE / 6 * 6);
+
+        // a line longer than the buffer is written whole
+        let long = vec![b'x'; BUF_SIZE + 1];
+        assert_eq!(fill(&long), long);
+    }
+
+    #[test]
+    fn test_repeat() {
+        let lines = |line: &[u8], count| {
+            let mut out = vec![];
+            repeat(&mut out, line, Some(count)).unwrap();
+            out
+        };
+        assert_eq!(lines(b"y\n", 0), b"");
+        assert_eq!(lines(b"y\n", 3), b"y\ny\ny\n");
+        assert_eq!(lines(b"ab", 2), b"abab");
+
+        // counts that take more than one buffer, with and without some over
+        let copies = BUF_SIZE / 2;
+        for count in [copies, copies + 1, 3 * copies - 1] {
+            assert_eq!(lines(b"y\n", count as u64), b"y\n".repeat(count));
+        }
+    }
+}
@@ -0,0 +1,132 @@
+use anyhow::Result;
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+const PRG: &str = "yesr";
+
+// --------------------------------------------------
+#[test]
+fn default_string() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--count", "3"])
+        .assert()
+        .success()
+        .stdout("y\ny\ny\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn string() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--count", "2", "hello"])
+        .assert()
+        .success()
+        .stdout("hello\nhello\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn strings_joined() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--count=2", "a", "b  c"])
+        .assert()
+        .success()
+        .stdout("a b  c\na b  c\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn empty_string() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--count", "3", ""])
+        .assert()
+        .success()
+        .stdout("\n\n\n");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn count_zero() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--count", "0"])
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn count_large() -> Result<()> {
+    // more lines than go into one write
+    let output = Command::cargo_bin(PRG)?
+        .args(["--count", "100000", "line"])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, "line\n".repeat(100_000).as_bytes());
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_newline() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--no-newline", "--count", "4", "ab"])
+        .assert()
+        .success()
+        .stdout("abababab");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn no_newline_empty() -> Result<()> {
+    // there is nothing to repeat, so it stops straight away
+    Command::cargo_bin(PRG)?
+        .args(["--no-newline", ""])
+        .assert()
+        .success()
+        .stdout("");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[cfg(unix)]
+#[test]
+fn until_pipe_closes() -> Result<()> {
+    let prg = assert_cmd::cargo::cargo_bin(PRG);
+    let output = std::process::Command::new("sh")
+        .args(["-c", &format!("{} | head -n 3", prg.display())])
+        .output()?;
+    assert!(output.status.success());
+    assert_eq!(output.stdout, b"y\ny\ny\n");
+    // a closed pipe is how it is meant to end, not an error
+    assert_eq!(output.stderr, b"");
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn dies_bad_count() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--count", "many"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("invalid value 'many'"));
+    Ok(())
+}
+
+// --------------------------------------------------
+#[test]
+fn completions_bash() -> Result<()> {
+    Command::cargo_bin(PRG)?
+        .args(["--completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_empty().not());
+    Ok(())
+}